@@ -0,0 +1,25 @@
+#include <stddef.h>
+
+// Defined in assembly files.
+void exit(int code);
+int write(int fd, char* str, size_t len);
+
+// Defined in other.c.
+void set_shared(void);
+
+// Also declared in other.c, both are tentative definitions merged by the linker.
+int shared __attribute__((common));
+
+// Declared with a larger size in other.c, which must be the one allocated.
+int larger __attribute__((common));
+
+void _start() {
+    set_shared();
+    if (shared == 42) {
+        write(1, "shared is 42\n", 13);
+        exit(0);
+    } else {
+        write(1, "shared is not 42\n", 17);
+        exit(1);
+    }
+}
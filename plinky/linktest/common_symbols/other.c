@@ -0,0 +1,8 @@
+// Also declared in main.c, both are tentative definitions merged by the linker.
+int shared __attribute__((common));
+
+long long larger __attribute__((common));
+
+void set_shared(void) {
+    shared = 42;
+}
@@ -1,7 +1,13 @@
 use anyhow::{bail, Error};
-use plinky_test_harness::prerequisites::{Arch, Prerequisites};
-use plinky_test_harness::utils::record_snapshot;
+use plinky_diagnostics::widgets::{HexDump, Widget};
+use plinky_elf::ids::serial::SerialIds;
+use plinky_elf::render_elf::RenderElfFilters;
+use plinky_elf::ElfObject;
+use plinky_test_harness::prerequisites::{set_plinky_path, Arch, Prerequisites};
+use plinky_test_harness::utils::{assert_snapshot, find_executable, record_snapshot};
 use plinky_test_harness::{Test, TestGatherer};
+use std::fs::File;
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use tempfile::TempDir;
@@ -23,11 +29,20 @@ impl TestGatherer for Linktest {
                 TestArch::X86_64 => "x86-64",
             };
 
+            // Tests running the output under an emulator are skipped when it's not installed.
+            let ignore = test_toml.ignore.clone().or_else(|| {
+                let program = test_toml.run_under.first()?;
+                match find_executable(program) {
+                    Some(_) => None,
+                    None => Some(format!("{program} is not installed")),
+                }
+            });
+
             let execution = TestExecution::new(path.into(), test_toml.clone(), *arch)?;
             tests.push(Test {
                 name: format!("{name} ({arch_name})"),
                 exec: Box::new(move || execution.exec()),
-                ignore: test_toml.ignore.clone(),
+                ignore,
             });
         }
         Ok(tests)
@@ -69,6 +84,10 @@ impl TestExecution {
         let (res, err) = match self.settings.kind {
             TestKind::LinkFail => (!self.link()?, "linking was supposed to fail but passed!"),
             TestKind::LinkPass => (self.link()?, "linking was supposed to pass but failed!"),
+            TestKind::LinkAndInspect => {
+                (self.inspect()?, "linking was supposed to pass but failed!")
+            }
+            TestKind::LinkAndDump => (self.dump()?, "linking was supposed to pass but failed!"),
             TestKind::RunFail => (!self.run()?, "running was supposed to fail but passed!"),
             TestKind::RunPass => (self.run()?, "running was supposed to pass but failed!"),
         };
@@ -90,12 +109,49 @@ impl TestExecution {
         self.record_snapshot("linker", "linking", &mut command)
     }
 
+    fn inspect(&self) -> Result<bool, Error> {
+        if !self.link()? {
+            return Ok(false);
+        }
+
+        let filters = match &self.settings.inspect {
+            Some(filters) => RenderElfFilters::parse(filters)?,
+            None => RenderElfFilters::all(),
+        };
+        let mut file = BufReader::new(File::open(self.dest_dir.join("a.out"))?);
+        let object = ElfObject::load(&mut file, &mut SerialIds::new())?;
+        let rendered = plinky_elf::render_elf::render(&object, &filters).render_to_string();
+
+        assert_snapshot(&format!("inspect{}", self.suffix()), &self.root, rendered)?;
+        Ok(true)
+    }
+
+    fn dump(&self) -> Result<bool, Error> {
+        if !self.link()? {
+            return Ok(false);
+        }
+
+        let content = std::fs::read(self.dest_dir.join("a.out"))?;
+        let rendered = HexDump::new(content).render_to_string();
+
+        assert_snapshot(&format!("dump{}", self.suffix()), &self.root, rendered)?;
+        Ok(true)
+    }
+
     fn run(&self) -> Result<bool, Error> {
         if !self.link()? {
             bail!("linking was supposed to pass but failed!");
         }
 
-        let mut command = Command::new(self.dest_dir.join("a.out"));
+        let binary = self.dest_dir.join("a.out");
+        let mut command = match self.settings.run_under.split_first() {
+            Some((program, args)) => {
+                let mut command = Command::new(program);
+                command.args(args).arg(binary);
+                command
+            }
+            None => Command::new(binary),
+        };
         command.current_dir(&self.dest_dir);
 
         self.record_snapshot("run", "running", &mut command)
@@ -129,6 +185,13 @@ struct TestSettings {
     archs: Vec<TestArch>,
     #[serde(default)]
     debug_print: Vec<String>,
+    /// Command prepended when running the linked binary, like `qemu-aarch64` to run binaries
+    /// for a different architecture than the host.
+    #[serde(default)]
+    run_under: Vec<String>,
+    /// Filters applied when rendering the output of `link-and-inspect` tests.
+    #[serde(default)]
+    inspect: Option<String>,
     #[serde(flatten)]
     prerequisites: Prerequisites,
 }
@@ -149,11 +212,16 @@ fn default_test_archs() -> Vec<TestArch> {
 enum TestKind {
     LinkFail,
     LinkPass,
+    /// Link the inputs, and snapshot the structure of the resulting ELF file.
+    LinkAndInspect,
+    /// Link the inputs, and snapshot a hex dump of the output file (for non-ELF outputs).
+    LinkAndDump,
     RunFail,
     RunPass,
 }
 
 fn main() {
+    set_plinky_path(Path::new(env!("CARGO_BIN_EXE_ld.plinky")));
     let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("linktest");
     plinky_test_harness::main(&path, Linktest);
 }
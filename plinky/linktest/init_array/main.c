@@ -0,0 +1,30 @@
+#include <stddef.h>
+
+// Defined in assembly files.
+void exit(int code);
+int write(int fd, char* str, size_t len);
+
+// Defined by the linker.
+extern void (*__init_array_start[])(void);
+extern void (*__init_array_end[])(void);
+
+// Constructors with a priority must run first, in ascending priority order.
+__attribute__((constructor(200))) static void second(void) {
+    write(1, "priority 200\n", 13);
+}
+
+__attribute__((constructor(101))) static void first(void) {
+    write(1, "priority 101\n", 13);
+}
+
+// Constructors without a priority run last, in the order of the input objects.
+__attribute__((constructor)) static void third(void) {
+    write(1, "main.c\n", 7);
+}
+
+void _start() {
+    for (void (**ctor)(void) = __init_array_start; ctor != __init_array_end; ctor++) {
+        (*ctor)();
+    }
+    exit(0);
+}
@@ -0,0 +1,8 @@
+#include <stddef.h>
+
+// Defined in assembly files.
+int write(int fd, char* str, size_t len);
+
+__attribute__((constructor)) static void fourth(void) {
+    write(1, "other.c\n", 8);
+}
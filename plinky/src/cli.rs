@@ -1,24 +1,103 @@
 use crate::debug_print::filters::{ObjectsFilter, ObjectsFilterParseError};
 use plinky_elf::render_elf::{RenderElfFilters, RenderElfFiltersParseError};
+use plinky_elf::ElfPermissions;
 use plinky_macros::{Display, Error};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 // GNU ld loves to be inconsistent, and thus some long flags are prefixed with a single dash
 // rather than a double dash. To ensure we still parse the CLI correctly, we have a list of
 // flags that should be emitted as LongShortFlag.
-const LONG_SHORT_FLAG: &[&str] = &["no-pie", "pie"];
+const LONG_SHORT_FLAG: &[&str] = &["Bsymbolic", "Bsymbolic-functions", "no-pie", "pie", "static"];
 
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) struct CliOptions {
-    pub(crate) inputs: Vec<PathBuf>,
+    pub(crate) inputs: Vec<CliInput>,
+    pub(crate) just_symbols: Vec<PathBuf>,
     pub(crate) output: PathBuf,
     pub(crate) entry: String,
+    pub(crate) defsym: BTreeMap<String, u64>,
+    /// Symbols whose definition and references are printed (with `--trace-symbol`/`-y`).
+    pub(crate) trace_symbols: Vec<String>,
+    /// Symbols whose undefined references are redirected to `__wrap_SYMBOL`, with references to
+    /// `__real_SYMBOL` going to the original definition (with `--wrap`).
+    pub(crate) wrap: Vec<String>,
+    /// Permissions forced on the sections with the given names (with `--section-perms`).
+    pub(crate) section_perms: BTreeMap<String, ElfPermissions>,
     pub(crate) gc_sections: bool,
+    /// Keep the relocations of the inputs in the output (`--emit-relocs`/`-q`).
+    pub(crate) emit_relocs: bool,
+    pub(crate) icf: bool,
+    pub(crate) print_icf_sections: bool,
+    pub(crate) strip_section_headers: bool,
+    pub(crate) output_format: OutputFormat,
+    /// Size the output file is padded to (with `--pad-to`), using `fill_byte`.
+    pub(crate) pad_to: Option<u64>,
+    pub(crate) fill_byte: u8,
+    /// Byte written in the gaps between the parts of the output (with `--fill`).
+    pub(crate) fill: u8,
+    pub(crate) build_id: Option<BuildIdStyle>,
+    /// Extra input mixed into the build-id, to tell apart builds with the same contents.
+    pub(crate) build_id_seed: Option<String>,
+    pub(crate) print_memory_usage: bool,
+    /// Print the relocations of the inputs and exit without linking (with `--list-relocs`).
+    pub(crate) list_relocs: bool,
+    pub(crate) max_errors: Option<usize>,
+    pub(crate) fatal_warnings: bool,
+    /// Required alignment of the entry point, overriding the instruction alignment of the machine.
+    pub(crate) entry_alignment: Option<u64>,
+    /// How many times `-v`/`--verbose` was passed: inputs and archive members are printed from
+    /// level 1, segment layout decisions from level 2.
+    pub(crate) verbose: u8,
     pub(crate) debug_print: BTreeSet<DebugPrint>,
-    pub(crate) executable_stack: bool,
+    /// Overrides the stack executability requested by the inputs' `.note.GNU-stack` sections
+    /// (with `-z execstack`/`-z noexecstack`).
+    pub(crate) executable_stack: Option<bool>,
+    /// Whether to warn when the stack is executable (disabled by `--no-warn-execstack`).
+    pub(crate) warn_execstack: bool,
+    /// Whether to warn about segments both writable and executable (disabled by
+    /// `--no-warn-rwx-segments`).
+    pub(crate) warn_rwx_segments: bool,
+    pub(crate) static_linking: bool,
     pub(crate) dynamic_linker: Option<String>,
+    pub(crate) gnu_debuglink: Option<PathBuf>,
+    /// Fail when symbols referenced by the output are not defined (`--no-undefined`/`-z defs`).
+    pub(crate) no_undefined: bool,
+    /// File listing the symbols `--no-undefined` allows to be undefined, one per line.
+    pub(crate) allow_undefined_file: Option<PathBuf>,
     pub(crate) mode: Mode,
+    pub(crate) segments_layout: SegmentsLayout,
+    /// Place read-only sections in the executable segment rather than in a segment of their own
+    /// (`--no-rosegment`).
+    pub(crate) no_rosegment: bool,
+    /// Whether `.eh_frame_hdr` should be generated (`--eh-frame-hdr` or `--no-eh-frame-hdr`), by
+    /// default only for dynamic executables. plinky doesn't generate it yet, so nothing reads this.
+    pub(crate) eh_frame_hdr: bool,
+    pub(crate) sort_sections: SortSections,
+    pub(crate) symtab_sort: SymtabSort,
+    pub(crate) discard_locals: DiscardLocals,
+    pub(crate) hash_style: HashStyle,
+    pub(crate) symbolic: SymbolicBinding,
+    /// Extra `DT_NULL` entries at the end of the dynamic section, for tools patching it later.
+    pub(crate) spare_dynamic_tags: usize,
+    /// Sort the dynamic relocations by address (disabled by `--no-sort-dyn-relocs`).
+    pub(crate) sort_dyn_relocs: bool,
+    pub(crate) color: ColorMode,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct CliInput {
+    pub(crate) path: PathBuf,
+    pub(crate) options: CliInputOptions,
+}
+
+/// Options affecting how the inputs following them in the command line are loaded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub(crate) struct CliInputOptions {
+    /// Load all members of archives, rather than only the ones defining undefined symbols
+    /// (`--whole-archive`, disabled again by `--no-whole-archive`).
+    pub(crate) whole_archive: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -27,12 +106,124 @@ pub(crate) enum Mode {
     PositionIndependent,
 }
 
+/// How segments are placed in memory. The names of the variants mirror the GNU ld flags.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum SegmentsLayout {
+    /// Every segment starts at a page boundary.
+    PageAligned,
+    /// Segments are packed next to each other without any page alignment (`-n`/`--nmagic`).
+    NMagic,
+    /// Like [`SegmentsLayout::NMagic`], but the text segment is also writable (`-N`/`--omagic`).
+    OMagic,
+}
+
+/// Order of the sections in the section header table of the output (`--sort-sections`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum SortSections {
+    /// Sections are emitted in the order they were loaded from the inputs.
+    None,
+    /// Sections are emitted sorted by name, which reduces the noise when diffing two outputs.
+    Name,
+}
+
+/// Order of the symbols in the `.symtab` of the output (`--output-symtab-sort`). Local symbols
+/// are always placed before global ones, as required by ELF, and each group is sorted on its own.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum SymtabSort {
+    /// Symbols are emitted in the order they were loaded from the inputs.
+    None,
+    /// Symbols are sorted alphabetically by name.
+    Name,
+    /// Symbols are sorted by their value, which is the address for most symbols.
+    Value,
+}
+
+/// Which local symbols are left out of the `.symtab` of the output. Symbols referenced by the
+/// relocations kept with `--emit-relocs` are always preserved.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum DiscardLocals {
+    None,
+    /// Discard the local symbols generated by the compiler, starting with `.L` (`-X`/
+    /// `--discard-locals`).
+    Temporary,
+    /// Discard all local symbols (`-x`/`--discard-all`).
+    All,
+}
+
+/// Format of the file written to disk (`--oformat`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum OutputFormat {
+    Elf,
+    /// Raw memory image of the allocated sections, without any headers.
+    Binary,
+}
+
+/// Which hash tables are emitted in position independent executables (`--hash-style`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum HashStyle {
+    /// Emit a SysV `.hash` section.
+    Sysv,
+    /// Don't emit any hash section. The dynamic loader will not be able to look up symbols, so
+    /// this is only allowed when the dynamic symbol table is empty.
+    None,
+}
+
+/// How the build-id note is generated (`--build-id`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum BuildIdStyle {
+    /// Hash of the output contents.
+    Hash(BuildIdHash),
+    /// Random 128-bit UUID, different for every link.
+    Uuid,
+}
+
+/// Hash function used to calculate content-based build-ids.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum BuildIdHash {
+    /// SHA-1, which is also the default of `--build-id`.
+    Sha1,
+    Md5,
+}
+
+/// Which global symbols defined in the output are bound to their own definition rather than
+/// being resolved by the dynamic loader (`-Bsymbolic`/`-Bsymbolic-functions`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum SymbolicBinding {
+    None,
+    All,
+    Functions,
+}
+
+/// Whether diagnostics should be rendered with ANSI colors (`--color`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum ColorMode {
+    /// Colors are enabled only when stderr is a terminal and `NO_COLOR` is not set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub(crate) fn should_color_stderr(&self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                // https://no-color.org: the variable disables colors when present and not empty.
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                !no_color && std::io::stderr().is_terminal()
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord)]
 pub(crate) enum DebugPrint {
     LoadedObject(ObjectsFilter),
     Gc,
     RelocatedObject(ObjectsFilter),
     Layout,
+    RelocationsAnalysis,
     FinalElf(RenderElfFilters),
 }
 
@@ -43,18 +234,62 @@ pub(crate) fn parse<S: Into<String>, I: Iterator<Item = S>>(
     let mut lexer = CliLexer::new(&args, LONG_SHORT_FLAG);
 
     let mut inputs = Vec::new();
+    let mut input_options = CliInputOptions::default();
+    let mut just_symbols = Vec::new();
     let mut output = None;
     let mut entry = None;
     let mut executable_stack = None;
+    let mut warn_execstack = None;
+    let mut warn_rwx_segments = None;
     let mut gc_sections = None;
+    let mut emit_relocs = None;
+    let mut icf = None;
+    let mut print_icf_sections = None;
+    let mut strip_section_headers = None;
+    let mut build_id = None;
+    let mut build_id_seed = None;
+    let mut print_memory_usage = None;
+    let mut list_relocs = None;
+    let mut max_errors = None;
+    let mut fatal_warnings = None;
+    let mut entry_alignment = None;
+    let mut verbose: u8 = 0;
     let mut mode = None;
+    let mut static_linking = None;
+    let mut segments_layout = None;
+    let mut no_rosegment = None;
+    let mut eh_frame_hdr = None;
     let mut dynamic_linker = None;
+    let mut gnu_debuglink = None;
+    let mut no_undefined = None;
+    let mut allow_undefined_file = None;
+    let mut color = None;
+    let mut sort_sections = None;
+    let mut symtab_sort = None;
+    let mut discard_locals = None;
+    let mut hash_style = None;
+    let mut symbolic = None;
+    let mut output_format = None;
+    let mut pad_to = None;
+    let mut fill_byte = None;
+    let mut fill = None;
+    let mut spare_dynamic_tags = None;
+    let mut sort_dyn_relocs = None;
+    let mut defsym = BTreeMap::new();
+    let mut trace_symbols = Vec::new();
+    let mut wrap = Vec::new();
+    let mut section_perms = BTreeMap::new();
     let mut debug_print = BTreeSet::new();
 
     let mut previous_token: Option<CliToken<'_>> = None;
     while let Some(token) = lexer.next() {
         match token {
-            CliToken::StandaloneValue(val) => inputs.push(val.into()),
+            CliToken::StandaloneValue(val) => {
+                inputs.push(CliInput { path: val.into(), options: input_options })
+            }
+
+            CliToken::LongFlag("whole-archive") => input_options.whole_archive = true,
+            CliToken::LongFlag("no-whole-archive") => input_options.whole_archive = false,
 
             CliToken::LongFlag("output") | CliToken::ShortFlag("o") => {
                 reject_duplicate(&token, &mut output, || lexer.expect_flag_value(&token))?;
@@ -64,18 +299,245 @@ pub(crate) fn parse<S: Into<String>, I: Iterator<Item = S>>(
                 reject_duplicate(&token, &mut entry, || lexer.expect_flag_value(&token))?;
             }
 
+            CliToken::LongFlag("just-symbols") | CliToken::ShortFlag("R") => {
+                just_symbols.push(lexer.expect_flag_value(&token)?.into());
+            }
+
+            CliToken::LongFlag("trace-symbol") | CliToken::ShortFlag("y") => {
+                trace_symbols.push(lexer.expect_flag_value(&token)?.into());
+            }
+
+            CliToken::LongFlag("wrap") => {
+                wrap.push(lexer.expect_flag_value(&token)?.into());
+            }
+
+            CliToken::LongFlag("defsym") => {
+                let raw = lexer.expect_flag_value(&token)?;
+                let Some((name, value)) = raw.split_once('=') else {
+                    return Err(CliError::MissingDefsymValue(raw.into()));
+                };
+                let value = parse_number(value)
+                    .ok_or_else(|| CliError::UnsupportedDefsymExpression(value.into()))?;
+                if defsym.insert(name.to_string(), value).is_some() {
+                    return Err(CliError::DuplicateDefsym(name.into()));
+                }
+            }
+
+            CliToken::LongFlag("section-perms") => {
+                let raw = lexer.expect_flag_value(&token)?;
+                let Some((name, perms)) = raw.split_once('=') else {
+                    return Err(CliError::MissingSectionPermsValue(raw.into()));
+                };
+                if section_perms.insert(name.to_string(), parse_perms(perms)?).is_some() {
+                    return Err(CliError::DuplicateSectionPerms(name.into()));
+                }
+            }
+
             CliToken::LongFlag("dynamic-linker") => {
                 reject_duplicate(&token, &mut dynamic_linker, || lexer.expect_flag_value(&token))?;
             }
 
-            CliToken::LongShortFlag("no-pie") => {
+            CliToken::LongFlag("fatal-warnings") => {
+                reject_duplicate(&token, &mut fatal_warnings, || Ok(true))?
+            }
+
+            CliToken::LongFlag("entry-alignment") => {
+                reject_duplicate(&token, &mut entry_alignment, || {
+                    let raw = lexer.expect_flag_value(&token)?;
+                    parse_number(raw)
+                        .filter(|align| align.is_power_of_two())
+                        .ok_or_else(|| CliError::InvalidEntryAlignment(raw.into()))
+                })?;
+            }
+
+            CliToken::LongFlag("max-errors") => {
+                reject_duplicate(&token, &mut max_errors, || {
+                    let raw = lexer.expect_flag_value(&token)?;
+                    raw.parse::<usize>().map_err(|_| CliError::InvalidMaxErrors(raw.into()))
+                })?;
+            }
+
+            CliToken::LongFlag("spare-dynamic-tags") => {
+                reject_duplicate(&token, &mut spare_dynamic_tags, || {
+                    let raw = lexer.expect_flag_value(&token)?;
+                    raw.parse::<usize>().map_err(|_| CliError::InvalidSpareDynamicTags(raw.into()))
+                })?;
+            }
+
+            CliToken::LongFlag("sort-dyn-relocs") => reject_duplicate(
+                "--sort-dyn-relocs or --no-sort-dyn-relocs",
+                &mut sort_dyn_relocs,
+                || Ok(true),
+            )?,
+            CliToken::LongFlag("no-sort-dyn-relocs") => reject_duplicate(
+                "--sort-dyn-relocs or --no-sort-dyn-relocs",
+                &mut sort_dyn_relocs,
+                || Ok(false),
+            )?,
+
+            CliToken::LongFlag("color") => {
+                reject_duplicate(&token, &mut color, || {
+                    match lexer.expect_flag_value(&token)? {
+                        "auto" => Ok(ColorMode::Auto),
+                        "always" => Ok(ColorMode::Always),
+                        "never" => Ok(ColorMode::Never),
+                        other => Err(CliError::UnsupportedColorMode(other.into())),
+                    }
+                })?;
+            }
+
+            CliToken::LongFlag("sort-sections") => {
+                reject_duplicate(&token, &mut sort_sections, || {
+                    match lexer.expect_flag_value(&token)? {
+                        "none" => Ok(SortSections::None),
+                        "name" => Ok(SortSections::Name),
+                        other => Err(CliError::UnsupportedSortSections(other.into())),
+                    }
+                })?;
+            }
+
+            CliToken::LongFlag("output-symtab-sort") => {
+                reject_duplicate(&token, &mut symtab_sort, || {
+                    match lexer.expect_flag_value(&token)? {
+                        "none" => Ok(SymtabSort::None),
+                        "name" => Ok(SymtabSort::Name),
+                        "value" => Ok(SymtabSort::Value),
+                        other => Err(CliError::UnsupportedSymtabSort(other.into())),
+                    }
+                })?;
+            }
+
+            CliToken::LongFlag("discard-locals") | CliToken::ShortFlag("X") => reject_duplicate(
+                "-X/--discard-locals or -x/--discard-all",
+                &mut discard_locals,
+                || Ok(DiscardLocals::Temporary),
+            )?,
+            CliToken::LongFlag("discard-all") | CliToken::ShortFlag("x") => reject_duplicate(
+                "-X/--discard-locals or -x/--discard-all",
+                &mut discard_locals,
+                || Ok(DiscardLocals::All),
+            )?,
+
+            CliToken::LongFlag("hash-style") => {
+                reject_duplicate(&token, &mut hash_style, || {
+                    match lexer.expect_flag_value(&token)? {
+                        "sysv" => Ok(HashStyle::Sysv),
+                        "none" => Ok(HashStyle::None),
+                        other => Err(CliError::UnsupportedHashStyle(other.into())),
+                    }
+                })?;
+            }
+
+            CliToken::LongFlag("oformat") => {
+                reject_duplicate(&token, &mut output_format, || {
+                    match lexer.expect_flag_value(&token)? {
+                        "elf" => Ok(OutputFormat::Elf),
+                        "binary" => Ok(OutputFormat::Binary),
+                        other => Err(CliError::UnsupportedOutputFormat(other.into())),
+                    }
+                })?;
+            }
+
+            CliToken::LongFlag("pad-to") => {
+                reject_duplicate(&token, &mut pad_to, || {
+                    let raw = lexer.expect_flag_value(&token)?;
+                    parse_number(raw).ok_or_else(|| CliError::InvalidPadTo(raw.into()))
+                })?;
+            }
+
+            CliToken::LongFlag("fill-byte") => {
+                reject_duplicate(&token, &mut fill_byte, || {
+                    let raw = lexer.expect_flag_value(&token)?;
+                    parse_number(raw)
+                        .and_then(|byte| u8::try_from(byte).ok())
+                        .ok_or_else(|| CliError::InvalidFillByte(raw.into()))
+                })?;
+            }
+
+            CliToken::LongFlag("fill") => {
+                reject_duplicate(&token, &mut fill, || {
+                    let raw = lexer.expect_flag_value(&token)?;
+                    parse_number(raw)
+                        .and_then(|byte| u8::try_from(byte).ok())
+                        .ok_or_else(|| CliError::InvalidFill(raw.into()))
+                })?;
+            }
+
+            CliToken::LongFlag("no-pie") | CliToken::LongShortFlag("no-pie") => {
                 reject_multiple_modes(&mut mode, Mode::PositionDependent)?;
             }
 
-            CliToken::LongShortFlag("pie") => {
+            CliToken::LongFlag("pie") | CliToken::LongShortFlag("pie") => {
                 reject_multiple_modes(&mut mode, Mode::PositionIndependent)?;
             }
 
+            CliToken::LongFlag("static") | CliToken::LongShortFlag("static") => {
+                reject_duplicate(&token, &mut static_linking, || Ok(true))?
+            }
+
+            CliToken::LongFlag("nmagic") | CliToken::ShortFlag("n") => {
+                reject_duplicate("-n/--nmagic or -N/--omagic", &mut segments_layout, || {
+                    Ok(SegmentsLayout::NMagic)
+                })?
+            }
+
+            CliToken::LongFlag("omagic") | CliToken::ShortFlag("N") => {
+                reject_duplicate("-n/--nmagic or -N/--omagic", &mut segments_layout, || {
+                    Ok(SegmentsLayout::OMagic)
+                })?
+            }
+
+            CliToken::LongFlag("no-rosegment") => {
+                reject_duplicate(&token, &mut no_rosegment, || Ok(true))?
+            }
+
+            // plinky doesn't generate .eh_frame_hdr (nor the PT_GNU_EH_FRAME segment) yet, so these
+            // flags don't have any effect right now. They are accepted as compiler drivers pass
+            // --eh-frame-hdr when linking dynamic executables.
+            CliToken::LongFlag("eh-frame-hdr") => {
+                reject_duplicate("--eh-frame-hdr or --no-eh-frame-hdr", &mut eh_frame_hdr, || {
+                    Ok(true)
+                })?
+            }
+            CliToken::LongFlag("no-eh-frame-hdr") => {
+                reject_duplicate("--eh-frame-hdr or --no-eh-frame-hdr", &mut eh_frame_hdr, || {
+                    Ok(false)
+                })?
+            }
+
+            CliToken::LongShortFlag("Bsymbolic") => {
+                reject_duplicate("-Bsymbolic or -Bsymbolic-functions", &mut symbolic, || {
+                    Ok(SymbolicBinding::All)
+                })?
+            }
+            CliToken::LongShortFlag("Bsymbolic-functions") => {
+                reject_duplicate("-Bsymbolic or -Bsymbolic-functions", &mut symbolic, || {
+                    Ok(SymbolicBinding::Functions)
+                })?
+            }
+
+            CliToken::LongFlag("warn-execstack") => reject_duplicate(
+                "--warn-execstack or --no-warn-execstack",
+                &mut warn_execstack,
+                || Ok(true),
+            )?,
+            CliToken::LongFlag("no-warn-execstack") => reject_duplicate(
+                "--warn-execstack or --no-warn-execstack",
+                &mut warn_execstack,
+                || Ok(false),
+            )?,
+
+            CliToken::LongFlag("warn-rwx-segments") => reject_duplicate(
+                "--warn-rwx-segments or --no-warn-rwx-segments",
+                &mut warn_rwx_segments,
+                || Ok(true),
+            )?,
+            CliToken::LongFlag("no-warn-rwx-segments") => reject_duplicate(
+                "--warn-rwx-segments or --no-warn-rwx-segments",
+                &mut warn_rwx_segments,
+                || Ok(false),
+            )?,
+
             CliToken::ShortFlag("z") => match lexer.expect_flag_value(&token)? {
                 "execstack" => reject_duplicate(
                     "-z execstack or -z noexecstack",
@@ -87,6 +549,9 @@ pub(crate) fn parse<S: Into<String>, I: Iterator<Item = S>>(
                     &mut executable_stack,
                     || Ok(false),
                 )?,
+                "defs" => {
+                    reject_duplicate("--no-undefined or -z defs", &mut no_undefined, || Ok(true))?
+                }
                 other => return Err(CliError::UnsupportedFlag(format!("-z {other}"))),
             },
 
@@ -106,6 +571,7 @@ pub(crate) fn parse<S: Into<String>, I: Iterator<Item = S>>(
                         DebugPrint::RelocatedObject(ObjectsFilter::parse(filter)?)
                     }
                     ("layout", None) => DebugPrint::Layout,
+                    ("relocations-analysis", None) => DebugPrint::RelocationsAnalysis,
                     ("final-elf", None) => DebugPrint::FinalElf(RenderElfFilters::all()),
                     ("final-elf", Some(filter)) => {
                         DebugPrint::FinalElf(RenderElfFilters::parse(filter)?)
@@ -118,10 +584,70 @@ pub(crate) fn parse<S: Into<String>, I: Iterator<Item = S>>(
                 }
             }
 
+            CliToken::LongFlag("verbose") | CliToken::ShortFlag("v") => {
+                verbose = verbose.saturating_add(1);
+            }
+
+            CliToken::LongFlag("no-undefined") => {
+                reject_duplicate("--no-undefined or -z defs", &mut no_undefined, || Ok(true))?
+            }
+
+            CliToken::LongFlag("allow-undefined-file") => {
+                reject_duplicate(&token, &mut allow_undefined_file, || {
+                    lexer.expect_flag_value(&token)
+                })?;
+            }
+
+            CliToken::LongFlag("add-gnu-debuglink") => {
+                reject_duplicate(&token, &mut gnu_debuglink, || lexer.expect_flag_value(&token))?;
+            }
+
             CliToken::LongFlag("gc-sections") => {
                 reject_duplicate(&token, &mut gc_sections, || Ok(true))?
             }
 
+            CliToken::LongFlag("emit-relocs") | CliToken::ShortFlag("q") => {
+                reject_duplicate(&token, &mut emit_relocs, || Ok(true))?
+            }
+
+            CliToken::LongFlag("icf") => {
+                reject_duplicate(&token, &mut icf, || match lexer.expect_flag_value(&token)? {
+                    "all" => Ok(true),
+                    "none" => Ok(false),
+                    other => Err(CliError::UnsupportedIcf(other.into())),
+                })?;
+            }
+
+            CliToken::LongFlag("print-icf-sections") => {
+                reject_duplicate(&token, &mut print_icf_sections, || Ok(true))?
+            }
+
+            CliToken::LongFlag("strip-section-headers") => {
+                reject_duplicate(&token, &mut strip_section_headers, || Ok(true))?
+            }
+
+            CliToken::LongFlag("build-id") => {
+                reject_duplicate(&token, &mut build_id, || match lexer.optional_flag_value() {
+                    None | Some("sha1") => Ok(Some(BuildIdStyle::Hash(BuildIdHash::Sha1))),
+                    Some("md5") => Ok(Some(BuildIdStyle::Hash(BuildIdHash::Md5))),
+                    Some("uuid") => Ok(Some(BuildIdStyle::Uuid)),
+                    Some("none") => Ok(None),
+                    Some(other) => Err(CliError::UnsupportedBuildIdStyle(other.into())),
+                })?;
+            }
+
+            CliToken::LongFlag("build-id-seed") => {
+                reject_duplicate(&token, &mut build_id_seed, || lexer.expect_flag_value(&token))?;
+            }
+
+            CliToken::LongFlag("print-memory-usage") => {
+                reject_duplicate(&token, &mut print_memory_usage, || Ok(true))?
+            }
+
+            CliToken::LongFlag("list-relocs") => {
+                reject_duplicate(&token, &mut list_relocs, || Ok(true))?
+            }
+
             // If the flag value was not consumed in the previous iteration when the flag itself
             // was parsed, it means the flag didn't accept a value and we should error out.
             CliToken::FlagValue(_) => {
@@ -135,18 +661,109 @@ pub(crate) fn parse<S: Into<String>, I: Iterator<Item = S>>(
         previous_token = Some(token);
     }
 
+    // Position independent executables always contain a dynamic section, which is not allowed
+    // when linking statically.
+    let static_linking = static_linking.unwrap_or(false);
+    if static_linking && mode == Some(Mode::PositionIndependent) {
+        return Err(CliError::StaticPie);
+    }
+
+    let build_id = build_id.flatten();
+    if build_id_seed.is_some() && build_id.is_none() {
+        return Err(CliError::BuildIdSeedWithoutBuildId);
+    }
+
+    if fill_byte.is_some() && pad_to.is_none() {
+        return Err(CliError::FillByteWithoutPadTo);
+    }
+
+    let no_undefined = no_undefined.unwrap_or(false);
+    if allow_undefined_file.is_some() && !no_undefined {
+        return Err(CliError::AllowUndefinedFileWithoutNoUndefined);
+    }
+
     Ok(CliOptions {
         inputs,
+        just_symbols,
         output: output.unwrap_or("a.out").into(),
         entry: entry.unwrap_or("_start").into(),
+        defsym,
+        trace_symbols,
+        wrap,
+        section_perms,
         gc_sections: gc_sections.unwrap_or(false),
+        emit_relocs: emit_relocs.unwrap_or(false),
+        icf: icf.unwrap_or(false),
+        print_icf_sections: print_icf_sections.unwrap_or(false),
+        strip_section_headers: strip_section_headers.unwrap_or(false),
+        output_format: output_format.unwrap_or(OutputFormat::Elf),
+        pad_to,
+        fill_byte: fill_byte.unwrap_or(0),
+        fill: fill.unwrap_or(0),
+        build_id,
+        build_id_seed: build_id_seed.map(|s| s.into()),
+        print_memory_usage: print_memory_usage.unwrap_or(false),
+        list_relocs: list_relocs.unwrap_or(false),
+        // Like GCC's -fmax-errors, a limit of zero means there is no limit.
+        max_errors: max_errors.filter(|&max| max != 0),
+        fatal_warnings: fatal_warnings.unwrap_or(false),
+        entry_alignment,
+        verbose,
         debug_print,
-        executable_stack: executable_stack.unwrap_or(false),
+        executable_stack,
+        warn_execstack: warn_execstack.unwrap_or(true),
+        warn_rwx_segments: warn_rwx_segments.unwrap_or(true),
+        static_linking,
         dynamic_linker: dynamic_linker.map(|s| s.into()),
+        gnu_debuglink: gnu_debuglink.map(|s| s.into()),
+        no_undefined,
+        allow_undefined_file: allow_undefined_file.map(|s| s.into()),
         mode: mode.unwrap_or(Mode::PositionDependent),
+        segments_layout: segments_layout.unwrap_or(SegmentsLayout::PageAligned),
+        no_rosegment: no_rosegment.unwrap_or(false),
+        // Only dynamic executables get it by default, as their unwinder finds it through the
+        // PT_GNU_EH_FRAME segment rather than through symbols registered by the C runtime.
+        eh_frame_hdr: eh_frame_hdr.unwrap_or(mode == Some(Mode::PositionIndependent)),
+        sort_sections: sort_sections.unwrap_or(SortSections::None),
+        symtab_sort: symtab_sort.unwrap_or(SymtabSort::None),
+        discard_locals: discard_locals.unwrap_or(DiscardLocals::None),
+        hash_style: hash_style.unwrap_or(HashStyle::Sysv),
+        symbolic: symbolic.unwrap_or(SymbolicBinding::None),
+        // Same default as GNU ld.
+        spare_dynamic_tags: spare_dynamic_tags.unwrap_or(5),
+        sort_dyn_relocs: sort_dyn_relocs.unwrap_or(true),
+        color: color.unwrap_or(ColorMode::Auto),
     })
 }
 
+fn parse_number(raw: &str) -> Option<u64> {
+    match raw.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => raw.parse(),
+    }
+    .ok()
+}
+
+fn parse_perms(raw: &str) -> Result<ElfPermissions, CliError> {
+    let mut perms = ElfPermissions { read: false, write: false, execute: false };
+    for char in raw.chars() {
+        let flag = match char {
+            'r' => &mut perms.read,
+            'w' => &mut perms.write,
+            'x' => &mut perms.execute,
+            _ => return Err(CliError::InvalidSectionPerms(raw.into())),
+        };
+        if *flag {
+            return Err(CliError::InvalidSectionPerms(raw.into()));
+        }
+        *flag = true;
+    }
+    if raw.is_empty() {
+        return Err(CliError::InvalidSectionPerms(raw.into()));
+    }
+    Ok(perms)
+}
+
 fn reject_duplicate<T, F: FnOnce() -> Result<T, CliError>>(
     token: impl ToString,
     storage: &mut Option<T>,
@@ -179,12 +796,58 @@ pub(crate) enum CliError {
     BadObjectsFilter(#[from] ObjectsFilterParseError),
     #[display("failed to parse debug print filter")]
     BadRenderElfFilter(#[from] RenderElfFiltersParseError),
+    #[display("missing value in --defsym {f0} (expected symbol=value)")]
+    MissingDefsymValue(String),
+    #[display("unsupported --defsym expression {f0:?}, only numbers are supported")]
+    UnsupportedDefsymExpression(String),
+    #[display("symbol {f0} defined multiple times with --defsym")]
+    DuplicateDefsym(String),
+    #[display("missing value in --section-perms {f0} (expected section=perms)")]
+    MissingSectionPermsValue(String),
+    #[display("invalid --section-perms permissions {f0:?}, expected a combination of r, w and x")]
+    InvalidSectionPerms(String),
+    #[display("permissions of section {f0} set multiple times with --section-perms")]
+    DuplicateSectionPerms(String),
+    #[display("unsupported --sort-sections mode {f0:?}, expected name or none")]
+    UnsupportedSortSections(String),
+    #[display("unsupported --output-symtab-sort mode {f0:?}, expected name, value or none")]
+    UnsupportedSymtabSort(String),
+    #[display("invalid --max-errors value {f0:?}, expected a number")]
+    InvalidMaxErrors(String),
+    #[display("invalid --entry-alignment value {f0:?}, expected a power of two")]
+    InvalidEntryAlignment(String),
+    #[display("invalid --spare-dynamic-tags value {f0:?}, expected a number")]
+    InvalidSpareDynamicTags(String),
+    #[display("unsupported --hash-style {f0:?}, expected sysv or none")]
+    UnsupportedHashStyle(String),
+    #[display("unsupported --oformat {f0:?}, expected elf or binary")]
+    UnsupportedOutputFormat(String),
+    #[display("unsupported --icf mode {f0:?}, expected all or none")]
+    UnsupportedIcf(String),
+    #[display("unsupported --color mode {f0:?}, expected auto, always or never")]
+    UnsupportedColorMode(String),
     #[display("debug print enabled multiple times: {f0}")]
     DuplicateDebugPrint(String),
     #[display("flag {f0} is not supported")]
     UnsupportedFlag(String),
     #[display("flag {f0} provided multiple times")]
     DuplicateFlag(String),
+    #[display("-static cannot be combined with -pie")]
+    StaticPie,
+    #[display("--build-id-seed requires --build-id")]
+    BuildIdSeedWithoutBuildId,
+    #[display("unsupported --build-id style {f0:?}, expected sha1, md5, uuid or none")]
+    UnsupportedBuildIdStyle(String),
+    #[display("invalid --pad-to value {f0:?}, expected a number")]
+    InvalidPadTo(String),
+    #[display("invalid --fill-byte value {f0:?}, expected a number between 0 and 0xff")]
+    InvalidFillByte(String),
+    #[display("--fill-byte requires --pad-to")]
+    FillByteWithoutPadTo,
+    #[display("--allow-undefined-file requires --no-undefined")]
+    AllowUndefinedFileWithoutNoUndefined,
+    #[display("invalid --fill value {f0:?}, expected a number between 0 and 0xff")]
+    InvalidFill(String),
     #[display("multiple flags changing the linking mode are passed")]
     MultipleModeChanges,
     #[display("flag {f0} does not accept values")]
@@ -231,6 +894,18 @@ impl<'a> CliLexer<'a> {
             _ => Err(CliError::MissingValueForFlag(flag.to_string())),
         }
     }
+
+    /// Value of a flag that can be passed either alone or as `--flag=value`. Unlike
+    /// [`expect_flag_value`](Self::expect_flag_value), the next argument is never consumed.
+    fn optional_flag_value(&mut self) -> Option<&'a str> {
+        match self.force_next {
+            Some(CliToken::FlagValue(value)) => {
+                self.force_next = None;
+                Some(value)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl<'a> Iterator for CliLexer<'a> {
@@ -363,6 +1038,36 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_whole_archive() {
+        let input = |path: &str, whole_archive| CliInput {
+            path: path.into(),
+            options: CliInputOptions { whole_archive },
+        };
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec![
+                    input("foo", false),
+                    input("libbar.a", true),
+                    input("libbaz.a", true),
+                    input("libquux.a", false),
+                ],
+                ..default_options()
+            }),
+            parse(
+                [
+                    "foo",
+                    "--whole-archive",
+                    "libbar.a",
+                    "libbaz.a",
+                    "--no-whole-archive",
+                    "libquux.a",
+                ]
+                .into_iter()
+            )
+        )
+    }
+
     #[test]
     fn test_output_flags() {
         const VARIANTS: &[&[&str]] = &[
@@ -439,6 +1144,88 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_defsym() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                defsym: BTreeMap::from([("bar".into(), 0x2a), ("baz".into(), 10)]),
+                ..default_options()
+            }),
+            parse(["foo", "--defsym", "bar=0x2a", "--defsym=baz=10"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_trace_symbol() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                trace_symbols: vec!["bar".into(), "baz".into()],
+                ..default_options()
+            }),
+            parse(["foo", "--trace-symbol=bar", "-y", "baz"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_section_perms() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                section_perms: BTreeMap::from([
+                    (".rodata".into(), ElfPermissions { read: true, write: true, execute: false }),
+                    (".text".into(), ElfPermissions { read: true, write: true, execute: true }),
+                ]),
+                ..default_options()
+            }),
+            parse(
+                ["foo", "--section-perms", ".rodata=rw", "--section-perms=.text=xwr"].into_iter()
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_section_perms() {
+        assert_eq!(
+            Err(CliError::MissingSectionPermsValue(".rodata".into())),
+            parse(["foo", "--section-perms", ".rodata"].into_iter())
+        );
+        for perms in ["", "rwz", "rr"] {
+            assert_eq!(
+                Err(CliError::InvalidSectionPerms(perms.into())),
+                parse(["foo", "--section-perms", &format!(".rodata={perms}")].into_iter())
+            );
+        }
+        assert_eq!(
+            Err(CliError::DuplicateSectionPerms(".rodata".into())),
+            parse(
+                ["foo", "--section-perms", ".rodata=r", "--section-perms", ".rodata=rw"]
+                    .into_iter()
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_defsym() {
+        assert_eq!(
+            Err(CliError::MissingDefsymValue("bar".into())),
+            parse(["foo", "--defsym", "bar"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::UnsupportedDefsymExpression("baz + 1".into())),
+            parse(["foo", "--defsym", "bar=baz + 1"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_defsym() {
+        assert_eq!(
+            Err(CliError::DuplicateDefsym("bar".into())),
+            parse(["foo", "--defsym", "bar=1", "--defsym", "bar=2"].into_iter())
+        );
+    }
+
     #[test]
     fn test_debug_print() {
         let variants = [
@@ -457,6 +1244,10 @@ mod tests {
                 ],
                 &["foo", "--debug-print", "loaded-object=@env", "--debug-print=relocated-object"],
             ),
+            (
+                btreeset![DebugPrint::RelocationsAnalysis],
+                &["foo", "--debug-print", "relocations-analysis"],
+            ),
         ];
         for (expected, flags) in variants {
             assert_eq!(
@@ -500,19 +1291,19 @@ mod tests {
     #[test]
     fn test_no_executable_stack_flag() {
         let args = parse(["input_file"].into_iter()).unwrap();
-        assert!(!args.executable_stack);
+        assert_eq!(None, args.executable_stack);
     }
 
     #[test]
     fn test_enabling_executable_stack() {
         let args = parse(["input_file", "-z", "execstack"].into_iter()).unwrap();
-        assert!(args.executable_stack);
+        assert_eq!(Some(true), args.executable_stack);
     }
 
     #[test]
     fn test_disabling_executable_stack() {
         let args = parse(["input_file", "-z", "noexecstack"].into_iter()).unwrap();
-        assert!(!args.executable_stack);
+        assert_eq!(Some(false), args.executable_stack);
     }
 
     #[test]
@@ -530,6 +1321,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_security_warnings() {
+        let variants = [
+            ("--warn-execstack", true, true),
+            ("--no-warn-execstack", false, true),
+            ("--warn-rwx-segments", true, true),
+            ("--no-warn-rwx-segments", true, false),
+        ];
+        for (flag, warn_execstack, warn_rwx_segments) in variants {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    warn_execstack,
+                    warn_rwx_segments,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--warn-execstack or --no-warn-execstack".into())),
+            parse(["foo", "--warn-execstack", "--no-warn-execstack"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--warn-rwx-segments or --no-warn-rwx-segments".into())),
+            parse(["foo", "--no-warn-rwx-segments", "--no-warn-rwx-segments"].into_iter())
+        );
+    }
+
     #[test]
     fn test_gc_sections() {
         assert_eq!(
@@ -539,65 +1359,817 @@ mod tests {
     }
 
     #[test]
-    fn test_duplicate_gc_sections() {
+    fn test_emit_relocs() {
+        for flag in ["-q", "--emit-relocs"] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    emit_relocs: true,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_icf() {
+        let variants = [("all", true), ("none", false)];
+        for (value, expected) in variants {
+            assert_eq!(
+                Ok(CliOptions { inputs: vec!["foo".into()], icf: expected, ..default_options() }),
+                parse(["foo", &format!("--icf={value}")].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_icf() {
         assert_eq!(
-            Err(CliError::DuplicateFlag("--gc-sections".into())),
-            parse(["foo", "--gc-sections", "--gc-sections"].into_iter())
+            Err(CliError::UnsupportedIcf("safe".into())),
+            parse(["foo", "--icf=safe"].into_iter())
         );
     }
 
     #[test]
-    fn test_dynamic_linker() {
+    fn test_print_icf_sections() {
         assert_eq!(
             Ok(CliOptions {
                 inputs: vec!["foo".into()],
-                dynamic_linker: Some("bar".into()),
+                print_icf_sections: true,
                 ..default_options()
             }),
-            parse(["foo", "--dynamic-linker=bar"].into_iter())
+            parse(["foo", "--print-icf-sections"].into_iter())
         );
     }
 
     #[test]
-    fn test_duplicate_dynamic_linker() {
+    fn test_strip_section_headers() {
         assert_eq!(
-            Err(CliError::DuplicateFlag("--dynamic-linker".into())),
-            parse(["foo", "--dynamic-linker", "bar", "--dynamic-linker=baz"].into_iter())
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                strip_section_headers: true,
+                ..default_options()
+            }),
+            parse(["foo", "--strip-section-headers"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--strip-section-headers".into())),
+            parse(["foo", "--strip-section-headers", "--strip-section-headers"].into_iter())
         );
     }
 
     #[test]
-    fn test_no_pie() {
+    fn test_print_memory_usage() {
         assert_eq!(
             Ok(CliOptions {
                 inputs: vec!["foo".into()],
-                mode: Mode::PositionDependent,
+                print_memory_usage: true,
                 ..default_options()
             }),
-            parse(["foo", "-no-pie"].into_iter())
+            parse(["foo", "--print-memory-usage"].into_iter())
         );
     }
 
     #[test]
-    fn test_pie() {
+    fn test_list_relocs() {
+        assert_eq!(
+            Ok(CliOptions { inputs: vec!["foo".into()], list_relocs: true, ..default_options() }),
+            parse(["foo", "--list-relocs"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--list-relocs".into())),
+            parse(["foo", "--list-relocs", "--list-relocs"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_just_symbols() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                just_symbols: vec!["rom.elf".into(), "bar".into(), "baz".into()],
+                ..default_options()
+            }),
+            parse(
+                ["foo", "--just-symbols=rom.elf", "-R", "bar", "--just-symbols", "baz"].into_iter()
+            )
+        );
+    }
+
+    #[test]
+    fn test_no_undefined() {
+        for flags in [&["--no-undefined"][..], &["-z", "defs"]] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    no_undefined: true,
+                    ..default_options()
+                }),
+                parse(std::iter::once("foo").chain(flags.iter().copied()))
+            );
+        }
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--no-undefined or -z defs".into())),
+            parse(["foo", "--no-undefined", "-z", "defs"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_allow_undefined_file() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                no_undefined: true,
+                allow_undefined_file: Some("allowed.txt".into()),
+                ..default_options()
+            }),
+            parse(["foo", "--no-undefined", "--allow-undefined-file", "allowed.txt"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--allow-undefined-file".into())),
+            parse(["foo", "--allow-undefined-file=a", "--allow-undefined-file=b"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::AllowUndefinedFileWithoutNoUndefined),
+            parse(["foo", "--allow-undefined-file", "allowed.txt"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                wrap: vec!["malloc".into(), "free".into()],
+                ..default_options()
+            }),
+            parse(["foo", "--wrap", "malloc", "--wrap=free"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::MissingValueForFlag("--wrap".into())),
+            parse(["foo", "--wrap"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_just_symbols_without_value() {
+        assert_eq!(
+            Err(CliError::MissingValueForFlag("-R".into())),
+            parse(["foo", "-R"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_verbose() {
+        assert_eq!(
+            Ok(CliOptions { inputs: vec!["foo".into()], verbose: 1, ..default_options() }),
+            parse(["foo", "-v"].into_iter())
+        );
+        assert_eq!(
+            Ok(CliOptions { inputs: vec!["foo".into()], verbose: 3, ..default_options() }),
+            parse(["-v", "foo", "--verbose", "-v"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_verbose_does_not_accept_values() {
+        assert_eq!(
+            Err(CliError::FlagDoesNotAcceptValues("--verbose".into())),
+            parse(["foo", "--verbose=2"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_max_errors() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                max_errors: Some(10),
+                ..default_options()
+            }),
+            parse(["foo", "--max-errors", "10"].into_iter())
+        );
+        assert_eq!(
+            Ok(CliOptions { inputs: vec!["foo".into()], max_errors: None, ..default_options() }),
+            parse(["foo", "--max-errors=0"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_build_id() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into(), "bar".into()],
+                build_id: Some(BuildIdStyle::Hash(BuildIdHash::Sha1)),
+                ..default_options()
+            }),
+            parse(["foo", "--build-id", "bar"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_build_id_styles() {
+        for (style, expected) in [
+            ("sha1", Some(BuildIdStyle::Hash(BuildIdHash::Sha1))),
+            ("md5", Some(BuildIdStyle::Hash(BuildIdHash::Md5))),
+            ("uuid", Some(BuildIdStyle::Uuid)),
+            ("none", None),
+        ] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    build_id: expected,
+                    ..default_options()
+                }),
+                parse(["foo", format!("--build-id={style}").as_str()].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_unsupported_build_id_style() {
+        assert_eq!(
+            Err(CliError::UnsupportedBuildIdStyle("sha256".into())),
+            parse(["foo", "--build-id=sha256"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_build_id_seed_with_build_id_none() {
+        assert_eq!(
+            Err(CliError::BuildIdSeedWithoutBuildId),
+            parse(["foo", "--build-id=none", "--build-id-seed=release-1"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_build_id_seed() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                build_id: Some(BuildIdStyle::Hash(BuildIdHash::Sha1)),
+                build_id_seed: Some("release-1".into()),
+                ..default_options()
+            }),
+            parse(["foo", "--build-id", "--build-id-seed=release-1"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_build_id_seed_without_build_id() {
+        assert_eq!(
+            Err(CliError::BuildIdSeedWithoutBuildId),
+            parse(["foo", "--build-id-seed=release-1"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_pad_to() {
+        assert_eq!(
+            Ok(CliOptions { inputs: vec!["foo".into()], pad_to: Some(0x100), ..default_options() }),
+            parse(["foo", "--pad-to", "0x100"].into_iter())
+        );
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                pad_to: Some(512),
+                fill_byte: 0xff,
+                ..default_options()
+            }),
+            parse(["foo", "--pad-to=512", "--fill-byte=0xff"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_invalid_pad_to() {
+        assert_eq!(
+            Err(CliError::InvalidPadTo("big".into())),
+            parse(["foo", "--pad-to", "big"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::InvalidFillByte("0x100".into())),
+            parse(["foo", "--pad-to", "16", "--fill-byte", "0x100"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::FillByteWithoutPadTo),
+            parse(["foo", "--fill-byte", "0xff"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            Ok(CliOptions { inputs: vec!["foo".into()], fill: 0x90, ..default_options() }),
+            parse(["foo", "--fill=0x90"].into_iter())
+        );
+        assert_eq!(
+            Ok(CliOptions { inputs: vec!["foo".into()], fill: 204, ..default_options() }),
+            parse(["foo", "--fill", "204"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::InvalidFill("0x1ff".into())),
+            parse(["foo", "--fill", "0x1ff"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--fill".into())),
+            parse(["foo", "--fill=1", "--fill=2"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_spare_dynamic_tags() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                spare_dynamic_tags: 0,
+                ..default_options()
+            }),
+            parse(["foo", "--spare-dynamic-tags=0"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_invalid_spare_dynamic_tags() {
+        assert_eq!(
+            Err(CliError::InvalidSpareDynamicTags("-1".into())),
+            parse(["foo", "--spare-dynamic-tags=-1"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--spare-dynamic-tags".into())),
+            parse(["foo", "--spare-dynamic-tags=1", "--spare-dynamic-tags=2"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_sort_dyn_relocs() {
+        for (flag, expected) in [("--sort-dyn-relocs", true), ("--no-sort-dyn-relocs", false)] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    sort_dyn_relocs: expected,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--sort-dyn-relocs or --no-sort-dyn-relocs".into())),
+            parse(["foo", "--sort-dyn-relocs", "--no-sort-dyn-relocs"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_fatal_warnings() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                fatal_warnings: true,
+                ..default_options()
+            }),
+            parse(["foo", "--fatal-warnings"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_entry_alignment() {
+        for (value, expected) in [("4", 4), ("0x10", 16)] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    entry_alignment: Some(expected),
+                    ..default_options()
+                }),
+                parse(["foo", &format!("--entry-alignment={value}")].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_entry_alignment() {
+        for value in ["0", "3", "four"] {
+            assert_eq!(
+                Err(CliError::InvalidEntryAlignment(value.into())),
+                parse(["foo", &format!("--entry-alignment={value}")].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_max_errors() {
+        assert_eq!(
+            Err(CliError::InvalidMaxErrors("lots".into())),
+            parse(["foo", "--max-errors=lots"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--max-errors".into())),
+            parse(["foo", "--max-errors=1", "--max-errors=2"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_gc_sections() {
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--gc-sections".into())),
+            parse(["foo", "--gc-sections", "--gc-sections"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_dynamic_linker() {
+        assert_eq!(
+            Ok(CliOptions {
+                inputs: vec!["foo".into()],
+                dynamic_linker: Some("bar".into()),
+                ..default_options()
+            }),
+            parse(["foo", "--dynamic-linker=bar"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_dynamic_linker() {
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--dynamic-linker".into())),
+            parse(["foo", "--dynamic-linker", "bar", "--dynamic-linker=baz"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_add_gnu_debuglink() {
         assert_eq!(
             Ok(CliOptions {
                 inputs: vec!["foo".into()],
-                mode: Mode::PositionIndependent,
+                gnu_debuglink: Some("foo.debug".into()),
                 ..default_options()
             }),
-            parse(["foo", "-pie"].into_iter())
+            parse(["foo", "--add-gnu-debuglink=foo.debug"].into_iter())
         );
     }
 
+    #[test]
+    fn test_duplicate_add_gnu_debuglink() {
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--add-gnu-debuglink".into())),
+            parse(["foo", "--add-gnu-debuglink", "a", "--add-gnu-debuglink=b"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_no_pie() {
+        for flag in ["-no-pie", "--no-pie"] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    mode: Mode::PositionDependent,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_pie() {
+        for flag in ["-pie", "--pie"] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    mode: Mode::PositionIndependent,
+                    eh_frame_hdr: true,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+    }
+
     #[test]
     fn test_duplicate_modes() {
+        let flags = ["-pie", "--pie", "-no-pie", "--no-pie"];
+        for first in flags {
+            for second in flags {
+                assert_eq!(
+                    Err(CliError::MultipleModeChanges),
+                    parse(["foo", first, second].into_iter())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_static() {
+        for flag in ["-static", "--static"] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    static_linking: true,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    static_linking: true,
+                    mode: Mode::PositionDependent,
+                    ..default_options()
+                }),
+                parse(["foo", flag, "-no-pie"].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_static_pie() {
+        assert_eq!(Err(CliError::StaticPie), parse(["foo", "-static", "-pie"].into_iter()));
+        assert_eq!(Err(CliError::StaticPie), parse(["foo", "--pie", "--static"].into_iter()));
+    }
+
+    #[test]
+    fn test_nmagic() {
+        for flag in ["-n", "--nmagic"] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    segments_layout: SegmentsLayout::NMagic,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_omagic() {
+        for flag in ["-N", "--omagic"] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    segments_layout: SegmentsLayout::OMagic,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicate_segments_layout() {
+        let cases = [["foo", "-n", "--nmagic"], ["foo", "-N", "--omagic"], ["foo", "-n", "-N"]];
+        for case in cases {
+            assert_eq!(
+                Err(CliError::DuplicateFlag("-n/--nmagic or -N/--omagic".into())),
+                parse(case.into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_rosegment() {
+        assert_eq!(
+            Ok(CliOptions { inputs: vec!["foo".into()], no_rosegment: true, ..default_options() }),
+            parse(["foo", "--no-rosegment"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--no-rosegment".into())),
+            parse(["foo", "--no-rosegment", "--no-rosegment"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_eh_frame_hdr() {
+        for (flag, expected) in [("--eh-frame-hdr", true), ("--no-eh-frame-hdr", false)] {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    eh_frame_hdr: expected,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--eh-frame-hdr or --no-eh-frame-hdr".into())),
+            parse(["foo", "--eh-frame-hdr", "--no-eh-frame-hdr"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_eh_frame_hdr_default() {
+        for (flags, expected) in [
+            (&[][..], false),
+            (&["-no-pie"], false),
+            (&["-pie"], true),
+            (&["-pie", "--no-eh-frame-hdr"], false),
+            (&["-no-pie", "--eh-frame-hdr"], true),
+        ] {
+            let options = parse(["foo"].into_iter().chain(flags.iter().copied())).unwrap();
+            assert_eq!(expected, options.eh_frame_hdr, "flags: {flags:?}");
+        }
+    }
+
+    #[test]
+    fn test_sort_sections() {
+        let variants = [("none", SortSections::None), ("name", SortSections::Name)];
+        for (value, expected) in variants {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    sort_sections: expected,
+                    ..default_options()
+                }),
+                parse(["foo", &format!("--sort-sections={value}")].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_symtab_sort() {
+        let variants =
+            [("none", SymtabSort::None), ("name", SymtabSort::Name), ("value", SymtabSort::Value)];
+        for (value, expected) in variants {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    symtab_sort: expected,
+                    ..default_options()
+                }),
+                parse(["foo", &format!("--output-symtab-sort={value}")].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_symtab_sort() {
+        assert_eq!(
+            Err(CliError::UnsupportedSymtabSort("size".into())),
+            parse(["foo", "--output-symtab-sort=size"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--output-symtab-sort".into())),
+            parse(["foo", "--output-symtab-sort=name", "--output-symtab-sort=none"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_invalid_sort_sections() {
+        // Sections don't track their alignment, so sorting by alignment is not supported.
+        assert_eq!(
+            Err(CliError::UnsupportedSortSections("alignment".into())),
+            parse(["foo", "--sort-sections=alignment"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--sort-sections".into())),
+            parse(["foo", "--sort-sections=name", "--sort-sections=none"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_hash_style() {
+        let variants = [("sysv", HashStyle::Sysv), ("none", HashStyle::None)];
+        for (value, expected) in variants {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    hash_style: expected,
+                    ..default_options()
+                }),
+                parse(["foo", &format!("--hash-style={value}")].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_hash_style() {
+        // GNU hash sections are not implemented yet.
+        assert_eq!(
+            Err(CliError::UnsupportedHashStyle("gnu".into())),
+            parse(["foo", "--hash-style=gnu"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--hash-style".into())),
+            parse(["foo", "--hash-style=sysv", "--hash-style=none"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_symbolic() {
+        let variants = [
+            ("-Bsymbolic", SymbolicBinding::All),
+            ("-Bsymbolic-functions", SymbolicBinding::Functions),
+        ];
+        for (flag, expected) in variants {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    symbolic: expected,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiple_symbolic() {
         assert_eq!(
-            Err(CliError::MultipleModeChanges),
-            parse(["foo", "-no-pie", "-pie"].into_iter())
+            Err(CliError::DuplicateFlag("-Bsymbolic or -Bsymbolic-functions".into())),
+            parse(["foo", "-Bsymbolic", "-Bsymbolic-functions"].into_iter())
         );
     }
 
+    #[test]
+    fn test_discard_locals() {
+        let variants = [
+            ("-X", DiscardLocals::Temporary),
+            ("--discard-locals", DiscardLocals::Temporary),
+            ("-x", DiscardLocals::All),
+            ("--discard-all", DiscardLocals::All),
+        ];
+        for (flag, expected) in variants {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    discard_locals: expected,
+                    ..default_options()
+                }),
+                parse(["foo", flag].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiple_discard_locals() {
+        for flags in [["-X", "-x"], ["-x", "--discard-all"]] {
+            assert_eq!(
+                Err(CliError::DuplicateFlag("-X/--discard-locals or -x/--discard-all".into())),
+                parse(["foo", flags[0], flags[1]].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_oformat() {
+        let variants = [("elf", OutputFormat::Elf), ("binary", OutputFormat::Binary)];
+        for (value, expected) in variants {
+            assert_eq!(
+                Ok(CliOptions {
+                    inputs: vec!["foo".into()],
+                    output_format: expected,
+                    ..default_options()
+                }),
+                parse(["foo", &format!("--oformat={value}")].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_oformat() {
+        assert_eq!(
+            Err(CliError::UnsupportedOutputFormat("srec".into())),
+            parse(["foo", "--oformat=srec"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--oformat".into())),
+            parse(["foo", "--oformat=elf", "--oformat=binary"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_color() {
+        let variants =
+            [("auto", ColorMode::Auto), ("always", ColorMode::Always), ("never", ColorMode::Never)];
+        for (value, expected) in variants {
+            assert_eq!(
+                Ok(CliOptions { inputs: vec!["foo".into()], color: expected, ..default_options() }),
+                parse(["foo", &format!("--color={value}")].into_iter())
+            );
+            assert_eq!(
+                Ok(CliOptions { inputs: vec!["foo".into()], color: expected, ..default_options() }),
+                parse(["foo", "--color", value].into_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_color() {
+        assert_eq!(
+            Err(CliError::UnsupportedColorMode("rainbow".into())),
+            parse(["foo", "--color=rainbow"].into_iter())
+        );
+        assert_eq!(
+            Err(CliError::DuplicateFlag("--color".into())),
+            parse(["foo", "--color=always", "--color=never"].into_iter())
+        );
+    }
+
+    #[test]
+    fn test_color_mode_forced() {
+        assert!(ColorMode::Always.should_color_stderr());
+        assert!(!ColorMode::Never.should_color_stderr());
+    }
+
     #[test]
     fn test_unknown_flags() {
         assert_eq!(
@@ -606,16 +2178,60 @@ mod tests {
         )
     }
 
+    impl From<&str> for CliInput {
+        fn from(path: &str) -> Self {
+            CliInput { path: path.into(), options: CliInputOptions::default() }
+        }
+    }
+
     fn default_options() -> CliOptions {
         CliOptions {
             inputs: Vec::new(),
+            just_symbols: Vec::new(),
             output: "a.out".into(),
             entry: "_start".into(),
+            defsym: BTreeMap::new(),
+            trace_symbols: Vec::new(),
+            wrap: Vec::new(),
+            section_perms: BTreeMap::new(),
             gc_sections: false,
+            emit_relocs: false,
+            icf: false,
+            print_icf_sections: false,
+            strip_section_headers: false,
+            output_format: OutputFormat::Elf,
+            pad_to: None,
+            fill_byte: 0,
+            fill: 0,
+            build_id: None,
+            build_id_seed: None,
+            print_memory_usage: false,
+            list_relocs: false,
+            max_errors: None,
+            fatal_warnings: false,
+            entry_alignment: None,
+            verbose: 0,
             debug_print: BTreeSet::new(),
-            executable_stack: false,
+            executable_stack: None,
+            warn_execstack: true,
+            warn_rwx_segments: true,
+            static_linking: false,
             dynamic_linker: None,
+            gnu_debuglink: None,
+            no_undefined: false,
+            allow_undefined_file: None,
             mode: Mode::PositionDependent,
+            segments_layout: SegmentsLayout::PageAligned,
+            no_rosegment: false,
+            eh_frame_hdr: false,
+            sort_sections: SortSections::None,
+            symtab_sort: SymtabSort::None,
+            hash_style: HashStyle::Sysv,
+            symbolic: SymbolicBinding::None,
+            discard_locals: DiscardLocals::None,
+            spare_dynamic_tags: 5,
+            sort_dyn_relocs: true,
+            color: ColorMode::Auto,
         }
     }
 }
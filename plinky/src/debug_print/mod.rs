@@ -1,51 +1,109 @@
 pub(crate) mod filters;
 mod render_gc;
+mod render_icf;
 mod render_layout;
+mod render_memory_usage;
 mod render_object;
+mod render_relocations_analysis;
 mod utils;
 
 use crate::cli::DebugPrint;
 use crate::debug_print::render_gc::render_gc;
+use crate::debug_print::render_icf::render_icf;
 use crate::debug_print::render_layout::render_layout;
+use crate::debug_print::render_memory_usage::render_memory_usage;
 use crate::debug_print::render_object::render_object;
+use crate::debug_print::render_relocations_analysis::render_relocations_analysis;
+use crate::debug_print::utils::{permissions, section_name, segment_type};
+use crate::interner::intern;
 use crate::linker::LinkerCallbacks;
 use crate::passes::build_elf::ids::BuiltElfIds;
 use crate::passes::gc_sections::RemovedSection;
+use crate::passes::icf::FoldedSections;
 use crate::passes::layout::Layout;
 use crate::repr::object::Object;
-use plinky_diagnostics::{Diagnostic, DiagnosticKind};
+use crate::repr::symbols::SymbolValue;
+use plinky_diagnostics::widgets::Widget;
+use plinky_diagnostics::{Diagnostic, DiagnosticKind, WriterOptions};
 use plinky_elf::ElfObject;
 use std::collections::BTreeSet;
 
 pub(crate) struct DebugCallbacks {
     pub(crate) print: BTreeSet<DebugPrint>,
+    pub(crate) print_memory_usage: bool,
+    pub(crate) print_icf_sections: bool,
+    pub(crate) verbose: u8,
+    pub(crate) trace_symbols: Vec<String>,
+    pub(crate) writer_options: WriterOptions,
 }
 
 impl LinkerCallbacks for DebugCallbacks {
+    fn on_warning(&self, warning: Diagnostic) {
+        self.render(warning);
+    }
+
     fn on_inputs_loaded(&self, object: &Object) {
+        for name in &self.trace_symbols {
+            let Ok(symbol) = object.symbols.get_global(intern(name)) else { continue };
+            // Like GNU ld, the traced symbols are printed to stdout rather than stderr.
+            match symbol.value {
+                SymbolValue::Undefined | SymbolValue::Null => {}
+                _ => println!("{}: definition of {name}", symbol.span),
+            }
+            for reference in object.symbols.references_to(symbol.id) {
+                println!("{reference}: reference to {name}");
+            }
+        }
         for print in &self.print {
             if let DebugPrint::LoadedObject(filters) = print {
-                render(render_object("loaded object", filters, object, None));
+                self.render(render_object("loaded object", filters, object, None));
             }
         }
     }
 
     fn on_sections_removed_by_gc(&self, object: &Object, removed: &[RemovedSection]) {
         if self.print.contains(&DebugPrint::Gc) {
-            render(render_gc(object, removed));
+            self.render(render_gc(object, removed));
+        }
+    }
+
+    fn on_sections_folded_by_icf(&self, object: &Object, folded: &[FoldedSections]) {
+        if self.print_icf_sections {
+            // Like lld, the folded sections are printed to stdout rather than stderr.
+            println!("{}", render_icf(object, folded).render_to_string());
         }
     }
 
     fn on_layout_calculated(&self, object: &Object, layout: &Layout) {
+        if self.verbose >= 2 {
+            for segment in layout.iter_segments() {
+                let sections = segment
+                    .sections
+                    .iter()
+                    .map(|id| section_name(object, *id))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                eprintln!(
+                    "verbose: placing {} segment at {:#x} (align {:#x}, {}) containing [{sections}]",
+                    segment_type(&segment.type_),
+                    segment.start,
+                    segment.align,
+                    permissions(&segment.perms),
+                );
+            }
+        }
         if self.print.contains(&DebugPrint::Layout) {
-            render(render_layout(object, layout));
+            self.render(render_layout(object, layout));
+        }
+        if self.print.contains(&DebugPrint::RelocationsAnalysis) {
+            self.render(render_relocations_analysis(object));
         }
     }
 
     fn on_relocations_applied(&self, object: &Object, layout: &Layout) {
         for print in &self.print {
             if let DebugPrint::RelocatedObject(filters) = print {
-                render(render_object(
+                self.render(render_object(
                     "object after relocations are applied",
                     filters,
                     object,
@@ -56,9 +114,13 @@ impl LinkerCallbacks for DebugCallbacks {
     }
 
     fn on_elf_built(&self, elf: &ElfObject<BuiltElfIds>) {
+        if self.print_memory_usage {
+            // Like GNU ld, the memory usage is printed to stdout rather than stderr.
+            println!("{}", render_memory_usage(elf).render_to_string());
+        }
         for print in &self.print {
             if let DebugPrint::FinalElf(filters) = print {
-                render(
+                self.render(
                     Diagnostic::new(DiagnosticKind::DebugPrint, "built elf")
                         .add(plinky_elf::render_elf::render(elf, filters)),
                 );
@@ -67,6 +129,8 @@ impl LinkerCallbacks for DebugCallbacks {
     }
 }
 
-fn render(diagnostic: Diagnostic) {
-    eprintln!("{diagnostic}\n");
+impl DebugCallbacks {
+    fn render(&self, diagnostic: Diagnostic) {
+        eprintln!("{}\n", diagnostic.render_to_string_with_options(self.writer_options));
+    }
 }
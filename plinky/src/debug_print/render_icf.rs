@@ -0,0 +1,25 @@
+use crate::debug_print::utils::section_name;
+use crate::passes::icf::FoldedSections;
+use crate::repr::object::Object;
+use plinky_diagnostics::widgets::Table;
+
+pub(super) fn render_icf(object: &Object, folded: &[FoldedSections]) -> Table {
+    let mut table = Table::new();
+    table.set_title("Sections folded by identical code folding:");
+    table.add_row(["Kept section", "Folded section", "Source"]);
+    for group in folded {
+        table.add_row([
+            section_name(object, group.kept.id),
+            String::new(),
+            group.kept.source.to_string(),
+        ]);
+        for section in &group.folded {
+            table.add_row([
+                String::new(),
+                section_name(object, section.id),
+                section.source.to_string(),
+            ]);
+        }
+    }
+    table
+}
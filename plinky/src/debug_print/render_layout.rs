@@ -1,6 +1,6 @@
-use crate::debug_print::utils::section_name;
+use crate::debug_print::utils::{section_name, segment_type};
 use crate::passes::deduplicate::Deduplication;
-use crate::passes::layout::{Layout, SectionLayout, SegmentType};
+use crate::passes::layout::{Layout, SectionLayout};
 use crate::repr::object::Object;
 use plinky_diagnostics::widgets::{Table, Widget};
 use plinky_diagnostics::{Diagnostic, DiagnosticKind};
@@ -34,13 +34,7 @@ pub(super) fn render_layout(object: &Object, layout: &Layout) -> Diagnostic {
         segments.add_row([
             format!("{:#x}", segment.start),
             format!("{:#x}", segment.align),
-            match segment.type_ {
-                SegmentType::ElfHeader => "elf header".into(),
-                SegmentType::Program => "program".into(),
-                SegmentType::Uninitialized => "uninit".into(),
-                SegmentType::Dynamic => "dynamic".into(),
-                SegmentType::Interpreter => "interpreter".into(),
-            },
+            segment_type(&segment.type_).into(),
             format!("{:?}", segment.perms),
             segment
                 .sections
@@ -0,0 +1,47 @@
+use crate::debug_print::utils::permissions;
+use crate::passes::build_elf::ids::BuiltElfIds;
+use plinky_diagnostics::widgets::Table;
+use plinky_elf::{ElfObject, ElfSectionContent, ElfSegmentContent, ElfSegmentType};
+
+pub(super) fn render_memory_usage(elf: &ElfObject<BuiltElfIds>) -> Table {
+    let mut table = Table::new();
+    table.set_title("Memory usage:");
+    table.add_row(["Segment", "Address", "File size", "Memory size"]);
+
+    let mut total_file_size = 0;
+    let mut total_memory_size = 0;
+    let load_segments = elf.segments.iter().filter(|s| s.type_ == ElfSegmentType::Load);
+    for (idx, segment) in load_segments.enumerate() {
+        let ElfSegmentContent::Sections(section_ids) = &segment.content else { continue };
+
+        let mut address = None;
+        let mut file_size = 0;
+        let mut memory_size = 0;
+        for id in section_ids {
+            let section = &elf.sections[id];
+            let size = section.content.content_size(elf.env.class) as u64;
+            address.get_or_insert(section.memory_address);
+            if !matches!(section.content, ElfSectionContent::Uninitialized(_)) {
+                file_size += size;
+            }
+            memory_size += size;
+        }
+        total_file_size += file_size;
+        total_memory_size += memory_size;
+
+        table.add_row([
+            format!("#{idx} ({})", permissions(&segment.perms)),
+            format!("{:#x}", address.unwrap_or(0)),
+            format!("{file_size:#x}"),
+            format!("{memory_size:#x}"),
+        ]);
+    }
+    table.add_row([
+        "Total".to_string(),
+        String::new(),
+        format!("{total_file_size:#x}"),
+        format!("{total_memory_size:#x}"),
+    ]);
+
+    table
+}
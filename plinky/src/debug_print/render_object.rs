@@ -72,6 +72,11 @@ fn render_data_section(
         }
     };
 
+    let link_order = object
+        .link_order
+        .get(&section.id)
+        .map(|&linked| Text::new(format!("ordered with section {}", section_name(object, linked))));
+
     let relocations = if data.relocations.is_empty() {
         None
     } else {
@@ -87,6 +92,7 @@ fn render_data_section(
                 section.source
             ))
             .add_iter(deduplication)
+            .add_iter(link_order)
             .add_iter(render_layout(layout, section.id))
             .add(HexDump::new(data.bytes.clone()))
             .add_iter(relocations),
@@ -144,13 +150,19 @@ fn render_symbols<'a>(
             SymbolType::Function => "function",
             SymbolType::Object => "object",
             SymbolType::Section => "section",
+            SymbolType::GnuIfunc => "gnu ifunc",
+            SymbolType::Tls => "tls",
         };
         let visibility = match symbol.visibility {
             SymbolVisibility::Local => "local",
-            SymbolVisibility::Global { weak: true, hidden: true } => "global (weak, hidden)",
-            SymbolVisibility::Global { weak: true, hidden: false } => "global (weak)",
-            SymbolVisibility::Global { weak: false, hidden: true } => "global (hidden)",
-            SymbolVisibility::Global { weak: false, hidden: false } => "global",
+            SymbolVisibility::Global { weak: true, hidden: true, .. } => "global (weak, hidden)",
+            SymbolVisibility::Global { weak: true, protected: true, .. } => {
+                "global (weak, protected)"
+            }
+            SymbolVisibility::Global { weak: true, .. } => "global (weak)",
+            SymbolVisibility::Global { hidden: true, .. } => "global (hidden)",
+            SymbolVisibility::Global { protected: true, .. } => "global (protected)",
+            SymbolVisibility::Global { .. } => "global",
         };
         let value = match symbol.value {
             SymbolValue::Absolute { value } => format!("{value}"),
@@ -160,6 +172,9 @@ fn render_symbols<'a>(
             SymbolValue::SectionVirtualAddress { section, memory_address } => {
                 format!("{memory_address} (in {})", section_name(object, section))
             }
+            SymbolValue::Common { size, align } => {
+                format!("<common> (size: {size:#x}, align: {align:#x})")
+            }
             SymbolValue::Undefined => "<undefined>".into(),
             SymbolValue::Null => "<null>".into(),
         };
@@ -0,0 +1,71 @@
+use crate::cli::Mode;
+use crate::debug_print::utils::{section_name, symbol_name};
+use crate::repr::object::Object;
+use crate::repr::relocations::{Relocation, RelocationType};
+use crate::repr::sections::SectionContent;
+use crate::repr::symbols::SymbolType;
+use plinky_diagnostics::widgets::{Table, Text};
+use plinky_diagnostics::{Diagnostic, DiagnosticKind};
+
+pub(super) fn render_relocations_analysis(object: &Object) -> Diagnostic {
+    let mut table = Table::new();
+    table.add_row(["Section", "Offset", "Type", "Symbol", "Decision"]);
+    let mut empty = true;
+    for section in object.sections.iter() {
+        let SectionContent::Data(data) = &section.content else { continue };
+        for relocation in &data.relocations {
+            table.add_row([
+                section_name(object, section.id),
+                format!("{}", relocation.offset),
+                format!("{:?}", relocation.type_),
+                symbol_name(object, relocation.symbol),
+                decision(object, relocation).into(),
+            ]);
+            empty = false;
+        }
+    }
+
+    let diagnostic = Diagnostic::new(DiagnosticKind::DebugPrint, "relocations analysis");
+    if empty {
+        diagnostic.add(Text::new("no relocations to apply"))
+    } else {
+        diagnostic.add(table)
+    }
+}
+
+/// Explain how the relocation will be applied, mirroring the logic of the relocate pass.
+fn decision(object: &Object, relocation: &Relocation) -> &'static str {
+    let symbol = object.symbols.get(relocation.symbol);
+    let ifunc = matches!(symbol.type_, SymbolType::GnuIfunc);
+    let interposable = symbol.can_be_interposed(object.symbolic);
+    match (relocation.type_, object.mode) {
+        (RelocationType::GOTRelative32 | RelocationType::GOTIndex32, _) => "needs GOT slot",
+        (RelocationType::FillGOTSlot, Mode::PositionIndependent) if ifunc => {
+            "needs IRELATIVE dynamic relocation"
+        }
+        (RelocationType::FillGOTSlot, Mode::PositionIndependent) if !interposable => {
+            "needs RELATIVE dynamic relocation"
+        }
+        (RelocationType::FillGOTSlot, Mode::PositionIndependent) => "needs dynamic relocation",
+        (RelocationType::Absolute64, Mode::PositionIndependent) if !interposable => {
+            "needs RELATIVE dynamic relocation"
+        }
+        (RelocationType::Absolute64, Mode::PositionIndependent) => "needs dynamic relocation",
+        (RelocationType::IRelative, _) => "needs IRELATIVE dynamic relocation",
+        (RelocationType::Relative, _) => "needs RELATIVE dynamic relocation",
+        // There is no PLT: calls are always resolved to the symbol itself.
+        (RelocationType::PLT32, _) => "resolved statically (direct call)",
+        (
+            RelocationType::Absolute32
+            | RelocationType::AbsoluteSigned32
+            | RelocationType::Absolute64
+            | RelocationType::Size32
+            | RelocationType::Size64
+            | RelocationType::Relative32
+            | RelocationType::GOTLocationRelative32
+            | RelocationType::OffsetFromGOT32
+            | RelocationType::FillGOTSlot,
+            _,
+        ) => "resolved statically",
+    }
+}
@@ -1,3 +1,4 @@
+use crate::passes::layout::SegmentType;
 use crate::repr::object::Object;
 use crate::repr::symbols::SymbolValue;
 use crate::utils::ints::ExtractNumber;
@@ -23,6 +24,18 @@ pub(super) fn permissions(perms: &ElfPermissions) -> String {
     }
 }
 
+pub(super) fn segment_type(type_: &SegmentType) -> &'static str {
+    match type_ {
+        SegmentType::ElfHeader => "elf header",
+        SegmentType::Program => "program",
+        SegmentType::Uninitialized => "uninit",
+        SegmentType::Dynamic => "dynamic",
+        SegmentType::Interpreter => "interpreter",
+        SegmentType::Tls => "tls",
+        SegmentType::Note => "note",
+    }
+}
+
 pub(super) fn section_name(object: &Object, id: SectionId) -> String {
     object
         .sections
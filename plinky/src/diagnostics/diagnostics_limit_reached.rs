@@ -0,0 +1,8 @@
+use plinky_diagnostics::widgets::Text;
+use plinky_diagnostics::{Diagnostic, DiagnosticKind};
+
+pub(crate) fn build(hidden: usize) -> Diagnostic {
+    let plural = if hidden == 1 { "" } else { "s" };
+    Diagnostic::new(DiagnosticKind::Warning, format!("… and {hidden} more diagnostic{plural}"))
+        .add(Text::new("the limit set with --max-errors was reached"))
+}
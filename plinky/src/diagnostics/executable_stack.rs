@@ -0,0 +1,11 @@
+use plinky_diagnostics::widgets::Text;
+use plinky_diagnostics::{Diagnostic, DiagnosticKind};
+
+pub(crate) fn build() -> Diagnostic {
+    Diagnostic::new(DiagnosticKind::Warning, "the output requires an executable stack").add(
+        Text::new(
+            "an executable stack makes memory corruption bugs easier to exploit, pass \
+             -z noexecstack if no input needs it or --no-warn-execstack to silence this",
+        ),
+    )
+}
@@ -0,0 +1,13 @@
+use plinky_diagnostics::widgets::Text;
+use plinky_diagnostics::{Diagnostic, DiagnosticKind};
+
+pub(crate) fn build(symbol: &str, address: u64, alignment: u64) -> Diagnostic {
+    Diagnostic::new(
+        DiagnosticKind::Warning,
+        format!("entry point {symbol} at {address:#x} is not aligned to {alignment:#x} bytes"),
+    )
+    .add(Text::new(
+        "the program might crash when it starts, as the machine might not be able to execute \
+         instructions at this address",
+    ))
+}
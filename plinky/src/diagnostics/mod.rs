@@ -1 +1,7 @@
+pub(crate) mod diagnostics_limit_reached;
+pub(crate) mod executable_stack;
+pub(crate) mod misaligned_entry_point;
 pub(crate) mod no_symbol_table_at_archive_start;
+pub(crate) mod rwx_segment;
+pub(crate) mod symbol_with_gnu_warning;
+pub(crate) mod text_relocation;
@@ -0,0 +1,13 @@
+use plinky_diagnostics::widgets::Text;
+use plinky_diagnostics::{Diagnostic, DiagnosticKind};
+
+pub(crate) fn build(address: u64, sections: &[String]) -> Diagnostic {
+    Diagnostic::new(
+        DiagnosticKind::Warning,
+        format!("segment at {address:#x} is readable, writable and executable"),
+    )
+    .add(Text::new(format!(
+        "the segment contains {}, pass --no-warn-rwx-segments to silence this",
+        sections.join(", ")
+    )))
+}
@@ -0,0 +1,7 @@
+use plinky_diagnostics::widgets::Text;
+use plinky_diagnostics::{Diagnostic, DiagnosticKind, ObjectSpan};
+
+pub(crate) fn build(symbol: &str, message: &str, section: &str, source: &ObjectSpan) -> Diagnostic {
+    Diagnostic::new(DiagnosticKind::Warning, message)
+        .add(Text::new(format!("symbol `{symbol}` is referenced by section {section} in {source}")))
+}
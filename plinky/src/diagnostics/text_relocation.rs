@@ -0,0 +1,13 @@
+use plinky_diagnostics::widgets::Text;
+use plinky_diagnostics::{Diagnostic, DiagnosticKind, ObjectSpan};
+
+pub(crate) fn build(section: &str, source: &ObjectSpan) -> Diagnostic {
+    Diagnostic::new(
+        DiagnosticKind::Warning,
+        format!("read-only section {section} requires dynamic relocations"),
+    )
+    .add(Text::new(format!(
+        "the dynamic loader will have to make the section writable to relocate it (section \
+         defined in {source}), consider compiling it as position independent code"
+    )))
+}
@@ -1,28 +1,60 @@
 use crate::cli::CliOptions;
+use crate::diagnostics::diagnostics_limit_reached;
 use crate::passes;
 use crate::passes::build_elf::ids::BuiltElfIds;
 use crate::passes::build_elf::ElfBuilderError;
+use crate::passes::build_id::BuildIdError;
 use crate::passes::deduplicate::DeduplicationError;
 use crate::passes::gc_sections::RemovedSection;
+use crate::passes::icf::FoldedSections;
+use crate::passes::inject_gnu_debuglink::InjectGnuDebuglinkError;
+use crate::passes::inject_interpreter::InjectInterpreterError;
 use crate::passes::layout::Layout;
 use crate::passes::load_inputs::LoadInputsError;
+use crate::passes::merge_functions_arrays::MergeFunctionsArraysError;
+use crate::passes::merge_tls::MergeTlsError;
 use crate::passes::relocate::RelocationError;
 use crate::passes::replace_section_relative_symbols::ReplaceSectionRelativeSymbolsError;
+use crate::passes::undefined_symbols::UndefinedSymbolsError;
 use crate::passes::write_to_disk::WriteToDiskError;
 use crate::repr::object::Object;
+use plinky_diagnostics::Diagnostic;
 use plinky_elf::ids::serial::SerialIds;
 use plinky_elf::ElfObject;
 use plinky_macros::{Display, Error};
-use crate::passes::inject_interpreter::InjectInterpreterError;
 
 pub(crate) fn link_driver(
     options: &CliOptions,
     callbacks: &dyn LinkerCallbacks,
 ) -> Result<(), LinkerError> {
+    let mut warnings = Vec::new();
+    let elf = build(options, callbacks, &mut warnings);
+    // An error stops linking, and counts towards the --max-errors limit like the warnings.
+    let warnings_count = warnings.len();
+    emit_warnings(options, callbacks, warnings, elf.is_err());
+    let elf = elf?;
+
+    if options.fatal_warnings && warnings_count > 0 {
+        return Err(LinkerError::FatalWarnings(warnings_count));
+    }
+    passes::write_to_disk::run(elf, options)?;
+
+    Ok(())
+}
+
+/// Run all the passes up to building the final ELF, collecting the warnings they emit.
+fn build(
+    options: &CliOptions,
+    callbacks: &dyn LinkerCallbacks,
+    warnings: &mut Vec<Diagnostic>,
+) -> Result<ElfObject<BuiltElfIds>, LinkerError> {
     let mut ids = SerialIds::new();
 
     let mut object = passes::load_inputs::run(options, &mut ids)?;
+    passes::allocate_common::run(&mut ids, &mut object);
+    passes::merge_functions_arrays::run(&mut ids, &mut object)?;
     let interp_section = passes::inject_interpreter::run(&options, &mut ids, &mut object)?;
+    passes::inject_gnu_debuglink::run(options, &mut ids, &mut object)?;
     callbacks.on_inputs_loaded(&object);
 
     if options.gc_sections {
@@ -30,33 +62,71 @@ pub(crate) fn link_driver(
         callbacks.on_sections_removed_by_gc(&object, &removed);
     }
 
+    if options.icf {
+        let folded = passes::icf::run(&mut object);
+        callbacks.on_sections_folded_by_icf(&object, &folded);
+    }
+
+    // This runs after garbage collection, to avoid warning about references in removed sections.
+    warnings.extend(passes::gnu_warnings::run(&object));
+    passes::undefined_symbols::check(options, &object)?;
+
+    passes::merge_tls::run(&mut object)?;
+
+    passes::build_id::inject(options, &mut ids, &mut object);
+
     let deduplications = passes::deduplicate::run(&mut object, &mut ids)?;
 
     passes::generate_got::generate_got(&mut ids, &mut object);
 
     let layout = passes::layout::run(&object, deduplications, interp_section);
     callbacks.on_layout_calculated(&object, &layout);
+    warnings.extend(passes::security_warnings::run(options, &object, &layout));
 
     passes::relocate::run(&mut object, &layout)?;
+    warnings.extend(passes::relocate::text_relocation_warnings(&object));
     callbacks.on_relocations_applied(&object, &layout);
 
     passes::remove_section_symbols::remove(&mut object);
     passes::replace_section_relative_symbols::replace(&mut object, &layout)?;
     passes::demote_global_hidden_symbols::run(&mut object);
 
-    let elf = passes::build_elf::run(object, layout, ids)?;
+    let (mut elf, build_warnings) = passes::build_elf::run(object, layout, ids)?;
+    warnings.extend(build_warnings);
+    passes::build_id::fill(options, &mut elf)?;
     callbacks.on_elf_built(&elf);
 
-    passes::write_to_disk::run(elf, &options.output)?;
+    Ok(elf)
+}
 
-    Ok(())
+/// Send the warnings to the callbacks, replacing the ones past `--max-errors` with a note. The
+/// error that stopped linking (if any) is reported afterwards, and takes one of the slots.
+fn emit_warnings(
+    options: &CliOptions,
+    callbacks: &dyn LinkerCallbacks,
+    warnings: Vec<Diagnostic>,
+    failed: bool,
+) {
+    let total = warnings.len();
+    let limit = options.max_errors.map(|max| max.saturating_sub(failed as usize));
+    let shown = limit.unwrap_or(total).min(total);
+    for warning in warnings.into_iter().take(shown) {
+        callbacks.on_warning(warning);
+    }
+    if shown < total {
+        callbacks.on_warning(diagnostics_limit_reached::build(total - shown));
+    }
 }
 
 pub(crate) trait LinkerCallbacks {
+    fn on_warning(&self, _warning: Diagnostic) {}
+
     fn on_inputs_loaded(&self, _object: &Object) {}
 
     fn on_sections_removed_by_gc(&self, _object: &Object, _removed: &[RemovedSection]) {}
 
+    fn on_sections_folded_by_icf(&self, _object: &Object, _folded: &[FoldedSections]) {}
+
     fn on_layout_calculated(&self, _object: &Object, _layout: &Layout) {}
 
     fn on_relocations_applied(&self, _object: &Object, _layout: &Layout) {}
@@ -71,8 +141,18 @@ pub(crate) enum LinkerError {
     #[transparent]
     DeduplicationFailed(DeduplicationError),
     #[transparent]
+    MergeFunctionsArraysFailed(MergeFunctionsArraysError),
+    #[transparent]
+    MergeTlsFailed(MergeTlsError),
+    #[transparent]
     InjectInterpreterFailed(InjectInterpreterError),
     #[transparent]
+    InjectGnuDebuglinkFailed(InjectGnuDebuglinkError),
+    #[transparent]
+    BuildIdFailed(BuildIdError),
+    #[transparent]
+    UndefinedSymbolsCheckFailed(UndefinedSymbolsError),
+    #[transparent]
     RelocationFailed(RelocationError),
     #[transparent]
     ElfBuildFailed(ElfBuilderError),
@@ -80,4 +160,6 @@ pub(crate) enum LinkerError {
     ReplaceSectionRelativeSymbolsFailed(ReplaceSectionRelativeSymbolsError),
     #[transparent]
     WriteToDiskFailed(WriteToDiskError),
+    #[display("{f0} warning(s) treated as errors due to --fatal-warnings")]
+    FatalWarnings(usize),
 }
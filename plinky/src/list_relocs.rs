@@ -0,0 +1,34 @@
+use crate::cli::CliOptions;
+use crate::passes::load_inputs::read_objects::{ObjectsReader, ReadObjectsError};
+use crate::repr::symbols::Symbols;
+use plinky_diagnostics::widgets::Widget;
+use plinky_elf::ids::serial::SerialIds;
+use plinky_macros::{Display, Error};
+
+/// Print the relocations of each input (with `--list-relocs`), without linking them.
+///
+/// Inputs are read the same way as when linking, except that all the members of archives are
+/// listed, as there are no undefined symbols to decide which ones would be needed.
+pub(crate) fn list_relocs(options: &CliOptions) -> Result<(), ListRelocsError> {
+    if options.inputs.is_empty() {
+        return Err(ListRelocsError::NoInputFiles);
+    }
+
+    let mut ids = SerialIds::new();
+    let symbols = Symbols::new(&mut ids);
+    let mut reader = ObjectsReader::all_archive_members(&options.inputs, options.verbose >= 1);
+    while let Some((source, object)) = reader.next_object(&mut ids, &symbols)? {
+        println!("relocations of {source}:");
+        println!("{}", plinky_elf::render_elf::render_relocations(&object).render_to_string());
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Error, Display)]
+pub(crate) enum ListRelocsError {
+    #[display("no input files were provided")]
+    NoInputFiles,
+    #[transparent]
+    ReadFailed(ReadObjectsError),
+}
@@ -1,10 +1,14 @@
 #![feature(error_generic_member_access)]
 #![feature(array_windows)]
 
+use crate::cli::{CliOptions, ColorMode};
 use crate::debug_print::DebugCallbacks;
 use crate::linker::link_driver;
-use plinky_diagnostics::Diagnostic;
+use crate::list_relocs::list_relocs;
+use plinky_diagnostics::widgets::Widget;
+use plinky_diagnostics::{Diagnostic, Style, WidgetWriter, WriterOptions};
 use std::error::{request_ref, Error};
+use std::io::IsTerminal;
 use std::process::ExitCode;
 
 mod cli;
@@ -12,20 +16,30 @@ mod debug_print;
 mod diagnostics;
 mod interner;
 mod linker;
+mod list_relocs;
 mod passes;
 mod repr;
 mod utils;
 
-fn app() -> Result<(), Box<dyn Error>> {
-    let options = cli::parse(std::env::args().skip(1))?;
-
-    let callbacks = DebugCallbacks { print: options.debug_print.clone() };
-    link_driver(&options, &callbacks)?;
+fn app(options: &CliOptions) -> Result<(), Box<dyn Error>> {
+    let callbacks = DebugCallbacks {
+        print: options.debug_print.clone(),
+        print_memory_usage: options.print_memory_usage,
+        print_icf_sections: options.print_icf_sections,
+        verbose: options.verbose,
+        trace_symbols: options.trace_symbols.clone(),
+        writer_options: stderr_writer_options(options.color),
+    };
+    if options.list_relocs {
+        list_relocs(options)?;
+    } else {
+        link_driver(options, &callbacks)?;
+    }
 
     Ok(())
 }
 
-fn render_error(err: Box<dyn Error>) -> ExitCode {
+fn render_error(err: Box<dyn Error>, writer_options: WriterOptions) -> ExitCode {
     let mut diagnostic = None;
     let mut current: Option<&(dyn Error + 'static)> = Some(&*err);
     while let Some(current_err) = current {
@@ -37,9 +51,11 @@ fn render_error(err: Box<dyn Error>) -> ExitCode {
     }
 
     if let Some(diagnostic) = diagnostic {
-        eprintln!("{diagnostic}");
+        eprintln!("{}", diagnostic.render_to_string_with_options(writer_options));
     } else {
-        eprintln!("error: {err}");
+        let mut prefix = String::new();
+        WidgetWriter::with_options(&mut prefix, writer_options).push_styled(Style::Error, "error");
+        eprintln!("{prefix}: {err}");
 
         let mut source = err.source();
         while let Some(s) = source {
@@ -52,8 +68,22 @@ fn render_error(err: Box<dyn Error>) -> ExitCode {
 }
 
 fn main() -> ExitCode {
-    match app() {
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        // We cannot know the value of --color if parsing failed, so use the default.
+        Err(err) => return render_error(Box::new(err), stderr_writer_options(ColorMode::Auto)),
+    };
+
+    match app(&options) {
         Ok(()) => ExitCode::SUCCESS,
-        Err(err) => render_error(err),
+        Err(err) => render_error(err, stderr_writer_options(options.color)),
     }
 }
+
+fn stderr_writer_options(color: ColorMode) -> WriterOptions {
+    // Only wrap lines when a human is reading the output, to avoid mangling redirected output.
+    let max_width = std::io::stderr().is_terminal().then(|| {
+        std::env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()).unwrap_or(80)
+    });
+    WriterOptions { color: color.should_color_stderr(), max_width }
+}
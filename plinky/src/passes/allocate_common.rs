@@ -0,0 +1,40 @@
+use crate::interner::intern;
+use crate::repr::object::Object;
+use crate::repr::sections::{Section, SectionContent, UninitializedSection};
+use crate::repr::symbols::{Symbol, SymbolValue};
+use crate::utils::ints::align_up;
+use plinky_diagnostics::ObjectSpan;
+use plinky_elf::ids::serial::SerialIds;
+use plinky_elf::ElfPermissions;
+
+/// Give storage to the common symbols (tentative definitions not overridden by a real one), by
+/// allocating all of them in a synthesized `.bss` section. Each symbol is placed at an offset
+/// aligned to its own alignment, and is then moved to point to its slot in the section. The
+/// section itself is aligned to the largest of those alignments.
+pub(crate) fn run(ids: &mut SerialIds, object: &mut Object) {
+    let is_common =
+        |(_id, symbol): (_, &Symbol)| matches!(symbol.value, SymbolValue::Common { .. });
+    if !object.symbols.iter().any(is_common) {
+        return;
+    }
+
+    let id = ids.allocate_section_id();
+    let mut len = 0;
+    let mut section_align = 1;
+    for (_id, symbol) in object.symbols.iter_mut() {
+        let SymbolValue::Common { size, align } = symbol.value else { continue };
+        section_align = section_align.max(align);
+        let offset = align_up(len, align);
+        len = offset + size;
+        symbol.size = size;
+        symbol.value = SymbolValue::SectionRelative { section: id, offset: (offset as i64).into() };
+    }
+
+    object.sections.add(Section {
+        id,
+        name: intern(".bss"),
+        perms: ElfPermissions { read: true, write: true, execute: false },
+        source: ObjectSpan::new_synthetic(),
+        content: SectionContent::Uninitialized(UninitializedSection { len, align: section_align }),
+    });
+}
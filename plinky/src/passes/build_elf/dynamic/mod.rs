@@ -1,18 +1,33 @@
 mod sysv_hash;
 
 use super::symbols::SymbolTableKind;
+use crate::cli::{HashStyle, SymbolicBinding};
 use crate::passes::build_elf::dynamic::sysv_hash::create_sysv_hash;
 use crate::passes::build_elf::relocations::create_rela;
 use crate::passes::build_elf::symbols::create_symbols;
-use crate::passes::build_elf::ElfBuilder;
+use crate::passes::build_elf::{ElfBuilder, ElfBuilderError};
 use crate::passes::layout::{SectionLayout, Segment, SegmentType};
+use crate::repr::relocations::{Relocation, RelocationType};
 use crate::utils::ints::ExtractNumber;
 use plinky_elf::raw::{RawRela, RawSymbol};
 use plinky_elf::{
-    ElfDynamic, ElfDynamicDirective, ElfDynamicFlags1, ElfPermissions, ElfSectionContent,
+    ElfDynamic, ElfDynamicDirective, ElfDynamicFlags, ElfDynamicFlags1, ElfFunctionsArray,
+    ElfPermissions, ElfSectionContent,
 };
 use plinky_utils::raw_types::{RawType, RawTypeAsPointerSize};
 
+/// Dynamic relocations are emitted in the order relocations are processed, which doesn't match
+/// the order of their addresses when multiple sections need them. Loaders process them faster
+/// when sorted, and the sort is stable, keeping the output deterministic.
+///
+/// IRELATIVE relocations are kept last regardless of their address, as their resolvers can only
+/// run once all the other relocations have been applied.
+fn sort_by_address(relocations: &mut [Relocation]) {
+    relocations.sort_by_key(|relocation| {
+        (relocation.type_ == RelocationType::IRelative, relocation.offset)
+    });
+}
+
 macro_rules! add_section {
     ($builder:expr, $segment:expr, $name:expr, $content:expr) => {
         add_section!(
@@ -47,7 +62,7 @@ macro_rules! add_section {
     }};
 }
 
-pub(crate) fn add(builder: &mut ElfBuilder) {
+pub(crate) fn add(builder: &mut ElfBuilder) -> Result<(), ElfBuilderError> {
     let bits = builder.object.env.class;
     let mut segment = builder.layout.prepare_segment();
 
@@ -66,6 +81,9 @@ pub(crate) fn add(builder: &mut ElfBuilder) {
     let dynsym = builder.ids.allocate_section_id();
     let dynsym_addr = add_section!(builder, segment, ".dynsym", symbols.symbol_table, dynsym);
 
+    if builder.object.sort_dyn_relocs {
+        sort_by_address(&mut builder.object.dynamic_relocations);
+    }
     let rela = create_rela(
         builder.object.dynamic_relocations.iter(),
         builder.object.env.class,
@@ -76,32 +94,82 @@ pub(crate) fn add(builder: &mut ElfBuilder) {
     let rela_len = rela.content_size(bits);
     let rela_addr = add_section!(builder, segment, ".rela.dyn", rela);
 
-    let hash_addr = add_section!(
-        builder,
-        segment,
-        ".hash",
-        create_sysv_hash(
-            builder.object.symbols.iter_dynamic_symbols().map(|(_id, sym)| sym),
-            dynsym,
-        )
-    );
+    let mut directives = Vec::new();
+    match builder.object.hash_style {
+        HashStyle::Sysv => {
+            let hash_addr = add_section!(
+                builder,
+                segment,
+                ".hash",
+                create_sysv_hash(
+                    builder.object.symbols.iter_dynamic_symbols().map(|(_id, sym)| sym),
+                    dynsym,
+                )
+            );
+            directives.push(ElfDynamicDirective::Hash { address: hash_addr.extract() });
+        }
+        HashStyle::None => {
+            if builder.object.symbols.has_dynamic_symbols() {
+                return Err(ElfBuilderError::HashRequiredForDynamicSymbols);
+            }
+        }
+    }
 
     let dynamic_id = builder.ids.allocate_section_id();
     let dynamic_old_id = builder.old_ids.allocate_section_id();
+    directives.extend([
+        ElfDynamicDirective::StringTable { address: dynstr_addr.extract() },
+        ElfDynamicDirective::StringTableSize { bytes: dynstr_len as _ },
+        ElfDynamicDirective::SymbolTable { address: dynsym_addr.extract() },
+        ElfDynamicDirective::SymbolTableEntrySize { bytes: RawSymbol::size(bits) as _ },
+        ElfDynamicDirective::Rela { address: rela_addr.extract() },
+        ElfDynamicDirective::RelaSize { bytes: rela_len as _ },
+        ElfDynamicDirective::RelaEntrySize { bytes: RawRela::size(bits) as _ },
+        ElfDynamicDirective::Flags1(ElfDynamicFlags1 { pie: true }),
+    ]);
+    for (&old_id, &kind) in &builder.object.functions_arrays {
+        let Some(section) = builder.sections.built_from(old_id) else { continue };
+        let address = section.memory_address;
+        let bytes = section.content.content_size(bits) as u64;
+        directives.extend(match kind {
+            ElfFunctionsArray::PreInit => [
+                ElfDynamicDirective::PreInitArray { address },
+                ElfDynamicDirective::PreInitArraySize { bytes },
+            ],
+            ElfFunctionsArray::Init => [
+                ElfDynamicDirective::InitArray { address },
+                ElfDynamicDirective::InitArraySize { bytes },
+            ],
+            ElfFunctionsArray::Fini => [
+                ElfDynamicDirective::FiniArray { address },
+                ElfDynamicDirective::FiniArraySize { bytes },
+            ],
+        });
+    }
+    // Like GNU ld, both the legacy DT_TEXTREL and DF_TEXTREL in DT_FLAGS are emitted.
+    if !builder.object.text_relocations.is_empty() {
+        directives.extend([
+            ElfDynamicDirective::RelocationsWillModifyText,
+            ElfDynamicDirective::Flags(ElfDynamicFlags {
+                origin: false,
+                symbolic: false,
+                text_relocations: true,
+                bind_now: false,
+                static_tls: false,
+            }),
+        ]);
+    }
+    // GNU ld only sets DT_SYMBOLIC for -Bsymbolic, as -Bsymbolic-functions doesn't apply to all
+    // the symbols of the output.
+    if builder.object.symbolic == SymbolicBinding::All {
+        directives.push(ElfDynamicDirective::Symbolic);
+    }
+    // Spare entries are added after the terminator, as the dynamic loader stops at the first one.
+    // The section size is computed from the directives, so they are included in the layout.
+    directives.extend((0..=builder.object.spare_dynamic_tags).map(|_| ElfDynamicDirective::Null));
     let dynamic = ElfSectionContent::Dynamic(ElfDynamic {
         string_table: symbols.string_table_id,
-        directives: vec![
-            ElfDynamicDirective::Hash { address: hash_addr.extract() },
-            ElfDynamicDirective::StringTable { address: dynstr_addr.extract() },
-            ElfDynamicDirective::StringTableSize { bytes: dynstr_len as _ },
-            ElfDynamicDirective::SymbolTable { address: dynsym_addr.extract() },
-            ElfDynamicDirective::SymbolTableEntrySize { bytes: RawSymbol::size(bits) as _ },
-            ElfDynamicDirective::Rela { address: rela_addr.extract() },
-            ElfDynamicDirective::RelaSize { bytes: rela_len as _ },
-            ElfDynamicDirective::RelaEntrySize { bytes: RawRela::size(bits) as _ },
-            ElfDynamicDirective::Flags1(ElfDynamicFlags1 { pie: true }),
-            ElfDynamicDirective::Null,
-        ],
+        directives,
     });
     let dynamic_addr =
         add_section!(builder, segment, ".dynamic", dynamic, dynamic_id, dynamic_old_id);
@@ -113,7 +181,9 @@ pub(crate) fn add(builder: &mut ElfBuilder) {
 
     builder.layout.add_segment(Segment {
         start: dynamic_addr.extract(),
-        align: <u64 as RawTypeAsPointerSize>::size(bits) as _,
+        align: builder
+            .layout
+            .alignment_of(SegmentType::Dynamic, <u64 as RawTypeAsPointerSize>::size(bits) as _),
         type_: SegmentType::Dynamic,
         perms: ElfPermissions { read: true, write: false, execute: false },
         sections: vec![dynamic_old_id],
@@ -126,4 +196,44 @@ pub(crate) fn add(builder: &mut ElfBuilder) {
         perms: ElfPermissions { read: true, write: false, execute: false },
         sections: Vec::new(),
     });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plinky_elf::ids::serial::SerialIds;
+
+    #[test]
+    fn test_sort_by_address() {
+        let mut ids = SerialIds::new();
+        let mut relocation = |type_, offset: i64| Relocation {
+            type_,
+            symbol: ids.allocate_symbol_id(),
+            offset: offset.into(),
+            addend: Some(0.into()),
+        };
+        let mut relocations = vec![
+            relocation(RelocationType::Relative, 0x3010),
+            relocation(RelocationType::IRelative, 0x1000),
+            relocation(RelocationType::Relative, 0x2000),
+            relocation(RelocationType::Relative, 0x3008),
+            relocation(RelocationType::IRelative, 0x0800),
+            relocation(RelocationType::Relative, 0x3000),
+        ];
+
+        sort_by_address(&mut relocations);
+        assert_eq!(
+            vec![
+                (RelocationType::Relative, 0x2000),
+                (RelocationType::Relative, 0x3000),
+                (RelocationType::Relative, 0x3008),
+                (RelocationType::Relative, 0x3010),
+                (RelocationType::IRelative, 0x0800),
+                (RelocationType::IRelative, 0x1000),
+            ],
+            relocations.iter().map(|r| (r.type_, r.offset.extract())).collect::<Vec<_>>()
+        );
+    }
 }
@@ -5,32 +5,46 @@ mod sections;
 mod symbols;
 
 use super::layout::SegmentType;
-use crate::cli::Mode;
+use crate::cli::{Mode, SortSections};
+use crate::diagnostics::misaligned_entry_point;
 use crate::interner::Interned;
 use crate::passes::build_elf::ids::{BuiltElfIds, BuiltElfSectionId, BuiltElfStringId};
 use crate::passes::build_elf::sections::Sections;
-use crate::passes::build_elf::symbols::{create_symbols, SymbolTableKind};
-use crate::passes::layout::Layout;
+use crate::passes::build_elf::symbols::{create_symbols, is_discarded, SymbolTableKind};
+use crate::passes::build_id;
+use crate::passes::layout::{sort_link_order, Layout};
 use crate::repr::object::Object;
 use crate::repr::sections::SectionContent;
 use crate::repr::symbols::{ResolveSymbolError, ResolvedSymbol};
 use crate::utils::ints::{Address, ExtractNumber};
+use plinky_diagnostics::Diagnostic;
+use plinky_elf::errors::SegmentsCheckError;
 use plinky_elf::ids::serial::SerialIds;
+use plinky_elf::ids::StringIdGetters;
 use plinky_elf::{
-    ElfObject, ElfPermissions, ElfProgramSection, ElfSectionContent, ElfSegment, ElfSegmentContent,
-    ElfSegmentType, ElfStringTable, ElfType, ElfUninitializedSection, RawBytes,
+    ElfMachine, ElfNotesTable, ElfObject, ElfPermissions, ElfProgramSection, ElfSectionContent,
+    ElfSegment, ElfSegmentContent, ElfSegmentType, ElfStringTable, ElfType,
+    ElfUninitializedSection, RawBytes,
 };
 use plinky_macros::{Display, Error};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::num::NonZeroU64;
 
+/// Build the final ELF object, returning it along with the warnings emitted while building it.
 pub(crate) fn run(
     object: Object,
     layout: Layout,
     old_ids: SerialIds,
-) -> Result<ElfObject<BuiltElfIds>, ElfBuilderError> {
+) -> Result<(ElfObject<BuiltElfIds>, Vec<Diagnostic>), ElfBuilderError> {
     let mut ids = BuiltElfIds::new();
-    let builder = ElfBuilder { object, layout, sections: Sections::new(&mut ids), ids, old_ids };
+    let builder = ElfBuilder {
+        object,
+        layout,
+        sections: Sections::new(&mut ids),
+        ids,
+        old_ids,
+        warnings: Vec::new(),
+    };
     builder.build()
 }
 
@@ -40,31 +54,47 @@ struct ElfBuilder {
     sections: Sections,
     ids: BuiltElfIds,
     old_ids: SerialIds,
+    warnings: Vec<Diagnostic>,
 }
 
 impl ElfBuilder {
-    fn build(mut self) -> Result<ElfObject<BuiltElfIds>, ElfBuilderError> {
+    fn build(mut self) -> Result<(ElfObject<BuiltElfIds>, Vec<Diagnostic>), ElfBuilderError> {
         let entry = self.prepare_entry_point()?;
         self.prepare_sections();
 
         match self.object.mode {
             Mode::PositionDependent => {}
-            Mode::PositionIndependent => dynamic::add(&mut self),
+            Mode::PositionIndependent => dynamic::add(&mut self)?,
         }
 
+        // Symbols referenced by the relocations kept with --emit-relocs can't be discarded.
+        let referenced = self
+            .object
+            .retained_relocations
+            .values()
+            .flatten()
+            .map(|relocation| relocation.symbol)
+            .collect::<BTreeSet<_>>();
+        let null_symbol_id = self.object.symbols.null_symbol_id();
+        let discard_locals = self.object.discard_locals;
         let symbols = create_symbols(
-            self.object.symbols.iter(),
-            self.object.symbols.null_symbol_id(),
+            self.object.symbols.iter().filter(|(id, symbol)| {
+                *id == null_symbol_id
+                    || referenced.contains(id)
+                    || !is_discarded(symbol, discard_locals)
+            }),
+            null_symbol_id,
             &mut self.ids,
             &mut self.sections,
-            SymbolTableKind::SymTab,
+            SymbolTableKind::SymTab { sort: self.object.symtab_sort },
         );
-        self.sections.create(".symtab", symbols.symbol_table).add(&mut self.ids);
+        let symtab = self.sections.create(".symtab", symbols.symbol_table).add(&mut self.ids);
+        relocations::add_retained_relocations(&mut self, symtab, &symbols.conversion);
         self.sections.create(".strtab", symbols.string_table).add_with_id(symbols.string_table_id);
 
         let segments = self.prepare_segments();
 
-        Ok(ElfObject {
+        let elf = ElfObject {
             env: self.object.env,
             type_: match self.object.mode {
                 Mode::PositionDependent => ElfType::Executable,
@@ -73,10 +103,12 @@ impl ElfBuilder {
             entry,
             sections: self.sections.finalize(),
             segments,
-        })
+        };
+        elf.program_headers_overlap_check().map_err(ElfBuilderError::InvalidSegments)?;
+        Ok((elf, self.warnings))
     }
 
-    fn prepare_entry_point(&self) -> Result<Option<NonZeroU64>, ElfBuilderError> {
+    fn prepare_entry_point(&mut self) -> Result<Option<NonZeroU64>, ElfBuilderError> {
         let symbol = self.object.symbols.get(self.object.entry_point);
         let resolved = symbol
             .resolve(&self.layout, 0.into())
@@ -86,21 +118,63 @@ impl ElfBuilder {
             ResolvedSymbol::Absolute(_) => {
                 Err(ElfBuilderError::EntryPointNotAnAddress(symbol.name))
             }
-            ResolvedSymbol::Address { memory_address, .. } => Ok(Some(
-                NonZeroU64::new(
+            ResolvedSymbol::Address { memory_address, .. } => {
+                let address = NonZeroU64::new(
                     memory_address
                         .extract()
                         .try_into()
                         .map_err(|_| ElfBuilderError::EntrypointIsOutOfBounds(memory_address))?,
                 )
-                .ok_or(ElfBuilderError::EntrypointIsZero(symbol.name))?,
-            )),
+                .ok_or(ElfBuilderError::EntrypointIsZero(symbol.name))?;
+
+                let alignment = self
+                    .object
+                    .entry_alignment
+                    .unwrap_or_else(|| instruction_alignment(self.object.env.machine));
+                if address.get() % alignment != 0 {
+                    self.warnings.push(misaligned_entry_point::build(
+                        &symbol.name.resolve(),
+                        address.get(),
+                        alignment,
+                    ));
+                }
+
+                Ok(Some(address))
+            }
         }
     }
 
     fn prepare_sections(&mut self) {
+        let mut sections = Vec::new();
         while let Some(section) = self.object.sections.pop_first() {
+            sections.push(section);
+        }
+        match self.object.sort_sections {
+            SortSections::None => {}
+            // The sort is stable, so sections with the same name are kept in their original order.
+            SortSections::Name => sections.sort_by_key(|section| section.name.resolve()),
+        }
+        sort_link_order(&mut sections, &self.object.link_order);
+
+        for section in sections {
+            let tls_align = self.object.tls_sections.get(&section.id).copied();
             match &section.content {
+                SectionContent::Data(_)
+                    if self.object.build_id_section.map(|(id, _)| id) == Some(section.id) =>
+                {
+                    let (_, style) = self.object.build_id_section.unwrap();
+                    self.sections
+                        .create(
+                            &section.name.resolve(),
+                            ElfSectionContent::Note(ElfNotesTable {
+                                align: build_id::BUILD_ID_NOTE_ALIGN,
+                                notes: vec![build_id::placeholder_note(style)],
+                            }),
+                        )
+                        .layout(self.layout.of_section(section.id))
+                        .old_id(section.id)
+                        .add(&mut self.ids);
+                }
                 SectionContent::Data(data) => {
                     self.sections
                         .create(
@@ -108,6 +182,17 @@ impl ElfBuilder {
                             ElfSectionContent::Program(ElfProgramSection {
                                 perms: section.perms,
                                 deduplication: data.deduplication,
+                                tls: tls_align.is_some(),
+                                align: tls_align.unwrap_or(1),
+                                // Set once all sections are built, as the linked section
+                                // might not have an ID yet.
+                                link_order: None,
+                                functions_array: self
+                                    .object
+                                    .functions_arrays
+                                    .get(&section.id)
+                                    .copied(),
+                                compression: None,
                                 raw: RawBytes(data.bytes.clone()),
                             }),
                         )
@@ -121,6 +206,8 @@ impl ElfBuilder {
                             &section.name.resolve(),
                             ElfSectionContent::Uninitialized(ElfUninitializedSection {
                                 perms: section.perms,
+                                tls: tls_align.is_some(),
+                                align: tls_align.unwrap_or(uninit.align),
                                 len: uninit.len,
                             }),
                         )
@@ -130,6 +217,10 @@ impl ElfBuilder {
                 }
             }
         }
+
+        for (&section, &linked) in &self.object.link_order {
+            self.sections.set_link_order(section, linked);
+        }
     }
 
     fn prepare_segments(&self) -> Vec<ElfSegment<BuiltElfIds>> {
@@ -144,6 +235,8 @@ impl ElfBuilder {
                         SegmentType::Uninitialized => ElfSegmentType::Load,
                         SegmentType::Dynamic => ElfSegmentType::Dynamic,
                         SegmentType::Interpreter => ElfSegmentType::Interpreter,
+                        SegmentType::Tls => ElfSegmentType::Tls,
+                        SegmentType::Note => ElfSegmentType::Note,
                     },
                     perms: segment.perms,
                     content: match segment.type_ {
@@ -164,9 +257,7 @@ impl ElfBuilder {
             ));
         }
 
-        // Segments have to be in order in memory, otherwise they will not be loaded.
-        elf_segments.sort_by_key(|(addr, segment)| (segment.type_, *addr));
-        let mut elf_segments = elf_segments.into_iter().map(|(_a, s)| s).collect::<Vec<_>>();
+        let mut elf_segments = sort_segments(elf_segments);
 
         // Finally add whether the stack should be executable.
         elf_segments.push(ElfSegment {
@@ -184,6 +275,32 @@ impl ElfBuilder {
     }
 }
 
+/// Alignment required for instructions to be executed by the machine.
+fn instruction_alignment(machine: ElfMachine) -> u64 {
+    match machine {
+        // x86 instructions have a variable length, and can start at any address.
+        ElfMachine::X86 | ElfMachine::X86_64 => 1,
+        // AArch64 instructions are always 4 bytes long and must be aligned to their size.
+        ElfMachine::Aarch64 => 4,
+    }
+}
+
+fn sort_segments(
+    mut segments: Vec<(u64, ElfSegment<BuiltElfIds>)>,
+) -> Vec<ElfSegment<BuiltElfIds>> {
+    // Segments have to be in order in memory, otherwise they will not be loaded. Segments with
+    // the same type and address (like the ones not allocated in memory) are ordered by their first
+    // section, so that the program headers don't depend on the order segments were created in.
+    segments.sort_by_key(|(addr, segment)| {
+        let first_section = match &segment.content {
+            ElfSegmentContent::Sections(sections) => sections.first().copied(),
+            _ => None,
+        };
+        (segment.type_, *addr, first_section)
+    });
+    segments.into_iter().map(|(_addr, segment)| segment).collect()
+}
+
 struct PendingStringsTable {
     id: BuiltElfSectionId,
     strings: BTreeMap<u32, String>,
@@ -206,6 +323,10 @@ impl PendingStringsTable {
         BuiltElfStringId::new(self.id, offset)
     }
 
+    fn get(&self, id: BuiltElfStringId) -> &str {
+        self.strings.get(&id.offset()).expect("string not present in the table")
+    }
+
     fn into_elf(self) -> ElfSectionContent<BuiltElfIds> {
         ElfSectionContent::StringTable(ElfStringTable::new(self.strings))
     }
@@ -221,4 +342,46 @@ pub(crate) enum ElfBuilderError {
     EntrypointIsZero(Interned<String>),
     #[display("the entry point address {f0} is out of bounds")]
     EntrypointIsOutOfBounds(Address),
+    #[display("the segments of the built ELF are not valid")]
+    InvalidSegments(#[source] SegmentsCheckError<BuiltElfIds>),
+    #[display("--hash-style=none cannot be used when there are dynamic symbols to look up")]
+    HashRequiredForDynamicSymbols,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_segments_with_same_type_and_address() {
+        let mut ids = BuiltElfIds::new();
+        let first = ids.allocate_section_id();
+        let second = ids.allocate_section_id();
+        let segment = |section| {
+            (
+                0,
+                ElfSegment {
+                    type_: ElfSegmentType::Load,
+                    perms: ElfPermissions { read: true, write: false, execute: false },
+                    content: ElfSegmentContent::Sections(vec![section]),
+                    align: 1,
+                },
+            )
+        };
+        let first_sections = |segments: Vec<ElfSegment<BuiltElfIds>>| {
+            segments
+                .into_iter()
+                .map(|s| match s.content {
+                    ElfSegmentContent::Sections(sections) => sections[0],
+                    _ => panic!("segment without sections"),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let sorted = sort_segments(vec![segment(first), segment(second)]);
+        assert_eq!(vec![first, second], first_sections(sorted));
+
+        let sorted = sort_segments(vec![segment(second), segment(first)]);
+        assert_eq!(vec![first, second], first_sections(sorted));
+    }
 }
@@ -1,4 +1,5 @@
 use crate::passes::build_elf::ids::{BuiltElfIds, BuiltElfSectionId, BuiltElfSymbolId};
+use crate::passes::build_elf::ElfBuilder;
 use crate::repr::relocations::{Relocation, RelocationType};
 use crate::utils::ints::ExtractNumber;
 use plinky_elf::ids::serial::SymbolId;
@@ -31,6 +32,29 @@ pub(crate) fn create_rela<'a>(
     })
 }
 
+/// Add the input relocations kept with `--emit-relocs` to the output, one `.rela` (or `.rel`)
+/// section per relocated section. The symbols are renumbered to their index in `.symtab`.
+pub(super) fn add_retained_relocations(
+    builder: &mut ElfBuilder,
+    symbol_table: BuiltElfSectionId,
+    symbol_conversion: &BTreeMap<SymbolId, BuiltElfSymbolId>,
+) {
+    for (section_id, relocations) in std::mem::take(&mut builder.object.retained_relocations) {
+        let applies_to = builder.sections.new_id_of(section_id);
+        let prefix = if relocations.iter().any(|r| r.addend.is_some()) { ".rela" } else { ".rel" };
+        let name = format!("{prefix}{}", builder.sections.name_of(applies_to));
+
+        let content = create_rela(
+            relocations.iter(),
+            builder.object.env.class,
+            applies_to,
+            symbol_table,
+            symbol_conversion,
+        );
+        builder.sections.create(&name, content).add(&mut builder.ids);
+    }
+}
+
 fn convert_relocation_type(class: ElfClass, type_: RelocationType) -> ElfRelocationType {
     macro_rules! unsupported {
         () => {{
@@ -41,6 +65,9 @@ fn convert_relocation_type(class: ElfClass, type_: RelocationType) -> ElfRelocat
     match (&class, &type_) {
         (ElfClass::Elf32, RelocationType::Absolute32) => ElfRelocationType::X86_32,
         (ElfClass::Elf32, RelocationType::AbsoluteSigned32) => unsupported!(),
+        (ElfClass::Elf32, RelocationType::Absolute64) => unsupported!(),
+        (ElfClass::Elf32, RelocationType::Size32) => unsupported!(),
+        (ElfClass::Elf32, RelocationType::Size64) => unsupported!(),
         (ElfClass::Elf32, RelocationType::Relative32) => ElfRelocationType::X86_PC32,
         (ElfClass::Elf32, RelocationType::PLT32) => unsupported!(),
         (ElfClass::Elf32, RelocationType::GOTRelative32) => unsupported!(),
@@ -48,9 +75,14 @@ fn convert_relocation_type(class: ElfClass, type_: RelocationType) -> ElfRelocat
         (ElfClass::Elf32, RelocationType::GOTLocationRelative32) => ElfRelocationType::X86_GOTPC,
         (ElfClass::Elf32, RelocationType::OffsetFromGOT32) => ElfRelocationType::X86_GOTOff,
         (ElfClass::Elf32, RelocationType::FillGOTSlot) => ElfRelocationType::X86_GLOB_DAT,
+        (ElfClass::Elf32, RelocationType::IRelative) => ElfRelocationType::X86_IRelative,
+        (ElfClass::Elf32, RelocationType::Relative) => ElfRelocationType::X86_Relative,
 
         (ElfClass::Elf64, RelocationType::Absolute32) => ElfRelocationType::X86_64_32,
         (ElfClass::Elf64, RelocationType::AbsoluteSigned32) => ElfRelocationType::X86_64_32S,
+        (ElfClass::Elf64, RelocationType::Absolute64) => ElfRelocationType::X86_64_64,
+        (ElfClass::Elf64, RelocationType::Size32) => ElfRelocationType::X86_64_Size32,
+        (ElfClass::Elf64, RelocationType::Size64) => ElfRelocationType::X86_64_Size64,
         (ElfClass::Elf64, RelocationType::Relative32) => ElfRelocationType::X86_64_PC32,
         (ElfClass::Elf64, RelocationType::PLT32) => ElfRelocationType::X86_64_PLT32,
         (ElfClass::Elf64, RelocationType::GOTRelative32) => ElfRelocationType::X86_64_GOTPCRel,
@@ -58,5 +90,7 @@ fn convert_relocation_type(class: ElfClass, type_: RelocationType) -> ElfRelocat
         (ElfClass::Elf64, RelocationType::GOTLocationRelative32) => unsupported!(),
         (ElfClass::Elf64, RelocationType::OffsetFromGOT32) => unsupported!(),
         (ElfClass::Elf64, RelocationType::FillGOTSlot) => ElfRelocationType::X86_64_GlobDat,
+        (ElfClass::Elf64, RelocationType::IRelative) => ElfRelocationType::X86_64_IRelative,
+        (ElfClass::Elf64, RelocationType::Relative) => ElfRelocationType::X86_64_Relative,
     }
 }
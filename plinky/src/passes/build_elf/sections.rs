@@ -52,6 +52,29 @@ impl Sections {
         *self.ids_map.get(&old_id).expect("could not convert section ids")
     }
 
+    /// Built section created from the section with the old ID, if it was not removed.
+    pub(super) fn built_from(&self, old_id: SectionId) -> Option<&ElfSection<BuiltElfIds>> {
+        self.sections.get(self.ids_map.get(&old_id)?)
+    }
+
+    /// Preserve `SHF_LINK_ORDER` in the output, unless either of the sections was removed.
+    pub(super) fn set_link_order(&mut self, old_id: SectionId, linked_old_id: SectionId) {
+        let (Some(id), Some(&linked)) =
+            (self.ids_map.get(&old_id), self.ids_map.get(&linked_old_id))
+        else {
+            return;
+        };
+        if let Some(ElfSection { content: ElfSectionContent::Program(program), .. }) =
+            self.sections.get_mut(id)
+        {
+            program.link_order = Some(linked);
+        }
+    }
+
+    pub(super) fn name_of(&self, id: BuiltElfSectionId) -> &str {
+        self.names.get(self.sections.get(&id).expect("section not built").name)
+    }
+
     pub(super) fn finalize(mut self) -> BTreeMap<BuiltElfSectionId, ElfSection<BuiltElfIds>> {
         let shstrtab = self.names.add(".shstrtab");
         self.sections.insert(
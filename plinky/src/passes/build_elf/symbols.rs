@@ -1,3 +1,4 @@
+use crate::cli::{DiscardLocals, SymtabSort};
 use crate::passes::build_elf::ids::{BuiltElfIds, BuiltElfSectionId, BuiltElfSymbolId};
 use crate::passes::build_elf::sections::Sections;
 use crate::passes::build_elf::PendingStringsTable;
@@ -21,12 +22,15 @@ pub(super) fn create_symbols<'a>(
     let mut conversion = BTreeMap::new();
 
     let mut null_symbol = None;
+    let mut section_symbols = Vec::new();
     let mut global_symbols = Vec::new();
     let mut local_by_source = BTreeMap::new();
     for (symbol_id, symbol) in input_symbols {
         if symbol_id == null_symbol_id {
             assert!(null_symbol.is_none());
             null_symbol = Some(symbol);
+        } else if let SymbolType::Section = symbol.type_ {
+            section_symbols.push(symbol);
         } else if let SymbolVisibility::Global { .. } = &symbol.visibility {
             global_symbols.push(symbol);
         } else {
@@ -42,6 +46,17 @@ pub(super) fn create_symbols<'a>(
         &mut conversion,
         null_symbol.expect("missing null symbol"),
     );
+    // Like GNU ld, section symbols come right after the null symbol, outside of any STT_FILE.
+    for symbol in section_symbols {
+        add_symbol(ids, sections, &mut symbols, &mut strings, &mut conversion, symbol);
+    }
+
+    if let SymbolTableKind::SymTab { sort } = kind {
+        for symbols_in_file in local_by_source.values_mut() {
+            sort_symbols(symbols_in_file, sort);
+        }
+        sort_symbols(&mut global_symbols, sort);
+    }
 
     for (file, symbols_in_file) in local_by_source {
         symbols.insert(
@@ -67,7 +82,7 @@ pub(super) fn create_symbols<'a>(
     CreateSymbolsOutput {
         symbol_table: ElfSectionContent::SymbolTable(ElfSymbolTable {
             dynsym: match kind {
-                SymbolTableKind::SymTab => false,
+                SymbolTableKind::SymTab { .. } => false,
                 SymbolTableKind::DynSym => true,
             },
             symbols,
@@ -79,10 +94,33 @@ pub(super) fn create_symbols<'a>(
 }
 
 pub(super) enum SymbolTableKind {
-    SymTab,
+    SymTab { sort: SymtabSort },
+    // The order of dynamic symbols can't be customized, as it must match the hash table.
     DynSym,
 }
 
+/// Whether the symbol is left out of `.symtab` because of `-X`/`--discard-locals` or
+/// `-x`/`--discard-all`.
+pub(super) fn is_discarded(symbol: &Symbol, discard: DiscardLocals) -> bool {
+    if let SymbolVisibility::Global { .. } = symbol.visibility {
+        return false;
+    }
+    match discard {
+        DiscardLocals::None => false,
+        DiscardLocals::Temporary => symbol.name.resolve().starts_with(".L"),
+        DiscardLocals::All => true,
+    }
+}
+
+// The sort is stable, so symbols with the same key are kept in their original order.
+fn sort_symbols(symbols: &mut [&Symbol], sort: SymtabSort) {
+    match sort {
+        SymtabSort::None => {}
+        SymtabSort::Name => symbols.sort_by_key(|symbol| symbol.name.resolve()),
+        SymtabSort::Value => symbols.sort_by_key(|symbol| symbol_value(symbol)),
+    }
+}
+
 pub(super) struct CreateSymbolsOutput {
     pub(super) symbol_table: ElfSectionContent<BuiltElfIds>,
     pub(super) string_table: ElfSectionContent<BuiltElfIds>,
@@ -105,19 +143,22 @@ fn add_symbol(
             name: strings.add(symbol.name.resolve().as_str()),
             binding: match &symbol.visibility {
                 SymbolVisibility::Local => ElfSymbolBinding::Local,
-                SymbolVisibility::Global { weak: true, hidden: _ } => ElfSymbolBinding::Weak,
-                SymbolVisibility::Global { weak: false, hidden: _ } => ElfSymbolBinding::Global,
+                SymbolVisibility::Global { weak: true, .. } => ElfSymbolBinding::Weak,
+                SymbolVisibility::Global { weak: false, .. } => ElfSymbolBinding::Global,
             },
             visibility: match &symbol.visibility {
                 SymbolVisibility::Local => ElfSymbolVisibility::Default,
-                SymbolVisibility::Global { weak: _, hidden: false } => ElfSymbolVisibility::Default,
-                SymbolVisibility::Global { weak: _, hidden: true } => ElfSymbolVisibility::Hidden,
+                SymbolVisibility::Global { hidden: true, .. } => ElfSymbolVisibility::Hidden,
+                SymbolVisibility::Global { protected: true, .. } => ElfSymbolVisibility::Protected,
+                SymbolVisibility::Global { .. } => ElfSymbolVisibility::Default,
             },
             type_: match &symbol.type_ {
                 SymbolType::NoType => ElfSymbolType::NoType,
                 SymbolType::Function => ElfSymbolType::Function,
                 SymbolType::Object => ElfSymbolType::Object,
                 SymbolType::Section => ElfSymbolType::Section,
+                SymbolType::GnuIfunc => ElfSymbolType::GnuIfunc,
+                SymbolType::Tls => ElfSymbolType::Tls,
             },
             definition: match &symbol.value {
                 SymbolValue::Absolute { .. } => ElfSymbolDefinition::Absolute,
@@ -127,22 +168,30 @@ fn add_symbol(
                 SymbolValue::SectionVirtualAddress { section, .. } => {
                     ElfSymbolDefinition::Section(sections.new_id_of(*section))
                 }
+                SymbolValue::Common { .. } => {
+                    panic!("common symbols should have been allocated before this stage");
+                }
                 SymbolValue::Undefined => ElfSymbolDefinition::Undefined,
                 SymbolValue::Null => ElfSymbolDefinition::Undefined,
             },
-            value: match &symbol.value {
-                SymbolValue::Absolute { value } => value.extract(),
-                SymbolValue::SectionRelative { .. } => {
-                    panic!("section relative addresses should not reach this stage");
-                }
-                SymbolValue::SectionVirtualAddress { memory_address, .. } => {
-                    memory_address.extract()
-                }
-                SymbolValue::Undefined => 0,
-                SymbolValue::Null => 0,
-            },
+            value: symbol_value(symbol),
             size: 0,
         },
     );
     conversion.insert(symbol.id, id);
 }
+
+fn symbol_value(symbol: &Symbol) -> u64 {
+    match &symbol.value {
+        SymbolValue::Absolute { value } => value.extract(),
+        SymbolValue::SectionRelative { .. } => {
+            panic!("section relative addresses should not reach this stage");
+        }
+        SymbolValue::SectionVirtualAddress { memory_address, .. } => memory_address.extract(),
+        SymbolValue::Common { .. } => {
+            panic!("common symbols should have been allocated before this stage");
+        }
+        SymbolValue::Undefined => 0,
+        SymbolValue::Null => 0,
+    }
+}
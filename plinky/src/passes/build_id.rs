@@ -0,0 +1,194 @@
+use crate::cli::{BuildIdHash, BuildIdStyle, CliOptions};
+use crate::interner::intern;
+use crate::passes::build_elf::ids::BuiltElfIds;
+use crate::repr::object::Object;
+use crate::repr::sections::{DataSection, Section, SectionContent};
+use plinky_diagnostics::ObjectSpan;
+use plinky_elf::errors::WriteError;
+use plinky_elf::ids::serial::SerialIds;
+use plinky_elf::ids::{ElfIds, StringIdGetters};
+use plinky_elf::{
+    ElfDeduplication, ElfNote, ElfNotesTable, ElfObject, ElfPermissions, ElfSectionContent,
+};
+use plinky_macros::{Display, Error};
+use plinky_utils::md5::Md5;
+use plinky_utils::sha1::Sha1;
+use std::fs::File;
+use std::io::Read;
+
+/// Like all the notes except `.note.gnu.property`, build-ids are aligned to 4 bytes.
+pub(crate) const BUILD_ID_NOTE_ALIGN: u64 = 4;
+
+/// Reserve the `.note.gnu.build-id` section (with `--build-id`). Its content is only known once
+/// the final ELF is built, so the note is filled in by [`fill`].
+pub(crate) fn inject(options: &CliOptions, ids: &mut SerialIds, object: &mut Object) {
+    let Some(style) = options.build_id else { return };
+
+    let id = ids.allocate_section_id();
+    object.sections.add(Section {
+        id,
+        name: intern(".note.gnu.build-id"),
+        perms: ElfPermissions { read: true, write: false, execute: false },
+        source: ObjectSpan::new_synthetic(),
+        content: SectionContent::Data(DataSection {
+            deduplication: ElfDeduplication::Disabled,
+            // Only used to reserve space in the layout: build_elf emits a note section instead.
+            bytes: vec![0; placeholder_note(style).size(object.env.class, BUILD_ID_NOTE_ALIGN)],
+            relocations: Vec::new(),
+        }),
+    });
+    object.build_id_section = Some((id, style));
+}
+
+pub(crate) fn placeholder_note(style: BuildIdStyle) -> ElfNote {
+    ElfNote::GnuBuildId(vec![0; build_id_len(style)])
+}
+
+fn build_id_len(style: BuildIdStyle) -> usize {
+    match style {
+        BuildIdStyle::Hash(BuildIdHash::Sha1) => 20,
+        BuildIdStyle::Hash(BuildIdHash::Md5) | BuildIdStyle::Uuid => 16,
+    }
+}
+
+/// Replace the placeholder build-id with the hash of the final ELF (or a random UUID).
+pub(crate) fn fill(
+    options: &CliOptions,
+    elf: &mut ElfObject<BuiltElfIds>,
+) -> Result<(), BuildIdError> {
+    let Some(style) = options.build_id else { return Ok(()) };
+
+    let build_id = match style {
+        BuildIdStyle::Hash(hash) => calculate(hash, options.build_id_seed.as_deref(), elf)
+            .map_err(BuildIdError::ContentHash)?,
+        BuildIdStyle::Uuid => random_uuid()?.to_vec(),
+    };
+    for section in elf.sections.values_mut() {
+        let ElfSectionContent::Note(ElfNotesTable { notes, .. }) = &mut section.content else {
+            continue;
+        };
+        for note in notes {
+            match note {
+                ElfNote::GnuBuildId(id) => id.clone_from(&build_id),
+                ElfNote::Unknown(_) => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The build-id only depends on the loaded contents and on the symbol values: input paths,
+/// timestamps and other metadata don't influence it, making builds reproducible.
+fn calculate<I>(
+    hash: BuildIdHash,
+    seed: Option<&str>,
+    elf: &ElfObject<I>,
+) -> Result<Vec<u8>, WriteError<I>>
+where
+    I: ElfIds,
+    I::StringId: StringIdGetters<I>,
+{
+    let mut input = Vec::new();
+    if let Some(seed) = seed {
+        input.extend_from_slice(&(seed.len() as u64).to_le_bytes());
+        input.extend_from_slice(seed.as_bytes());
+    }
+    input.extend_from_slice(&elf.content_hash()?);
+    for section in elf.sections.values() {
+        let ElfSectionContent::SymbolTable(table) = &section.content else { continue };
+        for symbol in table.symbols.values() {
+            input.extend_from_slice(&symbol.value.to_le_bytes());
+            input.extend_from_slice(&symbol.size.to_le_bytes());
+        }
+    }
+
+    Ok(match hash {
+        BuildIdHash::Sha1 => {
+            let mut hasher = Sha1::new();
+            hasher.update(&input);
+            hasher.finish().to_vec()
+        }
+        BuildIdHash::Md5 => {
+            let mut hasher = Md5::new();
+            hasher.update(&input);
+            hasher.finish().to_vec()
+        }
+    })
+}
+
+/// Version 4 (random) UUID, with the randomness provided by the operating system.
+fn random_uuid() -> Result<[u8; 16], BuildIdError> {
+    let mut uuid = [0; 16];
+    File::open("/dev/urandom")
+        .and_then(|mut file| file.read_exact(&mut uuid))
+        .map_err(BuildIdError::RandomnessUnavailable)?;
+    uuid[6] = (uuid[6] & 0x0f) | 0x40; // Version 4
+    uuid[8] = (uuid[8] & 0x3f) | 0x80; // RFC 4122 variant
+    Ok(uuid)
+}
+
+#[derive(Debug, Error, Display)]
+pub(crate) enum BuildIdError {
+    #[display("failed to read random bytes for --build-id=uuid")]
+    RandomnessUnavailable(#[source] std::io::Error),
+    #[display("failed to hash the output contents")]
+    ContentHash(#[source] WriteError<BuiltElfIds>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plinky_elf::{ElfObjectBuilder, ElfType};
+
+    fn build(path: &str, code: &[u8]) -> ElfObject<SerialIds> {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let perms = ElfPermissions { read: true, write: false, execute: true };
+        builder.add_program_section(".text", perms, code);
+        // Like debug info, the comment is not loaded in memory but still ends up in the file.
+        let perms = ElfPermissions { read: false, write: false, execute: false };
+        builder.add_program_section(".comment", perms, path.as_bytes());
+        builder.build()
+    }
+
+    #[test]
+    fn test_input_path_does_not_change_build_id() {
+        assert_eq!(
+            calculate(BuildIdHash::Sha1, None, &build("/tmp/a/main.S", b"\x90\xc3")).unwrap(),
+            calculate(BuildIdHash::Sha1, None, &build("/home/b/main.S", b"\x90\xc3")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_content_changes_build_id() {
+        assert_ne!(
+            calculate(BuildIdHash::Sha1, None, &build("main.S", b"\x90\xc3")).unwrap(),
+            calculate(BuildIdHash::Sha1, None, &build("main.S", b"\x90\x90\xc3")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_seed_changes_build_id() {
+        let object = build("main.S", b"\x90\xc3");
+        let sha1 = |seed| calculate(BuildIdHash::Sha1, seed, &object).unwrap();
+        assert_ne!(sha1(None), sha1(Some("seed")));
+        assert_ne!(sha1(Some("a")), sha1(Some("b")));
+    }
+
+    #[test]
+    fn test_build_id_len() {
+        let object = build("main.S", b"\x90\xc3");
+        for hash in [BuildIdHash::Sha1, BuildIdHash::Md5] {
+            let style = BuildIdStyle::Hash(hash);
+            assert_eq!(build_id_len(style), calculate(hash, None, &object).unwrap().len());
+        }
+        assert_eq!(build_id_len(BuildIdStyle::Uuid), random_uuid().unwrap().len());
+    }
+
+    #[test]
+    fn test_random_uuid() {
+        let uuid = random_uuid().unwrap();
+        assert_eq!(0x40, uuid[6] & 0xf0);
+        assert_eq!(0x80, uuid[8] & 0xc0);
+        assert_ne!(uuid, random_uuid().unwrap());
+    }
+}
@@ -0,0 +1,183 @@
+use plinky_elf::ids::{ElfIds, StringIdGetters};
+use plinky_elf::{ElfObject, ElfSectionContent};
+use plinky_macros::{Display, Error};
+
+/// Lay out the allocated sections as a flat binary (with `--oformat binary`), starting at the
+/// lowest section address. Gaps between sections are filled with `fill` (`--fill`), and
+/// uninitialized sections followed by other sections are zero-filled, while uninitialized
+/// sections at the end are omitted, like GNU objcopy does. Only program and uninitialized
+/// sections are included.
+pub(crate) fn build<I: ElfIds>(
+    object: &ElfObject<I>,
+    fill: u8,
+) -> Result<Vec<u8>, FlatBinaryError> {
+    let mut sections = Vec::new();
+    for section in object.sections.values() {
+        let (perms, bytes, len) = match &section.content {
+            ElfSectionContent::Program(program) => {
+                (program.perms, Some(&program.raw.0[..]), program.raw.len() as u64)
+            }
+            ElfSectionContent::Uninitialized(uninit) => (uninit.perms, None, uninit.len),
+            _ => continue,
+        };
+        if !perms.read || len == 0 {
+            continue;
+        }
+        sections.push(FlatSection {
+            name: section_name(object, &section.name),
+            address: section.memory_address,
+            bytes,
+            len,
+        });
+    }
+    sections.sort_by_key(|section| section.address);
+
+    let Some(start) = sections.first().map(|section| section.address) else {
+        return Ok(Vec::new());
+    };
+    let mut output = Vec::new();
+    let mut uninitialized: Vec<&FlatSection> = Vec::new();
+    let mut previous: Option<&FlatSection> = None;
+    for section in &sections {
+        if let Some(previous) = previous {
+            if section.address < previous.address + previous.len {
+                return Err(FlatBinaryError::OverlappingSections {
+                    first: previous.name.clone(),
+                    second: section.name.clone(),
+                });
+            }
+        }
+        match section.bytes {
+            Some(bytes) => {
+                // Everything before this section is filled to put the contents at the right
+                // offset, except for the uninitialized sections, which must stay zeroed.
+                output.resize((section.address - start) as usize, fill);
+                for uninit in uninitialized.drain(..) {
+                    let offset = (uninit.address - start) as usize;
+                    output[offset..offset + uninit.len as usize].fill(0);
+                }
+                output.extend_from_slice(bytes);
+            }
+            None => uninitialized.push(section),
+        }
+        previous = Some(section);
+    }
+    Ok(output)
+}
+
+fn section_name<I: ElfIds>(object: &ElfObject<I>, name: &I::StringId) -> String {
+    match object.sections.get(name.section()).map(|section| &section.content) {
+        Some(ElfSectionContent::StringTable(table)) => {
+            table.get(name.offset()).unwrap_or("<unknown>").into()
+        }
+        _ => "<unknown>".into(),
+    }
+}
+
+struct FlatSection<'a> {
+    name: String,
+    address: u64,
+    bytes: Option<&'a [u8]>,
+    len: u64,
+}
+
+#[derive(Debug, Error, Display)]
+pub(crate) enum FlatBinaryError {
+    #[display("sections {first} and {second} overlap in the flat binary")]
+    OverlappingSections { first: String, second: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plinky_elf::ids::serial::SerialIds;
+    use plinky_elf::{ElfObjectBuilder, ElfPermissions, ElfType};
+
+    const R: ElfPermissions = ElfPermissions { read: true, write: false, execute: false };
+    const RW: ElfPermissions = ElfPermissions { read: true, write: true, execute: false };
+
+    enum Content {
+        Program(&'static [u8]),
+        Uninitialized(u64),
+        NotAllocated(&'static [u8]),
+    }
+
+    fn object(sections: &[(&str, u64, Content)]) -> ElfObject<SerialIds> {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let mut addresses = Vec::new();
+        for (name, address, content) in sections {
+            let id = match content {
+                Content::Program(bytes) => builder.add_program_section(name, R, *bytes),
+                Content::Uninitialized(len) => builder.add_uninitialized_section(name, RW, *len),
+                Content::NotAllocated(bytes) => builder.add_program_section(
+                    name,
+                    ElfPermissions { read: false, write: false, execute: false },
+                    *bytes,
+                ),
+            };
+            addresses.push((id, *address));
+        }
+        let mut object = builder.build();
+        for (id, address) in addresses {
+            object.sections.get_mut(&id).unwrap().memory_address = address;
+        }
+        object
+    }
+
+    #[test]
+    fn test_padded_layout() {
+        let object = object(&[
+            (".data", 0x1010, Content::Program(b"\x05\x06")),
+            (".text", 0x1000, Content::Program(b"\x01\x02\x03")),
+            (".bss", 0x1004, Content::Uninitialized(4)),
+            (".comment", 0, Content::NotAllocated(b"plinky")),
+            (".bss.tail", 0x1012, Content::Uninitialized(0x100)),
+        ]);
+        assert_eq!(
+            vec![
+                0x01, 0x02, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, // .text, padding and .bss
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // gap
+                0x05, 0x06, // .data, with the trailing .bss.tail omitted
+            ],
+            build(&object, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        let object = object(&[
+            (".text", 0x1000, Content::Program(b"\x01\x02\x03")),
+            (".bss", 0x1004, Content::Uninitialized(2)),
+            (".data", 0x1008, Content::Program(b"\x05\x06")),
+        ]);
+        assert_eq!(
+            vec![
+                0x01, 0x02, 0x03, 0xcc, // .text and padding
+                0x00, 0x00, 0xcc, 0xcc, // .bss and gap
+                0x05, 0x06, // .data
+            ],
+            build(&object, 0xcc).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_no_allocated_sections() {
+        let object = object(&[(".comment", 0, Content::NotAllocated(b"plinky"))]);
+        assert!(build(&object, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_sections() {
+        let object = object(&[
+            (".text", 0x1000, Content::Program(b"\x01\x02\x03\x04")),
+            (".bss", 0x1002, Content::Uninitialized(4)),
+        ]);
+        match build(&object, 0) {
+            Err(FlatBinaryError::OverlappingSections { first, second }) => {
+                assert_eq!(".text", first);
+                assert_eq!(".bss", second);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
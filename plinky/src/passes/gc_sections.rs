@@ -14,6 +14,7 @@ pub(crate) fn run(object: &mut Object) -> Vec<RemovedSection> {
                 SymbolValue::SectionRelative { section, .. } => Some((id, section)),
                 SymbolValue::SectionVirtualAddress { section, .. } => Some((id, section)),
                 SymbolValue::Absolute { .. } => None,
+                SymbolValue::Common { .. } => None,
                 SymbolValue::Undefined => None,
                 SymbolValue::Null => None,
             })
@@ -30,6 +31,12 @@ pub(crate) fn run(object: &mut Object) -> Vec<RemovedSection> {
         }
     }
 
+    // The functions arrays are never referenced by the code, as they are called by the dynamic
+    // loader or the C runtime.
+    for &section_id in object.functions_arrays.keys() {
+        visitor.queue.insert(section_id);
+    }
+
     visitor.add(object.entry_point);
     visitor.process(object);
 
@@ -10,6 +10,8 @@ use std::collections::{BTreeMap, BTreeSet};
 
 pub(crate) fn generate_got(ids: &mut SerialIds, object: &mut Object) {
     let mut needs_got = false;
+    // Slots are assigned in symbol ID order rather than in the order relocations are found, which
+    // is stable across runs as IDs are allocated in the order the inputs are loaded.
     let mut symbols = BTreeSet::new();
     for section in object.sections.iter() {
         let SectionContent::Data(data) = &section.content else {
@@ -82,3 +84,93 @@ impl GOT {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{
+        DiscardLocals, HashStyle, Mode, SegmentsLayout, SortSections, SymbolicBinding, SymtabSort,
+    };
+    use crate::repr::sections::Sections;
+    use crate::repr::symbols::Symbols;
+    use plinky_elf::{ElfABI, ElfClass, ElfEndian, ElfEnvironment, ElfMachine};
+
+    #[test]
+    fn test_slots_are_stable_across_runs() {
+        let first = got_layout();
+        let second = got_layout();
+        assert_eq!(first, second);
+
+        // Relocations reference the symbols in reverse order, but slots follow the symbol IDs.
+        let offsets = first.iter().map(|(_, offset)| *offset).collect::<Vec<_>>();
+        assert_eq!(vec![Offset::from(0), Offset::from(8), Offset::from(16)], offsets);
+    }
+
+    fn got_layout() -> Vec<(SymbolId, Offset)> {
+        let mut ids = SerialIds::new();
+        let symbols = Symbols::new(&mut ids);
+        let referenced =
+            [ids.allocate_symbol_id(), ids.allocate_symbol_id(), ids.allocate_symbol_id()];
+
+        let mut object = Object {
+            env: ElfEnvironment {
+                class: ElfClass::Elf64,
+                endian: ElfEndian::Little,
+                abi: ElfABI::SystemV,
+                machine: ElfMachine::X86_64,
+            },
+            sections: Sections::new(),
+            entry_point: symbols.null_symbol_id(),
+            symbols,
+            dynamic_relocations: Vec::new(),
+            retained_relocations: BTreeMap::new(),
+            emit_relocs: false,
+            got: None,
+            mode: Mode::PositionDependent,
+            segments_layout: SegmentsLayout::PageAligned,
+            no_rosegment: false,
+            sort_sections: SortSections::None,
+            symtab_sort: SymtabSort::None,
+            discard_locals: DiscardLocals::None,
+            hash_style: HashStyle::Sysv,
+            symbolic: SymbolicBinding::None,
+            spare_dynamic_tags: 0,
+            sort_dyn_relocs: true,
+            entry_alignment: None,
+            executable_stack: false,
+            gnu_stack_section_ignored: false,
+            build_id_section: None,
+            text_relocations: BTreeSet::new(),
+            tls_sections: BTreeMap::new(),
+            link_order: BTreeMap::new(),
+            functions_arrays: BTreeMap::new(),
+            symbol_warnings: BTreeMap::new(),
+        };
+        object.sections.add(Section {
+            id: ids.allocate_section_id(),
+            name: intern(".text"),
+            perms: ElfPermissions { read: true, write: false, execute: true },
+            source: ObjectSpan::new_synthetic(),
+            content: SectionContent::Data(DataSection {
+                deduplication: ElfDeduplication::Disabled,
+                bytes: vec![0; 12],
+                relocations: referenced
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(idx, symbol)| Relocation {
+                        type_: RelocationType::GOTRelative32,
+                        symbol: *symbol,
+                        offset: Offset::from(idx as i64 * 4),
+                        addend: Some(Offset::from(-4)),
+                    })
+                    .collect(),
+            }),
+        });
+
+        generate_got(&mut ids, &mut object);
+        let got = object.got.unwrap();
+        assert_eq!(referenced.to_vec(), got.offsets.keys().copied().collect::<Vec<_>>());
+        got.offsets.into_iter().collect()
+    }
+}
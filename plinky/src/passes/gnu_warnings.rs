@@ -0,0 +1,32 @@
+use crate::diagnostics::symbol_with_gnu_warning;
+use crate::repr::object::Object;
+use crate::repr::sections::SectionContent;
+use plinky_diagnostics::Diagnostic;
+use std::collections::BTreeSet;
+
+/// Emit the messages of `.gnu.warning.SYMBOL` sections for each symbol referenced by at least one
+/// relocation. Each warning is only emitted once, for the first reference found.
+pub(crate) fn run(object: &Object) -> Vec<Diagnostic> {
+    if object.symbol_warnings.is_empty() {
+        return Vec::new();
+    }
+
+    let mut already_warned = BTreeSet::new();
+    let mut diagnostics = Vec::new();
+    for section in object.sections.iter() {
+        let SectionContent::Data(data) = &section.content else { continue };
+        for relocation in &data.relocations {
+            let symbol = object.symbols.get(relocation.symbol);
+            let Some(message) = object.symbol_warnings.get(&symbol.name) else { continue };
+            if already_warned.insert(symbol.name) {
+                diagnostics.push(symbol_with_gnu_warning::build(
+                    &symbol.name.resolve(),
+                    message,
+                    &section.name.resolve(),
+                    &section.source,
+                ));
+            }
+        }
+    }
+    diagnostics
+}
@@ -0,0 +1,72 @@
+use crate::repr::object::Object;
+use crate::repr::sections::{DataSection, SectionContent};
+use crate::repr::symbols::SymbolValue;
+use plinky_diagnostics::ObjectSpan;
+use plinky_elf::ids::serial::SectionId;
+use plinky_elf::ElfDeduplication;
+
+/// Identical code folding (with `--icf=all`): executable sections with the same permissions,
+/// contents and relocations are merged into the first one that was loaded, and the symbols
+/// pointing to the folded sections are moved to it.
+///
+/// Relocations are only considered identical if they point to the same symbol, so sections
+/// referencing themselves (or other sections that could be folded) are never folded.
+pub(crate) fn run(object: &mut Object) -> Vec<FoldedSections> {
+    let mut groups: Vec<FoldedSections> = Vec::new();
+    for section in object.sections.iter() {
+        let SectionContent::Data(data) = &section.content else { continue };
+        if !section.perms.execute || section.perms.write {
+            continue;
+        }
+        if data.deduplication != ElfDeduplication::Disabled {
+            continue;
+        }
+
+        let existing = groups.iter_mut().find(|group| {
+            let kept = object.sections.get(group.kept.id).unwrap();
+            let SectionContent::Data(kept_data) = &kept.content else { unreachable!() };
+            kept.perms == section.perms && identical(object, kept_data, data)
+        });
+        let folded = FoldedSection { id: section.id, source: section.source.clone() };
+        match existing {
+            Some(group) => group.folded.push(folded),
+            None => groups.push(FoldedSections { kept: folded, folded: Vec::new() }),
+        }
+    }
+    groups.retain(|group| !group.folded.is_empty());
+
+    for group in &groups {
+        for folded in &group.folded {
+            for (_, symbol) in object.symbols.iter_mut() {
+                if let SymbolValue::SectionRelative { section, .. } = &mut symbol.value {
+                    if *section == folded.id {
+                        *section = group.kept.id;
+                    }
+                }
+            }
+            object.sections.remove(folded.id, None);
+        }
+    }
+    groups
+}
+
+fn identical(object: &Object, a: &DataSection, b: &DataSection) -> bool {
+    a.bytes == b.bytes
+        && a.relocations.len() == b.relocations.len()
+        && a.relocations.iter().zip(b.relocations.iter()).all(|(a, b)| {
+            a.type_ == b.type_
+                && a.offset == b.offset
+                && a.addend == b.addend
+                && object.symbols.get(a.symbol).id == object.symbols.get(b.symbol).id
+        })
+}
+
+pub(crate) struct FoldedSections {
+    pub(crate) kept: FoldedSection,
+    pub(crate) folded: Vec<FoldedSection>,
+}
+
+pub(crate) struct FoldedSection {
+    pub(crate) id: SectionId,
+    pub(crate) source: ObjectSpan,
+}
@@ -0,0 +1,80 @@
+use crate::cli::CliOptions;
+use crate::interner::intern;
+use crate::repr::object::Object;
+use crate::repr::sections::{DataSection, Section, SectionContent};
+use plinky_diagnostics::ObjectSpan;
+use plinky_elf::ids::serial::SerialIds;
+use plinky_elf::{ElfDeduplication, ElfEndian, ElfPermissions};
+use plinky_macros::{Display, Error};
+use plinky_utils::crc32::crc32;
+use std::path::PathBuf;
+
+/// Add a `.gnu_debuglink` section pointing debuggers to the file containing the debug info
+/// (with `--add-gnu-debuglink`). Only the file name is stored: debuggers look it up in their
+/// own search paths, and use the CRC to ensure it matches.
+pub(crate) fn run(
+    options: &CliOptions,
+    ids: &mut SerialIds,
+    object: &mut Object,
+) -> Result<(), InjectGnuDebuglinkError> {
+    let Some(path) = &options.gnu_debuglink else { return Ok(()) };
+
+    let contents =
+        std::fs::read(path).map_err(|e| InjectGnuDebuglinkError::ReadFailed(path.clone(), e))?;
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| InjectGnuDebuglinkError::UnsupportedFileName(path.clone()))?;
+
+    object.sections.add(Section {
+        id: ids.allocate_section_id(),
+        name: intern(".gnu_debuglink"),
+        perms: ElfPermissions { read: false, write: false, execute: false },
+        source: ObjectSpan::new_synthetic(),
+        content: SectionContent::Data(DataSection {
+            deduplication: ElfDeduplication::Disabled,
+            bytes: section_content(name, crc32(&contents), object.env.endian),
+            relocations: Vec::new(),
+        }),
+    });
+    Ok(())
+}
+
+/// The file name is null-terminated and padded to a multiple of 4 bytes, followed by the CRC.
+fn section_content(name: &str, crc: u32, endian: ElfEndian) -> Vec<u8> {
+    let mut bytes = name.as_bytes().to_vec();
+    bytes.push(0);
+    while bytes.len() % 4 != 0 {
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(&match endian {
+        ElfEndian::Little => crc.to_le_bytes(),
+        ElfEndian::Big => crc.to_be_bytes(),
+    });
+    bytes
+}
+
+#[derive(Debug, Error, Display)]
+pub(crate) enum InjectGnuDebuglinkError {
+    #[display("failed to read the debug file {f0:?} to link with --add-gnu-debuglink")]
+    ReadFailed(PathBuf, #[source] std::io::Error),
+    #[display("the name of the debug file {f0:?} is not valid UTF-8")]
+    UnsupportedFileName(PathBuf),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_padding() {
+        assert_eq!(
+            b"abc\0\x78\x56\x34\x12".to_vec(),
+            section_content("abc", 0x12345678, ElfEndian::Little)
+        );
+        assert_eq!(
+            b"abcd\0\0\0\0\x78\x56\x34\x12".to_vec(),
+            section_content("abcd", 0x12345678, ElfEndian::Little)
+        );
+    }
+}
@@ -1,12 +1,15 @@
+use crate::cli::{Mode, SegmentsLayout, SortSections};
+use crate::passes::build_id::BUILD_ID_NOTE_ALIGN;
 use crate::passes::deduplicate::Deduplication;
 use crate::repr::object::Object;
-use crate::repr::sections::SectionContent;
-use crate::utils::ints::{Address, Offset, OutOfBoundsError};
+use crate::repr::sections::{Section, SectionContent};
+use crate::utils::ints::{align_up, Address, ExtractNumber, Offset, OutOfBoundsError};
 use plinky_elf::ids::serial::SectionId;
 use plinky_elf::ElfPermissions;
 use plinky_macros::{Display, Error};
+use plinky_utils::raw_types::RawTypeAsPointerSize;
+use std::borrow::Borrow;
 use std::collections::BTreeMap;
-use crate::cli::Mode;
 
 const PAGE_SIZE: u64 = 0x1000;
 const STATIC_BASE_ADDRESS: u64 = 0x400000;
@@ -17,8 +20,32 @@ pub(crate) fn run(
     deduplications: BTreeMap<SectionId, Deduplication>,
     interp_section: Option<SectionId>,
 ) -> Layout {
+    let mut sections = object.sections.iter().collect::<Vec<_>>();
+    match object.sort_sections {
+        SortSections::None => {}
+        // The order in memory must match the order in the section header table (which is sorted
+        // in build_elf), as the sections of a segment are written to the ELF file in that order.
+        SortSections::Name => sections.sort_by_key(|section| section.name.resolve()),
+    }
+    sort_link_order(&mut sections, &object.link_order);
+
     let mut grouped: BTreeMap<_, Vec<_>> = BTreeMap::new();
-    for section in object.sections.iter() {
+    let mut tls_sections = Vec::new();
+    for section in sections {
+        if let Some(&align) = object.tls_sections.get(&section.id) {
+            tls_sections.push((section, align));
+            continue;
+        }
+        let mut perms = section.perms;
+        if object.segments_layout == SegmentsLayout::OMagic && perms.execute {
+            perms.write = true;
+        }
+        // With --no-rosegment, read-only sections are grouped with the executable ones, so that
+        // both end up in a single RX segment.
+        let read_only = perms.read && !perms.write && !perms.execute;
+        if object.no_rosegment && read_only && Some(section.id) != interp_section {
+            perms.execute = true;
+        }
         match &section.content {
             SectionContent::Data(data) => grouped
                 .entry((
@@ -27,14 +54,14 @@ pub(crate) fn run(
                     } else {
                         SegmentType::Program
                     },
-                    section.perms,
+                    perms,
                 ))
                 .or_default()
-                .push((section.id, data.bytes.len() as u64)),
+                .push((section.id, data.bytes.len() as u64, data_align(object, section.id))),
             SectionContent::Uninitialized(uninit) => grouped
-                .entry((SegmentType::Uninitialized, section.perms))
+                .entry((SegmentType::Uninitialized, perms))
                 .or_default()
-                .push((section.id, uninit.len)),
+                .push((section.id, uninit.len, uninit.align)),
         }
     }
 
@@ -43,6 +70,10 @@ pub(crate) fn run(
             Mode::PositionDependent => STATIC_BASE_ADDRESS,
             Mode::PositionIndependent => PIE_BASE_ADDRESS,
         },
+        segment_alignment: match object.segments_layout {
+            SegmentsLayout::PageAligned => PAGE_SIZE,
+            SegmentsLayout::NMagic | SegmentsLayout::OMagic => 1,
+        },
         segments: Vec::new(),
         sections: BTreeMap::new(),
         deduplications,
@@ -50,23 +81,93 @@ pub(crate) fn run(
     for ((type_, perms), sections) in grouped.into_iter() {
         if perms.read || perms.write || perms.execute {
             let mut segment = layout.prepare_segment();
-            for &(section, len) in &sections {
+            for &(section, len, align) in &sections {
+                segment.align_to(align);
                 segment.add_section(section, len);
             }
             segment.finalize(type_, perms);
         } else {
             // Avoid allocating sections that cannot be accessed at runtime.
-            for (section, _) in sections {
+            for (section, _, _) in sections {
                 layout.sections.insert(section, SectionLayout::NotAllocated);
             }
         }
     }
 
+    // The initialized thread-local data has to be placed before the uninitialized one, as the
+    // latter is not stored in the ELF file.
+    tls_sections
+        .sort_by_key(|(section, _)| matches!(section.content, SectionContent::Uninitialized(_)));
+    if !tls_sections.is_empty() {
+        layout.add_tls_template(&tls_sections);
+    }
+
+    // Like other linkers, point a PT_NOTE segment to the build-id, so that it can be found without
+    // the section headers (for example in core dumps).
+    if let Some((id, _)) = object.build_id_section {
+        if let Some(SectionLayout::Allocated { address }) = layout.sections.get(&id) {
+            layout.segments.push(Segment {
+                start: address.extract(),
+                align: layout.alignment_of(SegmentType::Note, BUILD_ID_NOTE_ALIGN),
+                type_: SegmentType::Note,
+                perms: ElfPermissions { read: true, write: false, execute: false },
+                sections: vec![id],
+            });
+        }
+    }
+
     layout
 }
 
+/// Arrays of functions pointers are read by the C runtime one pointer at a time, so they must be
+/// aligned to the pointer size, and notes (like the build-id) to the alignment of their entries.
+/// Other data sections don't carry an alignment.
+fn data_align(object: &Object, section: SectionId) -> u64 {
+    if object.functions_arrays.contains_key(&section) {
+        <u64 as RawTypeAsPointerSize>::size(object.env.class) as u64
+    } else if object.build_id_section.map(|(id, _)| id) == Some(section) {
+        BUILD_ID_NOTE_ALIGN
+    } else {
+        1
+    }
+}
+
+/// Sections flagged with `SHF_LINK_ORDER` (like `.ARM.exidx`) must be in the same relative order
+/// as the sections they are linked to. Only the positions already occupied by those sections are
+/// shuffled, so the rest of the sections are not moved.
+pub(crate) fn sort_link_order<S: Borrow<Section>>(
+    sections: &mut Vec<S>,
+    link_order: &BTreeMap<SectionId, SectionId>,
+) {
+    let positions: BTreeMap<SectionId, usize> =
+        sections.iter().enumerate().map(|(idx, section)| (section.borrow().id, idx)).collect();
+
+    let mut slots = Vec::new();
+    let mut keys = Vec::new();
+    for (idx, section) in sections.iter().enumerate() {
+        let Some(linked) = link_order.get(&section.borrow().id) else { continue };
+        slots.push(idx);
+        // Sections linked to a section not being laid out keep their order, after the others.
+        keys.push(positions.get(linked).copied().unwrap_or(usize::MAX));
+    }
+    if slots.is_empty() {
+        return;
+    }
+
+    let mut order = (0..slots.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&idx| keys[idx]);
+
+    let mut taken = std::mem::take(sections).into_iter().map(Some).collect::<Vec<_>>();
+    let mut sorted = order.iter().map(|&idx| taken[slots[idx]].take()).collect::<Vec<_>>();
+    for (slot, section) in slots.iter().zip(sorted.iter_mut()) {
+        taken[*slot] = section.take();
+    }
+    sections.extend(taken.into_iter().map(|section| section.unwrap()));
+}
+
 pub(crate) struct Layout {
     current_address: u64,
+    segment_alignment: u64,
     segments: Vec<Segment>,
     sections: BTreeMap<SectionId, SectionLayout>,
     deduplications: BTreeMap<SectionId, Deduplication>,
@@ -117,10 +218,63 @@ impl Layout {
         self.segments.iter()
     }
 
+    /// Alignment of a segment of the given type, see [`SegmentType::alignment`].
+    pub(crate) fn alignment_of(&self, type_: SegmentType, content_alignment: u64) -> u64 {
+        type_.alignment(self.segment_alignment, content_alignment)
+    }
+
     pub(crate) fn add_segment(&mut self, segment: Segment) {
         self.segments.push(segment);
     }
 
+    /// Lay out the TLS template (the thread-local sections merged by `merge_tls`): the whole
+    /// template is aligned to the largest alignment of its sections, and the initialized part of
+    /// it is also loaded in memory as a regular segment.
+    fn add_tls_template(&mut self, sections: &[(&Section, u64)]) {
+        let align = sections.iter().map(|(_, align)| *align).max().unwrap_or(1);
+        let perms = sections[0].0.perms;
+
+        self.current_address = align_up(self.current_address, align);
+        let start = self.current_address;
+
+        let mut loaded = Vec::new();
+        let mut template = Vec::new();
+        for (section, section_align) in sections {
+            self.current_address = align_up(self.current_address, *section_align);
+            self.sections.insert(
+                section.id,
+                SectionLayout::Allocated { address: self.current_address.into() },
+            );
+            match &section.content {
+                SectionContent::Data(data) => {
+                    self.current_address += data.bytes.len() as u64;
+                    loaded.push(section.id);
+                }
+                SectionContent::Uninitialized(uninit) => self.current_address += uninit.len,
+            }
+            template.push(section.id);
+        }
+
+        if !loaded.is_empty() {
+            self.segments.push(Segment {
+                start,
+                align: self.segment_alignment,
+                type_: SegmentType::Program,
+                perms,
+                sections: loaded,
+            });
+        }
+        self.segments.push(Segment {
+            start,
+            align: self.alignment_of(SegmentType::Tls, align),
+            type_: SegmentType::Tls,
+            perms,
+            sections: template,
+        });
+
+        self.current_address = align_up(self.current_address, self.segment_alignment);
+    }
+
     pub(crate) fn prepare_segment(&mut self) -> PendingSegment {
         PendingSegment { start: self.current_address, sections: Vec::new(), layout: self }
     }
@@ -133,6 +287,11 @@ pub(crate) struct PendingSegment<'a> {
 }
 
 impl PendingSegment<'_> {
+    /// Move the address of the next section to a multiple of `align`.
+    pub(crate) fn align_to(&mut self, align: u64) {
+        self.layout.current_address = align_up(self.layout.current_address, align);
+    }
+
     pub(crate) fn add_section(&mut self, id: SectionId, len: u64) -> SectionLayout {
         let layout = SectionLayout::Allocated { address: self.layout.current_address.into() };
 
@@ -144,16 +303,19 @@ impl PendingSegment<'_> {
     }
 
     pub(crate) fn finalize(self, type_: SegmentType, perms: ElfPermissions) {
+        let align = self.layout.segment_alignment;
         self.layout.segments.push(Segment {
             start: self.start,
-            align: PAGE_SIZE,
+            align: self.layout.alignment_of(type_, 1),
             type_,
             perms,
             sections: self.sections,
         });
 
-        // Align to the page boundary.
-        self.layout.current_address = (self.layout.current_address + PAGE_SIZE) & !(PAGE_SIZE - 1);
+        // Align to the page boundary, unless segments are meant to be packed together.
+        if align > 1 {
+            self.layout.current_address = (self.layout.current_address + align) & !(align - 1);
+        }
     }
 }
 
@@ -171,13 +333,32 @@ pub(crate) struct Segment {
     pub(crate) sections: Vec<SectionId>,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub(crate) enum SegmentType {
     ElfHeader,
     Interpreter,
     Program,
     Uninitialized,
     Dynamic,
+    Tls,
+    Note,
+}
+
+impl SegmentType {
+    /// Alignment of the segments of this type, given the alignment of the loaded segments and the
+    /// alignment of the content of the segment.
+    fn alignment(&self, load_alignment: u64, content_alignment: u64) -> u64 {
+        match self {
+            SegmentType::ElfHeader | SegmentType::Program | SegmentType::Uninitialized => {
+                load_alignment
+            }
+            // The interpreter path is only read by the kernel, and doesn't need to be aligned.
+            SegmentType::Interpreter => 1,
+            // These segments describe content already loaded by another segment, which is read in
+            // place: they only need the natural alignment of that content.
+            SegmentType::Dynamic | SegmentType::Tls | SegmentType::Note => content_alignment,
+        }
+    }
 }
 
 #[derive(Debug, Display, Error)]
@@ -189,3 +370,21 @@ pub(crate) enum AddressResolutionError {
     #[transparent]
     OutOfBounds(OutOfBoundsError),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_alignment() {
+        for type_ in [SegmentType::ElfHeader, SegmentType::Program, SegmentType::Uninitialized] {
+            assert_eq!(0x1000, type_.alignment(0x1000, 8));
+            assert_eq!(1, type_.alignment(1, 8));
+        }
+        assert_eq!(1, SegmentType::Interpreter.alignment(0x1000, 8));
+        for type_ in [SegmentType::Dynamic, SegmentType::Tls, SegmentType::Note] {
+            assert_eq!(8, type_.alignment(0x1000, 8));
+            assert_eq!(4, type_.alignment(1, 4));
+        }
+    }
+}
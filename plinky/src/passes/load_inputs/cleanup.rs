@@ -1,18 +1,31 @@
 use crate::interner::intern;
+use crate::passes::load_inputs::merge_elf::GNU_WARNING_PREFIX;
 use crate::passes::load_inputs::section_groups::SectionGroups;
 use crate::repr::object::Object;
 use crate::repr::sections::SectionContent;
 use crate::repr::symbols::SymbolValue;
 
-pub(super) fn run(object: &mut Object, section_groups: &SectionGroups) {
+pub(super) fn run(
+    object: &mut Object,
+    section_groups: &SectionGroups,
+    executable_stack_override: Option<bool>,
+) {
     let gnu_stack = intern(".note.GNU-stack");
 
     let mut removed_gnu_stack = false;
+    let mut executable_stack_requested = false;
     let mut sections_to_remove = Vec::new();
     for section in object.sections.iter() {
+        // The only meaning of .note.GNU-stack is whether the input requires an executable stack
+        // (when the section is executable), so it's not copied in the output.
         if section.name == gnu_stack {
             sections_to_remove.push(section.id);
             removed_gnu_stack = true;
+            executable_stack_requested |= section.perms.execute;
+        }
+        // The content of these sections was already extracted into the object's symbol warnings.
+        if section.name.resolve().starts_with(GNU_WARNING_PREFIX) {
+            sections_to_remove.push(section.id);
         }
         if let SectionContent::Data(data) = &section.content {
             if data.bytes.is_empty() {
@@ -42,6 +55,7 @@ pub(super) fn run(object: &mut Object, section_groups: &SectionGroups) {
     }
 
     object.gnu_stack_section_ignored |= removed_gnu_stack;
+    object.executable_stack = executable_stack_override.unwrap_or(executable_stack_requested);
     for id in sections_to_remove {
         object.sections.remove(id, Some(&mut object.symbols));
     }
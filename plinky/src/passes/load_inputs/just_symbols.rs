@@ -0,0 +1,74 @@
+use crate::repr::symbols::{LoadSymbolsError, Symbols};
+use plinky_diagnostics::ObjectSpan;
+use plinky_elf::errors::LoadError;
+use plinky_elf::ids::serial::SerialIds;
+use plinky_elf::ids::StringIdGetters;
+use plinky_elf::{ElfObject, ElfSectionContent, ElfSymbolBinding, ElfSymbolDefinition};
+use plinky_macros::{Display, Error};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Import the defined global symbols of `path` as absolute definitions, without loading any of
+/// its sections (with `-R`/`--just-symbols`). Symbol values are used as-is, which makes sense for
+/// executables (like a fixed ROM image) where the values are already absolute addresses.
+pub(super) fn load(
+    ids: &mut SerialIds,
+    symbols: &mut Symbols,
+    path: &Path,
+) -> Result<(), JustSymbolsError> {
+    let mut reader = BufReader::new(File::open(path).map_err(JustSymbolsError::OpenFailed)?);
+    // The IDs of the file are not used in the output, so there is no need to share them.
+    let elf = ElfObject::load(&mut reader, &mut SerialIds::new())
+        .map_err(JustSymbolsError::ParseFailed)?;
+
+    let string_tables = elf
+        .sections
+        .iter()
+        .filter_map(|(id, section)| match &section.content {
+            ElfSectionContent::StringTable(table) => Some((*id, table)),
+            _ => None,
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let span = ObjectSpan::new_file(path);
+    for section in elf.sections.values() {
+        let ElfSectionContent::SymbolTable(table) = &section.content else { continue };
+        // Only the static symbol table is considered, as the dynamic one is a subset of it.
+        if table.dynsym {
+            continue;
+        }
+        for symbol in table.symbols.values() {
+            let weak = match symbol.binding {
+                ElfSymbolBinding::Global => false,
+                ElfSymbolBinding::Weak => true,
+                ElfSymbolBinding::Local | ElfSymbolBinding::Unknown(_) => continue,
+            };
+            match symbol.definition {
+                ElfSymbolDefinition::Absolute | ElfSymbolDefinition::Section(_) => {}
+                ElfSymbolDefinition::Undefined | ElfSymbolDefinition::Common => continue,
+            }
+            let name = string_tables
+                .get(symbol.name.section())
+                .and_then(|table| table.get(symbol.name.offset()))
+                .ok_or(JustSymbolsError::MissingSymbolName)?;
+            symbols
+                .add_just_symbols_definition(ids, name, symbol.value, weak, span.clone())
+                .map_err(|e| JustSymbolsError::InsertionFailed(name.into(), e))?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Error, Display)]
+pub(crate) enum JustSymbolsError {
+    #[display("failed to open the file")]
+    OpenFailed(#[source] std::io::Error),
+    #[display("failed to parse the file")]
+    ParseFailed(#[source] LoadError),
+    #[display("a symbol has a missing name")]
+    MissingSymbolName,
+    #[display("failed to add the symbol {f0}")]
+    InsertionFailed(String, #[source] LoadSymbolsError),
+}
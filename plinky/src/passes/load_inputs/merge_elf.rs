@@ -16,6 +16,8 @@ use plinky_elf::{
 use plinky_macros::{Display, Error};
 use std::collections::BTreeMap;
 
+pub(super) const GNU_WARNING_PREFIX: &str = ".gnu.warning.";
+
 pub(super) fn merge(
     object: &mut Object,
     strings: &mut Strings,
@@ -49,15 +51,21 @@ pub(super) fn merge(
                 relocations.insert(table.applies_to_section, table.relocations);
             }
             ElfSectionContent::Group(group) => pending_groups.push((section_id, group)),
-            ElfSectionContent::Hash(_) => {
+            ElfSectionContent::Hash(_) | ElfSectionContent::GnuHash(_) => {
                 // We don't need hash tables imported from the ELF file, we build our own.
             }
-            ElfSectionContent::Dynamic(_) => {
+            ElfSectionContent::Dynamic(_)
+            | ElfSectionContent::VersionSymbols(_)
+            | ElfSectionContent::VersionNeeded(_)
+            | ElfSectionContent::VersionDefined(_) => {
                 return Err(MergeElfError::UnsupportedDynamicSection);
             }
             ElfSectionContent::Note(table) => {
                 for note in table.notes {
                     match note {
+                        // The build-id identifies the input object, while the output will get
+                        // its own one if --build-id is passed.
+                        ElfNote::GnuBuildId(_) => {}
                         ElfNote::Unknown(unknown) => {
                             return Err(MergeElfError::UnsupportedUnknownNote {
                                 name: unknown.name,
@@ -95,6 +103,9 @@ pub(super) fn merge(
         if section_groups.should_skip_section(id) {
             continue;
         }
+        if uninit.tls {
+            object.tls_sections.insert(id, uninit.align);
+        }
         object.sections.add(Section {
             id,
             name: intern(
@@ -102,7 +113,10 @@ pub(super) fn merge(
             ),
             perms: uninit.perms,
             source: source.clone(),
-            content: SectionContent::Uninitialized(UninitializedSection { len: uninit.len }),
+            content: SectionContent::Uninitialized(UninitializedSection {
+                len: uninit.len,
+                align: uninit.align,
+            }),
         });
     }
 
@@ -110,11 +124,31 @@ pub(super) fn merge(
         if section_groups.should_skip_section(id) {
             continue;
         }
+        let name =
+            strings.get(name).map_err(|err| MergeElfError::MissingSectionName { id, err })?;
+
+        // GCC emits `.gnu.warning.SYMBOL` sections containing a message to show whenever SYMBOL
+        // is referenced. The section itself is then removed during cleanup.
+        if let Some(symbol) = name.strip_prefix(GNU_WARNING_PREFIX) {
+            let message = program.raw.0.split(|&byte| byte == 0).next().unwrap_or(&[]);
+            object
+                .symbol_warnings
+                .entry(intern(symbol))
+                .or_insert_with(|| String::from_utf8_lossy(message).trim().to_string());
+        }
+        if program.tls {
+            object.tls_sections.insert(id, program.align);
+        }
+        if let Some(linked) = program.link_order {
+            object.link_order.insert(id, linked);
+        }
+        if let Some(kind) = program.functions_array {
+            object.functions_arrays.insert(id, kind);
+        }
+
         object.sections.add(Section {
             id,
-            name: intern(
-                strings.get(name).map_err(|err| MergeElfError::MissingSectionName { id, err })?,
-            ),
+            name: intern(name),
             perms: program.perms,
             source: source.clone(),
             content: SectionContent::Data(DataSection {
@@ -166,6 +200,8 @@ fn merge_symbols(
             ElfSymbolType::Object => SymbolType::Object,
             ElfSymbolType::Function => SymbolType::Function,
             ElfSymbolType::Section => SymbolType::Section,
+            ElfSymbolType::GnuIfunc => SymbolType::GnuIfunc,
+            ElfSymbolType::Tls => SymbolType::Tls,
             // The file symbol type is not actually used, so we can omit it.
             ElfSymbolType::File => {
                 stt_file = Some(name);
@@ -176,9 +212,12 @@ fn merge_symbols(
             }
         };
 
-        let hidden = match elf_symbol.visibility {
-            ElfSymbolVisibility::Default => false,
-            ElfSymbolVisibility::Hidden => true,
+        let (hidden, protected) = match elf_symbol.visibility {
+            // Exported symbols only differ from default ones in how they are treated by
+            // symbol reduction, which plinky doesn't support.
+            ElfSymbolVisibility::Default | ElfSymbolVisibility::Exported => (false, false),
+            ElfSymbolVisibility::Hidden => (true, false),
+            ElfSymbolVisibility::Protected => (false, true),
             other => return Err(LoadSymbolsError::UnsupportedVisibility(other)),
         };
 
@@ -188,15 +227,17 @@ fn merge_symbols(
             type_,
             stt_file,
             span,
-            visibility: match (elf_symbol.binding, hidden) {
+            visibility: match (elf_symbol.binding, hidden || protected) {
                 (ElfSymbolBinding::Local, false) => SymbolVisibility::Local,
                 (ElfSymbolBinding::Local, true) => {
                     return Err(LoadSymbolsError::LocalHiddenSymbol);
                 }
-                (ElfSymbolBinding::Global, hidden) => {
-                    SymbolVisibility::Global { weak: false, hidden }
+                (ElfSymbolBinding::Global, _) => {
+                    SymbolVisibility::Global { weak: false, hidden, protected }
+                }
+                (ElfSymbolBinding::Weak, _) => {
+                    SymbolVisibility::Global { weak: true, hidden, protected }
                 }
-                (ElfSymbolBinding::Weak, hidden) => SymbolVisibility::Global { weak: true, hidden },
                 (ElfSymbolBinding::Unknown(_), _) => {
                     return Err(LoadSymbolsError::UnsupportedUnknownSymbolBinding);
                 }
@@ -206,12 +247,16 @@ fn merge_symbols(
                 ElfSymbolDefinition::Absolute => {
                     SymbolValue::Absolute { value: elf_symbol.value.into() }
                 }
-                ElfSymbolDefinition::Common => todo!(),
+                // The value of common symbols is their alignment rather than an address.
+                ElfSymbolDefinition::Common => {
+                    SymbolValue::Common { size: elf_symbol.size, align: elf_symbol.value }
+                }
                 ElfSymbolDefinition::Section(section) => SymbolValue::SectionRelative {
                     section,
                     offset: (elf_symbol.value as i64).into(),
                 },
             },
+            size: elf_symbol.size,
         };
 
         symbols.add_symbol(symbol)?;
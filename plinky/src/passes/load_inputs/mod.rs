@@ -1,4 +1,5 @@
 use crate::cli::CliOptions;
+use crate::passes::load_inputs::just_symbols::JustSymbolsError;
 use crate::passes::load_inputs::merge_elf::MergeElfError;
 use crate::passes::load_inputs::read_objects::{ObjectsReader, ReadObjectsError};
 use crate::passes::load_inputs::section_groups::SectionGroups;
@@ -8,23 +9,42 @@ use crate::repr::sections::Sections;
 use crate::repr::symbols::{LoadSymbolsError, Symbols};
 use plinky_diagnostics::ObjectSpan;
 use plinky_elf::ids::serial::SerialIds;
-use plinky_elf::ElfEnvironment;
+use plinky_elf::{ElfABI, ElfEndian, ElfEnvironment};
 use plinky_macros::{Display, Error};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
 
 mod cleanup;
 mod inject_version;
+mod just_symbols;
 mod merge_elf;
-mod read_objects;
+pub(crate) mod read_objects;
+mod references;
 mod section_groups;
 mod strings;
 
 pub(crate) fn run(options: &CliOptions, ids: &mut SerialIds) -> Result<Object, LoadInputsError> {
-    let mut reader = ObjectsReader::new(&options.inputs);
+    let mut reader = ObjectsReader::new(&options.inputs, options.verbose >= 1);
 
     let mut empty_symbols = Symbols::new(ids);
     let entry_point = empty_symbols
         .add_unknown_global(ids, &options.entry)
         .map_err(LoadInputsError::EntryInsertionFailed)?;
+    for (name, value) in &options.defsym {
+        empty_symbols
+            .add_command_line_definition(ids, name, *value)
+            .map_err(|e| LoadInputsError::DefsymInsertionFailed(name.clone(), e))?;
+    }
+    for path in &options.just_symbols {
+        just_symbols::load(ids, &mut empty_symbols, path)
+            .map_err(|e| LoadInputsError::JustSymbolsFailed(path.clone(), e))?;
+    }
+
+    // Wrapping is configured after adding the entry point and the command line definitions, as
+    // it only applies to the references coming from the input objects.
+    for name in &options.wrap {
+        empty_symbols.add_wrapped(name);
+    }
 
     let mut state = State::Empty {
         symbols: empty_symbols,
@@ -37,6 +57,13 @@ pub(crate) fn run(options: &CliOptions, ids: &mut SerialIds) -> Result<Object, L
             State::WithContent { object, .. } => &object.symbols,
         };
         let Some((source, elf)) = reader.next_object(ids, symbols)? else { break };
+        if options.static_linking && elf.is_shared_object() {
+            return Err(LoadInputsError::SharedObjectInStaticLink(source));
+        }
+        // Relocations and the other synthesized content are only written in little-endian.
+        if elf.env.endian == ElfEndian::Big {
+            return Err(LoadInputsError::BigEndianUnsupported(source));
+        }
 
         state = match state {
             State::Empty { symbols, mut section_groups, mut strings } => {
@@ -45,11 +72,30 @@ pub(crate) fn run(options: &CliOptions, ids: &mut SerialIds) -> Result<Object, L
                     sections: Sections::new(),
                     symbols,
                     dynamic_relocations: Vec::new(),
+                    retained_relocations: BTreeMap::new(),
+                    emit_relocs: options.emit_relocs,
                     got: None,
                     entry_point,
                     mode: options.mode,
-                    executable_stack: options.executable_stack,
+                    segments_layout: options.segments_layout,
+                    no_rosegment: options.no_rosegment,
+                    sort_sections: options.sort_sections,
+                    symtab_sort: options.symtab_sort,
+                    discard_locals: options.discard_locals,
+                    hash_style: options.hash_style,
+                    symbolic: options.symbolic,
+                    spare_dynamic_tags: options.spare_dynamic_tags,
+                    sort_dyn_relocs: options.sort_dyn_relocs,
+                    entry_alignment: options.entry_alignment,
+                    // Decided by the cleanup, once all the .note.GNU-stack sections are loaded.
+                    executable_stack: false,
                     gnu_stack_section_ignored: false,
+                    build_id_section: None,
+                    text_relocations: BTreeSet::new(),
+                    tls_sections: BTreeMap::new(),
+                    link_order: BTreeMap::new(),
+                    functions_arrays: BTreeMap::new(),
+                    symbol_warnings: BTreeMap::new(),
                 };
                 inject_version::run(ids, &mut object);
                 merge_elf::merge(
@@ -63,7 +109,7 @@ pub(crate) fn run(options: &CliOptions, ids: &mut SerialIds) -> Result<Object, L
                 State::WithContent { object, strings, section_groups, first_span: source }
             }
             State::WithContent { mut object, mut strings, mut section_groups, first_span } => {
-                if object.env != elf.env {
+                if !compatible_envs(&object.env, &elf.env) {
                     return Err(LoadInputsError::MismatchedEnv {
                         first_span: first_span.clone(),
                         first_env: object.env,
@@ -71,6 +117,9 @@ pub(crate) fn run(options: &CliOptions, ids: &mut SerialIds) -> Result<Object, L
                         current_env: elf.env,
                     });
                 }
+                if elf.env.abi == ElfABI::Gnu {
+                    object.env.abi = ElfABI::Gnu;
+                }
                 merge_elf::merge(
                     &mut object,
                     &mut strings,
@@ -87,7 +136,13 @@ pub(crate) fn run(options: &CliOptions, ids: &mut SerialIds) -> Result<Object, L
     match state {
         State::Empty { .. } => Err(LoadInputsError::NoInputFiles),
         State::WithContent { mut object, section_groups, .. } => {
-            cleanup::run(&mut object, &section_groups);
+            cleanup::run(&mut object, &section_groups, options.executable_stack);
+            references::run(&mut object);
+            for section in object.sections.iter_mut() {
+                if let Some(perms) = options.section_perms.get(section.name.resolve().as_str()) {
+                    section.perms = *perms;
+                }
+            }
             Ok(object)
         }
     }
@@ -107,14 +162,31 @@ enum State {
     },
 }
 
+/// Whether an object built for `current` can be linked together with ones built for `first`. The
+/// comparison is structural, so every machine (including the ones the linker can't relocate yet,
+/// like AArch64) is only rejected when mixed with a different one.
+fn compatible_envs(first: &ElfEnvironment, current: &ElfEnvironment) -> bool {
+    // The GNU ABI is a superset of System V, so objects using GNU extensions can be mixed with
+    // plain System V ones, marking the output as using the GNU ABI.
+    *first == ElfEnvironment { abi: first.abi, ..*current }
+}
+
 #[derive(Debug, Error, Display)]
 pub(crate) enum LoadInputsError {
     #[display("no input files were provided")]
     NoInputFiles,
     #[display("failed to add the entry point as an unknown symbol")]
     EntryInsertionFailed(#[source] LoadSymbolsError),
+    #[display("failed to add the symbol {f0} defined with --defsym")]
+    DefsymInsertionFailed(String, #[source] LoadSymbolsError),
+    #[display("failed to import the symbols of {f0:?} with --just-symbols")]
+    JustSymbolsFailed(PathBuf, #[source] JustSymbolsError),
     #[transparent]
     ReadFailed(ReadObjectsError),
+    #[display("shared object {f0} cannot be linked when -static is passed")]
+    SharedObjectInStaticLink(ObjectSpan),
+    #[display("big-endian object {f0} is not supported")]
+    BigEndianUnsupported(ObjectSpan),
     #[display("failed to include the ELF file {f0}")]
     MergeFailed(ObjectSpan, #[source] MergeElfError),
     #[display("environment of {first_span} is {first_env:?}, while environment of {current_span} is {current_env:?}")]
@@ -125,3 +197,31 @@ pub(crate) enum LoadInputsError {
         current_env: ElfEnvironment,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plinky_elf::{ElfClass, ElfMachine};
+
+    fn env(machine: ElfMachine, abi: ElfABI) -> ElfEnvironment {
+        ElfEnvironment { class: ElfClass::Elf64, endian: ElfEndian::Little, abi, machine }
+    }
+
+    #[test]
+    fn test_compatible_envs_same_machine() {
+        for machine in [ElfMachine::X86_64, ElfMachine::Aarch64] {
+            let system_v = env(machine, ElfABI::SystemV);
+            assert!(compatible_envs(&system_v, &system_v));
+            assert!(compatible_envs(&system_v, &env(machine, ElfABI::Gnu)));
+            assert!(compatible_envs(&env(machine, ElfABI::Gnu), &system_v));
+        }
+    }
+
+    #[test]
+    fn test_compatible_envs_different_machine() {
+        let x86_64 = env(ElfMachine::X86_64, ElfABI::SystemV);
+        let aarch64 = env(ElfMachine::Aarch64, ElfABI::SystemV);
+        assert!(!compatible_envs(&x86_64, &aarch64));
+        assert!(!compatible_envs(&aarch64, &x86_64));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::cli::CliInput;
 use crate::interner::intern;
 use crate::repr::symbols::{Symbol, SymbolValue, Symbols};
 use plinky_ar::{ArFile, ArMemberId, ArReadError, ArReader};
@@ -13,17 +14,24 @@ use std::path::{Path, PathBuf};
 
 type ObjectItem = (ObjectSpan, ElfObject<SerialIds>);
 
-pub(super) struct ObjectsReader<'a> {
-    remaining_files: &'a [PathBuf],
+pub(crate) struct ObjectsReader<'a> {
+    remaining_files: &'a [CliInput],
     current_archive: Option<PendingArchive>,
+    all_archive_members: bool,
+    verbose: bool,
 }
 
 impl<'a> ObjectsReader<'a> {
-    pub(super) fn new(paths: &'a [PathBuf]) -> Self {
-        Self { remaining_files: paths, current_archive: None }
+    pub(crate) fn new(inputs: &'a [CliInput], verbose: bool) -> Self {
+        Self { remaining_files: inputs, current_archive: None, all_archive_members: false, verbose }
     }
 
-    pub(super) fn next_object(
+    /// Read every member of the archives, as if `--whole-archive` was passed for all of them.
+    pub(crate) fn all_archive_members(inputs: &'a [CliInput], verbose: bool) -> Self {
+        Self { remaining_files: inputs, current_archive: None, all_archive_members: true, verbose }
+    }
+
+    pub(crate) fn next_object(
         &mut self,
         ids: &mut SerialIds,
         symbols: &Symbols,
@@ -36,7 +44,8 @@ impl<'a> ObjectsReader<'a> {
             if self.remaining_files.is_empty() {
                 return Ok(None);
             }
-            let path = &self.remaining_files[0];
+            let input = &self.remaining_files[0];
+            let path = &input.path;
             self.remaining_files = &self.remaining_files[1..];
 
             let mut r = BufReader::new(
@@ -44,16 +53,25 @@ impl<'a> ObjectsReader<'a> {
             );
             match FileType::from_magic_number(path, &mut r)? {
                 FileType::Elf => {
+                    if self.verbose {
+                        eprintln!("verbose: loading object {}", path.display());
+                    }
                     return Ok(Some((
                         ObjectSpan::new_file(path),
                         ElfObject::load(&mut r, ids)
                             .map_err(|e| ReadObjectsError::FileParseFailed(path.clone(), e))?,
-                    )))
+                    )));
                 }
                 FileType::Ar => {
-                    if let Some(archive) = PendingArchive::new(path.clone(), r, symbols)? {
-                        self.current_archive = Some(archive);
+                    if self.verbose {
+                        eprintln!("verbose: searching archive {}", path.display());
                     }
+                    self.current_archive = Some(PendingArchive::new(
+                        path.clone(),
+                        r,
+                        symbols,
+                        input.options.whole_archive || self.all_archive_members,
+                    )?);
                     continue;
                 }
             }
@@ -66,11 +84,24 @@ impl<'a> ObjectsReader<'a> {
     ) -> Result<Option<ObjectItem>, ReadObjectsError> {
         let Some(pending_archive) = &mut self.current_archive else { return Ok(None) };
         match pending_archive.next()? {
-            Some(file) => match ElfObject::load(&mut Cursor::new(file.content), ids) {
-                Ok(object) => Ok(Some((
-                    ObjectSpan::new_archive_member(&pending_archive.path, file.name),
-                    object,
-                ))),
+            Some((file, symbol)) => match ElfObject::load(&mut Cursor::new(file.content), ids) {
+                Ok(object) => {
+                    if self.verbose {
+                        eprintln!(
+                            "verbose: loading {} from archive {} ({})",
+                            file.name,
+                            pending_archive.path.display(),
+                            match symbol {
+                                Some(symbol) => format!("needed by `{symbol}`"),
+                                None => "--whole-archive".into(),
+                            }
+                        );
+                    }
+                    Ok(Some((
+                        ObjectSpan::new_archive_member(&pending_archive.path, file.name),
+                        object,
+                    )))
+                }
                 Err(err) => Err(ReadObjectsError::ArchiveFileParseFailed(
                     file.name,
                     pending_archive.path.clone(),
@@ -88,7 +119,14 @@ impl<'a> ObjectsReader<'a> {
 struct PendingArchive {
     path: PathBuf,
     reader: ArReader<BufReader<File>>,
-    pending_members: VecDeque<ArMemberId>,
+    pending_members: PendingMembers,
+}
+
+enum PendingMembers {
+    /// Members to load, along with the first undefined symbol that caused them to be loaded.
+    Needed(VecDeque<(ArMemberId, String)>),
+    /// All members are loaded in the order they appear in the archive (`--whole-archive`).
+    All,
 }
 
 impl PendingArchive {
@@ -96,9 +134,16 @@ impl PendingArchive {
         path: PathBuf,
         reader: BufReader<File>,
         symbols: &Symbols,
-    ) -> Result<Option<Self>, ReadObjectsError> {
-        let reader =
+        whole_archive: bool,
+    ) -> Result<Self, ReadObjectsError> {
+        let mut reader =
             ArReader::new(reader).map_err(|e| ReadObjectsError::ExtractFailed(path.clone(), e))?;
+        // Members of thin archives are stored next to the archive rather than inside it.
+        reader.set_thin_archive_dir(path.parent().unwrap_or(Path::new("")));
+        if whole_archive {
+            // No need for the symbol table, as there is nothing to look up.
+            return Ok(PendingArchive { path, reader, pending_members: PendingMembers::All });
+        }
 
         let Some(symbol_table) = reader.symbol_table().cloned() else {
             return Err(ReadObjectsError::NoSymbolTableAtArchiveStart {
@@ -109,6 +154,14 @@ impl PendingArchive {
 
         let mut pending_members = VecDeque::new();
         let mut pending_members_set = HashSet::new();
+        if symbols.iter_undefined().next().is_none() {
+            // Nothing left to resolve, so no member would be loaded.
+            return Ok(PendingArchive {
+                path,
+                reader,
+                pending_members: PendingMembers::Needed(pending_members),
+            });
+        }
         for (symbol_name, member_id) in symbol_table.symbols {
             if let Ok(Symbol { value: SymbolValue::Undefined, .. }) =
                 symbols.get_global(intern(&symbol_name))
@@ -117,23 +170,33 @@ impl PendingArchive {
                 // linker output (aka we need to store it in a Vec). The HashSet is used as a quick
                 // way to lookup, since it doesn't preserve ordering.
                 if pending_members_set.insert(member_id) {
-                    pending_members.push_back(member_id);
+                    pending_members.push_back((member_id, symbol_name));
                 }
             }
         }
 
-        Ok(Some(PendingArchive { path, reader, pending_members }))
+        Ok(PendingArchive {
+            path,
+            reader,
+            pending_members: PendingMembers::Needed(pending_members),
+        })
     }
 
-    fn next(&mut self) -> Result<Option<ArFile>, ReadObjectsError> {
-        if let Some(member_id) = self.pending_members.pop_front() {
-            Ok(Some(
-                self.reader
-                    .read_member_by_id(&member_id)
-                    .map_err(|e| ReadObjectsError::ExtractFailed(self.path.clone(), e))?,
-            ))
-        } else {
-            Ok(None)
+    /// Returns the next member to load, along with the undefined symbol it was loaded for (if any).
+    fn next(&mut self) -> Result<Option<(ArFile, Option<String>)>, ReadObjectsError> {
+        let extract_err = |e| ReadObjectsError::ExtractFailed(self.path.clone(), e);
+        match &mut self.pending_members {
+            PendingMembers::Needed(pending) => match pending.pop_front() {
+                Some((member_id, symbol)) => Ok(Some((
+                    self.reader.read_member_by_id(&member_id).map_err(extract_err)?,
+                    Some(symbol),
+                ))),
+                None => Ok(None),
+            },
+            PendingMembers::All => match self.reader.next() {
+                Some(file) => Ok(Some((file.map_err(extract_err)?, None))),
+                None => Ok(None),
+            },
         }
     }
 }
@@ -156,7 +219,7 @@ impl FileType {
 
         match &magic {
             [0x7F, b'E', b'L', b'F', ..] => Ok(FileType::Elf),
-            b"!<arch>\n" => Ok(FileType::Ar),
+            b"!<arch>\n" | b"!<thin>\n" => Ok(FileType::Ar),
             _ => Err(ReadObjectsError::UnsupportedFileType),
         }
     }
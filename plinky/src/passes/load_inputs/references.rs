@@ -0,0 +1,13 @@
+use crate::repr::object::Object;
+use crate::repr::sections::SectionContent;
+
+/// Record which objects reference each symbol, based on the relocations of their sections. This
+/// runs after the cleanup, so that sections of discarded section groups are not considered.
+pub(super) fn run(object: &mut Object) {
+    for section in object.sections.iter() {
+        let SectionContent::Data(data) = &section.content else { continue };
+        for relocation in &data.relocations {
+            object.symbols.add_reference(relocation.symbol, section.source.clone());
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::interner::intern;
+use crate::repr::object::Object;
+use crate::repr::sections::{DataSection, Section, SectionContent};
+use crate::repr::symbols::{SymbolValue, SymbolVisibility};
+use crate::utils::ints::{align_up, Offset, OutOfBoundsError};
+use plinky_diagnostics::ObjectSpan;
+use plinky_elf::ids::serial::{SectionId, SerialIds};
+use plinky_elf::{ElfDeduplication, ElfFunctionsArray, ElfPermissions};
+use plinky_macros::{Display, Error};
+use plinky_utils::raw_types::RawTypeAsPointerSize;
+use std::collections::BTreeMap;
+
+const KINDS: [(ElfFunctionsArray, &str); 3] = [
+    (ElfFunctionsArray::PreInit, "preinit_array"),
+    (ElfFunctionsArray::Init, "init_array"),
+    (ElfFunctionsArray::Fini, "fini_array"),
+];
+
+/// Merge the arrays of functions called at startup and exit into a single `.preinit_array`,
+/// `.init_array` and `.fini_array` section, as the dynamic loader (and the C runtime in static
+/// executables) only knows about one array of each kind.
+///
+/// Like GNU ld, sections with a priority suffix (like `.init_array.00101`) are placed first,
+/// sorted by ascending priority, followed by the sections without a priority in input order.
+/// The `__init_array_start` and `__init_array_end` symbols (and the equivalent ones for the other
+/// kinds) are then defined to point to the bounds of the merged section, if referenced. When no
+/// input provides an array of that kind, the symbols are defined to an empty section instead.
+pub(crate) fn run(
+    ids: &mut SerialIds,
+    object: &mut Object,
+) -> Result<(), MergeFunctionsArraysError> {
+    for (kind, name) in KINDS {
+        let mut members = object
+            .sections
+            .iter()
+            .filter(|section| object.functions_arrays.get(&section.id) == Some(&kind))
+            .map(|section| (priority(&section.name.resolve(), name), section.id))
+            .collect::<Vec<_>>();
+        // The sort is stable, so sections with the same priority are kept in input order.
+        members.sort_by_key(|(priority, _)| *priority);
+        let members = members.into_iter().map(|(_, id)| id).collect::<Vec<_>>();
+
+        let merged = match merge(object, &members, name)? {
+            Some(merged) => merged,
+            None if bounds_referenced(object, name) => add_empty(ids, object, kind, name),
+            None => continue,
+        };
+        define_bounds(object, merged, name);
+    }
+    Ok(())
+}
+
+/// Priority of a section, parsed from the `.NNNNN` suffix of its name. Sections without a
+/// priority are sorted after all the sections with one.
+fn priority(section_name: &str, base: &str) -> u64 {
+    section_name
+        .strip_prefix('.')
+        .and_then(|name| name.strip_prefix(base))
+        .and_then(|suffix| suffix.strip_prefix('.'))
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(u64::MAX)
+}
+
+fn merge(
+    object: &mut Object,
+    members: &[SectionId],
+    name: &str,
+) -> Result<Option<SectionId>, MergeFunctionsArraysError> {
+    let Some((&kept, rest)) = members.split_first() else { return Ok(None) };
+    let pointer_size = <u64 as RawTypeAsPointerSize>::size(object.env.class) as u64;
+
+    let mut moved = BTreeMap::new();
+    for &id in rest {
+        let section = object.sections.remove(id, None).unwrap();
+        let (SectionContent::Data(kept), SectionContent::Data(data)) =
+            (&mut object.sections.get_mut(kept).unwrap().content, section.content)
+        else {
+            return Err(MergeFunctionsArraysError::Uninitialized(name.into()));
+        };
+
+        let start = align_up(kept.bytes.len() as u64, pointer_size);
+        kept.bytes.resize(start as usize, 0);
+        kept.bytes.extend_from_slice(&data.bytes);
+        for mut relocation in data.relocations {
+            relocation.offset = relocation.offset.add(Offset::from(start as i64))?;
+            kept.relocations.push(relocation);
+        }
+
+        object.functions_arrays.remove(&id);
+        moved.insert(id, start);
+    }
+
+    for (_, symbol) in object.symbols.iter_mut() {
+        if let SymbolValue::SectionRelative { section, offset } = &mut symbol.value {
+            if let Some(&start) = moved.get(section) {
+                *section = kept;
+                *offset = offset.add(Offset::from(start as i64))?;
+            }
+        }
+    }
+
+    object.sections.get_mut(kept).unwrap().name = intern(format!(".{name}"));
+    Ok(Some(kept))
+}
+
+fn bounds_referenced(object: &Object, name: &str) -> bool {
+    ["start", "end"].iter().any(|suffix| {
+        object
+            .symbols
+            .get_global(intern(format!("__{name}_{suffix}")))
+            .is_ok_and(|symbol| matches!(symbol.value, SymbolValue::Undefined))
+    })
+}
+
+fn add_empty(
+    ids: &mut SerialIds,
+    object: &mut Object,
+    kind: ElfFunctionsArray,
+    name: &str,
+) -> SectionId {
+    let id = ids.allocate_section_id();
+    object.sections.add(Section {
+        id,
+        name: intern(format!(".{name}")),
+        perms: ElfPermissions { read: true, write: true, execute: false },
+        source: ObjectSpan::new_synthetic(),
+        content: SectionContent::Data(DataSection {
+            deduplication: ElfDeduplication::Disabled,
+            bytes: Vec::new(),
+            relocations: Vec::new(),
+        }),
+    });
+    object.functions_arrays.insert(id, kind);
+    id
+}
+
+fn define_bounds(object: &mut Object, section: SectionId, name: &str) {
+    let len = match &object.sections.get(section).unwrap().content {
+        SectionContent::Data(data) => data.bytes.len() as i64,
+        SectionContent::Uninitialized(uninit) => uninit.len as i64,
+    };
+    for (suffix, offset) in [("start", 0), ("end", len)] {
+        let Ok(symbol) = object.symbols.get_global(intern(format!("__{name}_{suffix}"))) else {
+            continue;
+        };
+        let id = symbol.id;
+        let symbol = object.symbols.get_mut(id);
+        if let SymbolValue::Undefined = symbol.value {
+            symbol.value = SymbolValue::SectionRelative { section, offset: offset.into() };
+            symbol.visibility =
+                SymbolVisibility::Global { weak: false, hidden: true, protected: false };
+        }
+    }
+}
+
+#[derive(Debug, Display, Error)]
+pub(crate) enum MergeFunctionsArraysError {
+    #[display("section {f0} can't be uninitialized")]
+    Uninitialized(String),
+    #[transparent]
+    OutOfBounds(OutOfBoundsError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority() {
+        assert_eq!(101, priority(".init_array.00101", "init_array"));
+        assert_eq!(65535, priority(".fini_array.65535", "fini_array"));
+        assert_eq!(u64::MAX, priority(".init_array", "init_array"));
+        assert_eq!(u64::MAX, priority(".init_array.foo", "init_array"));
+        assert_eq!(u64::MAX, priority(".ctors", "init_array"));
+    }
+}
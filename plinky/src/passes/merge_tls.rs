@@ -0,0 +1,88 @@
+use crate::interner::intern;
+use crate::repr::object::Object;
+use crate::repr::sections::SectionContent;
+use crate::repr::symbols::SymbolValue;
+use crate::utils::ints::{align_up, Offset, OutOfBoundsError};
+use plinky_elf::ids::serial::SectionId;
+use plinky_macros::{Display, Error};
+use std::collections::BTreeMap;
+
+/// Merge the thread-local sections of all inputs into the TLS template: a single `.tdata`
+/// section with the initialized data, and a single `.tbss` section with the uninitialized data.
+///
+/// Each contributed section is placed at an offset aligned to its own alignment, and the merged
+/// section inherits the largest alignment of its members. Symbols pointing to the merged sections
+/// are moved to the resulting section.
+pub(crate) fn run(object: &mut Object) -> Result<(), MergeTlsError> {
+    let mut data = Vec::new();
+    let mut uninit = Vec::new();
+    for section in object.sections.iter() {
+        let Some(&align) = object.tls_sections.get(&section.id) else { continue };
+        match &section.content {
+            SectionContent::Data(_) => data.push((section.id, align)),
+            SectionContent::Uninitialized(_) => uninit.push((section.id, align)),
+        }
+    }
+
+    merge(object, &data, ".tdata")?;
+    merge(object, &uninit, ".tbss")?;
+    Ok(())
+}
+
+fn merge(
+    object: &mut Object,
+    members: &[(SectionId, u64)],
+    name: &str,
+) -> Result<(), MergeTlsError> {
+    let Some((&(kept, kept_align), rest)) = members.split_first() else { return Ok(()) };
+
+    let mut moved = BTreeMap::new();
+    let mut max_align = kept_align;
+    for &(id, align) in rest {
+        max_align = max_align.max(align);
+
+        let section = object.sections.remove(id, None).unwrap();
+        let start = match (&mut object.sections.get_mut(kept).unwrap().content, section.content) {
+            (SectionContent::Data(kept), SectionContent::Data(data)) => {
+                let start = align_up(kept.bytes.len() as u64, align);
+                kept.bytes.resize(start as usize, 0);
+                kept.bytes.extend_from_slice(&data.bytes);
+                for mut relocation in data.relocations {
+                    relocation.offset = relocation.offset.add(Offset::from(start as i64))?;
+                    kept.relocations.push(relocation);
+                }
+                start
+            }
+            (SectionContent::Uninitialized(kept), SectionContent::Uninitialized(uninit)) => {
+                let start = align_up(kept.len, align);
+                kept.len = start + uninit.len;
+                kept.align = kept.align.max(uninit.align);
+                start
+            }
+            _ => unreachable!("merged thread-local sections must have the same kind of content"),
+        };
+
+        object.tls_sections.remove(&id);
+        moved.insert(id, start);
+    }
+
+    for (_, symbol) in object.symbols.iter_mut() {
+        if let SymbolValue::SectionRelative { section, offset } = &mut symbol.value {
+            if let Some(&start) = moved.get(section) {
+                *section = kept;
+                *offset = offset.add(Offset::from(start as i64))?;
+            }
+        }
+    }
+
+    object.sections.get_mut(kept).unwrap().name = intern(name);
+    object.tls_sections.insert(kept, max_align);
+    Ok(())
+}
+
+#[derive(Debug, Display, Error)]
+#[display("failed to merge the thread-local sections")]
+pub(crate) enum MergeTlsError {
+    #[transparent]
+    OutOfBounds(OutOfBoundsError),
+}
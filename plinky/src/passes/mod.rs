@@ -1,12 +1,22 @@
+pub(crate) mod allocate_common;
 pub(crate) mod build_elf;
+pub(crate) mod build_id;
 pub(crate) mod deduplicate;
 pub(crate) mod demote_global_hidden_symbols;
+pub(crate) mod flat_binary;
 pub(crate) mod gc_sections;
 pub(crate) mod generate_got;
+pub(crate) mod gnu_warnings;
+pub(crate) mod icf;
+pub(crate) mod inject_gnu_debuglink;
 pub(crate) mod inject_interpreter;
 pub(crate) mod layout;
 pub(crate) mod load_inputs;
+pub(crate) mod merge_functions_arrays;
+pub(crate) mod merge_tls;
 pub(crate) mod relocate;
 pub(crate) mod remove_section_symbols;
 pub(crate) mod replace_section_relative_symbols;
+pub(crate) mod security_warnings;
+pub(crate) mod undefined_symbols;
 pub(crate) mod write_to_disk;
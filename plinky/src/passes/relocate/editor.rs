@@ -1,6 +1,7 @@
 use crate::passes::relocate::RelocationErrorInner;
 use crate::repr::relocations::Relocation;
 use crate::utils::ints::{ExtractNumber, Offset, OutOfBoundsError};
+use std::ops::Range;
 
 pub(super) struct ByteEditor<'a> {
     pub(super) relocation: &'a Relocation,
@@ -15,6 +16,13 @@ impl ByteEditor<'_> {
         }
     }
 
+    pub(super) fn addend_64(&self) -> Result<Offset, RelocationErrorInner> {
+        match self.relocation.addend {
+            Some(addend) => Ok(addend),
+            None => Ok(i64::from_le_bytes(self.read()?).into()),
+        }
+    }
+
     pub(super) fn write_u32<N>(&mut self, value: N) -> Result<(), RelocationErrorInner>
     where
         N: ExtractNumber,
@@ -40,37 +48,88 @@ impl ByteEditor<'_> {
     }
 
     fn read<const LEN: usize>(&self) -> Result<[u8; LEN], RelocationErrorInner> {
-        let err = Err(RelocationErrorInner::OutOfBoundsAccess {
-            offset: self.relocation.offset,
-            len: LEN,
-            size: self.bytes.len(),
-        });
-
-        let Ok(start) = usize::try_from(self.relocation.offset.extract()) else { return err };
-        let Some(end) = start.checked_add(LEN) else { return err };
-        if end > self.bytes.len() {
-            return err;
-        }
-
+        let range = self.range(LEN)?;
         let mut data = [0; LEN];
-        data.copy_from_slice(&self.bytes[start..end]);
+        data.copy_from_slice(&self.bytes[range]);
         Ok(data)
     }
 
     fn write(&mut self, bytes: &[u8]) -> Result<(), RelocationErrorInner> {
-        let err = Err(RelocationErrorInner::OutOfBoundsAccess {
+        let range = self.range(bytes.len())?;
+        self.bytes[range].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Range of bytes accessed by the relocation, ensuring it fits within the section.
+    fn range(&self, len: usize) -> Result<Range<usize>, RelocationErrorInner> {
+        let err = || RelocationErrorInner::OutOfBoundsAccess {
             offset: self.relocation.offset,
-            len: bytes.len(),
+            len,
             size: self.bytes.len(),
-        });
+        };
 
-        let Ok(start) = usize::try_from(self.relocation.offset.extract()) else { return err };
-        let Some(end) = start.checked_add(bytes.len()) else { return err };
+        let start = usize::try_from(self.relocation.offset.extract()).map_err(|_| err())?;
+        let end = start.checked_add(len).ok_or_else(err)?;
         if end > self.bytes.len() {
-            return err;
+            return Err(err());
+        }
+        Ok(start..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::relocations::RelocationType;
+    use crate::utils::ints::Absolute;
+    use plinky_elf::ids::serial::SerialIds;
+
+    #[test]
+    fn test_write_at_the_end() {
+        let mut bytes = [0; 8];
+        let relocation = relocation(4);
+        let mut editor = ByteEditor { relocation: &relocation, bytes: &mut bytes };
+        editor.write_u32(Absolute::from(0x12345678u64)).unwrap();
+        assert_eq!([0, 0, 0, 0, 0x78, 0x56, 0x34, 0x12], bytes);
+    }
+
+    #[test]
+    fn test_write_past_the_end() {
+        let mut bytes = [0; 8];
+        let relocation = relocation(5);
+        let mut editor = ByteEditor { relocation: &relocation, bytes: &mut bytes };
+
+        assert_out_of_bounds(editor.write_u32(Absolute::from(0u64)), 5, 4);
+        assert_out_of_bounds(editor.write_i32(Absolute::from(0u64)), 5, 4);
+        assert_out_of_bounds(editor.write_u64(Absolute::from(0u64)), 5, 8);
+        assert_out_of_bounds(editor.addend_32().map(|_| ()), 5, 4);
+        assert_out_of_bounds(editor.addend_64().map(|_| ()), 5, 8);
+        assert_eq!([0; 8], bytes);
+    }
+
+    #[test]
+    fn test_write_with_negative_offset() {
+        let mut bytes = [0; 8];
+        let relocation = relocation(-1);
+        let mut editor = ByteEditor { relocation: &relocation, bytes: &mut bytes };
+        assert_out_of_bounds(editor.write_u32(Absolute::from(0u64)), -1, 4);
+    }
+
+    fn assert_out_of_bounds(result: Result<(), RelocationErrorInner>, offset: i64, width: usize) {
+        match result {
+            Err(RelocationErrorInner::OutOfBoundsAccess { offset: o, len, size }) => {
+                assert_eq!((Offset::from(offset), width, 8), (o, len, size));
+            }
+            other => panic!("expected out of bounds access, got {other:?}"),
         }
+    }
 
-        self.bytes[start..end].copy_from_slice(bytes);
-        Ok(())
+    fn relocation(offset: i64) -> Relocation {
+        Relocation {
+            type_: RelocationType::Absolute32,
+            symbol: SerialIds::new().allocate_symbol_id(),
+            offset: offset.into(),
+            addend: None,
+        }
     }
 }
@@ -1,43 +1,71 @@
 mod editor;
 
-use crate::cli::Mode;
+use crate::cli::{Mode, SymbolicBinding};
+use crate::diagnostics::text_relocation;
 use crate::passes::generate_got::GOT;
 use crate::passes::layout::{AddressResolutionError, Layout};
 use crate::passes::relocate::editor::ByteEditor;
 use crate::repr::object::Object;
 use crate::repr::relocations::{Relocation, RelocationType};
 use crate::repr::sections::{DataSection, SectionContent};
-use crate::repr::symbols::{MissingGlobalSymbol, ResolveSymbolError, ResolvedSymbol, Symbols};
+use crate::repr::symbols::{
+    MissingGlobalSymbol, ResolveSymbolError, ResolveSymbolErrorKind, ResolvedSymbol, SymbolType,
+    SymbolValue, SymbolVisibility, Symbols,
+};
 use crate::utils::ints::{Absolute, Address, Offset, OutOfBoundsError};
+use plinky_diagnostics::Diagnostic;
 use plinky_elf::ids::serial::SectionId;
 use plinky_elf::{ElfClass, ElfEnvironment};
 use plinky_macros::{Display, Error};
+use std::collections::{BTreeMap, BTreeSet};
 
 pub(crate) fn run(object: &mut Object, layout: &Layout) -> Result<(), RelocationError> {
     let mut relocator = Relocator {
         layout,
         symbols: &mut object.symbols,
         dynamic_relocations: &mut object.dynamic_relocations,
+        retained_relocations: object.emit_relocs.then_some(&mut object.retained_relocations),
         env: &object.env,
         got: object.got.as_ref(),
         mode: object.mode,
+        symbolic: object.symbolic,
     };
+    let mut text_relocations = BTreeSet::new();
     for section in object.sections.iter_mut() {
         match &mut section.content {
             SectionContent::Data(data) => {
+                let dynamic_relocations_before = relocator.dynamic_relocations.len();
                 relocator.relocate(section.id, data)?;
+                if !section.perms.write
+                    && relocator.dynamic_relocations.len() > dynamic_relocations_before
+                {
+                    text_relocations.insert(section.id);
+                }
             }
             SectionContent::Uninitialized(_) => {}
         }
     }
+    object.text_relocations = text_relocations;
     Ok(())
 }
 
+/// Warn about read-only sections that need to be relocated at runtime by the dynamic loader.
+pub(crate) fn text_relocation_warnings(object: &Object) -> Vec<Diagnostic> {
+    object
+        .text_relocations
+        .iter()
+        .filter_map(|&id| object.sections.get(id))
+        .map(|section| text_relocation::build(&section.name.resolve(), &section.source))
+        .collect()
+}
+
 struct Relocator<'a> {
     env: &'a ElfEnvironment,
     got: Option<&'a GOT>,
     dynamic_relocations: &'a mut Vec<Relocation>,
+    retained_relocations: Option<&'a mut BTreeMap<SectionId, Vec<Relocation>>>,
     mode: Mode,
+    symbolic: SymbolicBinding,
     layout: &'a Layout,
     symbols: &'a mut Symbols,
 }
@@ -49,14 +77,50 @@ impl<'a> Relocator<'a> {
         data_section: &mut DataSection,
     ) -> Result<(), RelocationError> {
         for relocation in data_section.relocations.drain(..) {
-            self.relocate_one(section_id, &relocation, &mut data_section.bytes).map_err(
-                |inner| RelocationError {
+            self.relocate_one(section_id, &relocation, &mut data_section.bytes)
+                .and_then(|()| self.retain(section_id, &relocation))
+                .map_err(|inner| RelocationError {
                     section_id,
                     offset: relocation.offset,
                     relocation_type: relocation.type_,
                     inner,
-                },
-            )?;
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Keep the relocation for `--emit-relocs`, pointing to the address it was applied to.
+    fn retain(
+        &mut self,
+        section_id: SectionId,
+        relocation: &Relocation,
+    ) -> Result<(), RelocationErrorInner> {
+        if self.retained_relocations.is_none() {
+            return Ok(());
+        }
+        let offset = self.layout.address(section_id, relocation.offset)?.1.as_offset()?;
+
+        // Section symbols are not emitted in the output, so relocations referencing them are
+        // changed to reference the null symbol, with the section address added to the addend.
+        // That's not possible with implicit addends, which keep referencing the section symbol
+        // (emitted in the output only in that case). Redirects are also resolved, as only the
+        // symbols they point to are emitted.
+        let symbol = self.symbols.get(relocation.symbol);
+        let (symbol, addend) = match (&symbol.type_, relocation.addend) {
+            (SymbolType::Section, Some(addend)) => (
+                self.symbols.null_symbol_id(),
+                Some(self.symbol_as_address(relocation, addend)?.as_offset()?),
+            ),
+            (_, addend) => (symbol.id, addend),
+        };
+
+        if let Some(retained) = &mut self.retained_relocations {
+            retained.entry(section_id).or_default().push(Relocation {
+                type_: relocation.type_,
+                symbol,
+                offset,
+                addend,
+            });
         }
         Ok(())
     }
@@ -68,6 +132,20 @@ impl<'a> Relocator<'a> {
         bytes: &mut [u8],
     ) -> Result<(), RelocationErrorInner> {
         let mut editor = ByteEditor { relocation, bytes };
+        if let SymbolType::GnuIfunc = self.symbols.get(relocation.symbol).type_ {
+            // The address of an ifunc is only known at runtime, so it can only be referenced
+            // through a GOT slot filled by the dynamic loader.
+            match relocation.type_ {
+                RelocationType::GOTRelative32 | RelocationType::GOTIndex32 => {}
+                RelocationType::FillGOTSlot if self.mode == Mode::PositionIndependent => {
+                    return self.push_irelative(section_id, relocation);
+                }
+                RelocationType::FillGOTSlot => {
+                    return Err(RelocationErrorInner::IfuncInPositionDependentExecutable);
+                }
+                _ => return Err(RelocationErrorInner::IfuncReferencedWithoutGOT),
+            }
+        }
         match relocation.type_ {
             RelocationType::Absolute32 => {
                 editor.write_u32(self.symbol_as_absolute(relocation, editor.addend_32()?)?)
@@ -75,6 +153,41 @@ impl<'a> Relocator<'a> {
             RelocationType::AbsoluteSigned32 => {
                 editor.write_i32(self.symbol_as_absolute(relocation, editor.addend_32()?)?)
             }
+            RelocationType::Absolute64 => match self.mode {
+                Mode::PositionDependent => {
+                    editor.write_u64(self.symbol_as_absolute(relocation, editor.addend_64()?)?)
+                }
+                Mode::PositionIndependent
+                    if !self.symbols.get(relocation.symbol).can_be_interposed(self.symbolic) =>
+                {
+                    self.absolute_64_locally(section_id, relocation, &mut editor)
+                }
+                Mode::PositionIndependent => {
+                    // The relocation might refer to the symbol through a redirect, which is not
+                    // emitted in the dynamic symbol table.
+                    let symbol = self.symbols.get(relocation.symbol).id;
+                    self.symbols.add_symbol_to_dynamic(symbol);
+
+                    self.dynamic_relocations.push(Relocation {
+                        type_: RelocationType::Absolute64,
+                        symbol,
+                        offset: self
+                            .layout
+                            .address(section_id, relocation.offset)?
+                            .1
+                            .as_offset()?,
+                        addend: Some(editor.addend_64()?),
+                    });
+
+                    Ok(())
+                }
+            },
+            RelocationType::Size32 => {
+                editor.write_u32(self.symbol_size(relocation, editor.addend_32()?)?)
+            }
+            RelocationType::Size64 => {
+                editor.write_u64(self.symbol_size(relocation, editor.addend_64()?)?)
+            }
             RelocationType::Relative32 | RelocationType::PLT32 => {
                 let symbol = self.symbol_as_address(relocation, editor.addend_32()?)?;
                 let offset = self.layout.address(section_id, relocation.offset.into())?.1;
@@ -108,6 +221,11 @@ impl<'a> Relocator<'a> {
                         ElfClass::Elf64 => editor.write_u64(symbol),
                     }
                 }
+                Mode::PositionIndependent
+                    if !self.symbols.get(relocation.symbol).can_be_interposed(self.symbolic) =>
+                {
+                    self.fill_got_slot_locally(section_id, relocation, &mut editor)
+                }
                 Mode::PositionIndependent => {
                     self.symbols.add_symbol_to_dynamic(relocation.symbol);
 
@@ -136,9 +254,82 @@ impl<'a> Relocator<'a> {
                 let got = self.layout.address(self.got()?.id, 0.into())?.1;
                 editor.write_i32(symbol.as_offset()?.add(got.as_offset()?.neg())?)
             }
+            RelocationType::IRelative => {
+                panic!("IRELATIVE relocations are only emitted as dynamic relocations")
+            }
+            RelocationType::Relative => {
+                panic!("RELATIVE relocations are only emitted as dynamic relocations")
+            }
+        }
+    }
+
+    /// Hidden and protected symbols always resolve to their definition in the output, so their
+    /// GOT slots don't need a symbol lookup at runtime. Protected symbols are still exported.
+    fn fill_got_slot_locally(
+        &mut self,
+        section_id: SectionId,
+        relocation: &Relocation,
+        editor: &mut ByteEditor<'_>,
+    ) -> Result<(), RelocationErrorInner> {
+        if let SymbolVisibility::Global { protected: true, .. } =
+            self.symbols.get(relocation.symbol).visibility
+        {
+            self.symbols.add_symbol_to_dynamic(relocation.symbol);
+        }
+        match self.symbol(relocation, 0.into())? {
+            ResolvedSymbol::Absolute(absolute) => match self.env.class {
+                ElfClass::Elf32 => editor.write_u32(absolute),
+                ElfClass::Elf64 => editor.write_u64(absolute),
+            },
+            ResolvedSymbol::Address { memory_address, .. } => {
+                self.dynamic_relocations.push(Relocation {
+                    type_: RelocationType::Relative,
+                    symbol: self.symbols.null_symbol_id(),
+                    offset: self.layout.address(section_id, relocation.offset)?.1.as_offset()?,
+                    addend: Some(memory_address.as_offset()?),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Pointers to symbols defined in the output only need the load address to be added at
+    /// runtime, while absolute values can be written directly.
+    fn absolute_64_locally(
+        &mut self,
+        section_id: SectionId,
+        relocation: &Relocation,
+        editor: &mut ByteEditor<'_>,
+    ) -> Result<(), RelocationErrorInner> {
+        match self.symbol(relocation, editor.addend_64()?)? {
+            ResolvedSymbol::Absolute(absolute) => editor.write_u64(absolute),
+            ResolvedSymbol::Address { memory_address, .. } => {
+                self.dynamic_relocations.push(Relocation {
+                    type_: RelocationType::Relative,
+                    symbol: self.symbols.null_symbol_id(),
+                    offset: self.layout.address(section_id, relocation.offset)?.1.as_offset()?,
+                    addend: Some(memory_address.as_offset()?),
+                });
+                Ok(())
+            }
         }
     }
 
+    fn push_irelative(
+        &mut self,
+        section_id: SectionId,
+        relocation: &Relocation,
+    ) -> Result<(), RelocationErrorInner> {
+        let resolver = self.symbol_as_address(relocation, 0.into())?;
+        self.dynamic_relocations.push(Relocation {
+            type_: RelocationType::IRelative,
+            symbol: self.symbols.null_symbol_id(),
+            offset: self.layout.address(section_id, relocation.offset)?.1.as_offset()?,
+            addend: Some(resolver.as_offset()?),
+        });
+        Ok(())
+    }
+
     fn got(&self) -> Result<&GOT, RelocationErrorInner> {
         self.got.ok_or(RelocationErrorInner::GOTRelativeWithoutGOT)
     }
@@ -162,15 +353,42 @@ impl<'a> Relocator<'a> {
         }
     }
 
+    fn symbol_size(
+        &self,
+        rel: &Relocation,
+        addend: Offset,
+    ) -> Result<Offset, RelocationErrorInner> {
+        let symbol = self.symbols.get(rel.symbol);
+        // The size of a symbol defined in another object is not known at link time. Like with
+        // their address, weak references that were never defined have a size of zero.
+        if let SymbolValue::Undefined = symbol.value {
+            if !symbol.is_weak() {
+                return Err(ResolveSymbolError {
+                    symbol: symbol.name,
+                    inner: ResolveSymbolErrorKind::Undefined,
+                }
+                .into());
+            }
+        }
+        let size = i64::try_from(symbol.size).map_err(|_| OutOfBoundsError)?;
+        Ok(Offset::from(size).add(addend)?)
+    }
+
     fn symbol_as_address(
         &self,
         rel: &Relocation,
         offset: Offset,
     ) -> Result<Address, RelocationErrorInner> {
         match self.symbol(rel, offset)? {
-            ResolvedSymbol::Absolute(_) => {
-                return Err(RelocationErrorInner::RelativeRelocationWithAbsoluteValue);
-            }
+            // The absolute value is used as the address, so that relative relocations compute the
+            // offset to it. In position independent executables the address of the relocation is
+            // not known at link time though, so the offset can't be calculated.
+            ResolvedSymbol::Absolute(absolute) => match self.mode {
+                Mode::PositionDependent => Ok(absolute.as_address()),
+                Mode::PositionIndependent => {
+                    Err(RelocationErrorInner::RelativeRelocationWithAbsoluteValue)
+                }
+            },
             ResolvedSymbol::Address { memory_address, .. } => Ok(memory_address),
         }
     }
@@ -200,8 +418,12 @@ pub(crate) enum RelocationErrorInner {
     OutOfBounds(OutOfBoundsError),
     #[display("relocation is trying to access offset {offset} (len: {len:#x}) on a section of size {size:#x}")]
     OutOfBoundsAccess { offset: Offset, len: usize, size: usize },
-    #[display("relative relocations with absolute values are not supported")]
+    #[display("relative relocations with absolute values are not supported in position independent executables")]
     RelativeRelocationWithAbsoluteValue,
     #[display("GOT-relative addressing used without a GOT")]
     GOTRelativeWithoutGOT,
+    #[display("ifunc symbols can only be referenced through the GOT")]
+    IfuncReferencedWithoutGOT,
+    #[display("ifunc symbols are only supported in position independent executables")]
+    IfuncInPositionDependentExecutable,
 }
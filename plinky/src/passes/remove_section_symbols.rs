@@ -1,11 +1,21 @@
 use crate::repr::object::Object;
 use crate::repr::symbols::SymbolType;
+use std::collections::BTreeSet;
 
 pub(crate) fn remove(object: &mut Object) {
+    // Relocations kept with `--emit-relocs` without an explicit addend can't fold the section
+    // address in the addend, so the section symbols they reference are kept.
+    let retained = object
+        .retained_relocations
+        .values()
+        .flatten()
+        .map(|relocation| relocation.symbol)
+        .collect::<BTreeSet<_>>();
+
     let mut to_remove = Vec::new();
     for (id, symbol) in object.symbols.iter() {
         match &symbol.type_ {
-            SymbolType::Section => to_remove.push(id),
+            SymbolType::Section if !retained.contains(&id) => to_remove.push(id),
             _ => {}
         }
     }
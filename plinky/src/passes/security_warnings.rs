@@ -0,0 +1,32 @@
+use crate::cli::{CliOptions, SegmentsLayout};
+use crate::diagnostics::{executable_stack, rwx_segment};
+use crate::passes::layout::{Layout, SegmentType};
+use crate::repr::object::Object;
+use plinky_diagnostics::Diagnostic;
+
+/// Warn about outputs weakening the protections against memory corruption bugs: executable stacks
+/// (unless `--no-warn-execstack`, or with `-z execstack`, which explicitly asks for one) and
+/// segments both writable and executable (unless `--no-warn-rwx-segments`, or with
+/// `-N`/`--omagic`, which asks for a writable text segment).
+pub(crate) fn run(options: &CliOptions, object: &Object, layout: &Layout) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let requested_execstack = options.executable_stack == Some(true);
+    if options.warn_execstack && object.executable_stack && !requested_execstack {
+        diagnostics.push(executable_stack::build());
+    }
+    if options.warn_rwx_segments && options.segments_layout != SegmentsLayout::OMagic {
+        for segment in layout.iter_segments() {
+            let loaded = matches!(segment.type_, SegmentType::Program | SegmentType::Uninitialized);
+            if loaded && segment.perms.read && segment.perms.write && segment.perms.execute {
+                let sections = segment
+                    .sections
+                    .iter()
+                    .filter_map(|&id| object.sections.get(id))
+                    .map(|section| section.name.resolve().to_string())
+                    .collect::<Vec<_>>();
+                diagnostics.push(rwx_segment::build(segment.start, &sections));
+            }
+        }
+    }
+    diagnostics
+}
@@ -0,0 +1,61 @@
+use crate::cli::CliOptions;
+use crate::interner::Interned;
+use crate::repr::object::Object;
+use crate::repr::sections::SectionContent;
+use crate::repr::symbols::{SymbolValue, SymbolVisibility};
+use plinky_diagnostics::ObjectSpan;
+use plinky_macros::{Display, Error};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// With `--no-undefined`, fail when a relocation references a symbol that no input defines, unless
+/// the symbol is listed in the `--allow-undefined-file` (for example because it's provided by the
+/// environment at runtime). Weak symbols are allowed to stay undefined.
+pub(crate) fn check(options: &CliOptions, object: &Object) -> Result<(), UndefinedSymbolsError> {
+    if !options.no_undefined {
+        return Ok(());
+    }
+    let allowed = match &options.allow_undefined_file {
+        Some(path) => read_allowed(path)
+            .map_err(|e| UndefinedSymbolsError::AllowedReadFailed(path.clone(), e))?,
+        None => BTreeSet::new(),
+    };
+
+    for section in object.sections.iter() {
+        let SectionContent::Data(data) = &section.content else { continue };
+        for relocation in &data.relocations {
+            let symbol = object.symbols.get(relocation.symbol);
+            let SymbolValue::Undefined = symbol.value else { continue };
+            if let SymbolVisibility::Global { weak: true, .. } = symbol.visibility {
+                continue;
+            }
+            if allowed.contains(symbol.name.resolve().as_str()) {
+                continue;
+            }
+            return Err(UndefinedSymbolsError::Undefined {
+                symbol: symbol.name,
+                section: section.name,
+                source: section.source.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The file contains one symbol name per line. Empty lines and lines starting with `#` are ignored.
+fn read_allowed(path: &Path) -> Result<BTreeSet<String>, std::io::Error> {
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect())
+}
+
+#[derive(Debug, Error, Display)]
+pub(crate) enum UndefinedSymbolsError {
+    #[display("failed to read the allowed undefined symbols from {f0:?}")]
+    AllowedReadFailed(PathBuf, #[source] std::io::Error),
+    #[display("undefined symbol {symbol} referenced by section {section} in {source} (--no-undefined was passed)")]
+    Undefined { symbol: Interned<String>, section: Interned<String>, source: ObjectSpan },
+}
@@ -1,18 +1,60 @@
+use crate::cli::{CliOptions, OutputFormat};
 use crate::passes::build_elf::ids::BuiltElfIds;
+use crate::passes::flat_binary::{self, FlatBinaryError};
 use plinky_elf::errors::WriteError;
-use plinky_elf::ElfObject;
+use plinky_elf::{ElfObject, ElfWriteOptions};
 use plinky_macros::Error;
 use std::fs::{File, Permissions};
-use std::io::BufWriter;
+use std::io::{self, BufWriter, Read, Write};
 use std::os::unix::prelude::PermissionsExt;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-pub(crate) fn run(object: ElfObject<BuiltElfIds>, dest: &Path) -> Result<(), WriteToDiskError> {
-    let mut file = BufWriter::new(
-        File::create(dest).map_err(|e| WriteToDiskError::FileCreation(dest.into(), e))?,
-    );
+pub(crate) fn run(
+    object: ElfObject<BuiltElfIds>,
+    options: &CliOptions,
+) -> Result<(), WriteToDiskError> {
+    let dest = &options.output;
+    let content = match options.output_format {
+        OutputFormat::Elf => {
+            let write_options = ElfWriteOptions {
+                omit_section_headers: options.strip_section_headers,
+                fill_byte: options.fill,
+            };
+            let mut content = Vec::new();
+            object
+                .write_with_options(&mut content, write_options)
+                .map_err(|e| WriteToDiskError::WriteFailed(dest.into(), e))?;
+            content
+        }
+        OutputFormat::Binary => flat_binary::build(&object, options.fill)
+            .map_err(|e| WriteToDiskError::FlatBinaryFailed(dest.into(), e))?,
+    };
 
-    object.write(&mut file).map_err(|e| WriteToDiskError::WriteFailed(dest.into(), e))?;
+    let len = content.len() as u64;
+    if let Some(pad_to) = options.pad_to {
+        if len > pad_to {
+            return Err(WriteToDiskError::LargerThanPadTo { path: dest.into(), len, pad_to });
+        }
+    }
+
+    let file = File::create(dest).map_err(|e| WriteToDiskError::FileCreation(dest.into(), e))?;
+    if let Some(pad_to) = options.pad_to {
+        // The padding is not buffered in memory, as --pad-to can be arbitrarily large. Resizing the
+        // file first rejects sizes the filesystem can't store, before writing anything.
+        file.set_len(pad_to).map_err(|e| WriteToDiskError::PadFailed(dest.into(), pad_to, e))?;
+    }
+    let mut file = BufWriter::new(file);
+    file.write_all(&content)
+        .and_then(|()| match options.pad_to {
+            // Resizing the file already filled it with zeroes.
+            Some(pad_to) if options.fill_byte != 0 => {
+                io::copy(&mut io::repeat(options.fill_byte).take(pad_to - len), &mut file)
+                    .map(|_| ())
+            }
+            _ => Ok(()),
+        })
+        .and_then(|()| file.flush())
+        .map_err(|e| WriteToDiskError::OutputWriteFailed(dest.into(), e))?;
 
     std::fs::set_permissions(dest, Permissions::from_mode(0o755))
         .map_err(|e| WriteToDiskError::PermissionSetFailed(dest.into(), e))?;
@@ -24,6 +66,10 @@ pub(crate) fn run(object: ElfObject<BuiltElfIds>, dest: &Path) -> Result<(), Wri
 pub(crate) enum WriteToDiskError {
     FileCreation(PathBuf, #[source] std::io::Error),
     WriteFailed(PathBuf, #[source] WriteError<BuiltElfIds>),
+    FlatBinaryFailed(PathBuf, #[source] FlatBinaryError),
+    LargerThanPadTo { path: PathBuf, len: u64, pad_to: u64 },
+    PadFailed(PathBuf, u64, #[source] std::io::Error),
+    OutputWriteFailed(PathBuf, #[source] std::io::Error),
     PermissionSetFailed(PathBuf, #[source] std::io::Error),
 }
 
@@ -36,6 +82,20 @@ impl std::fmt::Display for WriteToDiskError {
             WriteToDiskError::WriteFailed(path, _) => {
                 write!(f, "failed to serialize output to {}", path.display())
             }
+            WriteToDiskError::FlatBinaryFailed(path, _) => {
+                write!(f, "failed to lay out the flat binary for {}", path.display())
+            }
+            WriteToDiskError::LargerThanPadTo { path, len, pad_to } => write!(
+                f,
+                "output {} is {len:#x} bytes long, more than the {pad_to:#x} bytes of --pad-to",
+                path.display()
+            ),
+            WriteToDiskError::PadFailed(path, pad_to, _) => {
+                write!(f, "failed to pad output {} to {pad_to:#x} bytes", path.display())
+            }
+            WriteToDiskError::OutputWriteFailed(path, _) => {
+                write!(f, "failed to write output to {}", path.display())
+            }
             WriteToDiskError::PermissionSetFailed(path, _) => {
                 write!(f, "failed to make {} executable", path.display())
             }
@@ -1,10 +1,15 @@
-use crate::cli::Mode;
+use crate::cli::{
+    BuildIdStyle, DiscardLocals, HashStyle, Mode, SegmentsLayout, SortSections, SymbolicBinding,
+    SymtabSort,
+};
+use crate::interner::Interned;
 use crate::passes::generate_got::GOT;
 use crate::repr::relocations::Relocation;
 use crate::repr::sections::Sections;
 use crate::repr::symbols::Symbols;
-use plinky_elf::ids::serial::SymbolId;
-use plinky_elf::ElfEnvironment;
+use plinky_elf::ids::serial::{SectionId, SymbolId};
+use plinky_elf::{ElfEnvironment, ElfFunctionsArray};
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug)]
 pub(crate) struct Object {
@@ -12,9 +17,38 @@ pub(crate) struct Object {
     pub(crate) sections: Sections,
     pub(crate) symbols: Symbols,
     pub(crate) dynamic_relocations: Vec<Relocation>,
+    /// Relocations of the inputs kept in the output with `--emit-relocs`, with their offsets
+    /// converted to addresses. Only populated when `emit_relocs` is set.
+    pub(crate) retained_relocations: BTreeMap<SectionId, Vec<Relocation>>,
+    pub(crate) emit_relocs: bool,
     pub(crate) got: Option<GOT>,
     pub(crate) entry_point: SymbolId,
     pub(crate) mode: Mode,
+    pub(crate) segments_layout: SegmentsLayout,
+    /// Whether read-only sections are merged into the executable segment.
+    pub(crate) no_rosegment: bool,
+    pub(crate) sort_sections: SortSections,
+    pub(crate) symtab_sort: SymtabSort,
+    pub(crate) discard_locals: DiscardLocals,
+    pub(crate) hash_style: HashStyle,
+    pub(crate) symbolic: SymbolicBinding,
+    pub(crate) spare_dynamic_tags: usize,
+    pub(crate) sort_dyn_relocs: bool,
+    /// Alignment the entry point is checked against, if different from the machine's default.
+    pub(crate) entry_alignment: Option<u64>,
     pub(crate) executable_stack: bool,
     pub(crate) gnu_stack_section_ignored: bool,
+    /// Section reserved for the `NT_GNU_BUILD_ID` note and how to generate it. The note is
+    /// filled after the ELF is built.
+    pub(crate) build_id_section: Option<(SectionId, BuildIdStyle)>,
+    /// Read-only sections the dynamic loader needs to apply relocations to.
+    pub(crate) text_relocations: BTreeSet<SectionId>,
+    /// Thread-local sections, with their alignment.
+    pub(crate) tls_sections: BTreeMap<SectionId, u64>,
+    /// Sections flagged with `SHF_LINK_ORDER`, with the section they are ordered with.
+    pub(crate) link_order: BTreeMap<SectionId, SectionId>,
+    /// Arrays of functions called at startup or exit (like `.init_array`).
+    pub(crate) functions_arrays: BTreeMap<SectionId, ElfFunctionsArray>,
+    /// Messages from `.gnu.warning.SYMBOL` sections, to show when the symbol is referenced.
+    pub(crate) symbol_warnings: BTreeMap<Interned<String>, String>,
 }
@@ -3,10 +3,14 @@ use plinky_elf::ids::serial::{SerialIds, SymbolId};
 use plinky_elf::{ElfRelocation, ElfRelocationType};
 use plinky_macros::{Display, Error};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum RelocationType {
     Absolute32,
     AbsoluteSigned32,
+    Absolute64,
+    /// Writes the size of the symbol (plus the addend) rather than its address.
+    Size32,
+    Size64,
     Relative32,
     PLT32,
     GOTRelative32,
@@ -14,6 +18,12 @@ pub(crate) enum RelocationType {
     GOTLocationRelative32,
     OffsetFromGOT32,
     FillGOTSlot,
+    /// Only emitted as a dynamic relocation: the dynamic loader calls the resolver function at
+    /// the address in the addend, and stores the returned address at the relocation offset.
+    IRelative,
+    /// Only emitted as a dynamic relocation: the dynamic loader adds the load address to the
+    /// addend, and stores the result at the relocation offset.
+    Relative,
 }
 
 impl RelocationType {
@@ -21,6 +31,9 @@ impl RelocationType {
         match self {
             RelocationType::Absolute32 => false,
             RelocationType::AbsoluteSigned32 => false,
+            RelocationType::Absolute64 => false,
+            RelocationType::Size32 => false,
+            RelocationType::Size64 => false,
             RelocationType::Relative32 => false,
             RelocationType::PLT32 => false,
             RelocationType::GOTRelative32 => true,
@@ -28,6 +41,8 @@ impl RelocationType {
             RelocationType::GOTLocationRelative32 => false,
             RelocationType::OffsetFromGOT32 => false,
             RelocationType::FillGOTSlot => false,
+            RelocationType::IRelative => false,
+            RelocationType::Relative => false,
         }
     }
 
@@ -62,10 +77,13 @@ impl TryFrom<ElfRelocation<SerialIds>> for Relocation {
 
                 ElfRelocationType::X86_64_32 => RelocationType::Absolute32,
                 ElfRelocationType::X86_64_32S => RelocationType::AbsoluteSigned32,
+                ElfRelocationType::X86_64_64 => RelocationType::Absolute64,
                 ElfRelocationType::X86_64_PC32 => RelocationType::Relative32,
                 ElfRelocationType::X86_64_PLT32 => RelocationType::PLT32,
                 ElfRelocationType::X86_64_GOTPCRel => RelocationType::GOTRelative32,
                 ElfRelocationType::X86_64_GOTPCRelX => RelocationType::GOTRelative32,
+                ElfRelocationType::X86_64_Size32 => RelocationType::Size32,
+                ElfRelocationType::X86_64_Size64 => RelocationType::Size64,
 
                 elf_type => return Err(UnsupportedRelocationType { elf_type }),
             },
@@ -21,6 +21,10 @@ impl Sections {
         self.inner.get(&id)
     }
 
+    pub(crate) fn get_mut(&mut self, id: SectionId) -> Option<&mut Section> {
+        self.inner.get_mut(&id)
+    }
+
     pub(crate) fn add(&mut self, section: Section) {
         // Avoid stale data if the section was removed and then added again.
         self.names_of_removed_sections.remove(&section.id);
@@ -96,4 +100,5 @@ pub(crate) struct DataSection {
 #[derive(Debug)]
 pub(crate) struct UninitializedSection {
     pub(crate) len: u64,
+    pub(crate) align: u64,
 }
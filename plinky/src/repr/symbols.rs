@@ -1,3 +1,4 @@
+use crate::cli::SymbolicBinding;
 use crate::interner::{intern, Interned};
 use crate::passes::layout::{AddressResolutionError, Layout};
 use crate::utils::ints::{Absolute, Address, Offset, OutOfBoundsError};
@@ -5,6 +6,7 @@ use plinky_diagnostics::ObjectSpan;
 use plinky_elf::ids::serial::{SectionId, SerialIds, SymbolId};
 use plinky_elf::ElfSymbolVisibility;
 use plinky_macros::{Display, Error};
+use std::cmp::Ordering;
 use std::collections::{btree_map, BTreeMap, BTreeSet};
 
 #[derive(Debug)]
@@ -12,7 +14,13 @@ pub(crate) struct Symbols {
     null_symbol_id: SymbolId,
     symbols: BTreeMap<SymbolId, SymbolOrRedirect>,
     global_symbols: BTreeMap<Interned<String>, SymbolId>,
+    command_line_definitions: BTreeSet<Interned<String>>,
+    /// Symbols wrapped with `--wrap`.
+    wrapped: BTreeSet<Interned<String>>,
     dynamic_symbols: BTreeSet<SymbolId>,
+    /// Objects referencing each symbol, keyed by the resolved symbol ID: the ones declaring it as
+    /// undefined, and the ones with relocations pointing to it.
+    references: BTreeMap<SymbolId, Vec<ObjectSpan>>,
 }
 
 impl Symbols {
@@ -30,13 +38,17 @@ impl Symbols {
                 span: intern(ObjectSpan::new_synthetic()),
                 visibility: SymbolVisibility::Local,
                 value: SymbolValue::Null,
+                size: 0,
             }),
         );
         Self {
             null_symbol_id,
             symbols,
             global_symbols: BTreeMap::new(),
+            command_line_definitions: BTreeSet::new(),
+            wrapped: BTreeSet::new(),
             dynamic_symbols: BTreeSet::new(),
+            references: BTreeMap::new(),
         }
     }
 
@@ -52,12 +64,76 @@ impl Symbols {
             type_: SymbolType::NoType,
             stt_file: None,
             span: intern(ObjectSpan::new_synthetic()),
-            visibility: SymbolVisibility::Global { weak: false, hidden: false },
+            visibility: SymbolVisibility::Global { weak: false, hidden: false, protected: false },
             value: SymbolValue::Undefined,
+            size: 0,
         })?;
         Ok(id)
     }
 
+    /// Define a global symbol with an absolute value from the command line (with `--defsym`).
+    /// These definitions take precedence over any definition coming from the input objects.
+    pub(crate) fn add_command_line_definition(
+        &mut self,
+        ids: &mut SerialIds,
+        name: &str,
+        value: u64,
+    ) -> Result<SymbolId, LoadSymbolsError> {
+        let id = ids.allocate_symbol_id();
+        self.add_symbol(Symbol {
+            id,
+            name: intern(name),
+            type_: SymbolType::NoType,
+            stt_file: None,
+            span: intern(ObjectSpan::new_synthetic()),
+            visibility: SymbolVisibility::Global { weak: false, hidden: false, protected: false },
+            value: SymbolValue::Absolute { value: value.into() },
+            size: 0,
+        })?;
+        self.command_line_definitions.insert(intern(name));
+        Ok(id)
+    }
+
+    /// Define a global symbol with an absolute value imported from another file (with
+    /// `--just-symbols`). Unlike `--defsym`, these behave like definitions from input objects.
+    pub(crate) fn add_just_symbols_definition(
+        &mut self,
+        ids: &mut SerialIds,
+        name: &str,
+        value: u64,
+        weak: bool,
+        span: ObjectSpan,
+    ) -> Result<SymbolId, LoadSymbolsError> {
+        let id = ids.allocate_symbol_id();
+        self.add_symbol(Symbol {
+            id,
+            name: intern(name),
+            type_: SymbolType::NoType,
+            stt_file: None,
+            span: intern(span),
+            visibility: SymbolVisibility::Global { weak, hidden: false, protected: false },
+            value: SymbolValue::Absolute { value: value.into() },
+            size: 0,
+        })?;
+        Ok(id)
+    }
+
+    /// Redirect undefined references to the symbol to `__wrap_NAME`, and undefined references to
+    /// `__real_NAME` to the symbol (with `--wrap`). Like GNU ld, references to the symbol from the
+    /// same object defining it are not redirected, as they don't go through an undefined symbol.
+    pub(crate) fn add_wrapped(&mut self, name: &str) {
+        self.wrapped.insert(intern(name));
+    }
+
+    fn wrapped_name(&self, name: Interned<String>) -> Interned<String> {
+        let resolved = name.resolve();
+        match resolved.strip_prefix("__real_").map(intern) {
+            _ if self.wrapped.contains(&name) => intern(format!("__wrap_{resolved}")),
+            Some(real) if self.wrapped.contains(&real) => real,
+            _ => name,
+        }
+    }
+
     pub(crate) fn add_redirect(&mut self, from: SymbolId, to: SymbolId) {
         self.symbols.insert(from, SymbolOrRedirect::Redirect(to));
     }
@@ -67,7 +143,11 @@ impl Symbols {
             SymbolVisibility::Local => {
                 self.symbols.insert(symbol.id, SymbolOrRedirect::Symbol(symbol));
             }
-            SymbolVisibility::Global { weak: false, hidden: _ } => {
+            SymbolVisibility::Global { .. } => {
+                if let SymbolValue::Undefined = symbol.value {
+                    symbol.name = self.wrapped_name(symbol.name);
+                }
+
                 // For global symbols, we generate a new symbol ID for each unique name, and
                 // redirect to it all of the concrete references to that global name.
                 let global_id = *self.global_symbols.entry(symbol.name).or_insert(symbol.id);
@@ -78,27 +158,61 @@ impl Symbols {
                 // Ensure the ID contained in the symbol is the global ID, not the original ID.
                 symbol.id = global_id;
 
+                if let SymbolValue::Undefined = symbol.value {
+                    self.add_reference_to_resolved(global_id, (*symbol.span.resolve()).clone());
+                }
+
                 match self.symbols.entry(global_id) {
                     btree_map::Entry::Vacant(entry) => {
                         entry.insert(SymbolOrRedirect::Symbol(symbol));
                     }
                     btree_map::Entry::Occupied(mut entry) => {
-                        let SymbolOrRedirect::Symbol(existing_symbol) = entry.get() else {
+                        let SymbolOrRedirect::Symbol(existing_symbol) = entry.get_mut() else {
                             panic!("global symbols can't be a redirect");
                         };
-                        if let SymbolValue::Undefined = existing_symbol.value {
-                            entry.insert(SymbolOrRedirect::Symbol(symbol));
-                        } else if let SymbolValue::Undefined = symbol.value {
-                            // Nothing.
-                        } else {
-                            return Err(LoadSymbolsError::DuplicateGlobalSymbol(symbol.name));
+                        symbol.visibility.restrict_to(&existing_symbol.visibility);
+                        existing_symbol.visibility.restrict_to(&symbol.visibility);
+                        if self.command_line_definitions.contains(&symbol.name) {
+                            // Definitions from the command line always win.
+                            return Ok(());
+                        }
+                        match symbol.strength().cmp(&existing_symbol.strength()) {
+                            Ordering::Greater => {
+                                entry.insert(SymbolOrRedirect::Symbol(symbol));
+                            }
+                            Ordering::Equal if symbol.strength() == SymbolStrength::Strong => {
+                                return Err(LoadSymbolsError::DuplicateGlobalSymbol(symbol.name));
+                            }
+                            // Common symbols are tentative definitions, merged into a single one
+                            // large enough for all of them.
+                            Ordering::Equal if symbol.strength() == SymbolStrength::Common => {
+                                if let (
+                                    SymbolValue::Common { size, align },
+                                    SymbolValue::Common { size: other_size, align: other_align },
+                                ) = (&mut existing_symbol.value, &symbol.value)
+                                {
+                                    *size = (*size).max(*other_size);
+                                    *align = (*align).max(*other_align);
+                                    existing_symbol.size = *size;
+                                }
+                            }
+                            // An undefined symbol is weak only if all references to it are weak.
+                            Ordering::Equal if symbol.strength() == SymbolStrength::Undefined => {
+                                if let (
+                                    SymbolVisibility::Global { weak, .. },
+                                    SymbolVisibility::Global { weak: other_weak, .. },
+                                ) = (&mut existing_symbol.visibility, &symbol.visibility)
+                                {
+                                    *weak &= *other_weak;
+                                }
+                            }
+                            // When two definitions have the same strength (and are not both
+                            // strong), the first one to be loaded wins.
+                            Ordering::Equal | Ordering::Less => {}
                         }
                     }
                 }
             }
-            SymbolVisibility::Global { weak: true, hidden: _ } => {
-                todo!("weak symbols are not supported yet")
-            }
         }
         Ok(())
     }
@@ -120,6 +234,31 @@ impl Symbols {
         panic!("too many redirects while resolving symbol {id:?}");
     }
 
+    pub(crate) fn get_mut(&mut self, id: SymbolId) -> &mut Symbol {
+        let id = self.get(id).id;
+        match self.symbols.get_mut(&id) {
+            Some(SymbolOrRedirect::Symbol(symbol)) => symbol,
+            _ => panic!("symbol id doesn't point to a symbol"),
+        }
+    }
+
+    /// Record that the object references the symbol, if it wasn't recorded already.
+    pub(crate) fn add_reference(&mut self, id: SymbolId, span: ObjectSpan) {
+        self.add_reference_to_resolved(self.get(id).id, span);
+    }
+
+    fn add_reference_to_resolved(&mut self, resolved_id: SymbolId, span: ObjectSpan) {
+        let references = self.references.entry(resolved_id).or_default();
+        if !references.contains(&span) {
+            references.push(span);
+        }
+    }
+
+    /// Objects referencing the symbol, in the order they were loaded.
+    pub(crate) fn references_to(&self, id: SymbolId) -> &[ObjectSpan] {
+        self.references.get(&self.get(id).id).map(|spans| &spans[..]).unwrap_or(&[])
+    }
+
     pub(crate) fn get_global(
         &self,
         name: Interned<String>,
@@ -145,6 +284,18 @@ impl Symbols {
         })
     }
 
+    /// Global symbols that are still undefined, along with the objects referencing them (in the
+    /// order they were loaded).
+    pub(crate) fn iter_undefined(&self) -> impl Iterator<Item = (&Symbol, &[ObjectSpan])> {
+        self.global_symbols.values().filter_map(|&id| {
+            let symbol = self.get(id);
+            match symbol.value {
+                SymbolValue::Undefined => Some((symbol, self.references_to(id))),
+                _ => None,
+            }
+        })
+    }
+
     pub(crate) fn iters_with_redirects(&self) -> impl Iterator<Item = (SymbolId, &Symbol)> {
         self.symbols.keys().map(|&id| (id, self.get(id)))
     }
@@ -179,9 +330,37 @@ pub(crate) struct Symbol {
     pub(crate) span: Interned<ObjectSpan>,
     pub(crate) visibility: SymbolVisibility,
     pub(crate) value: SymbolValue,
+    /// Size of the object or function the symbol points to, or 0 if unknown.
+    pub(crate) size: u64,
 }
 
 impl Symbol {
+    /// Whether references to the symbol must go through the dynamic loader, taking into account
+    /// both the visibility and `-Bsymbolic`/`-Bsymbolic-functions`, which only bind symbols
+    /// defined in the output.
+    pub(crate) fn can_be_interposed(&self, symbolic: SymbolicBinding) -> bool {
+        let defined = !matches!(self.value, SymbolValue::Undefined | SymbolValue::Null);
+        let bound_locally = match symbolic {
+            SymbolicBinding::None => false,
+            SymbolicBinding::All => defined,
+            SymbolicBinding::Functions => defined && matches!(self.type_, SymbolType::Function),
+        };
+        self.visibility.can_be_interposed() && !bound_locally
+    }
+
+    pub(crate) fn is_weak(&self) -> bool {
+        matches!(self.visibility, SymbolVisibility::Global { weak: true, .. })
+    }
+
+    fn strength(&self) -> SymbolStrength {
+        match (&self.value, &self.visibility) {
+            (SymbolValue::Undefined, _) => SymbolStrength::Undefined,
+            (SymbolValue::Common { .. }, _) => SymbolStrength::Common,
+            (_, SymbolVisibility::Global { weak: true, .. }) => SymbolStrength::Weak,
+            (_, _) => SymbolStrength::Strong,
+        }
+    }
+
     pub(crate) fn resolve(
         &self,
         layout: &Layout,
@@ -193,10 +372,14 @@ impl Symbol {
             offset: Offset,
         ) -> Result<ResolvedSymbol, ResolveSymbolErrorKind> {
             match &symbol.value {
+                // Weak references that were never defined resolve to zero, allowing programs to
+                // check at runtime whether the symbol is available.
+                SymbolValue::Undefined if symbol.is_weak() => {
+                    Ok(ResolvedSymbol::Absolute(Absolute::from(0u64).offset(offset)?))
+                }
                 SymbolValue::Undefined => Err(ResolveSymbolErrorKind::Undefined),
                 SymbolValue::Absolute { value } => {
-                    assert!(offset == Offset::from(0));
-                    Ok(ResolvedSymbol::Absolute(*value))
+                    Ok(ResolvedSymbol::Absolute(value.offset(offset)?))
                 }
                 SymbolValue::SectionRelative { section, offset: section_offset } => {
                     match layout.address(*section, section_offset.add(offset)?) {
@@ -212,6 +395,9 @@ impl Symbol {
                         memory_address: memory_address.offset(offset)?,
                     })
                 }
+                SymbolValue::Common { .. } => {
+                    panic!("common symbols should have been allocated before resolving them");
+                }
                 SymbolValue::Null => Err(ResolveSymbolErrorKind::Null),
             }
         }
@@ -221,25 +407,68 @@ impl Symbol {
     }
 }
 
+/// Precedence of a global symbol definition when merging multiple symbols with the same name.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SymbolStrength {
+    Undefined,
+    Common,
+    Weak,
+    Strong,
+}
+
 #[derive(Debug)]
 pub(crate) enum SymbolType {
     NoType,
     Function,
     Object,
     Section,
+    /// The symbol points to a resolver function, which returns the address of the actual
+    /// function when called by the dynamic loader (through an IRELATIVE relocation).
+    GnuIfunc,
+    /// The symbol points to thread-local data.
+    Tls,
 }
 
 #[derive(Debug)]
 pub(crate) enum SymbolVisibility {
     Local,
-    Global { weak: bool, hidden: bool },
+    /// Hidden symbols are not visible outside of the output, while protected symbols are
+    /// exported but always resolve to the definition in the output (they can't be interposed).
+    Global {
+        weak: bool,
+        hidden: bool,
+        protected: bool,
+    },
 }
 
+impl SymbolVisibility {
+    /// Whether the dynamic loader can replace the symbol with a definition from another object.
+    pub(crate) fn can_be_interposed(&self) -> bool {
+        matches!(self, SymbolVisibility::Global { hidden: false, protected: false, .. })
+    }
+
+    /// Different objects can declare a different visibility for the same global symbol: like
+    /// GNU ld, the most constraining one wins (hidden, then protected).
+    fn restrict_to(&mut self, other: &SymbolVisibility) {
+        if let (
+            SymbolVisibility::Global { hidden, protected, .. },
+            SymbolVisibility::Global { hidden: other_hidden, protected: other_protected, .. },
+        ) = (self, other)
+        {
+            *hidden |= *other_hidden;
+            *protected = (*protected || *other_protected) && !*hidden;
+        }
+    }
+}
+
+/// Common values are tentative definitions (like `int foo;` in C with `-fcommon`), which don't
+/// have any storage until they're allocated in `.bss` by the allocate_common pass.
 #[derive(Debug)]
 pub(crate) enum SymbolValue {
     Absolute { value: Absolute },
     SectionRelative { section: SectionId, offset: Offset },
     SectionVirtualAddress { section: SectionId, memory_address: Address },
+    Common { size: u64, align: u64 },
     Undefined,
     Null,
 }
@@ -263,7 +492,7 @@ pub(crate) enum LoadSymbolsError {
     UnsupportedUnknownSymbolType,
     #[display("unsupported symbol visibility {f0:?}")]
     UnsupportedVisibility(ElfSymbolVisibility),
-    #[display("local symbols cannot have hidden visibility")]
+    #[display("local symbols cannot have hidden or protected visibility")]
     LocalHiddenSymbol,
     #[display("missing name for symbol {f0:?}")]
     MissingSymbolName(SymbolId),
@@ -290,3 +519,299 @@ pub(crate) enum ResolveSymbolErrorKind {
     #[transparent]
     OutOfBounds(OutOfBoundsError),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strong_overrides_undefined() {
+        let (mut ids, mut symbols) = setup();
+        add(&mut ids, &mut symbols, None, false).unwrap();
+        add(&mut ids, &mut symbols, Some(1), false).unwrap();
+        add(&mut ids, &mut symbols, None, false).unwrap();
+        assert_offset(&symbols, 1);
+    }
+
+    #[test]
+    fn test_weak_overrides_undefined() {
+        let (mut ids, mut symbols) = setup();
+        add(&mut ids, &mut symbols, None, false).unwrap();
+        add(&mut ids, &mut symbols, Some(1), true).unwrap();
+        add(&mut ids, &mut symbols, None, false).unwrap();
+        assert_offset(&symbols, 1);
+    }
+
+    #[test]
+    fn test_strong_overrides_weak() {
+        let (mut ids, mut symbols) = setup();
+        add(&mut ids, &mut symbols, Some(1), true).unwrap();
+        add(&mut ids, &mut symbols, Some(2), false).unwrap();
+        add(&mut ids, &mut symbols, Some(3), true).unwrap();
+        assert_offset(&symbols, 2);
+    }
+
+    #[test]
+    fn test_first_weak_wins() {
+        let (mut ids, mut symbols) = setup();
+        add(&mut ids, &mut symbols, Some(1), true).unwrap();
+        add(&mut ids, &mut symbols, Some(2), true).unwrap();
+        assert_offset(&symbols, 1);
+    }
+
+    #[test]
+    fn test_undefined_is_weak_only_if_all_references_are() {
+        let (mut ids, mut symbols) = setup();
+        add(&mut ids, &mut symbols, None, true).unwrap();
+        assert!(symbols.get_global(intern("foo")).unwrap().is_weak());
+        add(&mut ids, &mut symbols, None, false).unwrap();
+        add(&mut ids, &mut symbols, None, true).unwrap();
+        assert!(!symbols.get_global(intern("foo")).unwrap().is_weak());
+    }
+
+    #[test]
+    fn test_common_symbols_merge_to_the_largest() {
+        let (mut ids, mut symbols) = setup();
+        add_common(&mut ids, &mut symbols, 4, 4);
+        add(&mut ids, &mut symbols, None, false).unwrap();
+        add_common(&mut ids, &mut symbols, 8, 2);
+        add_common(&mut ids, &mut symbols, 2, 1);
+        match symbols.get_global(intern("foo")).unwrap().value {
+            SymbolValue::Common { size, align } => assert_eq!((8, 4), (size, align)),
+            ref other => panic!("unexpected value: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_definitions_override_common() {
+        for weak in [false, true] {
+            let (mut ids, mut symbols) = setup();
+            add_common(&mut ids, &mut symbols, 4, 4);
+            add(&mut ids, &mut symbols, Some(1), weak).unwrap();
+            add_common(&mut ids, &mut symbols, 8, 8);
+            assert_offset(&symbols, 1);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_strong() {
+        let (mut ids, mut symbols) = setup();
+        add(&mut ids, &mut symbols, Some(1), false).unwrap();
+        match add(&mut ids, &mut symbols, Some(2), false) {
+            Err(LoadSymbolsError::DuplicateGlobalSymbol(name)) if name == intern("foo") => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_command_line_overrides_everything() {
+        let (mut ids, mut symbols) = setup();
+        symbols.add_command_line_definition(&mut ids, "foo", 0x2a).unwrap();
+        add(&mut ids, &mut symbols, Some(1), true).unwrap();
+        add(&mut ids, &mut symbols, Some(2), false).unwrap();
+        add(&mut ids, &mut symbols, None, false).unwrap();
+
+        match symbols.get_global(intern("foo")).unwrap().value {
+            SymbolValue::Absolute { value } => assert_eq!(Absolute::from(0x2au64), value),
+            ref other => panic!("unexpected value: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_wrap() {
+        let (mut ids, mut symbols) = setup();
+        symbols.add_wrapped("foo");
+        let mut add_named = |name: &str, value| {
+            let id = ids.allocate_symbol_id();
+            symbols
+                .add_symbol(Symbol {
+                    id,
+                    name: intern(name),
+                    type_: SymbolType::NoType,
+                    stt_file: None,
+                    span: intern(ObjectSpan::new_synthetic()),
+                    visibility: SymbolVisibility::Global {
+                        weak: false,
+                        hidden: false,
+                        protected: false,
+                    },
+                    value,
+                    size: 0,
+                })
+                .unwrap();
+            id
+        };
+        let reference = add_named("foo", SymbolValue::Undefined);
+        let real_reference = add_named("__real_foo", SymbolValue::Undefined);
+        let definition = add_named("foo", SymbolValue::Absolute { value: 0x2au64.into() });
+        let unrelated = add_named("__real_bar", SymbolValue::Undefined);
+
+        assert_eq!(intern("__wrap_foo"), symbols.get(reference).name);
+        assert_eq!(symbols.get(definition).id, symbols.get(real_reference).id);
+        assert_eq!(intern("__real_bar"), symbols.get(unrelated).name);
+        assert!(symbols.get_global(intern("__real_foo")).is_err());
+    }
+
+    #[test]
+    fn test_most_constraining_visibility_wins() {
+        let global =
+            |hidden, protected| SymbolVisibility::Global { weak: false, hidden, protected };
+        for (first, second, expected) in [
+            (global(false, false), global(false, false), global(false, false)),
+            (global(true, false), global(false, false), global(true, false)),
+            (global(false, false), global(false, true), global(false, true)),
+            (global(false, true), global(true, false), global(true, false)),
+        ] {
+            let (mut ids, mut symbols) = setup();
+            // The definition comes second, to check the visibility of references is kept too.
+            add_with_visibility(&mut ids, &mut symbols, None, first).unwrap();
+            add_with_visibility(&mut ids, &mut symbols, Some(1), second).unwrap();
+            assert_eq!(
+                format!("{expected:?}"),
+                format!("{:?}", symbols.get_global(intern("foo")).unwrap().visibility)
+            );
+        }
+    }
+
+    #[test]
+    fn test_iter_undefined() {
+        let (mut ids, mut symbols) = setup();
+        let first = ObjectSpan::new_file("first.o");
+        let second = ObjectSpan::new_archive_member("libfoo.a", "second.o");
+        for (name, span, value) in [
+            ("foo", &first, SymbolValue::Undefined),
+            ("bar", &first, SymbolValue::Undefined),
+            ("foo", &second, SymbolValue::Undefined),
+            ("bar", &second, SymbolValue::Absolute { value: 0x2au64.into() }),
+            ("foo", &second, SymbolValue::Undefined),
+        ] {
+            symbols
+                .add_symbol(Symbol {
+                    id: ids.allocate_symbol_id(),
+                    name: intern(name),
+                    type_: SymbolType::NoType,
+                    stt_file: None,
+                    span: intern(span.clone()),
+                    visibility: SymbolVisibility::Global {
+                        weak: false,
+                        hidden: false,
+                        protected: false,
+                    },
+                    value,
+                    size: 0,
+                })
+                .unwrap();
+        }
+
+        let undefined = symbols
+            .iter_undefined()
+            .map(|(symbol, references)| (symbol.name, references.to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(intern("foo"), vec![first, second])], undefined);
+    }
+
+    #[test]
+    fn test_references_through_redirects() {
+        let (mut ids, mut symbols) = setup();
+        let mut add_foo = |span: &str, value| {
+            let id = ids.allocate_symbol_id();
+            symbols
+                .add_symbol(Symbol {
+                    id,
+                    name: intern("foo"),
+                    type_: SymbolType::NoType,
+                    stt_file: None,
+                    span: intern(ObjectSpan::new_file(span)),
+                    visibility: SymbolVisibility::Global {
+                        weak: false,
+                        hidden: false,
+                        protected: false,
+                    },
+                    value,
+                    size: 0,
+                })
+                .unwrap();
+            id
+        };
+        let undefined = add_foo("first.o", SymbolValue::Undefined);
+        let defined = add_foo("second.o", SymbolValue::Absolute { value: 0x2au64.into() });
+
+        // References are recorded against the global symbol, no matter the ID used for them.
+        symbols.add_reference(defined, ObjectSpan::new_file("second.o"));
+        symbols.add_reference(undefined, ObjectSpan::new_file("first.o"));
+        assert_eq!(
+            [ObjectSpan::new_file("first.o"), ObjectSpan::new_file("second.o")],
+            symbols.references_to(defined)
+        );
+        assert_eq!(symbols.references_to(defined), symbols.references_to(undefined));
+    }
+
+    fn setup() -> (SerialIds, Symbols) {
+        let mut ids = SerialIds::new();
+        let symbols = Symbols::new(&mut ids);
+        (ids, symbols)
+    }
+
+    fn add(
+        ids: &mut SerialIds,
+        symbols: &mut Symbols,
+        offset: Option<i64>,
+        weak: bool,
+    ) -> Result<(), LoadSymbolsError> {
+        let visibility = SymbolVisibility::Global { weak, hidden: false, protected: false };
+        add_with_visibility(ids, symbols, offset, visibility)
+    }
+
+    fn add_with_visibility(
+        ids: &mut SerialIds,
+        symbols: &mut Symbols,
+        offset: Option<i64>,
+        visibility: SymbolVisibility,
+    ) -> Result<(), LoadSymbolsError> {
+        let value = match offset {
+            Some(offset) => SymbolValue::SectionRelative {
+                section: ids.allocate_section_id(),
+                offset: offset.into(),
+            },
+            None => SymbolValue::Undefined,
+        };
+        symbols.add_symbol(Symbol {
+            id: ids.allocate_symbol_id(),
+            name: intern("foo"),
+            type_: SymbolType::NoType,
+            stt_file: None,
+            span: intern(ObjectSpan::new_synthetic()),
+            visibility,
+            value,
+            size: 0,
+        })
+    }
+
+    fn add_common(ids: &mut SerialIds, symbols: &mut Symbols, size: u64, align: u64) {
+        symbols
+            .add_symbol(Symbol {
+                id: ids.allocate_symbol_id(),
+                name: intern("foo"),
+                type_: SymbolType::Object,
+                stt_file: None,
+                span: intern(ObjectSpan::new_synthetic()),
+                visibility: SymbolVisibility::Global {
+                    weak: false,
+                    hidden: false,
+                    protected: false,
+                },
+                value: SymbolValue::Common { size, align },
+                size,
+            })
+            .unwrap();
+    }
+
+    fn assert_offset(symbols: &Symbols, expected: i64) {
+        match symbols.get_global(intern("foo")).unwrap().value {
+            SymbolValue::SectionRelative { offset, .. } => {
+                assert_eq!(Offset::from(expected), offset)
+            }
+            ref other => panic!("unexpected value: {other:?}"),
+        }
+    }
+}
@@ -35,6 +35,22 @@ macro_rules! int {
 
 int!(pub(crate) struct Absolute(u64));
 
+impl Absolute {
+    pub(crate) fn offset(&self, offset: Offset) -> Result<Absolute, OutOfBoundsError> {
+        Ok(Absolute(
+            i128::from(self.0)
+                .checked_add(i128::from(offset.0))
+                .ok_or(OutOfBoundsError)?
+                .try_into()
+                .map_err(|_| OutOfBoundsError)?,
+        ))
+    }
+
+    pub(crate) fn as_address(&self) -> Address {
+        Address(self.0)
+    }
+}
+
 int!(pub(crate) struct Address(u64));
 
 impl Address {
@@ -73,6 +89,12 @@ impl Offset {
     }
 }
 
+/// Round `value` up to the next multiple of `align`. An alignment of zero means no alignment, like
+/// in ELF files.
+pub(crate) fn align_up(value: u64, align: u64) -> u64 {
+    value.next_multiple_of(align.max(1))
+}
+
 pub(crate) trait ExtractNumber {
     type Type;
 
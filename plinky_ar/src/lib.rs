@@ -2,11 +2,13 @@
 
 mod reader;
 mod utils;
+mod writer;
 
 pub use crate::reader::{ArReadError, ArReader};
+pub use crate::writer::ArWriter;
 use std::collections::HashMap;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ArFile {
     pub name: String,
     pub content: Vec<u8>,
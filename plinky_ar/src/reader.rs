@@ -11,10 +11,15 @@ use plinky_utils::raw_types::{RawReadError, RawType};
 use plinky_utils::{Bits, Endian};
 use std::collections::HashMap;
 use std::io::{BufRead, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 
-const GNU_SYMBOL_TABLE_NAME: &str = "/";
-const GNU_FILE_NAMES_NAME: &str = "//";
+pub(crate) const GNU_SYMBOL_TABLE_NAME: &str = "/";
+pub(crate) const GNU_FILE_NAMES_NAME: &str = "//";
+const BSD_SYMBOL_TABLE_NAMES: &[&str] = &["__.SYMDEF", "__.SYMDEF SORTED"];
+const BSD_LONG_NAME_PREFIX: &str = "#1/";
+const MAGIC: &[u8; 8] = b"!<arch>\n";
+const THIN_MAGIC: &[u8; 8] = b"!<thin>\n";
 
 static NEXT_READER_SERIAL: AtomicU64 = AtomicU64::new(0);
 
@@ -22,6 +27,8 @@ pub struct ArReader<R: BufRead + Seek> {
     read: R,
     symbol_table: Option<ArSymbolTable>,
     gnu_file_names: Option<HashMap<u64, String>>,
+    /// Directory the members of a thin archive are relative to, if the archive is thin.
+    thin_archive_dir: Option<PathBuf>,
     serial: u64,
 }
 
@@ -31,11 +38,15 @@ impl<R: BufRead + Seek> ArReader<R> {
             read,
             symbol_table: None,
             gnu_file_names: None,
+            thin_archive_dir: None,
             serial: NEXT_READER_SERIAL.fetch_add(1, Ordering::Relaxed),
         };
 
         let magic: [u8; 8] = reader.read_raw()?;
-        if &magic != b"!<arch>\n" {
+        if &magic == THIN_MAGIC {
+            // Until told otherwise, paths are relative to the current directory.
+            reader.thin_archive_dir = Some(PathBuf::new());
+        } else if &magic != MAGIC {
             return Err(ArReadError::UnexpectedMagic(String::from_utf8_lossy(&magic).into()));
         }
 
@@ -51,6 +62,24 @@ impl<R: BufRead + Seek> ArReader<R> {
             reader.gnu_file_names = Some(reader.read_gnu_file_names(&content)?);
         }
 
+        // BSD archives only have the symbol table, but its name can be stored in the content, so
+        // the whole file needs to be read to know whether it's the symbol table.
+        let next_name = reader.peek_next_file_name()?.unwrap_or_default();
+        if reader.symbol_table.is_none()
+            && (next_name.starts_with(BSD_LONG_NAME_PREFIX)
+                || BSD_SYMBOL_TABLE_NAMES.contains(&next_name.as_str()))
+        {
+            let start =
+                reader.read.stream_position().map_err(ArReadError::GetCurrentPositionFailed)?;
+            let (header, mut content) = reader.read_raw_file()?;
+            let name = read_bsd_name(header.name.value.trim_end_matches(' '), &mut content)?;
+            if BSD_SYMBOL_TABLE_NAMES.contains(&name.as_str()) {
+                reader.symbol_table = Some(reader.read_bsd_symbol_table(&content)?);
+            } else {
+                reader.seek(start)?;
+            }
+        }
+
         Ok(reader)
     }
 
@@ -58,6 +87,19 @@ impl<R: BufRead + Seek> ArReader<R> {
         self.symbol_table.as_ref()
     }
 
+    /// Whether this is a thin archive, storing the paths of its members rather than their content.
+    pub fn is_thin(&self) -> bool {
+        self.thin_archive_dir.is_some()
+    }
+
+    /// Sets the directory the paths of the members of a thin archive are relative to, which is
+    /// the directory containing the archive. Does nothing for regular archives.
+    pub fn set_thin_archive_dir(&mut self, dir: &Path) {
+        if let Some(thin_archive_dir) = &mut self.thin_archive_dir {
+            *thin_archive_dir = dir.into();
+        }
+    }
+
     pub fn read_member_by_id(&mut self, id: &ArMemberId) -> Result<ArFile, ArReadError> {
         if id.reader_serial != self.serial {
             panic!("passed an ArMemberId to a different ArReader than the one that generated it");
@@ -78,9 +120,17 @@ impl<R: BufRead + Seek> ArReader<R> {
             return Ok(None);
         }
 
-        let (header, content) = self.read_raw_file()?;
-
+        let header = self.read_raw_header()?;
         let raw_name = header.name.value.trim_end_matches(' ');
+
+        // Members of thin archives only have the header, but the tables are still stored inline.
+        let mut content = match self.thin_archive_dir {
+            Some(_) if raw_name != GNU_SYMBOL_TABLE_NAME && raw_name != GNU_FILE_NAMES_NAME => {
+                Vec::new()
+            }
+            _ => self.read_raw_content(&header)?,
+        };
+
         let name = if raw_name == GNU_SYMBOL_TABLE_NAME {
             // GNU format, symbol table
             return Err(ArReadError::SymbolTableNotAtBeginning);
@@ -101,14 +151,21 @@ impl<R: BufRead + Seek> ArReader<R> {
                 .get(&offset)
                 .ok_or(ArReadError::MissingNameInGnuFileNamesTable(offset))?
                 .clone()
-        } else if let Some(_len) = raw_name.strip_prefix("#1/") {
-            // BSD format, lame.len() > 16, len is the size of name at the start of content
-            return Err(ArReadError::BsdFormatUnsupported);
         } else {
-            // BSD format, name.len() <= 16
-            return Err(ArReadError::BsdFormatUnsupported);
+            // BSD format, either with the name in the header or at the start of the content
+            let name = read_bsd_name(raw_name, &mut content)?;
+            if BSD_SYMBOL_TABLE_NAMES.contains(&name.as_str()) {
+                return Err(ArReadError::SymbolTableNotAtBeginning);
+            }
+            name
         };
 
+        if let Some(dir) = &self.thin_archive_dir {
+            let path = dir.join(&name);
+            content =
+                std::fs::read(&path).map_err(|e| ArReadError::ThinMemberReadFailed(path, e))?;
+        }
+
         Ok(Some(ArFile {
             name,
             content,
@@ -185,18 +242,59 @@ impl<R: BufRead + Seek> ArReader<R> {
         Ok(ArSymbolTable { symbols })
     }
 
+    fn read_bsd_symbol_table(
+        &self,
+        mut raw: &[u8],
+    ) -> Result<ArSymbolTable, ArSymbolTableReadError> {
+        // Unlike the rest of the archive, the BSD symbol table is encoded with the endianness of
+        // the machine that created it. Only little endian symbol tables are supported.
+        let entries_len = u32::read(Bits::Bits64, Endian::Little, &mut raw)? as usize;
+        let (mut entries, mut rest) =
+            raw.split_at_checked(entries_len).ok_or(ArSymbolTableReadError::TruncatedBsdTable)?;
+        let strings_len = u32::read(Bits::Bits64, Endian::Little, &mut rest)? as usize;
+        let strings = rest.get(..strings_len).ok_or(ArSymbolTableReadError::TruncatedBsdTable)?;
+
+        let mut symbols = HashMap::new();
+        while !entries.is_empty() {
+            let name_offset = u32::read(Bits::Bits64, Endian::Little, &mut entries)? as usize;
+            let header_offset = u32::read(Bits::Bits64, Endian::Little, &mut entries)?;
+
+            let name = strings.get(name_offset..).unwrap_or_default();
+            let Some(end) = name.iter().position(|&c| c == 0) else {
+                return Err(ArSymbolTableReadError::UnterminatedString);
+            };
+            let name = std::str::from_utf8(&name[..end])
+                .map_err(|_| ArSymbolTableReadError::NonUtf8SymbolName)?;
+            symbols.insert(
+                name.to_string(),
+                ArMemberId { reader_serial: self.serial, header_offset: header_offset as _ },
+            );
+        }
+
+        Ok(ArSymbolTable { symbols })
+    }
+
     fn read_raw_file(&mut self) -> Result<(RawHeader, Vec<u8>), ArReadError> {
+        let header = self.read_raw_header()?;
+        let content = self.read_raw_content(&header)?;
+        Ok((header, content))
+    }
+
+    fn read_raw_header(&mut self) -> Result<RawHeader, ArReadError> {
         let header: RawHeader = self.read_raw()?;
         if header.end_magic != [b'`', b'\n'] {
             return Err(ArReadError::InvalidEndMagic(header.end_magic));
         }
+        Ok(header)
+    }
 
+    fn read_raw_content(&mut self, header: &RawHeader) -> Result<Vec<u8>, ArReadError> {
         let mut content = vec![0; header.size.value as _];
         self.read.read_exact(&mut content).map_err(ArReadError::ContentReadFailed)?;
 
         self.align()?;
 
-        Ok((header, content))
+        Ok(content)
     }
 
     fn read_raw<T: RawType>(&mut self) -> Result<T, ArReadError> {
@@ -231,6 +329,24 @@ impl<R: BufRead + Seek> ArReader<R> {
     }
 }
 
+/// BSD archives store names up to 16 bytes in the header, while longer names (or names with spaces)
+/// are stored at the start of the content, with the header containing `#1/` and the name length.
+/// The name is removed from the content, and the padding at the end of the name is discarded.
+fn read_bsd_name(raw_name: &str, content: &mut Vec<u8>) -> Result<String, ArReadError> {
+    let Some(len) = raw_name.strip_prefix(BSD_LONG_NAME_PREFIX) else {
+        return Ok(raw_name.into());
+    };
+    let len: usize =
+        len.parse().map_err(|e| ArReadError::InvalidBsdLongNameLength(len.into(), e))?;
+    if len > content.len() {
+        return Err(ArReadError::BsdLongNameOutOfBounds(len));
+    }
+
+    let name = content.drain(..len).collect::<Vec<_>>();
+    let name = std::str::from_utf8(&name).map_err(|_| ArReadError::NonUtf8BsdLongName)?;
+    Ok(name.trim_end_matches('\0').into())
+}
+
 impl<R: BufRead + Seek> Iterator for ArReader<R> {
     type Item = Result<ArFile, ArReadError>;
 
@@ -240,14 +356,14 @@ impl<R: BufRead + Seek> Iterator for ArReader<R> {
 }
 
 #[derive(RawType)]
-struct RawHeader {
-    name: RawString<16>,
-    mtime: RawStringAsU64<12, 10>,
-    uid: RawStringAsU64<6, 10>,
-    gid: RawStringAsU64<6, 10>,
-    mode: RawStringAsU64<8, 8>,
-    size: RawStringAsU64<10, 10>,
-    end_magic: [u8; 2],
+pub(crate) struct RawHeader {
+    pub(crate) name: RawString<16>,
+    pub(crate) mtime: RawStringAsU64<12, 10>,
+    pub(crate) uid: RawStringAsU64<6, 10>,
+    pub(crate) gid: RawStringAsU64<6, 10>,
+    pub(crate) mode: RawStringAsU64<8, 8>,
+    pub(crate) size: RawStringAsU64<10, 10>,
+    pub(crate) end_magic: [u8; 2],
 }
 
 #[derive(Debug, Error, Display)]
@@ -282,8 +398,14 @@ pub enum ArReadError {
     SymbolTableReadFailed(#[from] ArSymbolTableReadError),
     #[display("invalid reference in symbol table")]
     InvalidReferenceInSymbolTable,
-    #[display("the BSD ar format is not supported")]
-    BsdFormatUnsupported,
+    #[display("invalid length for long BSD file name: {f0:?}")]
+    InvalidBsdLongNameLength(String, #[source] std::num::ParseIntError),
+    #[display("long BSD file name of {f0} bytes is longer than the file")]
+    BsdLongNameOutOfBounds(usize),
+    #[display("long BSD file name is not UTF-8")]
+    NonUtf8BsdLongName,
+    #[display("failed to read the member {f0:?} of the thin archive")]
+    ThinMemberReadFailed(PathBuf, #[source] std::io::Error),
     #[display("the symbol table is not at the beginning of the file")]
     SymbolTableNotAtBeginning,
     #[display("the file names table is not at the beginning of the file")]
@@ -300,6 +422,8 @@ pub enum ArSymbolTableReadError {
     NonUtf8SymbolName,
     #[display("extra data was found at the end of the symbol table")]
     ExtraDataAtEnd,
+    #[display("the BSD symbol table is truncated")]
+    TruncatedBsdTable,
 }
 
 #[cfg(test)]
@@ -337,10 +461,79 @@ mod tests {
 
     #[test]
     fn test_bsd_multiple_files() {
-        match parse!("bsd-multiple-files.a").unwrap_err() {
-            ArReadError::BsdFormatUnsupported => {}
-            other => panic!("unexpected error: {other:?}"),
-        }
+        assert_eq!(
+            (
+                None,
+                vec![
+                    ArFile {
+                        name: "unaligned-with-very-very-long-file-name.txt".into(),
+                        content: b"unaligned body\n".into(),
+                        modification_time: 0,
+                        owner_id: 0,
+                        group_id: 0,
+                        mode: 0o644,
+                    },
+                    ArFile {
+                        name: "aligned.txt".into(),
+                        content: b"hello\n".into(),
+                        modification_time: 0,
+                        owner_id: 0,
+                        group_id: 0,
+                        mode: 0o644,
+                    },
+                    ArFile {
+                        name: "also-aligned.txt".into(),
+                        content: b"aligned\n".into(),
+                        modification_time: 0,
+                        owner_id: 0,
+                        group_id: 0,
+                        mode: 0o644,
+                    },
+                ]
+            ),
+            parse!("bsd-multiple-files.a").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bsd_short_name() {
+        let content =
+            b"!<arch>\nexample.txt     0           0     0     644     6         `\nhello\n";
+        assert_eq!(
+            (
+                None,
+                vec![ArFile {
+                    name: "example.txt".into(),
+                    content: b"hello\n".into(),
+                    modification_time: 0,
+                    owner_id: 0,
+                    group_id: 0,
+                    mode: 0o644,
+                }]
+            ),
+            parse_archive(content).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bsd_objects() {
+        let mut content = Cursor::new(include_bytes!("../sample-archives/bsd-objects.a"));
+        let mut reader = ArReader::new(&mut content).unwrap();
+
+        let Some(table) = reader.symbol_table().cloned() else {
+            panic!("missing the symbol table");
+        };
+        assert_eq!(1, table.symbols.len());
+        let foo = reader.read_member_by_id(table.symbols.get("hello").unwrap()).unwrap();
+        assert_eq!("foo.o", foo.name);
+        assert_eq!(b"\x7fELF", &foo.content[..4]);
+
+        let file = reader.next().unwrap().unwrap();
+        assert_eq!("foo.o", file.name);
+        let file = reader.next().unwrap().unwrap();
+        assert_eq!("unaligned-with-very-very-long-file-name.txt", file.name);
+        assert_eq!(b"unaligned body\n", file.content.as_slice());
+        assert!(reader.next().is_none());
     }
 
     #[test]
@@ -446,6 +639,58 @@ mod tests {
         assert!(reader.next().is_none());
     }
 
+    #[test]
+    fn test_thin() {
+        let mut content = Cursor::new(include_bytes!("../sample-archives/thin.a"));
+        let mut reader = ArReader::new(&mut content).unwrap();
+        assert!(reader.is_thin());
+        reader.set_thin_archive_dir(Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/sample-archives"
+        )));
+
+        let Some(table) = reader.symbol_table().cloned() else {
+            panic!("first element is not the symbol table");
+        };
+        assert_eq!(1, table.symbols.len());
+        let foo = reader.read_member_by_id(table.symbols.get("hello").unwrap()).unwrap();
+        assert_eq!("thin/foo.o", foo.name);
+        assert_eq!(b"\x7fELF", &foo.content[..4]);
+
+        let file = reader.next().unwrap().unwrap();
+        assert_eq!("thin/foo.o", file.name);
+        let file = reader.next().unwrap().unwrap();
+        assert_eq!("thin/hello.txt", file.name);
+        assert_eq!(b"hello\n", file.content.as_slice());
+        let file = reader.next().unwrap().unwrap();
+        assert_eq!("thin/unaligned-with-very-very-long-file-name.txt", file.name);
+        assert_eq!(b"unaligned body\n", file.content.as_slice());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_thin_missing_member() {
+        // Without the directory of the archive, members are looked up in the current directory.
+        let mut content = Cursor::new(include_bytes!("../sample-archives/thin.a"));
+        let mut reader = ArReader::new(&mut content).unwrap();
+
+        match reader.next().unwrap().unwrap_err() {
+            ArReadError::ThinMemberReadFailed(path, _) if path == Path::new("thin/foo.o") => {}
+            other => panic!("expected ThinMemberReadFailed error, found {other:?}"),
+        }
+        // The next members can still be read, as the archive itself only contains the header.
+        match reader.next().unwrap().unwrap_err() {
+            ArReadError::ThinMemberReadFailed(path, _) if path == Path::new("thin/hello.txt") => {}
+            other => panic!("expected ThinMemberReadFailed error, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_regular_archive_is_not_thin() {
+        let mut content = Cursor::new(include_bytes!("../sample-archives/gnu-one-file.a"));
+        assert!(!ArReader::new(&mut content).unwrap().is_thin());
+    }
+
     #[test]
     fn test_gnu_file_names_table_at_end() {
         // This tests both various errors that could occur with GNU-formatted archives, and that we
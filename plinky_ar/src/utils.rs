@@ -33,9 +33,18 @@ impl<const LEN: usize> RawType for RawString<LEN> {
         &self,
         _bits: impl Into<Bits>,
         _endian: impl Into<Endian>,
-        _writer: &mut dyn std::io::Write,
+        writer: &mut dyn std::io::Write,
     ) -> Result<(), RawWriteError> {
-        unimplemented!();
+        if self.value.len() > LEN {
+            return Err(RawWriteError::custom::<Self>(format!(
+                "string {:?} is longer than {LEN} bytes",
+                self.value
+            )));
+        }
+        // Fields are padded with spaces rather than zeroes. The padding is calculated in bytes,
+        // as formatting with `{:<LEN$}` would count characters instead.
+        writer.write_all(self.value.as_bytes()).map_err(RawWriteError::io::<Self>)?;
+        writer.write_all(&[b' '; LEN][self.value.len()..]).map_err(RawWriteError::io::<Self>)
     }
 }
 
@@ -73,10 +82,19 @@ impl<const LEN: usize, const RADIX: u32> RawType for RawStringAsU64<LEN, RADIX>
 
     fn write(
         &self,
-        _bits: impl Into<Bits>,
-        _endian: impl Into<Endian>,
-        _writer: &mut dyn std::io::Write,
+        bits: impl Into<Bits>,
+        endian: impl Into<Endian>,
+        writer: &mut dyn std::io::Write,
     ) -> Result<(), RawWriteError> {
-        unimplemented!();
+        let value = match RADIX {
+            8 => format!("{:o}", self.value),
+            10 => self.value.to_string(),
+            _ => {
+                return Err(RawWriteError::custom::<Self>(format!(
+                    "radix {RADIX} is not supported, only octal and decimal numbers are"
+                )))
+            }
+        };
+        RawWriteError::wrap_type::<Self, _>(RawString::<LEN> { value }.write(bits, endian, writer))
     }
 }
@@ -0,0 +1,366 @@
+use crate::reader::{RawHeader, GNU_FILE_NAMES_NAME, GNU_SYMBOL_TABLE_NAME};
+use crate::utils::{RawString, RawStringAsU64};
+use crate::ArFile;
+use plinky_elf::ids::serial::SerialIds;
+use plinky_elf::ids::StringIdGetters;
+use plinky_elf::{ElfObject, ElfSectionContent, ElfSymbolBinding, ElfSymbolDefinition};
+use plinky_utils::raw_types::RawType;
+use plinky_utils::{Bits, Endian};
+use std::io::{Cursor, Error, ErrorKind, Write};
+
+/// Names longer than this don't fit in the header alongside their `/` terminator, and are stored
+/// in the GNU file names table instead.
+const MAX_SHORT_NAME_LEN: usize = 15;
+
+const MAGIC: &[u8] = b"!<arch>\n";
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+/// Writer for archives in the GNU format.
+///
+/// Both the symbol table and the file names table must be at the start of the archive, but their
+/// content depends on all the members. Members are thus kept until [`ArWriter::finish`] is called,
+/// which writes the whole archive to the underlying writer.
+pub struct ArWriter<W: Write> {
+    write: W,
+    members: Vec<ArFile>,
+}
+
+impl<W: Write> ArWriter<W> {
+    pub fn new(write: W) -> Self {
+        Self { write, members: Vec::new() }
+    }
+
+    pub fn add_member(&mut self, file: ArFile) {
+        self.members.push(file);
+    }
+
+    pub fn finish(mut self) -> Result<(), Error> {
+        let members = std::mem::take(&mut self.members);
+
+        let mut file_names = Vec::new();
+        let mut header_names = Vec::new();
+        for member in &members {
+            validate_name(&member.name)?;
+            if member.name.len() > MAX_SHORT_NAME_LEN {
+                header_names.push(format!("/{}", file_names.len()));
+                file_names.extend_from_slice(member.name.as_bytes());
+                file_names.extend_from_slice(b"/\n");
+            } else {
+                header_names.push(format!("{}/", member.name));
+            }
+        }
+
+        let mut member_symbols = Vec::new();
+        for member in &members {
+            member_symbols.push(defined_symbols(member)?);
+        }
+
+        // The symbol table refers to the members by the offset of their header, which depends on
+        // the size of the tables preceding them, including the symbol table itself.
+        let header_size = RawHeader::size(Bits::Bits64);
+        let symbol_count = member_symbols.iter().map(|symbols| symbols.len()).sum::<usize>();
+        let symbol_table_len = 4
+            + 4 * symbol_count
+            + member_symbols.iter().flatten().map(|name| name.len() + 1).sum::<usize>();
+        let mut offset = MAGIC.len() + header_size + padded(symbol_table_len);
+        if !file_names.is_empty() {
+            offset += header_size + padded(file_names.len());
+        }
+
+        let mut symbol_offsets = Vec::new();
+        let mut symbol_names = Vec::new();
+        for (member, symbols) in members.iter().zip(&member_symbols) {
+            for name in symbols {
+                symbol_offsets.extend_from_slice(&symbol_table_u32(offset)?.to_be_bytes());
+                symbol_names.extend_from_slice(name.as_bytes());
+                symbol_names.push(0);
+            }
+            offset += header_size + padded(member.content.len());
+        }
+        let mut symbol_table = Vec::with_capacity(symbol_table_len);
+        symbol_table.extend_from_slice(&symbol_table_u32(symbol_count)?.to_be_bytes());
+        symbol_table.extend_from_slice(&symbol_offsets);
+        symbol_table.extend_from_slice(&symbol_names);
+
+        self.write.write_all(MAGIC)?;
+        self.write_raw_file(
+            header(GNU_SYMBOL_TABLE_NAME.into(), symbol_table.len()),
+            &symbol_table,
+        )?;
+
+        if !file_names.is_empty() {
+            self.write_raw_file(header(GNU_FILE_NAMES_NAME.into(), file_names.len()), &file_names)?;
+        }
+
+        for (member, name) in members.iter().zip(header_names) {
+            let mut header = header(name, member.content.len());
+            header.mtime.value = member.modification_time;
+            header.uid.value = member.owner_id;
+            header.gid.value = member.group_id;
+            header.mode.value = member.mode;
+            self.write_raw_file(header, &member.content)?;
+        }
+
+        self.write.flush()
+    }
+
+    fn write_raw_file(&mut self, header: RawHeader, content: &[u8]) -> Result<(), Error> {
+        // Like when reading, the raw types don't depend on bits or endianness.
+        header.write(Bits::Bits64, Endian::Big, &mut self.write).map_err(Error::other)?;
+        self.write.write_all(content)?;
+
+        // The header is 60 bytes long, so members are aligned to 2 bytes as long as the content
+        // of the previous one is padded to an even length.
+        if padded(content.len()) != content.len() {
+            self.write.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Names of the global symbols defined by the member, if it's an ELF object.
+fn defined_symbols(member: &ArFile) -> Result<Vec<String>, Error> {
+    if !member.content.starts_with(ELF_MAGIC) {
+        return Ok(Vec::new());
+    }
+    // The IDs of the member are not used outside of this function, so they don't need to be shared.
+    let object = ElfObject::load(&mut Cursor::new(&member.content), &mut SerialIds::new())
+        .map_err(|e| {
+            Error::new(ErrorKind::InvalidData, format!("failed to parse {}: {e}", member.name))
+        })?;
+
+    let mut names = Vec::new();
+    for section in object.sections.values() {
+        let ElfSectionContent::SymbolTable(table) = &section.content else { continue };
+        if table.dynsym {
+            continue;
+        }
+        for symbol in table.symbols.values() {
+            match symbol.binding {
+                ElfSymbolBinding::Global | ElfSymbolBinding::Weak => {}
+                ElfSymbolBinding::Local | ElfSymbolBinding::Unknown(_) => continue,
+            }
+            match symbol.definition {
+                // Like GNU ar, common symbols are indexed too, as they are tentative definitions.
+                ElfSymbolDefinition::Absolute
+                | ElfSymbolDefinition::Common
+                | ElfSymbolDefinition::Section(_) => {}
+                ElfSymbolDefinition::Undefined => continue,
+            }
+            let name = match &object.sections.get(symbol.name.section()).map(|s| &s.content) {
+                Some(ElfSectionContent::StringTable(strings)) => strings.get(symbol.name.offset()),
+                _ => None,
+            };
+            let Some(name) = name else {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("a symbol of {} has a missing name", member.name),
+                ));
+            };
+            names.push(name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// The symbol table stores the symbol count and the member offsets as 32-bit numbers. Archives
+/// exceeding that would need the 64-bit `/SYM64/` table, which is not supported.
+fn symbol_table_u32(value: usize) -> Result<u32, Error> {
+    u32::try_from(value).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{value:#x} doesn't fit in the 32-bit symbol table of the archive"),
+        )
+    })
+}
+
+/// Content is padded so that members are aligned to 2 bytes.
+fn padded(len: usize) -> usize {
+    len + len % 2
+}
+
+fn header(name: String, size: usize) -> RawHeader {
+    RawHeader {
+        name: RawString { value: name },
+        mtime: RawStringAsU64 { value: 0 },
+        uid: RawStringAsU64 { value: 0 },
+        gid: RawStringAsU64 { value: 0 },
+        mode: RawStringAsU64 { value: 0 },
+        size: RawStringAsU64 { value: size as _ },
+        end_magic: [b'`', b'\n'],
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), Error> {
+    // Slashes terminate names in the GNU format, and newlines separate them in the file names
+    // table, so neither can be part of a name.
+    if name.is_empty() || name.contains(['/', '\n']) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{name:?} is not a valid name for an archive member"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArReader;
+    use plinky_elf::{ElfObjectBuilder, ElfPermissions, ElfSymbolType, ElfType};
+
+    #[test]
+    fn test_roundtrip() {
+        let members = vec![
+            ArFile {
+                name: "unaligned-with-very-very-long-file-name.txt".into(),
+                content: b"unaligned body\n".into(),
+                modification_time: 1703532181,
+                owner_id: 1000,
+                group_id: 1000,
+                mode: 0o100664,
+            },
+            ArFile {
+                name: "aligned.txt".into(),
+                content: b"hello\n".into(),
+                modification_time: 0,
+                owner_id: 0,
+                group_id: 0,
+                mode: 0o644,
+            },
+            ArFile {
+                name: "exactly-15-long".into(),
+                content: b"short".into(),
+                modification_time: 0,
+                owner_id: 0,
+                group_id: 0,
+                mode: 0o644,
+            },
+            ArFile {
+                name: "another-long-file-name.o".into(),
+                content: Vec::new(),
+                modification_time: 0,
+                owner_id: 0,
+                group_id: 0,
+                mode: 0o644,
+            },
+        ];
+
+        let mut buf = Vec::new();
+        let mut writer = ArWriter::new(&mut buf);
+        for member in &members {
+            writer.add_member(member.clone());
+        }
+        writer.finish().unwrap();
+
+        let mut cursor = Cursor::new(&buf);
+        let reader = ArReader::new(&mut cursor).unwrap();
+        assert!(reader.symbol_table().unwrap().symbols.is_empty());
+        assert_eq!(members, reader.collect::<Result<Vec<_>, _>>().unwrap());
+    }
+
+    #[test]
+    fn test_symbol_table() {
+        let mut buf = Vec::new();
+        let mut writer = ArWriter::new(&mut buf);
+        // Odd-sized and long-named, to check the padding and the file names table are accounted
+        // for in the offsets.
+        writer.add_member(ArFile { content: b"odd".into(), ..file("not-an-object-file.txt") });
+        writer.add_member(object_file("foo.o", &[("hello", true), ("local", false)]));
+        writer.add_member(object_file("bar.o", &[("goodbye", true), ("world", true)]));
+        writer.finish().unwrap();
+
+        let mut cursor = Cursor::new(&buf);
+        let mut reader = ArReader::new(&mut cursor).unwrap();
+        let table = reader.symbol_table().cloned().unwrap();
+        assert_eq!(3, table.symbols.len());
+        for (symbol, member) in [("hello", "foo.o"), ("goodbye", "bar.o"), ("world", "bar.o")] {
+            let id = table.symbols.get(symbol).unwrap();
+            assert_eq!(member, reader.read_member_by_id(id).unwrap().name);
+        }
+    }
+
+    #[test]
+    fn test_no_file_names_table_for_short_names() {
+        let mut buf = Vec::new();
+        let mut writer = ArWriter::new(&mut buf);
+        writer.add_member(file("foo.o"));
+        writer.finish().unwrap();
+
+        // Magic, then the symbol table, then the member right away.
+        assert_eq!(b"foo.o/ ", &buf[8 + 60 + 4..8 + 60 + 4 + 7]);
+    }
+
+    #[test]
+    fn test_non_ascii_names() {
+        let mut buf = Vec::new();
+        let mut writer = ArWriter::new(&mut buf);
+        writer.add_member(file("café.o"));
+        writer.add_member(file("ünïcödé.o"));
+        writer.finish().unwrap();
+
+        let reader = ArReader::new(Cursor::new(&buf)).unwrap();
+        let names = reader.map(|member| member.unwrap().name).collect::<Vec<_>>();
+        assert_eq!(vec!["café.o".to_string(), "ünïcödé.o".to_string()], names);
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        for name in ["", "foo/bar.o", "foo\n.o"] {
+            let mut writer = ArWriter::new(Vec::new());
+            writer.add_member(file(name));
+            assert_eq!(ErrorKind::InvalidInput, writer.finish().unwrap_err().kind());
+        }
+    }
+
+    #[test]
+    fn test_symbol_table_u32_overflow() {
+        // Archives this large can't be built in a test, so check the conversion on its own.
+        assert_eq!(0xffffffff, symbol_table_u32(0xffffffff).unwrap());
+        let err = symbol_table_u32(0x100000000).unwrap_err();
+        assert_eq!(ErrorKind::InvalidInput, err.kind());
+    }
+
+    fn object_file(name: &str, symbols: &[(&str, bool)]) -> ArFile {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let text = builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            [0xc3],
+        );
+        // Undefined symbols must not be part of the symbol table.
+        builder.add_symbol(
+            "undefined",
+            ElfSymbolBinding::Global,
+            ElfSymbolType::NoType,
+            ElfSymbolDefinition::Undefined,
+            0,
+            0,
+        );
+        for (symbol, global) in symbols {
+            builder.add_symbol(
+                symbol,
+                if *global { ElfSymbolBinding::Global } else { ElfSymbolBinding::Local },
+                ElfSymbolType::Function,
+                ElfSymbolDefinition::Section(text),
+                0,
+                1,
+            );
+        }
+
+        let mut content = Vec::new();
+        builder.build().write(&mut content).unwrap();
+        ArFile { content, ..file(name) }
+    }
+
+    fn file(name: &str) -> ArFile {
+        ArFile {
+            name: name.into(),
+            content: b"content\n".into(),
+            modification_time: 0,
+            owner_id: 0,
+            group_id: 0,
+            mode: 0o644,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::widgets::{Widget, WidgetGroup};
+use crate::{Style, WidgetWriter};
 
 pub struct Diagnostic {
     kind: DiagnosticKind,
@@ -33,16 +34,29 @@ impl std::fmt::Debug for Diagnostic {
     }
 }
 
-impl std::fmt::Display for Diagnostic {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.kind, self.message)?;
-
-        let children = self.children.render_to_string();
-        if !children.is_empty() {
-            write!(f, "\n{children}")?;
+impl Widget for Diagnostic {
+    fn render(&self, writer: &mut WidgetWriter<'_>) {
+        writer.push_styled(
+            match self.kind {
+                DiagnosticKind::Error => Style::Error,
+                DiagnosticKind::Warning => Style::Warning,
+                DiagnosticKind::DebugPrint => Style::DebugPrint,
+            },
+            &self.kind.to_string(),
+        );
+        writer.push_str(": ");
+        writer.push_styled(Style::Header, &self.message);
+
+        if !self.children.is_empty() {
+            writer.push('\n');
+            self.children.render(writer);
         }
+    }
+}
 
-        Ok(())
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_to_string())
     }
 }
 
@@ -65,8 +79,8 @@ impl std::fmt::Display for DiagnosticKind {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::configure_insta;
     use crate::widgets::{Table, Text};
+    use crate::{configure_insta, WriterOptions};
     use insta::assert_snapshot;
 
     #[test]
@@ -93,6 +107,51 @@ mod tests {
         assert_snapshot!(diagnostic.to_string());
     }
 
+    #[test]
+    fn test_color_disabled() {
+        let diagnostic = Diagnostic::new(DiagnosticKind::Error, "something went wrong")
+            .add(WidgetGroup::new().name("header").add(Text::new("content")));
+        assert_eq!(
+            diagnostic.to_string(),
+            diagnostic.render_to_string_with_options(WriterOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_color_enabled() {
+        let _config = configure_insta();
+
+        let mut table = Table::new();
+        table.set_title("Title:");
+        table.add_row(["Foo", "Bar"]);
+
+        let diagnostic = Diagnostic::new(DiagnosticKind::Warning, "something bad might happen")
+            .add(WidgetGroup::new().name("header").add(table));
+        assert_snapshot!(diagnostic
+            .render_to_string_with_options(WriterOptions { color: true, max_width: None }));
+    }
+
+    #[test]
+    fn test_fixed_width() {
+        let _config = configure_insta();
+
+        let mut table = Table::new();
+        table.set_title("Table wider than the maximum width:");
+        table.add_row(["Some long content", "More long content", "Even more long content"]);
+
+        let diagnostic = Diagnostic::new(DiagnosticKind::Error, "something went wrong")
+            .add(Text::new(
+                "This is a very long explanation of what went wrong, which doesn't fit in the \
+                 narrow terminal and will have to be wrapped.",
+            ))
+            .add(WidgetGroup::new().name("Nested group").add(Text::new(
+                "Nested content is wrapped too, accounting for the indentation of the group.",
+            )))
+            .add(table);
+        assert_snapshot!(diagnostic
+            .render_to_string_with_options(WriterOptions { color: false, max_width: Some(40) }));
+    }
+
     #[test]
     fn test_with_children() {
         let _config = configure_insta();
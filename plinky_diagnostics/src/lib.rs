@@ -5,7 +5,7 @@ mod writer;
 
 pub use crate::diagnostic::{Diagnostic, DiagnosticKind};
 pub use crate::span::ObjectSpan;
-pub use crate::writer::WidgetWriter;
+pub use crate::writer::{Style, WidgetWriter, WriterOptions};
 
 #[cfg(test)]
 #[must_use]
@@ -45,7 +45,8 @@ impl std::fmt::Display for ObjectSpan {
         match &self.0 {
             ObjectSpanInner::File(file) => write!(f, "{}", file.display()),
             ObjectSpanInner::ArchiveMember { archive, member } => {
-                write!(f, "{member} inside archive {}", archive.display())
+                // Same format used by GNU ld and other binutils.
+                write!(f, "{}({member})", archive.display())
             }
             ObjectSpanInner::Mix(items) => {
                 f.write_str("mix of ")?;
@@ -90,17 +91,14 @@ mod tests {
         let span4 = ObjectSpan::new_file("quux.o");
 
         assert_eq!("foo.o", span1.to_string());
-        assert_eq!("bar.o inside archive libutils.a", span2.to_string());
+        assert_eq!("libutils.a(bar.o)", span2.to_string());
+        assert_eq!("mix of foo.o and libutils.a(bar.o)", span1.merge(&span2).to_string());
         assert_eq!(
-            "mix of foo.o and bar.o inside archive libutils.a",
-            span1.merge(&span2).to_string()
-        );
-        assert_eq!(
-            "mix of foo.o, bar.o inside archive libutils.a and baz.o",
+            "mix of foo.o, libutils.a(bar.o) and baz.o",
             span1.merge(&span2).merge(&span3).to_string()
         );
         assert_eq!(
-            "mix of foo.o, bar.o inside archive libutils.a, baz.o and quux.o",
+            "mix of foo.o, libutils.a(bar.o), baz.o and quux.o",
             span1.merge(&span2).merge(&span3).merge(&span4).to_string()
         );
     }
@@ -1,5 +1,5 @@
 use crate::widgets::Widget;
-use crate::writer::IndentMode;
+use crate::writer::{IndentMode, Style};
 use crate::WidgetWriter;
 
 pub struct WidgetGroup {
@@ -33,12 +33,16 @@ impl WidgetGroup {
         }
         self
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
 }
 
 impl Widget for WidgetGroup {
     fn render(&self, writer: &mut WidgetWriter) {
         if let Some(name) = &self.name {
-            writer.push_str(name);
+            writer.push_styled(Style::Header, name);
             writer.push('\n');
         }
         writer.push_indent(" │", IndentMode::ShowAlways);
@@ -1,4 +1,4 @@
-use crate::widgets::{Table, Widget};
+use crate::widgets::{Table, Text, Widget};
 use crate::WidgetWriter;
 use std::fmt::Write;
 
@@ -6,20 +6,37 @@ const BYTES_PER_LINE: usize = 16;
 
 pub struct HexDump {
     data: Vec<u8>,
+    base_address: Option<u64>,
 }
 
 impl HexDump {
     pub fn new(data: impl Into<Vec<u8>>) -> Self {
-        Self { data: data.into() }
+        Self { data: data.into(), base_address: None }
+    }
+
+    /// Show an additional column with the address of each row, starting from `base_address`.
+    pub fn with_base_address(mut self, base_address: u64) -> Self {
+        self.base_address = Some(base_address);
+        self
     }
 }
 
 impl Widget for HexDump {
     fn render(&self, writer: &mut WidgetWriter<'_>) {
+        if self.data.is_empty() {
+            Text::new("(empty)").render(writer);
+            return;
+        }
+
+        let mut offsets = String::new();
         let mut hex = String::new();
         let mut ascii = String::new();
 
-        for chunk in self.data.chunks(BYTES_PER_LINE) {
+        for (row, chunk) in self.data.chunks(BYTES_PER_LINE).enumerate() {
+            if let Some(base_address) = self.base_address {
+                let address = base_address + (row * BYTES_PER_LINE) as u64;
+                offsets.write_fmt(format_args!("{address:#x}\n")).unwrap();
+            }
             for (i, byte) in chunk.iter().copied().enumerate() {
                 if i > 0 {
                     hex.push(' ');
@@ -36,7 +53,10 @@ impl Widget for HexDump {
         }
 
         let mut table = Table::new();
-        table.add_row([hex.trim(), ascii.trim()]);
+        match self.base_address {
+            Some(_) => table.add_row([offsets.trim(), hex.trim(), ascii.trim()]),
+            None => table.add_row([hex.trim(), ascii.trim()]),
+        }
         table.render(writer);
     }
 }
@@ -65,4 +85,21 @@ mod tests {
         let data = (0u8..=255u8).collect::<Vec<_>>();
         assert_snapshot!(HexDump::new(data).render_to_string());
     }
+
+    #[test]
+    fn test_hex_dump_with_base_address() {
+        let _config = configure_insta();
+
+        let data = b"Hello world, this is a hex dump!!!";
+        assert_snapshot!(HexDump::new(data.as_slice())
+            .with_base_address(0x401000)
+            .render_to_string());
+    }
+
+    #[test]
+    fn test_hex_dump_empty() {
+        let _config = configure_insta();
+
+        assert_snapshot!(HexDump::new(Vec::new()).with_base_address(0x401000).render_to_string());
+    }
 }
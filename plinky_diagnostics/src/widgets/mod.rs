@@ -9,14 +9,18 @@ pub use self::hex_dump::HexDump;
 pub use self::quoted_text::QuotedText;
 pub use self::table::Table;
 pub use self::text::Text;
-use crate::WidgetWriter;
+use crate::{WidgetWriter, WriterOptions};
 
 pub trait Widget {
     fn render(&self, writer: &mut WidgetWriter<'_>);
 
     fn render_to_string(&self) -> String {
+        self.render_to_string_with_options(WriterOptions::default())
+    }
+
+    fn render_to_string_with_options(&self, options: WriterOptions) -> String {
         let mut buffer = String::new();
-        let mut writer = WidgetWriter::new(&mut buffer);
+        let mut writer = WidgetWriter::with_options(&mut buffer, options);
         self.render(&mut writer);
         buffer
     }
@@ -1,5 +1,5 @@
 use crate::widgets::Widget;
-use crate::WidgetWriter;
+use crate::{Style, WidgetWriter};
 
 const UNICODE_CHARSET: TableCharset = TableCharset {
     vertical_separator: '│',
@@ -91,7 +91,7 @@ impl Widget for Table {
 
         if let Some(title) = &self.title {
             writer.push_str("  ");
-            writer.push_str(title);
+            writer.push_styled(Style::Header, title);
             writer.push_str("\n");
         }
         self.render_horizontal_border(writer, cells_len, &self.charset.first_junction);
@@ -13,7 +13,7 @@ impl Text {
 
 impl Widget for Text {
     fn render(&self, writer: &mut WidgetWriter) {
-        writer.push_str(&self.content);
+        writer.push_wrapped(&self.content);
     }
 }
 
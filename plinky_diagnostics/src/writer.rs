@@ -1,35 +1,42 @@
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WriterOptions {
+    /// Whether to emit ANSI escape codes when pushing styled content.
+    pub color: bool,
+    /// Maximum width of each line (including indentation) when pushing wrapped content. No
+    /// wrapping is performed when this is `None`.
+    pub max_width: Option<usize>,
+}
+
 pub struct WidgetWriter<'a> {
     buffer: &'a mut String,
+    options: WriterOptions,
 
     indent: Vec<Indent>,
     last_char: Option<char>,
+    column: usize,
 }
 
 impl<'a> WidgetWriter<'a> {
     pub fn new(buffer: &'a mut String) -> Self {
-        Self { buffer, indent: Vec::new(), last_char: None }
+        Self::with_options(buffer, WriterOptions::default())
     }
 
-    pub fn push(&mut self, content: char) {
-        let indents_to_consider = if content == '\n' {
-            indents_for_empty_line(&mut self.indent)
-        } else {
-            &mut self.indent
-        };
-        for indent in indents_to_consider {
-            if !indent.printed_this_line {
-                self.buffer.push_str(&indent.content);
-                indent.printed_this_line = true;
-            }
-        }
+    pub fn with_options(buffer: &'a mut String, options: WriterOptions) -> Self {
+        Self { buffer, options, indent: Vec::new(), last_char: None, column: 0 }
+    }
 
+    pub fn push(&mut self, content: char) {
+        self.push_pending_indents(content == '\n');
         self.buffer.push(content);
         self.last_char = Some(content);
 
         if content == '\n' {
+            self.column = 0;
             for indent in &mut self.indent {
                 indent.printed_this_line = false;
             }
+        } else {
+            self.column += 1;
         }
     }
 
@@ -39,6 +46,55 @@ impl<'a> WidgetWriter<'a> {
         }
     }
 
+    /// Push the content, breaking lines at spaces to avoid exceeding the maximum width. Words
+    /// longer than the available width are not split.
+    pub fn push_wrapped(&mut self, content: &str) {
+        let Some(width) = self.available_width() else {
+            self.push_str(content);
+            return;
+        };
+
+        for (idx, line) in content.split('\n').enumerate() {
+            if idx != 0 {
+                self.push('\n');
+            }
+            for (idx, word) in line.split(' ').enumerate() {
+                if idx != 0 {
+                    if self.column + 1 + word.chars().count() > width {
+                        self.push('\n');
+                    } else {
+                        self.push(' ');
+                    }
+                }
+                self.push_str(word);
+            }
+        }
+    }
+
+    pub fn push_styled(&mut self, style: Style, content: &str) {
+        if !self.options.color || content.is_empty() {
+            self.push_str(content);
+            return;
+        }
+
+        // Indents must be emitted before the escape code, to avoid styling them.
+        self.push_pending_indents(false);
+        self.buffer.push_str(style.ansi_code());
+        self.push_str(content);
+        self.buffer.push_str(RESET_ANSI_CODE);
+    }
+
+    fn push_pending_indents(&mut self, empty_line: bool) {
+        let indents_to_consider =
+            if empty_line { indents_for_empty_line(&mut self.indent) } else { &mut self.indent };
+        for indent in indents_to_consider {
+            if !indent.printed_this_line {
+                self.buffer.push_str(&indent.content);
+                indent.printed_this_line = true;
+            }
+        }
+    }
+
     pub(crate) fn push_indent(&mut self, indent: &str, mode: IndentMode) {
         self.indent.push(Indent::new(indent, mode));
     }
@@ -50,6 +106,33 @@ impl<'a> WidgetWriter<'a> {
     pub(crate) fn last_char(&self) -> Option<char> {
         self.last_char
     }
+
+    /// Width available to content on the current line, after accounting for indentation.
+    fn available_width(&self) -> Option<usize> {
+        let indent: usize = self.indent.iter().map(|indent| indent.content.chars().count()).sum();
+        self.options.max_width.map(|max| max.saturating_sub(indent).max(1))
+    }
+}
+
+const RESET_ANSI_CODE: &str = "\x1b[0m";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Style {
+    Header,
+    Error,
+    Warning,
+    DebugPrint,
+}
+
+impl Style {
+    fn ansi_code(&self) -> &'static str {
+        match self {
+            Style::Header => "\x1b[1m",
+            Style::Error => "\x1b[1;31m",
+            Style::Warning => "\x1b[1;33m",
+            Style::DebugPrint => "\x1b[1;36m",
+        }
+    }
 }
 
 fn indents_for_empty_line(indents: &mut [Indent]) -> &mut [Indent] {
@@ -85,6 +168,46 @@ pub(crate) enum IndentMode {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_push_styled_without_color() {
+        let mut buffer = String::new();
+        let mut writer = WidgetWriter::new(&mut buffer);
+        writer.push_styled(Style::Error, "error");
+        assert_eq!("error", buffer);
+    }
+
+    #[test]
+    fn test_push_styled_with_color() {
+        let mut buffer = String::new();
+        let mut writer =
+            WidgetWriter::with_options(&mut buffer, WriterOptions { color: true, max_width: None });
+        writer.push_indent("> ", IndentMode::ShowAlways);
+        writer.push_styled(Style::Error, "error");
+        writer.push_str(": message");
+        assert_eq!("> \x1b[1;31merror\x1b[0m: message", buffer);
+    }
+
+    #[test]
+    fn test_push_wrapped_without_max_width() {
+        let mut buffer = String::new();
+        let mut writer = WidgetWriter::new(&mut buffer);
+        writer.push_wrapped("hello world, this is a long line");
+        assert_eq!("hello world, this is a long line", buffer);
+    }
+
+    #[test]
+    fn test_push_wrapped() {
+        let mut buffer = String::new();
+        let options = WriterOptions { color: false, max_width: Some(12) };
+        let mut writer = WidgetWriter::with_options(&mut buffer, options);
+        writer.push_indent("| ", IndentMode::ShowAlways);
+        writer.push_wrapped("hello world, this is a long line\nwith a verylongwordinside");
+        assert_eq!(
+            "| hello\n| world,\n| this is a\n| long line\n| with a\n| verylongwordinside",
+            buffer
+        );
+    }
+
     #[test]
     fn test_indents_for_empty_line() {
         let i = |mode| Indent::new("_", mode);
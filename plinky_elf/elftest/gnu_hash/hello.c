@@ -0,0 +1,14 @@
+void a() {}
+void b() {}
+void c() {}
+void d() {}
+void e() {}
+void f() {}
+void g() {}
+void h() {}
+void i() {}
+void j() {}
+void k() {}
+void l() {}
+void m() {}
+void n() {}
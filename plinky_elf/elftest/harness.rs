@@ -10,6 +10,9 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use tempfile::TempDir;
 
+/// Length of `e_ident`, `e_type`, `e_machine` and `e_version` in the ELF header.
+const ELF_HEADER_IDENTITY_LEN: usize = 20;
+
 struct Elftest;
 
 impl TestGatherer for Elftest {
@@ -59,6 +62,14 @@ impl TestExecution {
         println!("building prerequisites in {}", self.dest_dir.display());
         self.toml.prerequisites.build(self.arch, &self.source_dir, &self.dest_dir)?;
 
+        if self.toml.read_fail {
+            if self.read_file(&self.toml.read)? {
+                bail!("reading the ELF file was supposed to fail but passed!");
+            }
+            let _ = std::fs::remove_dir_all(&self.dest_dir);
+            return Ok(());
+        }
+
         insta::allow_duplicates! {
             self.read(&self.toml.read)?;
             if self.toml.roundtrip {
@@ -83,10 +94,25 @@ impl TestExecution {
         ElfObject::load(&mut BufReader::new(File::open(self.dest_dir.join(file))?), &mut ids)?
             .write(&mut BufWriter::new(File::create_new(&dest)?))?;
 
+        // The identification, type, machine and version fields at the start of the header must be
+        // re-emitted byte for byte, while the rest of it depends on the layout chosen by the writer.
+        let original = std::fs::read(self.dest_dir.join(file))?;
+        let written = std::fs::read(&dest)?;
+        if original.get(..ELF_HEADER_IDENTITY_LEN) != written.get(..ELF_HEADER_IDENTITY_LEN) {
+            bail!("the header of the roundtrip differs from the original one");
+        }
+
         Ok(dest)
     }
 
     fn read(&self, file: &Path) -> Result<(), Error> {
+        if !self.read_file(file)? {
+            bail!("failed to read the ELF file");
+        }
+        Ok(())
+    }
+
+    fn read_file(&self, file: &Path) -> Result<bool, Error> {
         println!("reading {}...", file.display());
 
         let mut command = Command::new(env!("CARGO_BIN_EXE_read-elf"));
@@ -95,10 +121,7 @@ impl TestExecution {
             command.arg(filter);
         }
 
-        if !self.record_snapshot("read", "reading", &mut command)? {
-            bail!("failed to read the ELF file");
-        }
-        Ok(())
+        self.record_snapshot("read", "reading", &mut command)
     }
 
     fn record_snapshot(
@@ -124,6 +147,9 @@ struct TestToml {
     roundtrip: bool,
     #[serde(default)]
     filter: Option<String>,
+    /// Expect reading the file to fail, recording the error in the snapshot.
+    #[serde(default)]
+    read_fail: bool,
     #[serde(flatten)]
     prerequisites: Prerequisites,
 }
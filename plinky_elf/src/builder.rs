@@ -0,0 +1,349 @@
+use crate::ids::serial::{SectionId, SerialIds, StringId};
+use crate::{
+    ElfDeduplication, ElfEnvironment, ElfObject, ElfPermissions, ElfProgramSection, ElfSection,
+    ElfSectionContent, ElfStringTable, ElfSymbol, ElfSymbolBinding, ElfSymbolDefinition,
+    ElfSymbolTable, ElfSymbolType, ElfSymbolVisibility, ElfType, ElfUninitializedSection, RawBytes,
+};
+use std::collections::BTreeMap;
+use std::num::NonZeroU64;
+
+/// Helper to construct an [`ElfObject`] from scratch, taking care of allocating the IDs and
+/// populating the string tables for section and symbol names.
+pub struct ElfObjectBuilder {
+    env: ElfEnvironment,
+    type_: ElfType,
+    entry: Option<NonZeroU64>,
+    ids: SerialIds,
+    section_names_id: SectionId,
+    section_names: StringTableBuilder,
+    sections: BTreeMap<SectionId, ElfSection<SerialIds>>,
+    symbols: Vec<PendingSymbol>,
+}
+
+impl ElfObjectBuilder {
+    pub fn new(env: ElfEnvironment, type_: ElfType) -> Self {
+        let mut ids = SerialIds::new();
+        let null_id = ids.allocate_section_id();
+        let section_names_id = ids.allocate_section_id();
+
+        let mut builder = Self {
+            env,
+            type_,
+            entry: None,
+            ids,
+            section_names_id,
+            section_names: StringTableBuilder::new(),
+            sections: BTreeMap::new(),
+            symbols: Vec::new(),
+        };
+        builder.sections.insert(
+            null_id,
+            ElfSection {
+                name: StringId::new(section_names_id, 0),
+                memory_address: 0,
+                part_of_group: false,
+                content: ElfSectionContent::Null,
+            },
+        );
+        builder
+    }
+
+    pub fn set_entry(&mut self, entry: NonZeroU64) {
+        self.entry = Some(entry);
+    }
+
+    pub fn add_program_section(
+        &mut self,
+        name: &str,
+        perms: ElfPermissions,
+        bytes: impl Into<Vec<u8>>,
+    ) -> SectionId {
+        self.add_section(
+            name,
+            ElfSectionContent::Program(ElfProgramSection {
+                perms,
+                deduplication: ElfDeduplication::Disabled,
+                tls: false,
+                align: 1,
+                link_order: None,
+                functions_array: None,
+                compression: None,
+                raw: RawBytes(bytes.into()),
+            }),
+        )
+    }
+
+    pub fn add_uninitialized_section(
+        &mut self,
+        name: &str,
+        perms: ElfPermissions,
+        len: u64,
+    ) -> SectionId {
+        self.add_section(
+            name,
+            ElfSectionContent::Uninitialized(ElfUninitializedSection {
+                perms,
+                tls: false,
+                align: 1,
+                len,
+            }),
+        )
+    }
+
+    fn add_section(&mut self, name: &str, content: ElfSectionContent<SerialIds>) -> SectionId {
+        let id = self.ids.allocate_section_id();
+        let name = StringId::new(self.section_names_id, self.section_names.add(name));
+        self.sections
+            .insert(id, ElfSection { name, memory_address: 0, part_of_group: false, content });
+        id
+    }
+
+    pub fn add_symbol(
+        &mut self,
+        name: &str,
+        binding: ElfSymbolBinding,
+        type_: ElfSymbolType,
+        definition: ElfSymbolDefinition<SerialIds>,
+        value: u64,
+        size: u64,
+    ) {
+        self.symbols.push(PendingSymbol {
+            name: name.into(),
+            binding,
+            type_,
+            definition,
+            value,
+            size,
+        });
+    }
+
+    pub fn build(mut self) -> ElfObject<SerialIds> {
+        if !self.symbols.is_empty() {
+            self.build_symbol_table();
+        }
+
+        let name = StringId::new(self.section_names_id, self.section_names.add(".shstrtab"));
+        self.sections.insert(
+            self.section_names_id,
+            ElfSection {
+                name,
+                memory_address: 0,
+                part_of_group: false,
+                content: ElfSectionContent::StringTable(self.section_names.finish()),
+            },
+        );
+
+        ElfObject {
+            env: self.env,
+            type_: self.type_,
+            entry: self.entry,
+            sections: self.sections,
+            segments: Vec::new(),
+        }
+    }
+
+    fn build_symbol_table(&mut self) {
+        let symbol_table_id = self.ids.allocate_section_id();
+        let strings_id = self.ids.allocate_section_id();
+        let mut strings = StringTableBuilder::new();
+
+        let mut symbols = BTreeMap::new();
+        symbols.insert(
+            self.ids.allocate_symbol_id(),
+            ElfSymbol {
+                name: StringId::new(strings_id, 0),
+                binding: ElfSymbolBinding::Local,
+                type_: ElfSymbolType::NoType,
+                visibility: ElfSymbolVisibility::Default,
+                definition: ElfSymbolDefinition::Undefined,
+                value: 0,
+                size: 0,
+            },
+        );
+
+        // The ELF specification requires all local symbols to be placed before other symbols,
+        // so we need to reorder them before allocating their IDs.
+        let mut pending = std::mem::take(&mut self.symbols);
+        pending.sort_by_key(|symbol| symbol.binding != ElfSymbolBinding::Local);
+        for symbol in pending {
+            symbols.insert(
+                self.ids.allocate_symbol_id(),
+                ElfSymbol {
+                    name: StringId::new(strings_id, strings.add(&symbol.name)),
+                    binding: symbol.binding,
+                    type_: symbol.type_,
+                    visibility: ElfSymbolVisibility::Default,
+                    definition: symbol.definition,
+                    value: symbol.value,
+                    size: symbol.size,
+                },
+            );
+        }
+
+        for (id, name, content) in [
+            (
+                symbol_table_id,
+                ".symtab",
+                ElfSectionContent::SymbolTable(ElfSymbolTable { dynsym: false, symbols }),
+            ),
+            (strings_id, ".strtab", ElfSectionContent::StringTable(strings.finish())),
+        ] {
+            let name = StringId::new(self.section_names_id, self.section_names.add(name));
+            self.sections
+                .insert(id, ElfSection { name, memory_address: 0, part_of_group: false, content });
+        }
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl ElfObjectBuilder {
+    /// Builder for a little-endian x86-64 System V object, the environment most tests use.
+    pub fn x86_64(type_: ElfType) -> Self {
+        Self::new(
+            ElfEnvironment {
+                class: crate::ElfClass::Elf64,
+                endian: crate::ElfEndian::Little,
+                abi: crate::ElfABI::SystemV,
+                machine: crate::ElfMachine::X86_64,
+            },
+            type_,
+        )
+    }
+
+    /// Builder for a little-endian x86 System V object, for tests covering 32-bit objects.
+    pub fn x86(type_: ElfType) -> Self {
+        Self::new(
+            ElfEnvironment {
+                class: crate::ElfClass::Elf32,
+                endian: crate::ElfEndian::Little,
+                abi: crate::ElfABI::SystemV,
+                machine: crate::ElfMachine::X86,
+            },
+            type_,
+        )
+    }
+}
+
+struct PendingSymbol {
+    name: String,
+    binding: ElfSymbolBinding,
+    type_: ElfSymbolType,
+    definition: ElfSymbolDefinition<SerialIds>,
+    value: u64,
+    size: u64,
+}
+
+struct StringTableBuilder {
+    strings: BTreeMap<u32, String>,
+    next_offset: u32,
+}
+
+impl StringTableBuilder {
+    fn new() -> Self {
+        let mut strings = BTreeMap::new();
+        strings.insert(0, String::new());
+        Self { strings, next_offset: 1 }
+    }
+
+    fn add(&mut self, string: &str) -> u32 {
+        if string.is_empty() {
+            return 0;
+        }
+        if let Some((offset, _)) = self.strings.iter().find(|(_, existing)| *existing == string) {
+            return *offset;
+        }
+
+        let offset = self.next_offset;
+        self.strings.insert(offset, string.into());
+        self.next_offset += string.len() as u32 + 1;
+        offset
+    }
+
+    fn finish(self) -> ElfStringTable {
+        ElfStringTable::new(self.strings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::{ElfIds, StringIdGetters};
+    use crate::{ElfABI, ElfClass, ElfEndian, ElfMachine};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_build_and_write() {
+        let mut builder = ElfObjectBuilder::new(
+            ElfEnvironment {
+                class: ElfClass::Elf64,
+                endian: ElfEndian::Little,
+                abi: ElfABI::SystemV,
+                machine: ElfMachine::X86_64,
+            },
+            ElfType::Relocatable,
+        );
+        let text = builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            [0x90, 0xc3],
+        );
+        builder.add_symbol(
+            "_start",
+            ElfSymbolBinding::Global,
+            ElfSymbolType::Function,
+            ElfSymbolDefinition::Section(text),
+            0,
+            2,
+        );
+        builder.add_symbol(
+            "local",
+            ElfSymbolBinding::Local,
+            ElfSymbolType::NoType,
+            ElfSymbolDefinition::Section(text),
+            1,
+            0,
+        );
+
+        let mut buffer = Vec::new();
+        builder.build().write(&mut buffer).unwrap();
+        let object = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+
+        let section_names = object
+            .sections
+            .values()
+            .map(|section| resolve(&object, &section.name))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["", ".shstrtab", ".text", ".symtab", ".strtab"], section_names);
+
+        let symbols = object
+            .sections
+            .values()
+            .find_map(|section| match &section.content {
+                ElfSectionContent::SymbolTable(table) => Some(table),
+                _ => None,
+            })
+            .unwrap()
+            .symbols
+            .values()
+            .map(|symbol| (resolve(&object, &symbol.name), &symbol.binding))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("", &ElfSymbolBinding::Local),
+                ("local", &ElfSymbolBinding::Local),
+                ("_start", &ElfSymbolBinding::Global),
+            ],
+            symbols
+        );
+    }
+
+    fn resolve<'a>(
+        object: &'a ElfObject<SerialIds>,
+        id: &<SerialIds as ElfIds>::StringId,
+    ) -> &'a str {
+        let ElfSectionContent::StringTable(table) = &object.sections[id.section()].content else {
+            panic!("not a string table");
+        };
+        table.get(id.offset()).unwrap()
+    }
+}
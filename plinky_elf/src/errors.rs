@@ -1,8 +1,10 @@
 use crate::ids::ElfIds;
-use crate::writer::WriteLayoutError;
 use crate::ElfABI;
 use plinky_macros::{Display, Error};
 use plinky_utils::bitfields::BitfieldReadError;
+use plinky_utils::zlib::ZlibError;
+
+pub use crate::writer::WriteLayoutError;
 
 #[derive(Debug, Error, Display)]
 pub enum LoadError {
@@ -48,8 +50,34 @@ pub enum LoadError {
     FixedSizeChunksMergeWithZeroLenChunks { section_idx: u32 },
     #[display("flag SHF_MERGE was applied on an unsupported section (section {section_idx})")]
     MergeFlagOnUnsupportedSection { section_idx: u32 },
+    #[display("flag SHF_COMPRESSED was applied on an unsupported section (section {section_idx})")]
+    CompressedFlagOnUnsupportedSection { section_idx: u32 },
+    #[display("unsupported compression type {type_} in section {section_idx}")]
+    UnsupportedCompression { section_idx: u32, type_: u32 },
+    #[display("failed to decompress section {section_idx}")]
+    DecompressionFailed {
+        section_idx: u32,
+        #[source]
+        inner: ZlibError,
+    },
+    #[display("section {section_idx} should be {expected:#x} bytes when decompressed, but it's {actual:#x} bytes")]
+    DecompressedSizeMismatch { section_idx: u32, expected: u64, actual: u64 },
+    #[display("section content at offset {offset:#x} with size {size:#x} is beyond the end of the file, and no segment contains it")]
+    SectionContentBeyondEndOfFile { offset: u64, size: u64 },
+    #[display("notes aligned to {f0} bytes are not supported, only 4 and 8 are")]
+    UnsupportedNoteAlignment(u64),
     #[display("bad symbol visibility: {f0}")]
     BadSymbolVisibility(u8),
+    #[display("GNU hash table bucket points to symbol {bucket}, before the first hashed symbol {symbol_offset}")]
+    GnuHashBucketBeforeSymbolOffset { bucket: u32, symbol_offset: u32 },
+    #[display("unsupported revision {f0} of the symbol versioning tables")]
+    UnsupportedVersionRevision(u16),
+    #[display(
+        "symbol versioning chain ends after {found} entries, while {expected} were expected"
+    )]
+    TruncatedVersionChain { expected: u32, found: u32 },
+    #[display("failed to parse the flags field of the dynamic table")]
+    DynamicFlags(BitfieldReadError),
     #[display("failed to parse the flags1 field of the dynamic table")]
     DynamicFlags1(BitfieldReadError),
     #[display("failed to parse section header number {idx}")]
@@ -60,10 +88,14 @@ pub enum LoadError {
     },
     #[display("failed to parse section number {idx}")]
     FailedToParseSection {
-        idx: u16,
+        idx: u32,
         #[source]
         inner: Box<LoadError>,
     },
+    #[display("the program header count is PN_XNUM, but there are no section headers")]
+    MissingExtendedProgramHeaderCount,
+    #[display("the section header count stored in the null section ({f0}) is too large")]
+    BadExtendedSectionHeaderCount(u64),
     #[display("misaligned file: parsed until {current:#x}, expected to be at {expected:#x}")]
     MisalignedFile { current: usize, expected: usize },
 }
@@ -82,6 +114,17 @@ pub enum WriteError<I: ElfIds> {
     InconsistentSymbolNamesTableId,
     #[display("missing symbol table {symbol_table:?} for relocations table {relocations_table:?}")]
     MissingSymbolTableForRelocations { symbol_table: I::SectionId, relocations_table: I::SectionId },
+    #[display("symbol table {symbol_table:?} of relocations table {relocations_table:?} is not actually a symbol table")]
+    WrongSectionTypeForRelocationsSymbolTable {
+        relocations_table: I::SectionId,
+        symbol_table: I::SectionId,
+    },
+    #[display(
+        "missing section {section:?} the relocations table {relocations_table:?} applies to"
+    )]
+    MissingSectionForRelocations { section: I::SectionId, relocations_table: I::SectionId },
+    #[display("relocations table {relocations_table:?} applies to section {section:?}, which can't have relocations")]
+    WrongSectionTypeForRelocationsTarget { relocations_table: I::SectionId, section: I::SectionId },
     #[display("missing symbol {symbol_id:?} for relocation {relocation_idx} in table {relocations_table:?}")]
     MissingSymbolInRelocation {
         symbol_id: I::SymbolId,
@@ -92,8 +135,28 @@ pub enum WriteError<I: ElfIds> {
     WrongSectionTypeForGroupSymbolTable { group: I::SectionId, symbol_table: I::SectionId },
     #[display("group {group:?}'s signature {signature:?} is missing")]
     MissingGroupSignature { group: I::SectionId, signature: I::SymbolId },
+    #[display("too many sections ({count}), extended section numbering is not supported")]
+    TooManySections { count: usize },
+    #[display("too many segments ({count}) to store their count in the ELF header or in the section headers")]
+    TooManySegments { count: usize },
+    #[display("too many versions ({count}) in an entry of section {section:?}")]
+    TooManyVersions { section: I::SectionId, count: usize },
     #[display("value {value} in the dynamic table does not fit")]
     DynamicValueDoesNotFit { value: u64 },
     #[display("failed to calculate the resulting ELF layout")]
     LayoutError(#[from] WriteLayoutError),
 }
+
+#[derive(Debug, Error, Display)]
+pub enum SegmentsCheckError<I: ElfIds> {
+    #[display("LOAD segments #{first} and #{second} overlap in memory")]
+    OverlappingMemory { first: usize, second: usize },
+    #[display("LOAD segments #{first} and #{second} overlap in the file")]
+    OverlappingFile { first: usize, second: usize },
+    #[display("section {section:?} overlaps the previous section in LOAD segment #{segment}")]
+    OverlappingSections { segment: usize, section: I::SectionId },
+    #[display("section {section:?} in LOAD segment #{segment} is missing")]
+    MissingSection { segment: usize, section: I::SectionId },
+    #[display("LOAD segment #{segment} is larger in the file than in memory")]
+    FileSizeLargerThanMemorySize { segment: usize },
+}
@@ -1,8 +1,9 @@
 use crate::ids::ElfIds;
 use crate::{
-    ElfDynamic, ElfGroup, ElfHash, ElfObject, ElfRelocation, ElfRelocationsTable, ElfSection,
-    ElfSectionContent, ElfSegment, ElfSegmentContent, ElfSymbol, ElfSymbolDefinition,
-    ElfSymbolTable,
+    ElfDefinedVersion, ElfDynamic, ElfGnuHash, ElfGroup, ElfHash, ElfNeededVersion, ElfObject,
+    ElfProgramSection, ElfRelocation, ElfRelocationsTable, ElfSection, ElfSectionContent,
+    ElfSegment, ElfSegmentContent, ElfSymbol, ElfSymbolDefinition, ElfSymbolTable,
+    ElfVersionDefined, ElfVersionNeeded, ElfVersionNeededFile, ElfVersionSymbols,
 };
 use std::collections::BTreeMap;
 
@@ -79,7 +80,18 @@ where
                         part_of_group: section.part_of_group,
                         content: match section.content {
                             ElfSectionContent::Null => ElfSectionContent::Null,
-                            ElfSectionContent::Program(p) => ElfSectionContent::Program(p),
+                            ElfSectionContent::Program(p) => {
+                                ElfSectionContent::Program(ElfProgramSection {
+                                    perms: p.perms,
+                                    deduplication: p.deduplication,
+                                    tls: p.tls,
+                                    align: p.align,
+                                    link_order: p.link_order.map(|id| map.section_id(&id)),
+                                    functions_array: p.functions_array,
+                                    compression: p.compression,
+                                    raw: p.raw,
+                                })
+                            }
                             ElfSectionContent::Uninitialized(u) => {
                                 ElfSectionContent::Uninitialized(u)
                             }
@@ -149,12 +161,69 @@ where
                                 buckets: h.buckets,
                                 chain: h.chain,
                             }),
+                            ElfSectionContent::GnuHash(h) => {
+                                ElfSectionContent::GnuHash(ElfGnuHash {
+                                    symbol_table: map.section_id(&h.symbol_table),
+                                    symbol_offset: h.symbol_offset,
+                                    bloom_shift: h.bloom_shift,
+                                    bloom: h.bloom,
+                                    buckets: h.buckets,
+                                    chain: h.chain,
+                                })
+                            }
                             ElfSectionContent::Dynamic(d) => {
                                 ElfSectionContent::Dynamic(ElfDynamic {
                                     string_table: map.section_id(&d.string_table),
                                     directives: d.directives,
                                 })
                             }
+                            ElfSectionContent::VersionSymbols(v) => {
+                                ElfSectionContent::VersionSymbols(ElfVersionSymbols {
+                                    symbol_table: map.section_id(&v.symbol_table),
+                                    versions: v.versions,
+                                })
+                            }
+                            ElfSectionContent::VersionNeeded(v) => {
+                                ElfSectionContent::VersionNeeded(ElfVersionNeeded {
+                                    string_table: map.section_id(&v.string_table),
+                                    files: v
+                                        .files
+                                        .into_iter()
+                                        .map(|file| ElfVersionNeededFile {
+                                            file: map.string_id(&file.file),
+                                            versions: file
+                                                .versions
+                                                .into_iter()
+                                                .map(|version| ElfNeededVersion {
+                                                    name: map.string_id(&version.name),
+                                                    hash: version.hash,
+                                                    flags: version.flags,
+                                                    index: version.index,
+                                                })
+                                                .collect(),
+                                        })
+                                        .collect(),
+                                })
+                            }
+                            ElfSectionContent::VersionDefined(v) => {
+                                ElfSectionContent::VersionDefined(ElfVersionDefined {
+                                    string_table: map.section_id(&v.string_table),
+                                    versions: v
+                                        .versions
+                                        .into_iter()
+                                        .map(|version| ElfDefinedVersion {
+                                            flags: version.flags,
+                                            index: version.index,
+                                            hash: version.hash,
+                                            names: version
+                                                .names
+                                                .iter()
+                                                .map(|name| map.string_id(name))
+                                                .collect(),
+                                        })
+                                        .collect(),
+                                })
+                            }
                             ElfSectionContent::Note(n) => ElfSectionContent::Note(n),
                             ElfSectionContent::Unknown(u) => ElfSectionContent::Unknown(u),
                         },
@@ -171,6 +240,7 @@ where
                 content: match segment.content {
                     ElfSegmentContent::Empty => ElfSegmentContent::Empty,
                     ElfSegmentContent::ElfHeader => ElfSegmentContent::ElfHeader,
+                    ElfSegmentContent::Notes(notes) => ElfSegmentContent::Notes(notes),
                     ElfSegmentContent::Unknown(unknown) => ElfSegmentContent::Unknown(unknown),
                     ElfSegmentContent::Sections(ids) => ElfSegmentContent::Sections(
                         ids.into_iter().map(|id| map.section_id(&id)).collect(),
@@ -186,10 +256,24 @@ fn collect_string_ids<I: ElfIds>(object: &ElfObject<I>) -> Vec<I::StringId> {
     let mut ids = Vec::new();
     for section in object.sections.values() {
         ids.push(section.name.clone());
-        if let ElfSectionContent::SymbolTable(table) = &section.content {
-            for symbol in table.symbols.values() {
-                ids.push(symbol.name.clone());
+        match &section.content {
+            ElfSectionContent::SymbolTable(table) => {
+                for symbol in table.symbols.values() {
+                    ids.push(symbol.name.clone());
+                }
+            }
+            ElfSectionContent::VersionNeeded(needed) => {
+                for file in &needed.files {
+                    ids.push(file.file.clone());
+                    ids.extend(file.versions.iter().map(|version| version.name.clone()));
+                }
+            }
+            ElfSectionContent::VersionDefined(defined) => {
+                for version in &defined.versions {
+                    ids.extend(version.names.iter().cloned());
+                }
             }
+            _ => {}
         }
     }
     ids
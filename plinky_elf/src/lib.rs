@@ -1,5 +1,6 @@
 #![feature(error_generic_member_access)]
 
+mod builder;
 pub mod errors;
 pub mod ids;
 pub mod raw;
@@ -9,4 +10,16 @@ mod types;
 mod utils;
 mod writer;
 
+pub use self::builder::ElfObjectBuilder;
 pub use self::types::*;
+pub use self::writer::ElfWriteOptions;
+
+#[cfg(test)]
+fn configure_insta() -> impl Drop {
+    use insta::Settings;
+
+    let mut settings = Settings::clone_current();
+    settings.set_snapshot_path(concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots"));
+
+    settings.bind_to_scope()
+}
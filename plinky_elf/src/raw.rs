@@ -1,5 +1,14 @@
+use crate::ElfVersionFlags;
 use plinky_macros::{Bitfield, RawType};
-use plinky_utils::raw_types::RawPadding;
+use plinky_utils::raw_types::{
+    RawPadding, RawReadError, RawType, RawTypeAsPointerSize, RawWriteError,
+};
+use plinky_utils::{Bits, Endian};
+use std::io::{Read, Write};
+
+/// Value of the program header count in the ELF header when the actual count doesn't fit in it, and
+/// is stored in the `sh_info` field of the null section header instead.
+pub(crate) const PN_XNUM: u16 = 0xffff;
 
 #[derive(RawType)]
 pub struct RawIdentification {
@@ -68,8 +77,14 @@ pub struct RawSectionHeaderFlags {
     pub strings: bool,
     #[bit(6)]
     pub info_link: bool,
+    #[bit(7)]
+    pub link_order: bool,
     #[bit(9)]
     pub group: bool,
+    #[bit(10)]
+    pub tls: bool,
+    #[bit(11)]
+    pub compressed: bool,
 }
 
 #[derive(RawType)]
@@ -86,6 +101,64 @@ pub struct RawSymbol {
     pub size: u64,
 }
 
+/// Header at the start of sections with the `SHF_COMPRESSED` flag. It can't be derived, as the
+/// 64-bit variant has a reserved field that is missing in the 32-bit one.
+pub struct RawCompressionHeader {
+    pub type_: u32,
+    pub size: u64,
+    pub addr_align: u64,
+}
+
+impl RawType for RawCompressionHeader {
+    fn zero() -> Self {
+        Self { type_: 0, size: 0, addr_align: 0 }
+    }
+
+    fn size(bits: impl Into<Bits>) -> usize {
+        match bits.into() {
+            Bits::Bits32 => 12,
+            Bits::Bits64 => 24,
+        }
+    }
+
+    fn read(
+        bits: impl Into<Bits>,
+        endian: impl Into<Endian>,
+        reader: &mut dyn Read,
+    ) -> Result<Self, RawReadError> {
+        let (bits, endian) = (bits.into(), endian.into());
+        let type_ = RawReadError::wrap_field::<Self, _>("type_", u32::read(bits, endian, reader))?;
+        if let Bits::Bits64 = bits {
+            RawReadError::wrap_field::<Self, _>("reserved", u32::read(bits, endian, reader))?;
+        }
+        let size = RawReadError::wrap_field::<Self, _>(
+            "size",
+            <u64 as RawTypeAsPointerSize>::read(bits, endian, reader),
+        )?;
+        let addr_align = RawReadError::wrap_field::<Self, _>(
+            "addr_align",
+            <u64 as RawTypeAsPointerSize>::read(bits, endian, reader),
+        )?;
+        Ok(Self { type_, size, addr_align })
+    }
+
+    fn write(
+        &self,
+        bits: impl Into<Bits>,
+        endian: impl Into<Endian>,
+        writer: &mut dyn Write,
+    ) -> Result<(), RawWriteError> {
+        let (bits, endian) = (bits.into(), endian.into());
+        self.type_.write(bits, endian, writer)?;
+        if let Bits::Bits64 = bits {
+            0u32.write(bits, endian, writer)?;
+        }
+        RawTypeAsPointerSize::write(&self.size, bits, endian, writer)?;
+        RawTypeAsPointerSize::write(&self.addr_align, bits, endian, writer)?;
+        Ok(())
+    }
+}
+
 #[derive(RawType)]
 pub struct RawNoteHeader {
     pub name_size: u32,
@@ -144,6 +217,49 @@ pub struct RawHashHeader {
     pub chain_count: u32,
 }
 
+#[derive(RawType)]
+pub struct RawVersionNeeded {
+    pub version: u16,
+    pub count: u16,
+    pub file_offset: u32,
+    pub aux_offset: u32,
+    pub next_offset: u32,
+}
+
+#[derive(RawType)]
+pub struct RawVersionNeededAux {
+    pub hash: u32,
+    pub flags: ElfVersionFlags,
+    pub index: u16,
+    pub name_offset: u32,
+    pub next_offset: u32,
+}
+
+#[derive(RawType)]
+pub struct RawVersionDefinition {
+    pub version: u16,
+    pub flags: ElfVersionFlags,
+    pub index: u16,
+    pub count: u16,
+    pub hash: u32,
+    pub aux_offset: u32,
+    pub next_offset: u32,
+}
+
+#[derive(RawType)]
+pub struct RawVersionDefinitionAux {
+    pub name_offset: u32,
+    pub next_offset: u32,
+}
+
+#[derive(RawType)]
+pub struct RawGnuHashHeader {
+    pub bucket_count: u32,
+    pub symbol_offset: u32,
+    pub bloom_count: u32,
+    pub bloom_shift: u32,
+}
+
 #[derive(Bitfield)]
 #[bitfield_repr(u32)]
 pub struct RawGroupFlags {
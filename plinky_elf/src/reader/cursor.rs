@@ -40,6 +40,14 @@ impl<'a> ReadCursor<'a> {
         Ok(())
     }
 
+    /// Length of the whole file, without changing the current position.
+    pub(super) fn file_len(&mut self) -> Result<u64, LoadError> {
+        let current = self.current_position()?;
+        let len = self.reader.seek(SeekFrom::End(0))?;
+        self.seek_to(current)?;
+        Ok(len)
+    }
+
     pub(super) fn current_position(&mut self) -> Result<u64, LoadError> {
         Ok(self.reader.stream_position()?)
     }
@@ -1,36 +1,48 @@
 use crate::errors::LoadError;
 use crate::raw::RawNoteHeader;
 use crate::reader::ReadCursor;
-use crate::{ElfNote, ElfNotesTable, ElfUnknownNote, RawBytes};
+use crate::{ElfNote, ElfNotesTable, ElfUnknownNote, RawBytes, NT_GNU_BUILD_ID};
 
 pub(super) fn read_notes(
     cursor: &mut ReadCursor<'_>,
     raw_content: &[u8],
+    align: u64,
 ) -> Result<ElfNotesTable, LoadError> {
+    // Notes are padded to the alignment of their section or segment, which is either 4 or 8
+    // bytes. Some producers leave the alignment unset (or set it to 1), meaning 4.
+    let align = match align {
+        0..=4 => 4,
+        8 => 8,
+        other => return Err(LoadError::UnsupportedNoteAlignment(other)),
+    };
     let mut inner = std::io::Cursor::new(raw_content);
     let mut cursor = cursor.duplicate(&mut inner);
 
     let mut notes = Vec::new();
     while cursor.current_position()? != raw_content.len() as u64 {
-        notes.push(read_note(&mut cursor)?);
+        notes.push(read_note(&mut cursor, align)?);
     }
 
-    Ok(ElfNotesTable { notes })
+    Ok(ElfNotesTable { align, notes })
 }
 
-fn read_note(cursor: &mut ReadCursor<'_>) -> Result<ElfNote, LoadError> {
+fn read_note(cursor: &mut ReadCursor<'_>, align: u64) -> Result<ElfNote, LoadError> {
     let header: RawNoteHeader = cursor.read_raw()?;
 
     let mut name_bytes = cursor.read_vec(header.name_size as _)?;
     name_bytes.pop(); // Zero-terminated string
-    cursor.align_with_padding(8)?;
+    cursor.align_with_padding(align)?;
 
     let value_bytes = cursor.read_vec(header.value_size as _)?;
-    cursor.align_with_padding(8)?;
+    cursor.align_with_padding(align)?;
 
-    Ok(ElfNote::Unknown(ElfUnknownNote {
-        name: String::from_utf8(name_bytes)?,
-        value: RawBytes(value_bytes),
-        type_: header.type_,
-    }))
+    let name = String::from_utf8(name_bytes)?;
+    Ok(match (name.as_str(), header.type_) {
+        ("GNU", NT_GNU_BUILD_ID) => ElfNote::GnuBuildId(value_bytes),
+        _ => ElfNote::Unknown(ElfUnknownNote {
+            name,
+            value: RawBytes(value_bytes),
+            type_: header.type_,
+        }),
+    })
 }
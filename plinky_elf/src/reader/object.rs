@@ -1,8 +1,8 @@
 use crate::errors::LoadError;
-use crate::raw::{RawHeader, RawIdentification};
+use crate::raw::{RawHeader, RawIdentification, RawProgramHeader, RawSectionHeader, PN_XNUM};
 use crate::reader::program_header::{read_program_header, SegmentContentMapping};
 use crate::reader::sections::read_sections;
-use crate::reader::{PendingIds, PendingSectionId, ReadCursor};
+use crate::reader::{PendingIds, ReadCursor};
 use crate::{ElfABI, ElfClass, ElfEndian, ElfEnvironment, ElfMachine, ElfObject, ElfType};
 use std::collections::BTreeMap;
 use std::num::NonZeroU64;
@@ -23,11 +23,14 @@ pub(crate) fn read_object(cursor: &mut ReadCursor<'_>) -> Result<ElfObject<Pendi
     };
     let endian = match identification.endian {
         1 => ElfEndian::Little,
+        2 => ElfEndian::Big,
         other => return Err(LoadError::BadEndian(other)),
     };
     let abi = match (identification.abi, identification.abi_version) {
         (0, 0) => ElfABI::SystemV,
         (0, version) => return Err(LoadError::BadAbiVersion(ElfABI::SystemV, version)),
+        (3, 0) => ElfABI::Gnu,
+        (3, version) => return Err(LoadError::BadAbiVersion(ElfABI::Gnu, version)),
         (abi, _) => return Err(LoadError::BadAbi(abi)),
     };
 
@@ -48,30 +51,33 @@ pub(crate) fn read_object(cursor: &mut ReadCursor<'_>) -> Result<ElfObject<Pendi
     let machine = match header.machine {
         3 => ElfMachine::X86,
         62 => ElfMachine::X86_64,
+        183 => ElfMachine::Aarch64,
         other => return Err(LoadError::BadMachine(other)),
     };
 
-    let mut segment_content_map: SegmentContentMapping = BTreeMap::new();
-
-    let sections = read_sections(
-        cursor,
-        &mut segment_content_map,
-        header.section_headers_offset,
-        header.section_header_count,
-        header.section_header_size,
-        PendingSectionId(header.section_names_table_index as _),
-    )?;
-
-    let mut segments = Vec::new();
+    // Program headers are read before the sections, as they are needed to recover the content of
+    // sections with a broken offset. They are only converted into segments after the sections are
+    // read though, as segments refer to the sections they contain.
+    let mut program_headers = Vec::new();
     if header.program_headers_offset != 0 {
-        for idx in 0..header.program_header_count {
+        for idx in 0..read_program_header_count(cursor, &header)? {
             cursor.seek_to(
                 header.program_headers_offset + (header.program_header_size as u64 * idx as u64),
             )?;
-            segments.push(read_program_header(cursor, &segment_content_map)?);
+            program_headers.push(cursor.read_raw::<RawProgramHeader>()?);
         }
     }
 
+    let mut segment_content_map: SegmentContentMapping = BTreeMap::new();
+
+    let sections =
+        read_sections(cursor, &mut segment_content_map, &program_headers, &header, machine)?;
+
+    let segments = program_headers
+        .iter()
+        .map(|program_header| read_program_header(cursor, program_header, &segment_content_map))
+        .collect::<Result<Vec<_>, _>>()?;
+
     Ok(ElfObject::<PendingIds> {
         env: ElfEnvironment { class, endian, abi, machine },
         type_,
@@ -80,3 +86,944 @@ pub(crate) fn read_object(cursor: &mut ReadCursor<'_>) -> Result<ElfObject<Pendi
         segments,
     })
 }
+
+fn read_program_header_count(
+    cursor: &mut ReadCursor<'_>,
+    header: &RawHeader,
+) -> Result<u32, LoadError> {
+    if header.program_header_count != PN_XNUM {
+        return Ok(header.program_header_count as _);
+    }
+
+    // The count doesn't fit in the ELF header, and is stored in the info field of the first
+    // (null) section header instead.
+    if header.section_headers_offset == 0 {
+        return Err(LoadError::MissingExtendedProgramHeaderCount);
+    }
+    cursor.seek_to(header.section_headers_offset)?;
+    let first_section: RawSectionHeader = cursor.read_raw()?;
+    Ok(first_section.info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::WriteError;
+    use crate::ids::serial::{SectionId, SerialIds, StringId};
+    use crate::ids::StringIdGetters;
+    use crate::{
+        ElfCompression, ElfDefinedVersion, ElfDynamic, ElfDynamicDirective, ElfFunctionsArray,
+        ElfNeededVersion, ElfNote, ElfNotesTable, ElfObjectBuilder, ElfPermissions, ElfRelocation,
+        ElfRelocationType, ElfRelocationsTable, ElfSectionContent, ElfSegment, ElfSegmentContent,
+        ElfSegmentType, ElfStringTable, ElfSymbolBinding, ElfSymbolDefinition, ElfSymbolType,
+        ElfSymbolVersion, ElfUnknownSegmentContent, ElfVersionDefined, ElfVersionFlags,
+        ElfVersionNeeded, ElfVersionNeededFile, ElfVersionSymbols, ElfWriteOptions,
+    };
+    use std::collections::BTreeMap;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_without_section_headers() {
+        let mut buffer = executable(1);
+
+        // Strip the section headers from the ELF header: e_shoff, e_shnum and e_shstrndx.
+        buffer[0x28..0x30].fill(0);
+        buffer[0x3c..0x40].fill(0);
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        assert!(loaded.sections.is_empty());
+        assert_eq!(1, loaded.segments.len());
+        assert!(matches!(
+            &loaded.segments[0].content,
+            ElfSegmentContent::Unknown(ElfUnknownSegmentContent { virtual_address: 0x400000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_written_without_section_headers() {
+        let mut buffer = Vec::new();
+        executable_object(2)
+            .write_with_options(
+                &mut buffer,
+                ElfWriteOptions { omit_section_headers: true, ..ElfWriteOptions::default() },
+            )
+            .unwrap();
+        assert_eq!([0; 8], buffer[0x28..0x30]); // e_shoff
+        assert_eq!([0; 4], buffer[0x3c..0x40]); // e_shnum and e_shstrndx
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        assert!(loaded.sections.is_empty());
+        assert_eq!(2, loaded.segments.len());
+    }
+
+    #[test]
+    fn test_read_extended_program_header_count() {
+        let mut buffer = executable(3);
+
+        // Move the program header count (e_phnum) into the sh_info of the null section header.
+        buffer[0x38..0x3a].copy_from_slice(&PN_XNUM.to_le_bytes());
+        let shoff = u64::from_le_bytes(buffer[0x28..0x30].try_into().unwrap()) as usize;
+        buffer[shoff + 0x2c..shoff + 0x30].copy_from_slice(&3u32.to_le_bytes());
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        let addresses = loaded
+            .segments
+            .iter()
+            .map(|segment| match &segment.content {
+                ElfSegmentContent::Unknown(unknown) => unknown.virtual_address,
+                other => panic!("unexpected segment content: {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0x400000, 0x401000, 0x402000], addresses);
+    }
+
+    #[test]
+    fn test_read_extended_section_header_count() {
+        let mut buffer = executable(1);
+        let original = ElfObject::load(&mut Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+
+        // Move the section header count (e_shnum) into the sh_size of the null section header.
+        let shnum = u16::from_le_bytes(buffer[0x3c..0x3e].try_into().unwrap()) as u64;
+        buffer[0x3c..0x3e].fill(0);
+        let shoff = u64::from_le_bytes(buffer[0x28..0x30].try_into().unwrap()) as usize;
+        buffer[shoff + 0x20..shoff + 0x28].copy_from_slice(&shnum.to_le_bytes());
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        assert_eq!(original.sections.len(), loaded.sections.len());
+        let names = |object: &ElfObject<SerialIds>| {
+            object.sections.values().map(|s| s.name.offset()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(&original), names(&loaded));
+    }
+
+    #[test]
+    fn test_read_extended_program_header_count_without_section_headers() {
+        let mut buffer = executable(1);
+        buffer[0x28..0x30].fill(0);
+        buffer[0x38..0x3a].copy_from_slice(&PN_XNUM.to_le_bytes());
+
+        let err = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap_err();
+        assert!(matches!(err, LoadError::MissingExtendedProgramHeaderCount));
+    }
+
+    #[test]
+    fn test_write_extended_program_header_count() {
+        let mut buffer = Vec::new();
+        executable_object(PN_XNUM as u64 + 1).write(&mut buffer).unwrap();
+        assert_eq!(PN_XNUM.to_le_bytes(), buffer[0x38..0x3a]); // e_phnum
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        assert_eq!(PN_XNUM as usize + 1, loaded.segments.len());
+    }
+
+    #[test]
+    fn test_write_extended_program_header_count_without_section_headers() {
+        let err = executable_object(PN_XNUM as u64)
+            .write_with_options(
+                &mut Vec::new(),
+                ElfWriteOptions { omit_section_headers: true, ..ElfWriteOptions::default() },
+            )
+            .unwrap_err();
+        assert!(matches!(err, WriteError::TooManySegments { count: 0xffff }));
+    }
+
+    #[test]
+    fn test_write_extended_program_header_count_without_null_section() {
+        // The count can only be stored in the first section, which must then be the null one.
+        let mut object = executable_object(PN_XNUM as u64);
+        let null = *object.sections.keys().next().unwrap();
+        object.sections.remove(&null);
+
+        let err = object.write(&mut Vec::new()).unwrap_err();
+        assert!(matches!(err, WriteError::TooManySegments { count: 0xffff }));
+    }
+
+    #[test]
+    fn test_write_too_many_sections() {
+        // Indices from SHN_LORESERVE (0xff00) onwards can't be used for sections.
+        let err = object_with_sections(0xff00).write(&mut Vec::new()).unwrap_err();
+        assert!(matches!(err, WriteError::TooManySections { count: 0xff00 }));
+    }
+
+    #[test]
+    fn test_write_relocations_with_missing_symbol_table() {
+        let (mut object, relocations, text, missing) = object_with_relocations();
+        set_relocations_table(&mut object, relocations, missing, text);
+
+        let err = object.write(&mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            err,
+            WriteError::MissingSymbolTableForRelocations { symbol_table, relocations_table }
+                if symbol_table == missing && relocations_table == relocations
+        ));
+    }
+
+    #[test]
+    fn test_write_relocations_with_wrong_section_types() {
+        let (mut object, relocations, text, missing) = object_with_relocations();
+        set_relocations_table(&mut object, relocations, text, text);
+        let err = object.write(&mut Vec::new()).unwrap_err();
+        assert!(matches!(err, WriteError::WrongSectionTypeForRelocationsSymbolTable { .. }));
+
+        let symbol_table = *object
+            .sections
+            .iter()
+            .find(|(_, section)| matches!(section.content, ElfSectionContent::SymbolTable(_)))
+            .unwrap()
+            .0;
+        set_relocations_table(&mut object, relocations, symbol_table, missing);
+        let err = object.write(&mut Vec::new()).unwrap_err();
+        assert!(matches!(err, WriteError::MissingSectionForRelocations { .. }));
+
+        set_relocations_table(&mut object, relocations, symbol_table, symbol_table);
+        let err = object.write(&mut Vec::new()).unwrap_err();
+        assert!(matches!(err, WriteError::WrongSectionTypeForRelocationsTarget { .. }));
+
+        set_relocations_table(&mut object, relocations, symbol_table, text);
+        object.write(&mut Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn test_write_relocations_linked_to_null_section() {
+        let (mut object, relocations, text, _) = object_with_relocations();
+        let null = *object
+            .sections
+            .iter()
+            .find(|(_, section)| matches!(section.content, ElfSectionContent::Null))
+            .unwrap()
+            .0;
+        let null_symbol = *object
+            .sections
+            .values()
+            .find_map(|section| match &section.content {
+                ElfSectionContent::SymbolTable(table) => table.symbols.keys().next(),
+                _ => None,
+            })
+            .unwrap();
+        object.sections.get_mut(&relocations).unwrap().content =
+            ElfSectionContent::RelocationsTable(ElfRelocationsTable {
+                symbol_table: null,
+                applies_to_section: text,
+                relocations: vec![ElfRelocation {
+                    offset: 0x1000,
+                    symbol: null_symbol,
+                    relocation_type: ElfRelocationType::X86_64_IRelative,
+                    addend: Some(0x2000),
+                }],
+            });
+
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        // The relocation refers to the null symbol, and keeps its type and addend.
+        let mut rela = Vec::new();
+        rela.extend_from_slice(&0x1000u64.to_le_bytes());
+        rela.extend_from_slice(&37u64.to_le_bytes()); // R_X86_64_IRELATIVE
+        rela.extend_from_slice(&0x2000u64.to_le_bytes());
+        assert!(buffer.windows(rela.len()).any(|window| window == rela));
+    }
+
+    #[test]
+    fn test_write_relocations_linked_to_null_section_with_symbol() {
+        let (mut object, relocations, text, _) = object_with_relocations();
+        let null = *object.sections.keys().next().unwrap();
+        // Without a symbol table, only the null symbol can be referred to.
+        let foo = *object
+            .sections
+            .values()
+            .find_map(|section| match &section.content {
+                ElfSectionContent::SymbolTable(table) => table.symbols.keys().last(),
+                _ => None,
+            })
+            .unwrap();
+        object.sections.get_mut(&relocations).unwrap().content =
+            ElfSectionContent::RelocationsTable(ElfRelocationsTable {
+                symbol_table: null,
+                applies_to_section: text,
+                relocations: vec![ElfRelocation {
+                    offset: 0,
+                    symbol: foo,
+                    relocation_type: ElfRelocationType::X86_64_64,
+                    addend: Some(0),
+                }],
+            });
+
+        let err = object.write(&mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            err,
+            WriteError::MissingSymbolInRelocation { symbol_id, relocations_table, relocation_idx: 0 }
+                if symbol_id == foo && relocations_table == relocations
+        ));
+    }
+
+    #[test]
+    fn test_write_too_many_needed_versions() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::SharedObject);
+        let perms = ElfPermissions { read: true, write: false, execute: false };
+        let strings = builder.add_program_section(".dynstr", perms, []);
+        let verneed = builder.add_program_section(".gnu.version_r", perms, []);
+        let mut object = builder.build();
+
+        let version = || ElfNeededVersion {
+            name: StringId::new(strings, 0),
+            hash: 0,
+            flags: ElfVersionFlags { base: false, weak: false, info: false },
+            index: 2,
+        };
+        object.sections.get_mut(&strings).unwrap().content =
+            ElfSectionContent::StringTable(ElfStringTable::new(BTreeMap::from([(0, "".into())])));
+        object.sections.get_mut(&verneed).unwrap().content =
+            ElfSectionContent::VersionNeeded(ElfVersionNeeded {
+                string_table: strings,
+                files: vec![ElfVersionNeededFile {
+                    file: StringId::new(strings, 0),
+                    versions: (0..0x10000).map(|_| version()).collect(),
+                }],
+            });
+
+        let err = object.write(&mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            err,
+            WriteError::TooManyVersions { section, count: 0x10000 } if section == verneed
+        ));
+    }
+
+    #[test]
+    fn test_link_order_roundtrip() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let text = builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            [0xc3],
+        );
+        let metadata = builder.add_program_section(
+            ".metadata",
+            ElfPermissions { read: true, write: false, execute: false },
+            [0x42],
+        );
+        let mut object = builder.build();
+        let ElfSectionContent::Program(program) =
+            &mut object.sections.get_mut(&metadata).unwrap().content
+        else {
+            unreachable!()
+        };
+        program.link_order = Some(text);
+
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        let loaded = ElfObject::load(&mut Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+
+        let programs = loaded
+            .sections
+            .iter()
+            .filter_map(|(id, section)| match &section.content {
+                ElfSectionContent::Program(program) => Some((id, program)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let [(text, text_program), (_, metadata_program)] = programs[..] else {
+            panic!("expected two program sections, found {}", programs.len());
+        };
+        assert_eq!(None, text_program.link_order);
+        assert_eq!(Some(*text), metadata_program.link_order);
+        assert_eq!(vec![0x42], metadata_program.raw.0);
+
+        let mut rewritten = Vec::new();
+        loaded.write(&mut rewritten).unwrap();
+        assert_eq!(buffer, rewritten);
+    }
+
+    #[test]
+    fn test_compressed_sections_written_uncompressed() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let debug = builder.add_program_section(
+            ".debug_str",
+            ElfPermissions { read: false, write: false, execute: false },
+            *b"hello world",
+        );
+        let mut object = builder.build();
+        let ElfSectionContent::Program(program) =
+            &mut object.sections.get_mut(&debug).unwrap().content
+        else {
+            unreachable!()
+        };
+        program.compression = Some(ElfCompression::Zlib);
+
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        let loaded = ElfObject::load(&mut Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+
+        let program = loaded
+            .sections
+            .values()
+            .find_map(|section| match &section.content {
+                ElfSectionContent::Program(program) => Some(program),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(None, program.compression);
+        assert_eq!(b"hello world", &program.raw.0[..]);
+    }
+
+    #[test]
+    fn test_functions_array_roundtrip() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let perms = ElfPermissions { read: true, write: true, execute: false };
+        let mut arrays = Vec::new();
+        for (name, kind) in [
+            (".preinit_array", ElfFunctionsArray::PreInit),
+            (".init_array", ElfFunctionsArray::Init),
+            (".fini_array", ElfFunctionsArray::Fini),
+        ] {
+            arrays.push((builder.add_program_section(name, perms, [0; 8]), kind));
+        }
+        let mut object = builder.build();
+        for (id, kind) in &arrays {
+            let ElfSectionContent::Program(program) =
+                &mut object.sections.get_mut(id).unwrap().content
+            else {
+                unreachable!()
+            };
+            program.functions_array = Some(*kind);
+        }
+
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        let loaded = ElfObject::load(&mut Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+
+        let kinds = loaded
+            .sections
+            .values()
+            .filter_map(|section| match &section.content {
+                ElfSectionContent::Program(program) => Some(program.functions_array),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(arrays.iter().map(|(_, kind)| Some(*kind)).collect::<Vec<_>>(), kinds);
+
+        let mut rewritten = Vec::new();
+        loaded.write(&mut rewritten).unwrap();
+        assert_eq!(buffer, rewritten);
+    }
+
+    #[test]
+    fn test_absolute_symbol_roundtrip() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        builder.add_symbol(
+            "absolute",
+            ElfSymbolBinding::Global,
+            ElfSymbolType::NoType,
+            ElfSymbolDefinition::Absolute,
+            0x1234,
+            0,
+        );
+
+        let mut buffer = Vec::new();
+        builder.build().write(&mut buffer).unwrap();
+        let loaded = ElfObject::load(&mut Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+
+        let absolute = loaded
+            .sections
+            .values()
+            .filter_map(|section| match &section.content {
+                ElfSectionContent::SymbolTable(table) => Some(table.symbols.values()),
+                _ => None,
+            })
+            .flatten()
+            .filter(|symbol| matches!(symbol.definition, ElfSymbolDefinition::Absolute))
+            .collect::<Vec<_>>();
+        let [symbol] = absolute[..] else {
+            panic!("expected one absolute symbol, found {}", absolute.len());
+        };
+        assert_eq!(0x1234, symbol.value);
+        assert_eq!(ElfSymbolBinding::Global, symbol.binding);
+
+        let mut rewritten = Vec::new();
+        loaded.write(&mut rewritten).unwrap();
+        assert_eq!(buffer, rewritten);
+    }
+
+    #[test]
+    fn test_read_build_id_from_note_segment() {
+        let build_id: Vec<u8> = (0..20).collect();
+        // GNU build-id notes are 4-byte aligned: 12 bytes of header, "GNU\0" and the 20 bytes ID.
+        let mut note = Vec::new();
+        note.extend_from_slice(&4u32.to_le_bytes());
+        note.extend_from_slice(&(build_id.len() as u32).to_le_bytes());
+        note.extend_from_slice(&3u32.to_le_bytes());
+        note.extend_from_slice(b"GNU\0");
+        note.extend_from_slice(&build_id);
+
+        let mut object = executable_object(0);
+        object.segments.push(ElfSegment {
+            type_: ElfSegmentType::Note,
+            perms: ElfPermissions { read: true, write: false, execute: false },
+            content: ElfSegmentContent::Unknown(ElfUnknownSegmentContent {
+                file_offset: 0,
+                virtual_address: 0x400000,
+                file_size: note.len() as _,
+                memory_size: note.len() as _,
+            }),
+            align: 4,
+        });
+
+        // Append the note at the end of the file, which is only known after writing it once.
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        let ElfSegmentContent::Unknown(unknown) = &mut object.segments[0].content else {
+            unreachable!()
+        };
+        unknown.file_offset = buffer.len() as _;
+        buffer.clear();
+        object.write(&mut buffer).unwrap();
+        buffer.extend_from_slice(&note);
+
+        // Strip the section headers from the ELF header: e_shoff, e_shnum and e_shstrndx.
+        buffer[0x28..0x30].fill(0);
+        buffer[0x3c..0x40].fill(0);
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        assert!(loaded.sections.is_empty());
+        assert!(matches!(&loaded.segments[0].content, ElfSegmentContent::Notes(_)));
+        assert_eq!(Some(&build_id[..]), loaded.gnu_build_id());
+    }
+
+    #[test]
+    fn test_recover_note_section_beyond_end_of_file_from_segment() {
+        let build_id: Vec<u8> = (0..20).collect();
+        let (object, note) = executable_with_build_id_note(&build_id);
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        move_section_beyond_end_of_file(&mut buffer, raw_elf64::SHT_NOTE);
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        assert_eq!(Some(&build_id[..]), loaded.gnu_build_id());
+        // The segment refers to the recovered section, rather than reading the notes again.
+        let ElfSegmentContent::Sections(sections) = &loaded.segments[0].content else {
+            panic!("the note segment should contain the note section");
+        };
+        assert_eq!(vec![note], *sections);
+    }
+
+    #[test]
+    fn test_prefer_note_segment_over_stale_section_offset() {
+        let build_id: Vec<u8> = (0..20).collect();
+        let (object, _) = executable_with_build_id_note(&build_id);
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        // Point the section to the ELF header, which is inside the file but doesn't contain notes.
+        let header = raw_elf64::section_header_with_type(&buffer, raw_elf64::SHT_NOTE);
+        let offset = header + raw_elf64::SH_OFFSET;
+        buffer[offset..offset + 8].fill(0);
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        assert_eq!(Some(&build_id[..]), loaded.gnu_build_id());
+    }
+
+    #[test]
+    fn test_note_section_beyond_end_of_file_without_segment() {
+        let (mut object, _) = executable_with_build_id_note(&[0; 20]);
+        object.segments.clear();
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        move_section_beyond_end_of_file(&mut buffer, raw_elf64::SHT_NOTE);
+
+        let err = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap_err();
+        let LoadError::FailedToParseSection { inner, .. } = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert!(matches!(*inner, LoadError::SectionContentBeyondEndOfFile { .. }));
+    }
+
+    #[test]
+    fn test_note_alignment() {
+        let load_with_align = |align: u64| {
+            let (mut object, note) = executable_with_build_id_note(&[0; 20]);
+            object.segments.clear();
+            let ElfSectionContent::Note(table) =
+                &mut object.sections.get_mut(&note).unwrap().content
+            else {
+                unreachable!();
+            };
+            table.align = 8;
+            let mut buffer = Vec::new();
+            object.write(&mut buffer).unwrap();
+
+            // The writer refuses unsupported alignments, so change sh_addralign afterwards.
+            let idx = object.sections.keys().position(|id| *id == note).unwrap();
+            let shoff = u64::from_le_bytes(buffer[0x28..0x30].try_into().unwrap()) as usize;
+            let addralign = shoff + idx * 0x40 + 0x30;
+            buffer[addralign..addralign + 8].copy_from_slice(&align.to_le_bytes());
+            ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new())
+        };
+
+        assert_eq!(Some(&[0; 20][..]), load_with_align(8).unwrap().gnu_build_id());
+        let err = load_with_align(16).unwrap_err();
+        let LoadError::FailedToParseSection { inner, .. } = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert!(matches!(*inner, LoadError::UnsupportedNoteAlignment(16)));
+    }
+
+    #[test]
+    fn test_recover_dynamic_section_beyond_end_of_file_from_segment() {
+        let (object, dynamic) = executable_with_dynamic(vec![
+            ElfDynamicDirective::BindNow,
+            ElfDynamicDirective::InitArraySize { bytes: 16 },
+            ElfDynamicDirective::Null,
+        ]);
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        move_section_beyond_end_of_file(&mut buffer, raw_elf64::SHT_DYNAMIC);
+
+        let loaded = ElfObject::load(&mut Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+        let ElfSectionContent::Dynamic(recovered) = &loaded.sections[&dynamic].content else {
+            panic!("the dynamic section should be recovered from the segment");
+        };
+        assert!(matches!(
+            recovered.directives[..],
+            [
+                ElfDynamicDirective::BindNow,
+                ElfDynamicDirective::InitArraySize { bytes: 16 },
+                ElfDynamicDirective::Null,
+            ]
+        ));
+        let ElfSegmentContent::Sections(sections) = &loaded.segments[0].content else {
+            panic!("the dynamic segment should contain the dynamic section");
+        };
+        assert_eq!(vec![dynamic], *sections);
+    }
+
+    #[test]
+    fn test_big_endian_roundtrip() {
+        let mut object = executable_object(1);
+        object.env.endian = ElfEndian::Big;
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+
+        assert_eq!(2, buffer[5]); // EI_DATA
+        assert_eq!([0x00, 0x02], buffer[0x10..0x12]); // e_type
+        assert_eq!([0x00, 0x3e], buffer[0x12..0x14]); // e_machine
+        assert_eq!([0x00, 0x38], buffer[0x36..0x38]); // e_phentsize
+
+        let loaded = ElfObject::load(&mut Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+        assert_eq!(ElfEndian::Big, loaded.env.endian);
+        assert!(matches!(
+            &loaded.segments[0].content,
+            ElfSegmentContent::Unknown(ElfUnknownSegmentContent { virtual_address: 0x400000, .. })
+        ));
+
+        let mut rewritten = Vec::new();
+        loaded.write(&mut rewritten).unwrap();
+        assert_eq!(buffer, rewritten);
+    }
+
+    #[test]
+    fn test_symbol_versions_roundtrip() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::SharedObject);
+        for name in ["foo", "bar"] {
+            builder.add_symbol(
+                name,
+                ElfSymbolBinding::Global,
+                ElfSymbolType::Function,
+                ElfSymbolDefinition::Undefined,
+                0,
+                0,
+            );
+        }
+        // The content of these sections is replaced after building the object.
+        let perms = ElfPermissions { read: true, write: false, execute: false };
+        let strings = builder.add_program_section(".dynstr", perms, []);
+        let versym = builder.add_program_section(".gnu.version", perms, []);
+        let verneed = builder.add_program_section(".gnu.version_r", perms, []);
+        let verdef = builder.add_program_section(".gnu.version_d", perms, []);
+        let mut object = builder.build();
+
+        let symbol_table = *object
+            .sections
+            .iter()
+            .find(|(_, section)| matches!(section.content, ElfSectionContent::SymbolTable(_)))
+            .unwrap()
+            .0;
+        let string = |offset| StringId::new(strings, offset);
+        let flags = |weak| ElfVersionFlags { base: false, weak, info: false };
+        for (id, content) in [
+            (
+                strings,
+                ElfSectionContent::StringTable(ElfStringTable::new(BTreeMap::from([
+                    (0, "".into()),
+                    (1, "libc.so.6".into()),
+                    (11, "GLIBC_2.2.5".into()),
+                    (23, "GLIBC_2.34".into()),
+                    (34, "libfoo.so".into()),
+                    (44, "FOO_1".into()),
+                ]))),
+            ),
+            (
+                versym,
+                ElfSectionContent::VersionSymbols(ElfVersionSymbols {
+                    symbol_table,
+                    versions: vec![
+                        ElfSymbolVersion { index: 0, hidden: false },
+                        ElfSymbolVersion { index: 2, hidden: false },
+                        ElfSymbolVersion { index: 4, hidden: true },
+                    ],
+                }),
+            ),
+            (
+                verneed,
+                ElfSectionContent::VersionNeeded(ElfVersionNeeded {
+                    string_table: strings,
+                    files: vec![ElfVersionNeededFile {
+                        file: string(1),
+                        versions: vec![
+                            ElfNeededVersion {
+                                name: string(11),
+                                hash: 0x09691a75,
+                                flags: flags(false),
+                                index: 2,
+                            },
+                            ElfNeededVersion {
+                                name: string(23),
+                                hash: 0x069691b4,
+                                flags: flags(true),
+                                index: 3,
+                            },
+                        ],
+                    }],
+                }),
+            ),
+            (
+                verdef,
+                ElfSectionContent::VersionDefined(ElfVersionDefined {
+                    string_table: strings,
+                    versions: vec![
+                        ElfDefinedVersion {
+                            flags: ElfVersionFlags { base: true, weak: false, info: false },
+                            index: 1,
+                            hash: 0x0c2b9c0f,
+                            names: vec![string(34)],
+                        },
+                        ElfDefinedVersion {
+                            flags: flags(false),
+                            index: 4,
+                            hash: 0x00046d51,
+                            names: vec![string(44)],
+                        },
+                    ],
+                }),
+            ),
+        ] {
+            object.sections.get_mut(&id).unwrap().content = content;
+        }
+
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        let loaded = ElfObject::load(&mut Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+
+        let resolve = |id: &StringId| match &loaded.sections[id.section()].content {
+            ElfSectionContent::StringTable(table) => table.get(id.offset()).unwrap(),
+            _ => panic!("not a string table"),
+        };
+        let mut found = 0;
+        for section in loaded.sections.values() {
+            match &section.content {
+                ElfSectionContent::VersionSymbols(versions) => {
+                    let indexes = versions.versions.iter().map(|v| (v.index, v.hidden));
+                    assert_eq!(
+                        vec![(0, false), (2, false), (4, true)],
+                        indexes.collect::<Vec<_>>()
+                    );
+                }
+                ElfSectionContent::VersionNeeded(needed) => {
+                    let [file] = &needed.files[..] else { panic!("expected one needed file") };
+                    assert_eq!("libc.so.6", resolve(&file.file));
+                    let versions = file
+                        .versions
+                        .iter()
+                        .map(|v| (resolve(&v.name), v.hash, v.flags.weak, v.index))
+                        .collect::<Vec<_>>();
+                    assert_eq!(
+                        vec![
+                            ("GLIBC_2.2.5", 0x09691a75, false, 2),
+                            ("GLIBC_2.34", 0x069691b4, true, 3)
+                        ],
+                        versions
+                    );
+                }
+                ElfSectionContent::VersionDefined(defined) => {
+                    let versions = defined
+                        .versions
+                        .iter()
+                        .map(|v| (v.index, v.flags.base, v.names.iter().map(resolve).collect()))
+                        .collect::<Vec<(_, _, Vec<_>)>>();
+                    assert_eq!(
+                        vec![(1, true, vec!["libfoo.so"]), (4, false, vec!["FOO_1"])],
+                        versions
+                    );
+                }
+                _ => continue,
+            }
+            found += 1;
+        }
+        assert_eq!(3, found);
+
+        let mut rewritten = Vec::new();
+        loaded.write(&mut rewritten).unwrap();
+        assert_eq!(buffer, rewritten);
+    }
+
+    /// Object with a relocations table (whose content is set by the test), the section it should
+    /// apply to, and the ID of a section that doesn't exist anymore.
+    fn object_with_relocations() -> (ElfObject<SerialIds>, SectionId, SectionId, SectionId) {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let perms = ElfPermissions { read: true, write: false, execute: true };
+        let text = builder.add_program_section(".text", perms, [0xc3]);
+        let relocations = builder.add_program_section(".rela.text", perms, []);
+        let missing = builder.add_program_section(".missing", perms, []);
+        builder.add_symbol(
+            "foo",
+            ElfSymbolBinding::Global,
+            ElfSymbolType::Function,
+            ElfSymbolDefinition::Section(text),
+            0,
+            1,
+        );
+        let mut object = builder.build();
+        object.sections.remove(&missing);
+        (object, relocations, text, missing)
+    }
+
+    fn set_relocations_table(
+        object: &mut ElfObject<SerialIds>,
+        id: SectionId,
+        symbol_table: SectionId,
+        applies_to_section: SectionId,
+    ) {
+        object.sections.get_mut(&id).unwrap().content =
+            ElfSectionContent::RelocationsTable(ElfRelocationsTable {
+                symbol_table,
+                applies_to_section,
+                relocations: Vec::new(),
+            });
+    }
+
+    fn object_with_sections(count: usize) -> ElfObject<SerialIds> {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        // The builder already adds the null section and the section names table.
+        let perms = ElfPermissions { read: true, write: false, execute: false };
+        for _ in 2..count {
+            builder.add_program_section(".rodata", perms, []);
+        }
+        builder.build()
+    }
+
+    /// Executable with a `.note.gnu.build-id` section at 0x400000, contained in a note segment.
+    fn executable_with_build_id_note(build_id: &[u8]) -> (ElfObject<SerialIds>, SectionId) {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let note = builder.add_program_section(
+            ".note.gnu.build-id",
+            ElfPermissions { read: true, write: false, execute: false },
+            [],
+        );
+        let mut object = builder.build();
+        let section = object.sections.get_mut(&note).unwrap();
+        section.memory_address = 0x400000;
+        section.content = ElfSectionContent::Note(ElfNotesTable {
+            align: 4,
+            notes: vec![ElfNote::GnuBuildId(build_id.to_vec())],
+        });
+        object.segments.push(ElfSegment {
+            type_: ElfSegmentType::Note,
+            perms: ElfPermissions { read: true, write: false, execute: false },
+            content: ElfSegmentContent::Sections(vec![note]),
+            align: 4,
+        });
+        (object, note)
+    }
+
+    /// Executable with a `.dynamic` section at 0x600000, contained in a dynamic segment.
+    fn executable_with_dynamic(
+        directives: Vec<ElfDynamicDirective>,
+    ) -> (ElfObject<SerialIds>, SectionId) {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let perms = ElfPermissions { read: true, write: false, execute: false };
+        let dynstr = builder.add_program_section(".dynstr", perms, []);
+        let dynamic = builder.add_program_section(".dynamic", perms, []);
+        let mut object = builder.build();
+        object.sections.get_mut(&dynstr).unwrap().content =
+            ElfSectionContent::StringTable(ElfStringTable::new(BTreeMap::from([(0, "".into())])));
+        let section = object.sections.get_mut(&dynamic).unwrap();
+        section.memory_address = 0x600000;
+        section.content =
+            ElfSectionContent::Dynamic(ElfDynamic { string_table: dynstr, directives });
+        object.segments.push(ElfSegment {
+            type_: ElfSegmentType::Dynamic,
+            perms,
+            content: ElfSegmentContent::Sections(vec![dynamic]),
+            align: 8,
+        });
+        (object, dynamic)
+    }
+
+    /// Point the sh_offset of the (only) section of type `sh_type` past the end of the file, like
+    /// some post-processing tools do.
+    fn move_section_beyond_end_of_file(buffer: &mut [u8], sh_type: u32) {
+        let header = raw_elf64::section_header_with_type(buffer, sh_type);
+        let offset = header + raw_elf64::SH_OFFSET;
+        assert!(raw_elf64::read_u64(buffer, offset) < buffer.len() as u64);
+        buffer[offset..offset + 8].copy_from_slice(&0x10_0000u64.to_le_bytes());
+    }
+
+    /// Field offsets of little-endian ELF64 headers, to corrupt files after writing them.
+    mod raw_elf64 {
+        pub(super) const SHT_DYNAMIC: u32 = 6;
+        pub(super) const SHT_NOTE: u32 = 7;
+
+        const E_SHOFF: usize = 0x28;
+        const E_SHNUM: usize = 0x3c;
+        const SECTION_HEADER_SIZE: usize = 0x40;
+        const SH_TYPE: usize = 0x04;
+        pub(super) const SH_OFFSET: usize = 0x18;
+
+        pub(super) fn read_u64(buffer: &[u8], at: usize) -> u64 {
+            u64::from_le_bytes(buffer[at..at + 8].try_into().unwrap())
+        }
+
+        /// Offset of the header of the first section with the given `sh_type`.
+        pub(super) fn section_header_with_type(buffer: &[u8], sh_type: u32) -> usize {
+            let shoff = read_u64(buffer, E_SHOFF) as usize;
+            let shnum = u16::from_le_bytes(buffer[E_SHNUM..E_SHNUM + 2].try_into().unwrap());
+            (0..shnum as usize)
+                .map(|idx| shoff + idx * SECTION_HEADER_SIZE)
+                .find(|header| {
+                    buffer[header + SH_TYPE..header + SH_TYPE + 4] == sh_type.to_le_bytes()
+                })
+                .unwrap_or_else(|| panic!("no section with type {sh_type}"))
+        }
+    }
+
+    fn executable(segments: u64) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        executable_object(segments).write(&mut buffer).unwrap();
+        buffer
+    }
+
+    fn executable_object(segments: u64) -> ElfObject<SerialIds> {
+        let builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let mut object = builder.build();
+        for idx in 0..segments {
+            object.segments.push(ElfSegment {
+                type_: ElfSegmentType::Load,
+                perms: ElfPermissions { read: true, write: false, execute: true },
+                content: ElfSegmentContent::Unknown(ElfUnknownSegmentContent {
+                    file_offset: 0,
+                    virtual_address: 0x400000 + idx * 0x1000,
+                    file_size: 0x40,
+                    memory_size: 0x40,
+                }),
+                align: 0x1000,
+            });
+        }
+        object
+    }
+}
@@ -1,54 +1,78 @@
 use crate::errors::LoadError;
 use crate::raw::RawProgramHeader;
+use crate::reader::notes::read_notes;
 use crate::reader::{PendingIds, PendingSectionId, ReadCursor};
 use crate::{
-    ElfPermissions, ElfSegment, ElfSegmentContent, ElfSegmentType, ElfUnknownSegmentContent,
+    ElfNotesSegmentContent, ElfNotesTable, ElfPermissions, ElfSegment, ElfSegmentContent,
+    ElfSegmentType, ElfUnknownSegmentContent,
 };
 use std::collections::BTreeMap;
 
 pub(super) type SegmentContentMapping = BTreeMap<(u64, u64), PendingSectionId>;
 
+pub(super) const PT_DYNAMIC: u32 = 2;
+pub(super) const PT_NOTE: u32 = 4;
+
 pub(super) fn read_program_header(
     cursor: &mut ReadCursor<'_>,
+    header: &RawProgramHeader,
     content_map: &SegmentContentMapping,
 ) -> Result<ElfSegment<PendingIds>, LoadError> {
-    let header: RawProgramHeader = cursor.read_raw()?;
+    let type_ = match header.type_ {
+        0 => ElfSegmentType::Null,
+        1 => ElfSegmentType::Load,
+        PT_DYNAMIC => ElfSegmentType::Dynamic,
+        3 => ElfSegmentType::Interpreter,
+        PT_NOTE => ElfSegmentType::Note,
+        6 => ElfSegmentType::ProgramHeaderTable,
+        7 => ElfSegmentType::Tls,
+        0x6474e551 => ElfSegmentType::GnuStack,
+        0x6474e552 => ElfSegmentType::GnuRelRO,
+        other => ElfSegmentType::Unknown(other),
+    };
+
+    let content = if header.file_size == 0
+        && header.file_offset == 0
+        && header.virtual_address == 0
+        && header.memory_size == 0
+    {
+        ElfSegmentContent::Empty
+    } else if let Some(id) = content_map.get(&(header.file_offset, header.file_size)) {
+        ElfSegmentContent::Sections(vec![*id])
+    } else {
+        let location = ElfUnknownSegmentContent {
+            file_offset: header.file_offset,
+            virtual_address: header.virtual_address,
+            file_size: header.file_size,
+            memory_size: header.memory_size,
+        };
+        if let ElfSegmentType::Note = type_ {
+            ElfSegmentContent::Notes(ElfNotesSegmentContent {
+                location,
+                notes: read_segment_notes(cursor, header)?,
+            })
+        } else {
+            ElfSegmentContent::Unknown(location)
+        }
+    };
 
     Ok(ElfSegment {
-        type_: match header.type_ {
-            0 => ElfSegmentType::Null,
-            1 => ElfSegmentType::Load,
-            2 => ElfSegmentType::Dynamic,
-            3 => ElfSegmentType::Interpreter,
-            4 => ElfSegmentType::Note,
-            6 => ElfSegmentType::ProgramHeaderTable,
-            0x6474e551 => ElfSegmentType::GnuStack,
-            0x6474e552 => ElfSegmentType::GnuRelRO,
-            other => ElfSegmentType::Unknown(other),
-        },
+        type_,
         perms: ElfPermissions {
             read: header.flags.read,
             write: header.flags.write,
             execute: header.flags.execute,
         },
-
-        content: if header.file_size == 0
-            && header.file_offset == 0
-            && header.virtual_address == 0
-            && header.memory_size == 0
-        {
-            ElfSegmentContent::Empty
-        } else if let Some(id) = content_map.get(&(header.file_offset, header.file_size)) {
-            ElfSegmentContent::Sections(vec![*id])
-        } else {
-            ElfSegmentContent::Unknown(ElfUnknownSegmentContent {
-                file_offset: header.file_offset,
-                virtual_address: header.virtual_address,
-                file_size: header.file_size,
-                memory_size: header.memory_size,
-            })
-        },
-
+        content,
         align: header.align,
     })
 }
+
+fn read_segment_notes(
+    cursor: &mut ReadCursor<'_>,
+    header: &RawProgramHeader,
+) -> Result<ElfNotesTable, LoadError> {
+    cursor.seek_to(header.file_offset)?;
+    let raw = cursor.read_vec(header.file_size)?;
+    read_notes(cursor, &raw, header.align)
+}
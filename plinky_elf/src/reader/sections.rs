@@ -1,31 +1,45 @@
 use super::{PendingStringId, PendingSymbolId};
 use crate::errors::LoadError;
-use crate::raw::{RawGroupFlags, RawHashHeader, RawRel, RawRela, RawSectionHeader, RawSymbol};
+use crate::raw::{
+    RawCompressionHeader, RawGnuHashHeader, RawGroupFlags, RawHashHeader, RawHeader,
+    RawProgramHeader, RawRel, RawRela, RawSectionHeader, RawSymbol, RawVersionDefinition,
+    RawVersionDefinitionAux, RawVersionNeeded, RawVersionNeededAux,
+};
 use crate::reader::notes::read_notes;
-use crate::reader::program_header::SegmentContentMapping;
+use crate::reader::program_header::{SegmentContentMapping, PT_DYNAMIC, PT_NOTE};
 use crate::reader::{PendingIds, PendingSectionId, ReadCursor};
 use crate::{
-    ElfClass, ElfDeduplication, ElfDynamic, ElfDynamicDirective, ElfDynamicFlags1, ElfGroup,
-    ElfHash, ElfPLTRelocationsMode, ElfPermissions, ElfProgramSection, ElfRelocation,
-    ElfRelocationType, ElfRelocationsTable, ElfSection, ElfSectionContent, ElfStringTable,
-    ElfSymbol, ElfSymbolBinding, ElfSymbolDefinition, ElfSymbolTable, ElfSymbolType,
-    ElfSymbolVisibility, ElfUninitializedSection, ElfUnknownSection, RawBytes,
+    ElfClass, ElfCompression, ElfDeduplication, ElfDefinedVersion, ElfDynamic, ElfDynamicDirective,
+    ElfDynamicFlags, ElfDynamicFlags1, ElfFunctionsArray, ElfGnuHash, ElfGroup, ElfHash,
+    ElfMachine, ElfNeededVersion, ElfPLTRelocationsMode, ElfPermissions, ElfProgramSection,
+    ElfRelocation, ElfRelocationType, ElfRelocationsTable, ElfSection, ElfSectionContent,
+    ElfStringTable, ElfSymbol, ElfSymbolBinding, ElfSymbolDefinition, ElfSymbolTable,
+    ElfSymbolType, ElfSymbolVersion, ElfSymbolVisibility, ElfUninitializedSection,
+    ElfUnknownSection, ElfVersionDefined, ElfVersionNeeded, ElfVersionNeededFile,
+    ElfVersionSymbols, RawBytes,
 };
 use plinky_utils::bitfields::Bitfield;
+use plinky_utils::zlib;
 use std::collections::BTreeMap;
 use std::num::NonZeroU64;
 
 pub(super) fn read_sections(
     cursor: &mut ReadCursor<'_>,
     segment_content_map: &mut SegmentContentMapping,
-    offset: u64,
-    count: u16,
-    size: u16,
-    section_names_table: PendingSectionId,
+    program_headers: &[RawProgramHeader],
+    header: &RawHeader,
+    machine: ElfMachine,
 ) -> Result<BTreeMap<PendingSectionId, ElfSection<PendingIds>>, LoadError> {
+    let offset = header.section_headers_offset;
+    let size = header.section_header_size;
+    let section_names_table = PendingSectionId(header.section_names_table_index as _);
+
+    // Objects containing only program headers (like some stripped executables) have no section
+    // header table at all, and no section names table either.
     if offset == 0 {
         return Ok(BTreeMap::new());
     }
+    let count = read_section_header_count(cursor, header)?;
 
     let mut sections = BTreeMap::new();
     for idx in 0..count {
@@ -35,8 +49,10 @@ pub(super) fn read_sections(
             read_section(
                 cursor,
                 segment_content_map,
+                program_headers,
                 section_names_table,
                 PendingSectionId(idx as _),
+                machine,
             )
             .map_err(|inner| LoadError::FailedToParseSection { idx, inner: Box::new(inner) })?,
         );
@@ -45,11 +61,29 @@ pub(super) fn read_sections(
     Ok(sections)
 }
 
+fn read_section_header_count(
+    cursor: &mut ReadCursor<'_>,
+    header: &RawHeader,
+) -> Result<u32, LoadError> {
+    if header.section_header_count != 0 {
+        return Ok(header.section_header_count as _);
+    }
+
+    // A zero count with a section header table means the count doesn't fit in the ELF header, and
+    // is stored in the size field of the first (null) section header instead.
+    cursor.seek_to(header.section_headers_offset)?;
+    let first_section: RawSectionHeader = cursor.read_raw()?;
+    u32::try_from(first_section.size)
+        .map_err(|_| LoadError::BadExtendedSectionHeaderCount(first_section.size))
+}
+
 fn read_section(
     cursor: &mut ReadCursor<'_>,
     segment_content_map: &mut SegmentContentMapping,
+    program_headers: &[RawProgramHeader],
     section_names_table: PendingSectionId,
     current_section: PendingSectionId,
+    machine: ElfMachine,
 ) -> Result<ElfSection<PendingIds>, LoadError> {
     let header: RawSectionHeader = cursor.read_raw().map_err(|e| {
         LoadError::FailedToParseSectionHeader { idx: current_section.0, inner: Box::new(e) }
@@ -57,7 +91,7 @@ fn read_section(
 
     let ty = match header.type_ {
         0 => SectionType::Null,
-        1 => SectionType::Program,
+        1 => SectionType::Program(None),
         2 => SectionType::SymbolTable { dynsym: false },
         3 => SectionType::StringTable,
         4 => SectionType::Relocations { rela: true },
@@ -67,7 +101,14 @@ fn read_section(
         8 => SectionType::Uninit,
         9 => SectionType::Relocations { rela: false },
         11 => SectionType::SymbolTable { dynsym: true },
+        14 => SectionType::Program(Some(ElfFunctionsArray::Init)),
+        15 => SectionType::Program(Some(ElfFunctionsArray::Fini)),
+        16 => SectionType::Program(Some(ElfFunctionsArray::PreInit)),
         17 => SectionType::Group,
+        0x6ffffff6 => SectionType::GnuHash,
+        0x6ffffffd => SectionType::VersionDefined,
+        0x6ffffffe => SectionType::VersionNeeded,
+        0x6fffffff => SectionType::VersionSymbols,
         other => SectionType::Unknown(other),
     };
 
@@ -112,17 +153,40 @@ fn read_section(
         None
     };
 
+    // Only sections with program data can be compressed, as the other types are parsed.
+    if header.flags.compressed && !matches!(ty, SectionType::Program(_)) {
+        return Err(LoadError::CompressedFlagOnUnsupportedSection {
+            section_idx: current_section.0,
+        });
+    }
+
+    // Where the content was actually read from, which might differ from sh_offset if the section
+    // was recovered from a segment.
+    let mut offset = header.offset;
     let content = match ty {
         SectionType::Null => ElfSectionContent::Null,
-        SectionType::Program => ElfSectionContent::Program(ElfProgramSection {
-            perms: ElfPermissions {
-                read: header.flags.alloc,
-                write: header.flags.write,
-                execute: header.flags.exec,
-            },
-            deduplication: deduplication.take().unwrap_or(ElfDeduplication::Disabled),
-            raw: RawBytes(read_section_raw_content(&header, cursor)?),
-        }),
+        SectionType::Program(functions_array) => {
+            let raw = read_section_raw_content(&header, cursor)?;
+            let (compression, align, raw) = if header.flags.compressed {
+                read_compressed(cursor, &raw, current_section)?
+            } else {
+                (None, header.addr_align, raw)
+            };
+            ElfSectionContent::Program(ElfProgramSection {
+                perms: ElfPermissions {
+                    read: header.flags.alloc,
+                    write: header.flags.write,
+                    execute: header.flags.exec,
+                },
+                deduplication: deduplication.take().unwrap_or(ElfDeduplication::Disabled),
+                tls: header.flags.tls,
+                align: align.max(1),
+                link_order: header.flags.link_order.then_some(PendingSectionId(header.link)),
+                functions_array,
+                compression,
+                raw: RawBytes(raw),
+            })
+        }
         SectionType::SymbolTable { dynsym } => {
             let raw = read_section_raw_content(&header, cursor)?;
             read_symbol_table(cursor, &raw, PendingSectionId(header.link), current_section, dynsym)?
@@ -136,11 +200,14 @@ fn read_section(
                 PendingSectionId(header.link),
                 PendingSectionId(header.info),
                 rela,
+                machine,
             )?
         }
         SectionType::Note => {
-            let raw = read_section_raw_content(&header, cursor)?;
-            ElfSectionContent::Note(read_notes(cursor, &raw)?)
+            let raw;
+            (offset, raw) =
+                read_section_raw_content_or_segment(&header, cursor, program_headers, PT_NOTE)?;
+            ElfSectionContent::Note(read_notes(cursor, &raw, header.addr_align)?)
         }
         SectionType::Uninit => ElfSectionContent::Uninitialized(ElfUninitializedSection {
             perms: ElfPermissions {
@@ -148,6 +215,8 @@ fn read_section(
                 write: header.flags.write,
                 execute: header.flags.exec,
             },
+            tls: header.flags.tls,
+            align: header.addr_align.max(1),
             len: header.size,
         }),
         SectionType::Group => {
@@ -158,10 +227,28 @@ fn read_section(
             let raw = read_section_raw_content(&header, cursor)?;
             ElfSectionContent::Hash(read_hash(&header, &raw, cursor)?)
         }
-        SectionType::Dynamic => {
+        SectionType::GnuHash => {
             let raw = read_section_raw_content(&header, cursor)?;
+            ElfSectionContent::GnuHash(read_gnu_hash(&header, &raw, cursor)?)
+        }
+        SectionType::Dynamic => {
+            let raw;
+            (offset, raw) =
+                read_section_raw_content_or_segment(&header, cursor, program_headers, PT_DYNAMIC)?;
             ElfSectionContent::Dynamic(read_dynamic(&header, &raw, cursor)?)
         }
+        SectionType::VersionSymbols => {
+            let raw = read_section_raw_content(&header, cursor)?;
+            ElfSectionContent::VersionSymbols(read_version_symbols(&header, &raw, cursor)?)
+        }
+        SectionType::VersionNeeded => {
+            let raw = read_section_raw_content(&header, cursor)?;
+            ElfSectionContent::VersionNeeded(read_version_needed(&header, &raw, cursor)?)
+        }
+        SectionType::VersionDefined => {
+            let raw = read_section_raw_content(&header, cursor)?;
+            ElfSectionContent::VersionDefined(read_version_defined(&header, &raw, cursor)?)
+        }
         SectionType::Unknown(other) => ElfSectionContent::Unknown(ElfUnknownSection {
             id: other,
             raw: RawBytes(read_section_raw_content(&header, cursor)?),
@@ -172,7 +259,7 @@ fn read_section(
         return Err(LoadError::MergeFlagOnUnsupportedSection { section_idx: current_section.0 });
     }
 
-    segment_content_map.insert((header.offset, header.size), current_section);
+    segment_content_map.insert((offset, header.size), current_section);
 
     Ok(ElfSection {
         name: PendingStringId(section_names_table, header.name_offset),
@@ -190,9 +277,50 @@ fn read_section_raw_content(
     cursor.read_vec(header.size)
 }
 
+/// Notes and the dynamic section are also reachable through the `PT_NOTE` and `PT_DYNAMIC`
+/// segments, which is what the dynamic loader looks at. Some stripped or post-processed binaries
+/// have a stale `sh_offset` (possibly pointing past the end of the file): when a segment covers the
+/// section's address its content is read from there, falling back to `sh_offset` otherwise. The
+/// offset the content was found at is returned alongside it.
+fn read_section_raw_content_or_segment(
+    header: &RawSectionHeader,
+    cursor: &mut ReadCursor<'_>,
+    program_headers: &[RawProgramHeader],
+    segment_type: u32,
+) -> Result<(u64, Vec<u8>), LoadError> {
+    let file_len = cursor.file_len()?;
+    let fits = |offset: u64| offset.checked_add(header.size).is_some_and(|end| end <= file_len);
+
+    let start = header.memory_address;
+    let end = start.checked_add(header.size);
+    let from_segment = program_headers
+        .iter()
+        .filter(|segment| segment.type_ == segment_type)
+        .filter(|segment| {
+            let segment_end = segment.virtual_address.checked_add(segment.file_size);
+            start >= segment.virtual_address && end.is_some() && end <= segment_end
+        })
+        .filter_map(|segment| segment.file_offset.checked_add(start - segment.virtual_address))
+        .find(|offset| fits(*offset));
+
+    let offset = match from_segment {
+        Some(offset) => offset,
+        None if fits(header.offset) => header.offset,
+        None => {
+            return Err(LoadError::SectionContentBeyondEndOfFile {
+                offset: header.offset,
+                size: header.size,
+            })
+        }
+    };
+
+    cursor.seek_to(offset)?;
+    Ok((offset, cursor.read_vec(header.size)?))
+}
+
 enum SectionType {
     Null,
-    Program,
+    Program(Option<ElfFunctionsArray>),
     SymbolTable { dynsym: bool },
     StringTable,
     Relocations { rela: bool },
@@ -200,10 +328,45 @@ enum SectionType {
     Uninit,
     Group,
     Hash,
+    GnuHash,
     Dynamic,
+    VersionSymbols,
+    VersionNeeded,
+    VersionDefined,
     Unknown(u32),
 }
 
+/// Decompress a section with the `SHF_COMPRESSED` flag, returning the compression used and the
+/// alignment of the uncompressed content alongside it.
+fn read_compressed(
+    cursor: &mut ReadCursor<'_>,
+    raw_content: &[u8],
+    current_section: PendingSectionId,
+) -> Result<(Option<ElfCompression>, u64, Vec<u8>), LoadError> {
+    let mut inner = std::io::Cursor::new(raw_content);
+    let mut cursor = cursor.duplicate(&mut inner);
+    let section_idx = current_section.0;
+
+    let header: RawCompressionHeader = cursor.read_raw()?;
+    let compression = match header.type_ {
+        1 => ElfCompression::Zlib,
+        other => return Err(LoadError::UnsupportedCompression { section_idx, type_: other }),
+    };
+
+    let compressed = &raw_content[cursor.current_position()? as usize..];
+    let decompressed = zlib::decompress(compressed, header.size as usize)
+        .map_err(|inner| LoadError::DecompressionFailed { section_idx, inner })?;
+    if decompressed.len() as u64 != header.size {
+        return Err(LoadError::DecompressedSizeMismatch {
+            section_idx,
+            expected: header.size,
+            actual: decompressed.len() as u64,
+        });
+    }
+
+    Ok((Some(compression), header.addr_align, decompressed))
+}
+
 fn read_string_table(raw_content: &[u8]) -> Result<ElfSectionContent<PendingIds>, LoadError> {
     let mut strings = BTreeMap::new();
     let mut offset: usize = 0;
@@ -263,6 +426,8 @@ fn read_symbol(
             2 => ElfSymbolType::Function,
             3 => ElfSymbolType::Section,
             4 => ElfSymbolType::File,
+            6 => ElfSymbolType::Tls,
+            10 => ElfSymbolType::GnuIfunc,
             other => ElfSymbolType::Unknown(other),
         },
         visibility: match symbol.other {
@@ -291,13 +456,14 @@ fn read_relocations_table(
     symbol_table: PendingSectionId,
     applies_to_section: PendingSectionId,
     rela: bool,
+    machine: ElfMachine,
 ) -> Result<ElfSectionContent<PendingIds>, LoadError> {
     let mut inner = std::io::Cursor::new(raw_content);
     let mut cursor = cursor.duplicate(&mut inner);
 
     let mut relocations = Vec::new();
     while cursor.current_position()? != raw_content.len() as u64 {
-        relocations.push(read_relocation(&mut cursor, symbol_table, rela)?);
+        relocations.push(read_relocation(&mut cursor, symbol_table, rela, machine)?);
     }
 
     Ok(ElfSectionContent::RelocationsTable(ElfRelocationsTable {
@@ -311,6 +477,7 @@ fn read_relocation(
     cursor: &mut ReadCursor<'_>,
     symbol_table: PendingSectionId,
     rela: bool,
+    machine: ElfMachine,
 ) -> Result<ElfRelocation<PendingIds>, LoadError> {
     let (offset, info, addend) = if rela {
         let raw: RawRela = cursor.read_raw()?;
@@ -319,77 +486,13 @@ fn read_relocation(
         let raw: RawRel = cursor.read_raw()?;
         (raw.offset, raw.info, None)
     };
-    let (symbol, relocation_type) = match cursor.class {
-        ElfClass::Elf32 => (
-            (info >> 8) as u32,
-            match info & 0xF {
-                0 => ElfRelocationType::X86_None,
-                1 => ElfRelocationType::X86_32,
-                2 => ElfRelocationType::X86_PC32,
-                3 => ElfRelocationType::X86_GOT32,
-                4 => ElfRelocationType::X86_PLT32,
-                5 => ElfRelocationType::X86_COPY,
-                6 => ElfRelocationType::X86_GLOB_DAT,
-                7 => ElfRelocationType::X86_JMP_Slot,
-                8 => ElfRelocationType::X86_Relative,
-                9 => ElfRelocationType::X86_GOTOff,
-                10 => ElfRelocationType::X86_GOTPC,
-                11 => ElfRelocationType::X86_GOT32X,
-                other => ElfRelocationType::Unknown(other as _),
-            },
-        ),
-        ElfClass::Elf64 => (
-            (info >> 32) as u32,
-            match info & 0xFFFF_FFFF {
-                0 => ElfRelocationType::X86_64_None,
-                1 => ElfRelocationType::X86_64_64,
-                2 => ElfRelocationType::X86_64_PC32,
-                3 => ElfRelocationType::X86_64_GOT32,
-                4 => ElfRelocationType::X86_64_PLT32,
-                5 => ElfRelocationType::X86_64_Copy,
-                6 => ElfRelocationType::X86_64_GlobDat,
-                7 => ElfRelocationType::X86_64_JumpSlot,
-                8 => ElfRelocationType::X86_64_Relative,
-                9 => ElfRelocationType::X86_64_GOTPCRel,
-                10 => ElfRelocationType::X86_64_32,
-                11 => ElfRelocationType::X86_64_32S,
-                12 => ElfRelocationType::X86_64_16,
-                13 => ElfRelocationType::X86_64_PC16,
-                14 => ElfRelocationType::X86_64_8,
-                15 => ElfRelocationType::X86_64_PC8,
-                16 => ElfRelocationType::X86_64_DTPMod64,
-                17 => ElfRelocationType::X86_64_DTPOff64,
-                18 => ElfRelocationType::X86_64_TPOff64,
-                19 => ElfRelocationType::X86_64_TLSGD,
-                20 => ElfRelocationType::X86_64_TLSLD,
-                21 => ElfRelocationType::X86_64_DTPOff32,
-                22 => ElfRelocationType::X86_64_GOTTPOff,
-                23 => ElfRelocationType::X86_64_TPOff32,
-                24 => ElfRelocationType::X86_64_PC64,
-                25 => ElfRelocationType::X86_64_GOTOff64,
-                26 => ElfRelocationType::X86_64_GOTPC32,
-                32 => ElfRelocationType::X86_64_Size32,
-                33 => ElfRelocationType::X86_64_Size64,
-                34 => ElfRelocationType::X86_64_GOTPC32_TLSDesc,
-                35 => ElfRelocationType::X86_64_TLSDescCall,
-                36 => ElfRelocationType::X86_64_TLSDesc,
-                37 => ElfRelocationType::X86_64_IRelative,
-                38 => ElfRelocationType::X86_64_IRelative64,
-                41 => ElfRelocationType::X86_64_GOTPCRelX,
-                42 => ElfRelocationType::X86_64_Rex_GOTPCRelX,
-                43 => ElfRelocationType::X86_64_Code_4_GOTPCRelX,
-                44 => ElfRelocationType::X86_64_Code_4_GOTPCOff,
-                45 => ElfRelocationType::X86_64_Code_4_GOTPC32_TLSDesc,
-                46 => ElfRelocationType::X86_64_Code_5_GOTPCRelX,
-                47 => ElfRelocationType::X86_64_Code_5_GOTPCOff,
-                48 => ElfRelocationType::X86_64_Code_5_GOTPC32_TLSDesc,
-                49 => ElfRelocationType::X86_64_Code_6_GOTPCRelX,
-                50 => ElfRelocationType::X86_64_Code_6_GOTPCOff,
-                51 => ElfRelocationType::X86_64_Code_6_GOTPC32_TLSDesc,
-                other => ElfRelocationType::Unknown(other as _),
-            },
-        ),
+    // The class only determines how the info field is split: the meaning of the relocation type
+    // depends on the machine, as different machines reuse the same numbers.
+    let (symbol, raw_type) = match cursor.class {
+        ElfClass::Elf32 => ((info >> 8) as u32, (info & 0xFF) as u32),
+        ElfClass::Elf64 => ((info >> 32) as u32, (info & 0xFFFF_FFFF) as u32),
     };
+    let relocation_type = ElfRelocationType::from_raw(machine, raw_type);
 
     Ok(ElfRelocation {
         offset,
@@ -443,6 +546,186 @@ fn read_hash(
     Ok(hash)
 }
 
+fn read_gnu_hash(
+    header: &RawSectionHeader,
+    raw_content: &[u8],
+    cursor: &mut ReadCursor,
+) -> Result<ElfGnuHash<PendingIds>, LoadError> {
+    let mut inner = std::io::Cursor::new(raw_content);
+    let mut cursor = cursor.duplicate(&mut inner);
+
+    let hash_header: RawGnuHashHeader = cursor.read_raw()?;
+    let mut hash = ElfGnuHash {
+        symbol_table: PendingSectionId(header.link),
+        symbol_offset: hash_header.symbol_offset,
+        bloom_shift: hash_header.bloom_shift,
+        bloom: Vec::with_capacity(hash_header.bloom_count as _),
+        buckets: Vec::with_capacity(hash_header.bucket_count as _),
+        chain: Vec::new(),
+    };
+    for _ in 0..hash_header.bloom_count {
+        hash.bloom.push(match cursor.class {
+            ElfClass::Elf32 => cursor.read_raw::<u32>()? as _,
+            ElfClass::Elf64 => cursor.read_raw()?,
+        });
+    }
+    for _ in 0..hash_header.bucket_count {
+        hash.buckets.push(cursor.read_raw()?);
+    }
+
+    // The length of the chain is not stored anywhere: it ends with the last entry (the one with
+    // the lowest bit set) of the chain starting from the highest bucket.
+    let Some(&last_bucket) = hash.buckets.iter().max() else { return Ok(hash) };
+    if last_bucket == 0 {
+        return Ok(hash);
+    }
+    if last_bucket < hash.symbol_offset {
+        return Err(LoadError::GnuHashBucketBeforeSymbolOffset {
+            bucket: last_bucket,
+            symbol_offset: hash.symbol_offset,
+        });
+    }
+    for _ in hash.symbol_offset..last_bucket {
+        hash.chain.push(cursor.read_raw()?);
+    }
+    loop {
+        let entry: u32 = cursor.read_raw()?;
+        hash.chain.push(entry);
+        if entry & 1 == 1 {
+            break;
+        }
+    }
+    Ok(hash)
+}
+
+fn read_version_symbols(
+    header: &RawSectionHeader,
+    raw_content: &[u8],
+    cursor: &mut ReadCursor,
+) -> Result<ElfVersionSymbols<PendingIds>, LoadError> {
+    let mut inner = std::io::Cursor::new(raw_content);
+    let mut cursor = cursor.duplicate(&mut inner);
+
+    let mut versions = Vec::with_capacity(raw_content.len() / 2);
+    while cursor.current_position()? < raw_content.len() as u64 {
+        let raw: u16 = cursor.read_raw()?;
+        versions.push(ElfSymbolVersion { index: raw & 0x7fff, hidden: raw & 0x8000 != 0 });
+    }
+
+    Ok(ElfVersionSymbols { symbol_table: PendingSectionId(header.link), versions })
+}
+
+fn read_version_needed(
+    header: &RawSectionHeader,
+    raw_content: &[u8],
+    cursor: &mut ReadCursor,
+) -> Result<ElfVersionNeeded<PendingIds>, LoadError> {
+    let mut inner = std::io::Cursor::new(raw_content);
+    let mut cursor = cursor.duplicate(&mut inner);
+    let string_table = PendingSectionId(header.link);
+
+    // Entries are linked together through offsets relative to the start of the current entry,
+    // and each of them points to its own chain of auxiliary entries. A zero offset ends a chain.
+    let mut files = Vec::new();
+    let mut offset = 0;
+    for idx in 0..header.info {
+        cursor.seek_to(offset)?;
+        let raw: RawVersionNeeded = cursor.read_raw()?;
+        if raw.version != 1 {
+            return Err(LoadError::UnsupportedVersionRevision(raw.version));
+        }
+
+        let mut versions = Vec::with_capacity(raw.count as _);
+        let mut aux_offset = offset + raw.aux_offset as u64;
+        for aux_idx in 0..raw.count {
+            cursor.seek_to(aux_offset)?;
+            let aux: RawVersionNeededAux = cursor.read_raw()?;
+            versions.push(ElfNeededVersion {
+                name: PendingStringId(string_table, aux.name_offset),
+                hash: aux.hash,
+                flags: aux.flags,
+                index: aux.index,
+            });
+            if !next_version_entry(&mut aux_offset, aux.next_offset, aux_idx, raw.count)? {
+                break;
+            }
+        }
+
+        files.push(ElfVersionNeededFile {
+            file: PendingStringId(string_table, raw.file_offset),
+            versions,
+        });
+        if !next_version_entry(&mut offset, raw.next_offset, idx, header.info)? {
+            break;
+        }
+    }
+
+    Ok(ElfVersionNeeded { string_table, files })
+}
+
+fn read_version_defined(
+    header: &RawSectionHeader,
+    raw_content: &[u8],
+    cursor: &mut ReadCursor,
+) -> Result<ElfVersionDefined<PendingIds>, LoadError> {
+    let mut inner = std::io::Cursor::new(raw_content);
+    let mut cursor = cursor.duplicate(&mut inner);
+    let string_table = PendingSectionId(header.link);
+
+    // Same layout as the needed versions table: see the comment in read_version_needed.
+    let mut versions = Vec::new();
+    let mut offset = 0;
+    for idx in 0..header.info {
+        cursor.seek_to(offset)?;
+        let raw: RawVersionDefinition = cursor.read_raw()?;
+        if raw.version != 1 {
+            return Err(LoadError::UnsupportedVersionRevision(raw.version));
+        }
+
+        let mut names = Vec::with_capacity(raw.count as _);
+        let mut aux_offset = offset + raw.aux_offset as u64;
+        for aux_idx in 0..raw.count {
+            cursor.seek_to(aux_offset)?;
+            let aux: RawVersionDefinitionAux = cursor.read_raw()?;
+            names.push(PendingStringId(string_table, aux.name_offset));
+            if !next_version_entry(&mut aux_offset, aux.next_offset, aux_idx, raw.count)? {
+                break;
+            }
+        }
+
+        versions.push(ElfDefinedVersion {
+            flags: raw.flags,
+            index: raw.index,
+            hash: raw.hash,
+            names,
+        });
+        if !next_version_entry(&mut offset, raw.next_offset, idx, header.info)? {
+            break;
+        }
+    }
+
+    Ok(ElfVersionDefined { string_table, versions })
+}
+
+/// Move `offset` to the next entry of a symbol versioning chain, returning whether there is one.
+/// The chain must not end (with a zero `next_offset`) before all the `expected` entries are read.
+fn next_version_entry<N: Into<u32>>(
+    offset: &mut u64,
+    next_offset: u32,
+    idx: N,
+    expected: N,
+) -> Result<bool, LoadError> {
+    let (found, expected) = (idx.into() + 1, expected.into());
+    if next_offset != 0 {
+        *offset += next_offset as u64;
+        Ok(true)
+    } else if found < expected {
+        Err(LoadError::TruncatedVersionChain { expected, found })
+    } else {
+        Ok(false)
+    }
+}
+
 fn read_dynamic(
     header: &RawSectionHeader,
     raw_content: &[u8],
@@ -493,6 +776,18 @@ fn read_dynamic(
             22 => ElfDynamicDirective::RelocationsWillModifyText,
             23 => ElfDynamicDirective::JumpRel { address: value },
             24 => ElfDynamicDirective::BindNow,
+            25 => ElfDynamicDirective::InitArray { address: value },
+            26 => ElfDynamicDirective::FiniArray { address: value },
+            27 => ElfDynamicDirective::InitArraySize { bytes: value },
+            28 => ElfDynamicDirective::FiniArraySize { bytes: value },
+            30 => ElfDynamicDirective::Flags(
+                ElfDynamicFlags::read(value).map_err(LoadError::DynamicFlags)?,
+            ),
+            32 => ElfDynamicDirective::PreInitArray { address: value },
+            33 => ElfDynamicDirective::PreInitArraySize { bytes: value },
+            35 => ElfDynamicDirective::RelrSize { bytes: value },
+            36 => ElfDynamicDirective::Relr { address: value },
+            37 => ElfDynamicDirective::RelrEntrySize { bytes: value },
             0x6ffffef5 => ElfDynamicDirective::GnuHash { address: value },
             0x6ffffffb => ElfDynamicDirective::Flags1(
                 ElfDynamicFlags1::read(value).map_err(LoadError::DynamicFlags1)?,
@@ -503,3 +798,66 @@ fn read_dynamic(
 
     Ok(ElfDynamic { string_table: PendingSectionId(header.link), directives })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ElfEndian;
+    use plinky_utils::raw_types::RawType;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_relocation_type_depends_on_machine() {
+        // Relocation type 4 with symbol 1, in the 32-bit `r_info` format.
+        let raw = [0x10, 0, 0, 0, 0x04, 0x01, 0, 0];
+        let read = |machine| {
+            let mut inner = Cursor::new(raw);
+            let mut cursor = ReadCursor::new(&mut inner, ElfClass::Elf32, ElfEndian::Little);
+            read_relocation(&mut cursor, PendingSectionId(1), false, machine)
+                .unwrap()
+                .relocation_type
+        };
+
+        assert_eq!(ElfRelocationType::X86_PLT32, read(ElfMachine::X86));
+        assert_eq!(ElfRelocationType::X86_64_PLT32, read(ElfMachine::X86_64));
+    }
+
+    #[test]
+    fn test_version_needed_chain_ends_with_zero_offset() {
+        // A single file needing a single version, with both chains ended by a zero offset.
+        let mut raw = Vec::new();
+        for field in [1u16, 1] {
+            raw.extend_from_slice(&field.to_le_bytes()); // vn_version, vn_cnt
+        }
+        for field in [1u32, 16, 0] {
+            raw.extend_from_slice(&field.to_le_bytes()); // vn_file, vn_aux, vn_next
+        }
+        raw.extend_from_slice(&0x0d696910u32.to_le_bytes()); // vna_hash
+        raw.extend_from_slice(&[0, 0, 2, 0]); // vna_flags, vna_other
+        for field in [11u32, 0] {
+            raw.extend_from_slice(&field.to_le_bytes()); // vna_name, vna_next
+        }
+
+        let read = |info| {
+            let header = RawSectionHeader {
+                size: raw.len() as _,
+                link: 1,
+                info,
+                ..RawSectionHeader::zero()
+            };
+            let mut inner = Cursor::new(&raw);
+            let mut cursor = ReadCursor::new(&mut inner, ElfClass::Elf64, ElfEndian::Little);
+            read_version_needed(&header, &raw, &mut cursor)
+        };
+
+        let needed = read(1).unwrap();
+        assert_eq!(1, needed.files.len());
+        assert_eq!(1, needed.files[0].versions.len());
+
+        // The section claims two files, but the chain ends after the first one.
+        assert!(matches!(
+            read(2).unwrap_err(),
+            LoadError::TruncatedVersionChain { expected: 2, found: 1 }
+        ));
+    }
+}
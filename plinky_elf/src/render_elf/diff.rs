@@ -0,0 +1,309 @@
+use crate::ids::ElfIds;
+use crate::render_elf::meta::meta_rows;
+use crate::render_elf::sections::{render_section, symbol_columns};
+use crate::render_elf::segments::segment_columns;
+use crate::render_elf::utils::{resolve_string, MultipleWidgets};
+use crate::render_elf::RenderElfFilters;
+use crate::{ElfObject, ElfSection, ElfSectionContent};
+use plinky_diagnostics::widgets::{Table, Text, Widget};
+use std::collections::BTreeMap;
+
+const SYMBOL_FIELDS: [&str; 6] = ["binding", "type", "visibility", "definition", "value", "size"];
+
+pub(super) fn render_diff<A: ElfIds, B: ElfIds>(
+    a: &ElfObject<A>,
+    b: &ElfObject<B>,
+    filters: &RenderElfFilters,
+) -> Box<dyn Widget> {
+    let mut widgets: Vec<Box<dyn Widget>> = Vec::new();
+    if filters.meta {
+        widgets.extend(diff_meta(a, b));
+    }
+    widgets.extend(diff_sections(a, b, filters));
+    widgets.extend(diff_symbols(a, b, filters));
+    if filters.segments {
+        widgets.extend(diff_segments(a, b));
+    }
+
+    if widgets.is_empty() {
+        Box::new(Text::new("No differences between the two ELF objects."))
+    } else {
+        Box::new(MultipleWidgets(widgets))
+    }
+}
+
+fn diff_meta<A: ElfIds, B: ElfIds>(a: &ElfObject<A>, b: &ElfObject<B>) -> Option<Box<dyn Widget>> {
+    let mut table = Table::new();
+    table.set_title("Metadata:");
+    table.add_row(["Field", "Before", "After"]);
+    let mut changed = false;
+    for ((name, before), (_, after)) in meta_rows(a).into_iter().zip(meta_rows(b)) {
+        if before != after {
+            table.add_row([name.to_string(), before, after]);
+            changed = true;
+        }
+    }
+    changed.then(|| Box::new(table) as Box<dyn Widget>)
+}
+
+fn diff_sections<A: ElfIds, B: ElfIds>(
+    a: &ElfObject<A>,
+    b: &ElfObject<B>,
+    filters: &RenderElfFilters,
+) -> Option<Box<dyn Widget>> {
+    let mut table = Table::new();
+    table.set_title("Sections:");
+    table.add_row(["Change", "Section", "Details"]);
+    let mut changed = false;
+
+    let changes = match_by_name(sections_by_name(a, filters), sections_by_name(b, filters));
+    for (name, change) in changes {
+        let details = match change {
+            Change::Removed(section) => ("removed", format!("address: {:#x}", section.1)),
+            Change::Added(section) => ("added", format!("address: {:#x}", section.1)),
+            Change::Both(before, after) => {
+                let mut details = Vec::new();
+                if before.1 != after.1 {
+                    details.push(format!("address: {:#x} -> {:#x}", before.1, after.1));
+                }
+                if before.0 != after.0 {
+                    details.push("content changed".into());
+                }
+                if details.is_empty() {
+                    continue;
+                }
+                ("changed", details.join(", "))
+            }
+        };
+        table.add_row([details.0.to_string(), name, details.1]);
+        changed = true;
+    }
+
+    changed.then(|| Box::new(table) as Box<dyn Widget>)
+}
+
+/// Normalized content and memory address of each section matching the filters.
+fn sections_by_name<I: ElfIds>(
+    object: &ElfObject<I>,
+    filters: &RenderElfFilters,
+) -> Vec<(String, (String, u64))> {
+    object
+        .sections
+        .iter()
+        .filter(|(_, section)| filters.section(resolve_string(object, &section.name)))
+        .map(|(id, section)| {
+            let rendered = render_section(object, id, section).render_to_string();
+            // The first line contains the section name and address, which are compared separately.
+            let content = rendered.split_once('\n').map(|(_, content)| content).unwrap_or("");
+            (
+                resolve_string(object, &section.name).to_string(),
+                (strip_ids(content), section.memory_address),
+            )
+        })
+        .collect()
+}
+
+fn diff_symbols<A: ElfIds, B: ElfIds>(
+    a: &ElfObject<A>,
+    b: &ElfObject<B>,
+    filters: &RenderElfFilters,
+) -> Option<Box<dyn Widget>> {
+    let mut table = Table::new();
+    table.set_title("Symbols:");
+    table.add_row(["Change", "Symbol", "Before", "After"]);
+    let mut changed = false;
+
+    let changes = match_by_name(symbols_by_name(a, filters), symbols_by_name(b, filters));
+    for (name, change) in changes {
+        let (kind, before, after) = match change {
+            Change::Removed(_) => ("removed", "-".to_string(), "-".to_string()),
+            Change::Added(_) => ("added", "-".to_string(), "-".to_string()),
+            Change::Both(before, after) => {
+                let fields = SYMBOL_FIELDS.iter().zip(before.iter().zip(after.iter()));
+                let (before, after): (Vec<_>, Vec<_>) = fields
+                    .filter(|(_, (before, after))| before != after)
+                    .map(|(field, (before, after))| {
+                        (format!("{field}: {before}"), format!("{field}: {after}"))
+                    })
+                    .unzip();
+                if before.is_empty() {
+                    continue;
+                }
+                ("changed", before.join(", "), after.join(", "))
+            }
+        };
+        table.add_row([kind.to_string(), name, before, after]);
+        changed = true;
+    }
+
+    changed.then(|| Box::new(table) as Box<dyn Widget>)
+}
+
+/// Normalized columns of each named symbol, in symbol tables matching the filters.
+fn symbols_by_name<I: ElfIds>(
+    object: &ElfObject<I>,
+    filters: &RenderElfFilters,
+) -> Vec<(String, [String; 6])> {
+    let mut symbols = Vec::new();
+    for section in object.sections.values() {
+        let ElfSection { name: table_name, content: ElfSectionContent::SymbolTable(table), .. } =
+            section
+        else {
+            continue;
+        };
+        let table_name = resolve_string(object, table_name);
+        if !filters.section(table_name) {
+            continue;
+        }
+        for symbol in table.symbols.values() {
+            let name = resolve_string(object, &symbol.name);
+            if name.is_empty() {
+                continue;
+            }
+            symbols.push((
+                format!("{name} ({table_name})"),
+                symbol_columns(object, symbol).map(|column| strip_ids(&column)),
+            ));
+        }
+    }
+    symbols
+}
+
+fn diff_segments<A: ElfIds, B: ElfIds>(
+    a: &ElfObject<A>,
+    b: &ElfObject<B>,
+) -> Option<Box<dyn Widget>> {
+    let normalize = |columns: [String; 4]| columns.map(|column| strip_ids(&column));
+    let mut before =
+        a.segments.iter().map(|segment| normalize(segment_columns(a, segment))).collect::<Vec<_>>();
+    let mut added = Vec::new();
+    for segment in &b.segments {
+        let columns = normalize(segment_columns(b, segment));
+        match before.iter().position(|candidate| *candidate == columns) {
+            Some(pos) => {
+                before.remove(pos);
+            }
+            None => added.push(columns),
+        }
+    }
+
+    if before.is_empty() && added.is_empty() {
+        return None;
+    }
+
+    let mut table = Table::new();
+    table.set_title("Segments:");
+    table.add_row(["Change", "Type", "Perms", "Aligment", "Content"]);
+    for (kind, columns) in
+        before.into_iter().map(|c| ("removed", c)).chain(added.into_iter().map(|c| ("added", c)))
+    {
+        let [type_, perms, align, content] = columns;
+        table.add_row([kind.to_string(), type_, perms, align, content]);
+    }
+    Some(Box::new(table))
+}
+
+enum Change<T> {
+    Removed(T),
+    Added(T),
+    Both(T, T),
+}
+
+/// Pair the items of the two lists with the same name, in the order they appear. Names can
+/// appear multiple times (for example sections in object files), in which case the first
+/// occurrence in one list is paired with the first occurrence in the other list, and so on.
+fn match_by_name<T>(a: Vec<(String, T)>, b: Vec<(String, T)>) -> Vec<(String, Change<T>)> {
+    let mut b_by_name: BTreeMap<String, Vec<T>> = BTreeMap::new();
+    let mut b_order = Vec::new();
+    for (name, item) in b {
+        b_by_name.entry(name.clone()).or_default().push(item);
+        b_order.push(name);
+    }
+    // Reverse the lists to pop the first occurrence of each name.
+    b_by_name.values_mut().for_each(|items| items.reverse());
+
+    let mut result = Vec::new();
+    for (name, item) in a {
+        match b_by_name.get_mut(&name).and_then(|items| items.pop()) {
+            Some(other) => result.push((name, Change::Both(item, other))),
+            None => result.push((name, Change::Removed(item))),
+        }
+    }
+    for name in b_order {
+        if let Some(item) = b_by_name.get_mut(&name).and_then(|items| items.pop()) {
+            result.push((name, Change::Added(item)));
+        }
+    }
+    result
+}
+
+/// Remove the `#id` suffixes from rendered names, as IDs are not stable between objects.
+fn strip_ids(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    while let Some(chr) = chars.next() {
+        if chr == '#' && chars.peek().is_some_and(|next| next.is_ascii_digit()) {
+            while chars.peek().is_some_and(|next| next.is_ascii_digit()) {
+                chars.next();
+            }
+        } else {
+            result.push(chr);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::serial::SerialIds;
+    use crate::{
+        ElfObjectBuilder, ElfPermissions, ElfSymbolBinding, ElfSymbolDefinition, ElfSymbolType,
+        ElfType,
+    };
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_no_differences() {
+        let diff = render_diff(&object(false, 0), &object(false, 0), &RenderElfFilters::all());
+        assert_eq!("No differences between the two ELF objects.", diff.render_to_string());
+    }
+
+    #[test]
+    fn test_added_section_and_changed_symbol() {
+        let _config = crate::configure_insta();
+
+        let diff = render_diff(&object(false, 0), &object(true, 1), &RenderElfFilters::all());
+        assert_snapshot!(diff.render_to_string());
+    }
+
+    #[test]
+    fn test_strip_ids() {
+        assert_eq!(".text, .data (foo#bar)", strip_ids(".text#1, .data#42 (foo#bar)"));
+    }
+
+    fn object(with_data: bool, start_value: u64) -> ElfObject<SerialIds> {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let text = builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            [0x90, 0x90, 0xc3],
+        );
+        if with_data {
+            builder.add_program_section(
+                ".data",
+                ElfPermissions { read: true, write: true, execute: false },
+                *b"hello",
+            );
+        }
+        builder.add_symbol(
+            "_start",
+            ElfSymbolBinding::Global,
+            ElfSymbolType::Function,
+            ElfSymbolDefinition::Section(text),
+            start_value,
+            0,
+        );
+        builder.build()
+    }
+}
@@ -2,9 +2,10 @@ pub use crate::render_elf::filters::{RenderElfFilters, RenderElfFiltersParseErro
 
 use crate::ids::ElfIds;
 use crate::render_elf::utils::{resolve_string, MultipleWidgets};
-use crate::ElfObject;
+use crate::{ElfObject, ElfSectionContent};
 use plinky_diagnostics::widgets::Widget;
 
+mod diff;
 mod filters;
 mod meta;
 mod sections;
@@ -29,3 +30,24 @@ pub fn render<I: ElfIds + 'static>(
     }
     MultipleWidgets(widgets)
 }
+
+/// Render only the relocations tables of the object, regardless of their section names.
+pub fn render_relocations<I: ElfIds + 'static>(object: &ElfObject<I>) -> impl Widget {
+    let mut widgets: Vec<Box<dyn Widget>> = Vec::new();
+    for (id, section) in &object.sections {
+        if let ElfSectionContent::RelocationsTable(_) = &section.content {
+            widgets.push(Box::new(sections::render_section(object, id, section)));
+        }
+    }
+    MultipleWidgets(widgets)
+}
+
+/// Render the differences in metadata, sections, symbols and segments between two objects.
+/// Sections and symbols are matched by name, as their IDs are not stable between objects.
+pub fn render_diff<A: ElfIds, B: ElfIds>(
+    a: &ElfObject<A>,
+    b: &ElfObject<B>,
+    filters: &RenderElfFilters,
+) -> impl Widget {
+    diff::render_diff(a, b, filters)
+}
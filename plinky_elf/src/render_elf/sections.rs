@@ -1,12 +1,16 @@
 use crate::ids::ElfIds;
-use crate::render_elf::utils::{render_perms, section_name, symbol_name};
+use crate::render_elf::utils::{render_perms, resolve_string, section_name, symbol_name};
 use crate::{
-    ElfDeduplication, ElfDynamic, ElfDynamicDirective, ElfGroup, ElfHash, ElfNote, ElfNotesTable,
-    ElfObject, ElfPLTRelocationsMode, ElfProgramSection, ElfRelocationsTable, ElfSection,
-    ElfSectionContent, ElfStringTable, ElfSymbolBinding, ElfSymbolDefinition, ElfSymbolTable,
-    ElfSymbolType, ElfSymbolVisibility, ElfUninitializedSection, ElfUnknownSection,
+    ElfCompression, ElfDeduplication, ElfDynamic, ElfDynamicDirective, ElfFunctionsArray,
+    ElfGnuHash, ElfGroup, ElfHash, ElfNote, ElfNotesTable, ElfObject, ElfPLTRelocationsMode,
+    ElfProgramSection, ElfRelocationsTable, ElfSection, ElfSectionContent, ElfStringTable,
+    ElfSymbol, ElfSymbolBinding, ElfSymbolDefinition, ElfSymbolTable, ElfSymbolType,
+    ElfSymbolVisibility, ElfUninitializedSection, ElfUnknownSection, ElfVersionDefined,
+    ElfVersionFlags, ElfVersionNeeded, ElfVersionSymbols,
 };
 use plinky_diagnostics::widgets::{HexDump, Table, Text, Widget, WidgetGroup};
+use std::collections::BTreeMap;
+use std::fmt::Write;
 
 pub(super) fn render_section<I: ElfIds>(
     object: &ElfObject<I>,
@@ -15,15 +19,23 @@ pub(super) fn render_section<I: ElfIds>(
 ) -> impl Widget {
     let content: Vec<Box<dyn Widget>> = match &section.content {
         ElfSectionContent::Null => vec![Box::new(Text::new("empty section"))],
-        ElfSectionContent::Program(program) => render_section_program(program),
+        ElfSectionContent::Program(program) => render_section_program(object, program),
         ElfSectionContent::Uninitialized(uninit) => render_section_uninit(uninit),
         ElfSectionContent::SymbolTable(symbols) => render_section_symbols(object, id, symbols),
         ElfSectionContent::StringTable(strings) => render_section_strings(strings),
         ElfSectionContent::RelocationsTable(relocs) => render_section_relocs(object, relocs),
         ElfSectionContent::Group(group) => render_section_group(object, group),
         ElfSectionContent::Hash(hash) => render_section_hash(object, hash),
+        ElfSectionContent::GnuHash(hash) => render_section_gnu_hash(object, hash),
         ElfSectionContent::Note(notes) => render_section_notes(notes),
         ElfSectionContent::Dynamic(dynamic) => render_section_dynamic(object, dynamic),
+        ElfSectionContent::VersionSymbols(versions) => {
+            render_section_version_symbols(object, versions)
+        }
+        ElfSectionContent::VersionNeeded(needed) => render_section_version_needed(object, needed),
+        ElfSectionContent::VersionDefined(defined) => {
+            render_section_version_defined(object, defined)
+        }
         ElfSectionContent::Unknown(unknown) => render_section_unknown(unknown),
     };
 
@@ -41,7 +53,10 @@ pub(super) fn render_section<I: ElfIds>(
         .add_iter(content)
 }
 
-fn render_section_program(program: &ElfProgramSection) -> Vec<Box<dyn Widget>> {
+fn render_section_program<I: ElfIds>(
+    object: &ElfObject<I>,
+    program: &ElfProgramSection<I>,
+) -> Vec<Box<dyn Widget>> {
     let mut intro = format!("program data | permissions: {}", render_perms(&program.perms));
 
     match program.deduplication {
@@ -53,16 +68,36 @@ fn render_section_program(program: &ElfProgramSection) -> Vec<Box<dyn Widget>> {
             intro.push_str(&format!(" | deduplicating chunks of size {size:#x}"));
         }
     }
+    if program.tls {
+        intro.push_str(" | thread-local");
+    }
+    if let Some(linked) = &program.link_order {
+        intro.push_str(&format!(" | ordered with {}", section_name(object, linked)));
+    }
+    match program.functions_array {
+        None => {}
+        Some(ElfFunctionsArray::PreInit) => intro.push_str(" | pre-initialization functions"),
+        Some(ElfFunctionsArray::Init) => intro.push_str(" | initialization functions"),
+        Some(ElfFunctionsArray::Fini) => intro.push_str(" | finalization functions"),
+    }
+    match program.compression {
+        None => {}
+        Some(ElfCompression::Zlib) => intro.push_str(" | compressed with zlib"),
+    }
 
     vec![Box::new(Text::new(intro.trim())), Box::new(HexDump::new(program.raw.0.as_slice()))]
 }
 
 fn render_section_uninit(uninit: &ElfUninitializedSection) -> Vec<Box<dyn Widget>> {
-    vec![Box::new(Text::new(format!(
+    let mut intro = format!(
         "uninitialized | len: {:#x} | permissions: {}",
         uninit.len,
         render_perms(&uninit.perms)
-    )))]
+    );
+    if uninit.tls {
+        intro.push_str(" | thread-local");
+    }
+    vec![Box::new(Text::new(intro))]
 }
 
 fn render_section_symbols<I: ElfIds>(
@@ -78,44 +113,62 @@ fn render_section_symbols<I: ElfIds>(
     }
     table.add_row(["Name", "Binding", "Type", "Visibility", "Definition", "Value", "Size"]);
     for (id, symbol) in &symbols.symbols {
+        let [binding, type_, visibility, definition, value, size] = symbol_columns(object, symbol);
         table.add_row([
             symbol_name(object, section_id, id),
-            match symbol.binding {
-                ElfSymbolBinding::Local => "Local".into(),
-                ElfSymbolBinding::Global => "Global".into(),
-                ElfSymbolBinding::Weak => "Weak".into(),
-                ElfSymbolBinding::Unknown(unknown) => format!("<unknown: {unknown:#x}>"),
-            },
-            match symbol.type_ {
-                ElfSymbolType::NoType => "-".into(),
-                ElfSymbolType::Object => "Object".into(),
-                ElfSymbolType::Function => "Function".into(),
-                ElfSymbolType::Section => "Section".into(),
-                ElfSymbolType::File => "File".into(),
-                ElfSymbolType::Unknown(unknown) => format!("<unknown: {unknown:#x}>"),
-            },
-            match symbol.visibility {
-                ElfSymbolVisibility::Default => "Default",
-                ElfSymbolVisibility::Hidden => "Hidden",
-                ElfSymbolVisibility::Protected => "Protected",
-                ElfSymbolVisibility::Exported => "Exported",
-                ElfSymbolVisibility::Singleton => "Singleton",
-                ElfSymbolVisibility::Eliminate => "Eliminate",
-            }
-            .into(),
-            match &symbol.definition {
-                ElfSymbolDefinition::Undefined => "Undefined".into(),
-                ElfSymbolDefinition::Absolute => "Absolute".into(),
-                ElfSymbolDefinition::Common => "Common".into(),
-                ElfSymbolDefinition::Section(section_id) => section_name(object, section_id),
-            },
-            format!("{:#x}", symbol.value),
-            format!("{:#x}", symbol.size),
+            binding,
+            type_,
+            visibility,
+            definition,
+            value,
+            size,
         ])
     }
     vec![Box::new(table)]
 }
 
+/// Render the binding, type, visibility, definition, value and size of a symbol.
+pub(super) fn symbol_columns<I: ElfIds>(
+    object: &ElfObject<I>,
+    symbol: &ElfSymbol<I>,
+) -> [String; 6] {
+    [
+        match symbol.binding {
+            ElfSymbolBinding::Local => "Local".into(),
+            ElfSymbolBinding::Global => "Global".into(),
+            ElfSymbolBinding::Weak => "Weak".into(),
+            ElfSymbolBinding::Unknown(unknown) => format!("<unknown: {unknown:#x}>"),
+        },
+        match symbol.type_ {
+            ElfSymbolType::NoType => "-".into(),
+            ElfSymbolType::Object => "Object".into(),
+            ElfSymbolType::Function => "Function".into(),
+            ElfSymbolType::Section => "Section".into(),
+            ElfSymbolType::File => "File".into(),
+            ElfSymbolType::GnuIfunc => "GNU ifunc".into(),
+            ElfSymbolType::Tls => "TLS".into(),
+            ElfSymbolType::Unknown(unknown) => format!("<unknown: {unknown:#x}>"),
+        },
+        match symbol.visibility {
+            ElfSymbolVisibility::Default => "Default",
+            ElfSymbolVisibility::Hidden => "Hidden",
+            ElfSymbolVisibility::Protected => "Protected",
+            ElfSymbolVisibility::Exported => "Exported",
+            ElfSymbolVisibility::Singleton => "Singleton",
+            ElfSymbolVisibility::Eliminate => "Eliminate",
+        }
+        .into(),
+        match &symbol.definition {
+            ElfSymbolDefinition::Undefined => "Undefined".into(),
+            ElfSymbolDefinition::Absolute => "Absolute".into(),
+            ElfSymbolDefinition::Common => "Common".into(),
+            ElfSymbolDefinition::Section(section_id) => section_name(object, section_id),
+        },
+        format!("{:#x}", symbol.value),
+        format!("{:#x}", symbol.size),
+    ]
+}
+
 fn render_section_strings(strings: &ElfStringTable) -> Vec<Box<dyn Widget>> {
     let mut table = Table::new();
     table.set_title("Strings table:");
@@ -220,6 +273,13 @@ fn render_section_notes(notes: &ElfNotesTable) -> Vec<Box<dyn Widget>> {
 
     for note in &notes.notes {
         match note {
+            ElfNote::GnuBuildId(id) => {
+                let mut hex = String::new();
+                for byte in id {
+                    write!(hex, "{byte:02x}").unwrap();
+                }
+                output.push(Box::new(Text::new(format!("GNU build-id: {hex}"))) as Box<dyn Widget>)
+            }
             ElfNote::Unknown(unknown) => output.push(Box::new(
                 WidgetGroup::new()
                     .name(format!(
@@ -234,6 +294,49 @@ fn render_section_notes(notes: &ElfNotesTable) -> Vec<Box<dyn Widget>> {
     output
 }
 
+fn render_section_gnu_hash<I: ElfIds>(
+    object: &ElfObject<I>,
+    hash: &ElfGnuHash<I>,
+) -> Vec<Box<dyn Widget>> {
+    let ElfSectionContent::SymbolTable(symbol_table) =
+        &object.sections.get(&hash.symbol_table).unwrap().content
+    else {
+        panic!("hash table's symbol table is not a symbol table");
+    };
+
+    let info = Text::new(format!(
+        "GNU hash table for {}\n\
+         symbol offset: {}\n\
+         bloom filter:  {} words, shift {}",
+        section_name(object, &hash.symbol_table),
+        hash.symbol_offset,
+        hash.bloom.len(),
+        hash.bloom_shift,
+    ));
+
+    let mut content = Table::new();
+    content.set_title("Content:");
+    content.add_row(["Bucket ID", "Symbols in bucket"]);
+    for (id, &start) in hash.buckets.iter().enumerate() {
+        let mut symbols_str = String::new();
+        if start != 0 {
+            for index in start.. {
+                let symbol = symbol_table.symbols.keys().nth(index as usize).unwrap();
+                if index != start {
+                    symbols_str.push('\n');
+                }
+                symbols_str.push_str(&symbol_name(object, &hash.symbol_table, symbol));
+                if hash.chain[(index - hash.symbol_offset) as usize] & 1 == 1 {
+                    break;
+                }
+            }
+        }
+        content.add_row([id.to_string(), symbols_str]);
+    }
+
+    vec![Box::new(info), Box::new(content)]
+}
+
 fn render_section_dynamic<I: ElfIds>(
     object: &ElfObject<I>,
     dynamic: &ElfDynamic<I>,
@@ -314,6 +417,34 @@ fn render_section_dynamic<I: ElfIds>(
                 ("Jump PLT relocations", Value::Addr(address))
             }
             ElfDynamicDirective::BindNow => ("Bind now", Value::None),
+            ElfDynamicDirective::InitArray { address } => {
+                ("Initialization functions array", Value::Addr(address))
+            }
+            ElfDynamicDirective::FiniArray { address } => {
+                ("Finalization functions array", Value::Addr(address))
+            }
+            ElfDynamicDirective::InitArraySize { bytes } => {
+                ("Initialization functions array size", Value::Bytes(bytes))
+            }
+            ElfDynamicDirective::FiniArraySize { bytes } => {
+                ("Finalization functions array size", Value::Bytes(bytes))
+            }
+            ElfDynamicDirective::PreInitArray { address } => {
+                ("Pre-initialization functions array", Value::Addr(address))
+            }
+            ElfDynamicDirective::PreInitArraySize { bytes } => {
+                ("Pre-initialization functions array size", Value::Bytes(bytes))
+            }
+            ElfDynamicDirective::RelrSize { bytes } => {
+                ("Relative relocations size", Value::Bytes(bytes))
+            }
+            ElfDynamicDirective::Relr { address } => {
+                ("Relative relocations table", Value::Addr(address))
+            }
+            ElfDynamicDirective::RelrEntrySize { bytes } => {
+                ("Relative relocations entry size", Value::Bytes(bytes))
+            }
+            ElfDynamicDirective::Flags(flags) => ("Flags", Value::Str(flags.to_string())),
             ElfDynamicDirective::Flags1(flags1) => ("Flags1", Value::Str(flags1.to_string())),
             ElfDynamicDirective::Unknown { tag, value } => {
                 table.add_row([format!("<unknown {tag:#x}>"), format!("{value:#x}")]);
@@ -335,6 +466,123 @@ fn render_section_dynamic<I: ElfIds>(
     vec![Box::new(info), Box::new(table)]
 }
 
+fn render_section_version_symbols<I: ElfIds>(
+    object: &ElfObject<I>,
+    versions: &ElfVersionSymbols<I>,
+) -> Vec<Box<dyn Widget>> {
+    let ElfSectionContent::SymbolTable(symbol_table) =
+        &object.sections.get(&versions.symbol_table).unwrap().content
+    else {
+        panic!("version symbols table's symbol table is not a symbol table");
+    };
+
+    // Versions are referred to by their index, which is assigned by the definitions and by the
+    // needed versions tables of the object.
+    let mut names = BTreeMap::new();
+    for section in object.sections.values() {
+        match &section.content {
+            ElfSectionContent::VersionNeeded(needed) => {
+                for version in needed.files.iter().flat_map(|file| &file.versions) {
+                    names.insert(version.index, resolve_string(object, &version.name));
+                }
+            }
+            ElfSectionContent::VersionDefined(defined) => {
+                for version in &defined.versions {
+                    if let Some(name) = version.names.first() {
+                        names.insert(version.index, resolve_string(object, name));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let info = Text::new(format!(
+        "Version symbols table for {}",
+        section_name(object, &versions.symbol_table)
+    ));
+
+    let mut table = Table::new();
+    table.add_row(["Symbol", "Version"]);
+    for (symbol, version) in symbol_table.symbols.keys().zip(&versions.versions) {
+        let mut rendered = match (version.index, names.get(&version.index)) {
+            (0, _) => "local".to_string(),
+            (1, _) => "global".to_string(),
+            (index, Some(name)) => format!("{name} (index {index})"),
+            (index, None) => format!("<unknown index {index}>"),
+        };
+        if version.hidden {
+            rendered.push_str(" | hidden");
+        }
+        table.add_row([symbol_name(object, &versions.symbol_table, symbol), rendered]);
+    }
+
+    vec![Box::new(info), Box::new(table)]
+}
+
+fn render_section_version_needed<I: ElfIds>(
+    object: &ElfObject<I>,
+    needed: &ElfVersionNeeded<I>,
+) -> Vec<Box<dyn Widget>> {
+    let info = Text::new(format!(
+        "needed versions | string table: {}",
+        section_name(object, &needed.string_table)
+    ));
+
+    let mut table = Table::new();
+    table.add_row(["File", "Version", "Index", "Hash", "Flags"]);
+    for file in &needed.files {
+        for version in &file.versions {
+            table.add_row([
+                resolve_string(object, &file.file).to_string(),
+                resolve_string(object, &version.name).to_string(),
+                version.index.to_string(),
+                format!("{:#010x}", version.hash),
+                render_version_flags(&version.flags),
+            ]);
+        }
+    }
+
+    vec![Box::new(info), Box::new(table)]
+}
+
+fn render_section_version_defined<I: ElfIds>(
+    object: &ElfObject<I>,
+    defined: &ElfVersionDefined<I>,
+) -> Vec<Box<dyn Widget>> {
+    let info = Text::new(format!(
+        "defined versions | string table: {}",
+        section_name(object, &defined.string_table)
+    ));
+
+    let mut table = Table::new();
+    table.add_row(["Index", "Version", "Parents", "Hash", "Flags"]);
+    for version in &defined.versions {
+        let mut names = version.names.iter().map(|name| resolve_string(object, name));
+        table.add_row([
+            version.index.to_string(),
+            names.next().unwrap_or("-").to_string(),
+            names.collect::<Vec<_>>().join(", "),
+            format!("{:#010x}", version.hash),
+            render_version_flags(&version.flags),
+        ]);
+    }
+
+    vec![Box::new(info), Box::new(table)]
+}
+
+fn render_version_flags(flags: &ElfVersionFlags) -> String {
+    let names = [("base", flags.base), ("weak", flags.weak), ("info", flags.info)]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        "-".into()
+    } else {
+        names.join(", ")
+    }
+}
+
 fn render_section_unknown(unknown: &ElfUnknownSection) -> Vec<Box<dyn Widget>> {
     vec![
         Box::new(Text::new(format!("unknown section with type {:#x}", unknown.id))),
@@ -1,6 +1,6 @@
 use crate::ids::ElfIds;
 use crate::render_elf::utils::{render_perms, section_name};
-use crate::{ElfObject, ElfSegmentContent, ElfSegmentType};
+use crate::{ElfObject, ElfSegment, ElfSegmentContent, ElfSegmentType};
 use plinky_diagnostics::widgets::{Table, Text, Widget};
 
 pub(super) fn render_segments<I: ElfIds>(object: &ElfObject<I>) -> Box<dyn Widget> {
@@ -12,37 +12,52 @@ pub(super) fn render_segments<I: ElfIds>(object: &ElfObject<I>) -> Box<dyn Widge
     table.set_title("Segments:");
     table.add_row(["Type", "Perms", "Aligment", "Content"]);
     for segment in &object.segments {
-        table.add_row([
-            match segment.type_ {
-                ElfSegmentType::Null => "Null".into(),
-                ElfSegmentType::Load => "Load".into(),
-                ElfSegmentType::Dynamic => "Dynamic".into(),
-                ElfSegmentType::Interpreter => "Interpreter".into(),
-                ElfSegmentType::Note => "Note".into(),
-                ElfSegmentType::ProgramHeaderTable => "Program header table".into(),
-                ElfSegmentType::GnuStack => "GNU stack".into(),
-                ElfSegmentType::GnuRelRO => "GNU relocations read-only".into(),
-                ElfSegmentType::Unknown(id) => format!("<unknown: {id:#x}>"),
-            },
-            render_perms(&segment.perms),
-            format!("{:#x}", segment.align),
-            match &segment.content {
-                ElfSegmentContent::Empty => "-".into(),
-                ElfSegmentContent::ElfHeader => "elf header".into(),
-                ElfSegmentContent::Sections(sections) => sections
-                    .iter()
-                    .map(|id| section_name(object, id))
-                    .collect::<Vec<_>>()
-                    .join(", "),
-                ElfSegmentContent::Unknown(unknown) => format!(
-                    "file: {:#x} (len: {:#x}), memory: {:#x} (len: {:#x})",
-                    unknown.file_offset,
-                    unknown.file_size,
-                    unknown.virtual_address,
-                    unknown.memory_size
-                ),
-            },
-        ]);
+        table.add_row(segment_columns(object, segment));
     }
     Box::new(table)
 }
+
+/// Render the type, permissions, alignment and content of a segment.
+pub(super) fn segment_columns<I: ElfIds>(
+    object: &ElfObject<I>,
+    segment: &ElfSegment<I>,
+) -> [String; 4] {
+    [
+        match segment.type_ {
+            ElfSegmentType::Null => "Null".into(),
+            ElfSegmentType::Load => "Load".into(),
+            ElfSegmentType::Dynamic => "Dynamic".into(),
+            ElfSegmentType::Interpreter => "Interpreter".into(),
+            ElfSegmentType::Note => "Note".into(),
+            ElfSegmentType::ProgramHeaderTable => "Program header table".into(),
+            ElfSegmentType::Tls => "TLS".into(),
+            ElfSegmentType::GnuStack => "GNU stack".into(),
+            ElfSegmentType::GnuRelRO => "GNU relocations read-only".into(),
+            ElfSegmentType::Unknown(id) => format!("<unknown: {id:#x}>"),
+        },
+        render_perms(&segment.perms),
+        format!("{:#x}", segment.align),
+        match &segment.content {
+            ElfSegmentContent::Empty => "-".into(),
+            ElfSegmentContent::ElfHeader => "elf header".into(),
+            ElfSegmentContent::Sections(sections) => {
+                sections.iter().map(|id| section_name(object, id)).collect::<Vec<_>>().join(", ")
+            }
+            ElfSegmentContent::Notes(notes) => format!(
+                "notes: {}, file: {:#x} (len: {:#x}), memory: {:#x} (len: {:#x})",
+                notes.notes.notes.len(),
+                notes.location.file_offset,
+                notes.location.file_size,
+                notes.location.virtual_address,
+                notes.location.memory_size
+            ),
+            ElfSegmentContent::Unknown(unknown) => format!(
+                "file: {:#x} (len: {:#x}), memory: {:#x} (len: {:#x})",
+                unknown.file_offset,
+                unknown.file_size,
+                unknown.virtual_address,
+                unknown.memory_size
+            ),
+        },
+    ]
+}
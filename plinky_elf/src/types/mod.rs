@@ -2,15 +2,19 @@ mod string_table;
 
 pub use self::string_table::ElfStringTable;
 
-use crate::errors::{LoadError, WriteError};
+use crate::errors::{LoadError, SegmentsCheckError, WriteError, WriteLayoutError};
 use crate::ids::{convert, ConvertibleElfIds, ElfIds, StringIdGetters};
-use crate::raw::{RawGroupFlags, RawHashHeader, RawRel, RawRela, RawSymbol};
+use crate::raw::{
+    RawGnuHashHeader, RawGroupFlags, RawHashHeader, RawNoteHeader, RawRel, RawRela, RawSymbol,
+    RawVersionDefinition, RawVersionDefinitionAux, RawVersionNeeded, RawVersionNeededAux,
+};
 use crate::reader::{read_object, PendingIds, ReadCursor};
 use crate::utils::{render_hex, ReadSeek};
-use crate::writer::Writer;
+use crate::writer::{ElfWriteOptions, WriteLayout, Writer};
 use plinky_utils::raw_types::{RawType, RawTypeAsPointerSize};
+use plinky_utils::sha256::Sha256;
 use plinky_utils::{Bits, Endian};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::Write;
 use std::num::NonZeroU64;
 use std::ops::Deref;
@@ -40,9 +44,217 @@ impl<I: ElfIds> ElfObject<I> {
     where
         I::StringId: StringIdGetters<I>,
     {
-        let writer = Writer::new(write_to, self)?;
+        self.write_with_options(write_to, ElfWriteOptions::default())
+    }
+
+    pub fn write_with_options(
+        &self,
+        write_to: &mut dyn Write,
+        options: ElfWriteOptions,
+    ) -> Result<(), WriteError<I>>
+    where
+        I::StringId: StringIdGetters<I>,
+    {
+        let writer = Writer::new(write_to, self, options)?;
         writer.write()
     }
+
+    /// Calculate where each section would be placed in the file when writing the object with
+    /// the provided options, without actually writing it.
+    pub fn compute_layout(
+        &self,
+        options: ElfWriteOptions,
+    ) -> Result<ElfLayout<I>, WriteLayoutError> {
+        Ok(WriteLayout::new(self, options)?.into_elf_layout(self))
+    }
+
+    /// Find the `NT_GNU_BUILD_ID` note, either in a note section or in a note segment.
+    pub fn gnu_build_id(&self) -> Option<&[u8]> {
+        let from_sections = self.sections.values().filter_map(|section| match &section.content {
+            ElfSectionContent::Note(table) => Some(table),
+            _ => None,
+        });
+        let from_segments = self.segments.iter().filter_map(|segment| match &segment.content {
+            ElfSegmentContent::Notes(content) => Some(&content.notes),
+            _ => None,
+        });
+        from_sections.chain(from_segments).flat_map(|table| table.notes.iter()).find_map(|note| {
+            match note {
+                ElfNote::GnuBuildId(id) => Some(&id[..]),
+                ElfNote::Unknown(_) => None,
+            }
+        })
+    }
+
+    pub fn is_relocatable(&self) -> bool {
+        self.type_ == ElfType::Relocatable
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.type_ == ElfType::Executable
+    }
+
+    pub fn is_shared_object(&self) -> bool {
+        self.type_ == ElfType::SharedObject
+    }
+
+    pub fn is_core(&self) -> bool {
+        self.type_ == ElfType::Core
+    }
+
+    /// Hash the written contents of the allocated sections, in section table order. Two objects
+    /// with the same loaded contents produce the same hash, regardless of non-allocated metadata
+    /// (like comments or debug info).
+    pub fn content_hash(&self) -> Result<[u8; 32], WriteError<I>>
+    where
+        I::StringId: StringIdGetters<I>,
+    {
+        let options = ElfWriteOptions::default();
+        let layout = self.compute_layout(options)?;
+        let mut file = Vec::new();
+        self.write_with_options(&mut file, options)?;
+
+        let loaded = self
+            .segments
+            .iter()
+            .filter(|segment| segment.type_ == ElfSegmentType::Load)
+            .flat_map(|segment| match &segment.content {
+                ElfSegmentContent::Sections(ids) => ids.as_slice(),
+                _ => &[],
+            })
+            .collect::<BTreeSet<_>>();
+
+        let mut hasher = Sha256::new();
+        for (id, section) in &self.sections {
+            // The read permission is how SHF_ALLOC is represented for program and uninitialized
+            // sections. The other kinds of sections (like .dynamic or .rela.dyn) are allocated
+            // when a LOAD segment contains them.
+            let perms = match &section.content {
+                ElfSectionContent::Program(program) if program.perms.read => Some(program.perms),
+                ElfSectionContent::Uninitialized(uninit) if uninit.perms.read => Some(uninit.perms),
+                ElfSectionContent::Program(_) | ElfSectionContent::Uninitialized(_) => continue,
+                _ if loaded.contains(id) => None,
+                _ => continue,
+            };
+            if let Some(perms) = perms {
+                hasher.update(&[perms.read as u8, perms.write as u8, perms.execute as u8]);
+            }
+
+            let ElfSectionLayout { file_offset, file_size, .. } = layout.sections[id];
+            let bytes = match file_offset {
+                Some(offset) => &file[offset as usize..(offset + file_size) as usize],
+                None => &[],
+            };
+            hasher.update(&(section.content.content_size(self.env.class) as u64).to_le_bytes());
+            hasher.update(&(bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        }
+        Ok(hasher.finish())
+    }
+
+    /// Check that the memory ranges of the LOAD segments (and their file ranges, when known
+    /// before writing the object) don't overlap, and that the sections inside each of them are
+    /// laid out in order without overlapping each other.
+    pub fn program_headers_overlap_check(&self) -> Result<(), SegmentsCheckError<I>> {
+        let mut memory_ranges = Vec::new();
+        let mut file_ranges = Vec::new();
+        for (idx, segment) in self.segments.iter().enumerate() {
+            if segment.type_ != ElfSegmentType::Load {
+                continue;
+            }
+            match &segment.content {
+                ElfSegmentContent::Sections(ids) => {
+                    let mut range: Option<(u64, u64)> = None;
+                    for id in ids {
+                        let section = self.sections.get(id).ok_or_else(|| {
+                            SegmentsCheckError::MissingSection { segment: idx, section: id.clone() }
+                        })?;
+                        let start = section.memory_address;
+                        let end = start + section.content.content_size(self.env.class) as u64;
+                        range = match range {
+                            Some((_, previous_end)) if start < previous_end => {
+                                return Err(SegmentsCheckError::OverlappingSections {
+                                    segment: idx,
+                                    section: id.clone(),
+                                });
+                            }
+                            Some((segment_start, _)) => Some((segment_start, end)),
+                            None => Some((start, end)),
+                        };
+                    }
+                    memory_ranges.extend(range.map(|range| (idx, range)));
+                }
+                ElfSegmentContent::Notes(ElfNotesSegmentContent { location, .. })
+                | ElfSegmentContent::Unknown(location) => {
+                    if location.file_size > location.memory_size {
+                        return Err(SegmentsCheckError::FileSizeLargerThanMemorySize {
+                            segment: idx,
+                        });
+                    }
+                    let memory_start = location.virtual_address;
+                    memory_ranges.push((idx, (memory_start, memory_start + location.memory_size)));
+                    let file_start = location.file_offset;
+                    file_ranges.push((idx, (file_start, file_start + location.file_size)));
+                }
+                // The file offsets of the ELF header are only known when writing the object.
+                ElfSegmentContent::ElfHeader | ElfSegmentContent::Empty => {}
+            }
+        }
+
+        if let Some((first, second)) = find_overlap(memory_ranges) {
+            return Err(SegmentsCheckError::OverlappingMemory { first, second });
+        }
+        if let Some((first, second)) = find_overlap(file_ranges) {
+            return Err(SegmentsCheckError::OverlappingFile { first, second });
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn into_parts(
+        self,
+    ) -> (
+        ElfEnvironment,
+        ElfType,
+        Option<NonZeroU64>,
+        BTreeMap<I::SectionId, ElfSection<I>>,
+        Vec<ElfSegment<I>>,
+    ) {
+        (self.env, self.type_, self.entry, self.sections, self.segments)
+    }
+}
+
+/// Location of the object's contents in the written file, as returned by
+/// [`ElfObject::compute_layout`].
+#[derive(Debug)]
+pub struct ElfLayout<I: ElfIds> {
+    pub sections: BTreeMap<I::SectionId, ElfSectionLayout>,
+    /// Offset of the section header table, if it's going to be written.
+    pub section_headers_offset: Option<u64>,
+    pub program_headers_offset: u64,
+    /// Size of the whole file.
+    pub len: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElfSectionLayout {
+    /// Offset of the section in the file, or `None` for sections without contents in the file
+    /// (like uninitialized sections).
+    pub file_offset: Option<u64>,
+    pub file_size: u64,
+    pub memory_address: u64,
+}
+
+/// Find the first pair of segments whose (non-empty) ranges overlap, returning their indices.
+fn find_overlap(mut ranges: Vec<(usize, (u64, u64))>) -> Option<(usize, usize)> {
+    ranges.retain(|(_, (start, end))| start != end);
+    ranges.sort_by_key(|(_, range)| *range);
+    ranges.windows(2).find_map(|pair| match pair {
+        [(first, (_, first_end)), (second, (second_start, _))] if second_start < first_end => {
+            Some((*first, *second))
+        }
+        _ => None,
+    })
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -71,17 +283,21 @@ impl From<ElfClass> for Bits {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ElfABI {
     SystemV,
+    /// GNU extensions to System V, like `STT_GNU_IFUNC` symbols.
+    Gnu,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ElfEndian {
     Little,
+    Big,
 }
 
 impl From<ElfEndian> for Endian {
     fn from(value: ElfEndian) -> Self {
         match value {
             ElfEndian::Little => Endian::Little,
+            ElfEndian::Big => Endian::Big,
         }
     }
 }
@@ -98,6 +314,7 @@ pub enum ElfType {
 pub enum ElfMachine {
     X86,
     X86_64,
+    Aarch64,
 }
 
 #[derive(Debug)]
@@ -111,7 +328,7 @@ pub struct ElfSection<I: ElfIds> {
 #[derive(Debug)]
 pub enum ElfSectionContent<I: ElfIds> {
     Null,
-    Program(ElfProgramSection),
+    Program(ElfProgramSection<I>),
     Uninitialized(ElfUninitializedSection),
     SymbolTable(ElfSymbolTable<I>),
     StringTable(ElfStringTable),
@@ -119,7 +336,11 @@ pub enum ElfSectionContent<I: ElfIds> {
     Note(ElfNotesTable),
     Group(ElfGroup<I>),
     Hash(ElfHash<I>),
+    GnuHash(ElfGnuHash<I>),
     Dynamic(ElfDynamic<I>),
+    VersionSymbols(ElfVersionSymbols<I>),
+    VersionNeeded(ElfVersionNeeded<I>),
+    VersionDefined(ElfVersionDefined<I>),
     Unknown(ElfUnknownSection),
 }
 
@@ -151,12 +372,29 @@ impl<I: ElfIds> ElfSectionContent<I> {
                     + u32::size(bits) * h.buckets.len()
                     + u32::size(bits) * h.chain.len()
             }
+            ElfSectionContent::GnuHash(h) => {
+                RawGnuHashHeader::size(bits)
+                    + <u64 as RawTypeAsPointerSize>::size(bits) * h.bloom.len()
+                    + u32::size(bits) * h.buckets.len()
+                    + u32::size(bits) * h.chain.len()
+            }
             ElfSectionContent::Dynamic(d) => {
                 let size = <u64 as RawTypeAsPointerSize>::size(bits) * 2;
                 d.directives.len() * size
             }
-            ElfSectionContent::Note(_) => unimplemented!(),
-            ElfSectionContent::Unknown(_) => unimplemented!(),
+            ElfSectionContent::Note(n) => n.notes.iter().map(|note| note.size(bits, n.align)).sum(),
+            ElfSectionContent::VersionSymbols(v) => u16::size(bits) * v.versions.len(),
+            ElfSectionContent::VersionNeeded(v) => {
+                let versions = v.files.iter().map(|file| file.versions.len()).sum::<usize>();
+                RawVersionNeeded::size(bits) * v.files.len()
+                    + RawVersionNeededAux::size(bits) * versions
+            }
+            ElfSectionContent::VersionDefined(v) => {
+                let names = v.versions.iter().map(|version| version.names.len()).sum::<usize>();
+                RawVersionDefinition::size(bits) * v.versions.len()
+                    + RawVersionDefinitionAux::size(bits) * names
+            }
+            ElfSectionContent::Unknown(u) => u.raw.len(),
         }
     }
 }
@@ -169,29 +407,97 @@ pub enum ElfDeduplication {
 }
 
 #[derive(Debug)]
-pub struct ElfProgramSection {
+pub struct ElfProgramSection<I: ElfIds> {
     pub perms: ElfPermissions,
     pub deduplication: ElfDeduplication,
+    pub tls: bool,
+    pub align: u64,
+    /// Section this one must be ordered with when linking (`SHF_LINK_ORDER`), if any.
+    pub link_order: Option<I::SectionId>,
+    /// Whether the section is an array of function pointers called at startup or exit, rather
+    /// than plain program data.
+    pub functions_array: Option<ElfFunctionsArray>,
+    /// Compression of the section in the file it was read from (`SHF_COMPRESSED`), if any. The
+    /// raw content is always uncompressed, as it's decompressed when reading. The writer ignores
+    /// this and always emits the section uncompressed: recompressing is not supported yet, as
+    /// plinky_utils only implements zlib decompression.
+    pub compression: Option<ElfCompression>,
     pub raw: RawBytes,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ElfCompression {
+    /// `ELFCOMPRESS_ZLIB`.
+    Zlib,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ElfFunctionsArray {
+    /// `SHT_PREINIT_ARRAY`, called before any other initialization function.
+    PreInit,
+    /// `SHT_INIT_ARRAY`, called at startup.
+    Init,
+    /// `SHT_FINI_ARRAY`, called at exit.
+    Fini,
+}
+
 #[derive(Debug)]
 pub struct ElfUninitializedSection {
     pub perms: ElfPermissions,
+    pub tls: bool,
+    pub align: u64,
     pub len: u64,
 }
 
-#[derive(Debug)]
+pub const NT_GNU_BUILD_ID: u32 = 3;
+
+#[derive(Debug, Clone)]
 pub struct ElfNotesTable {
+    /// Alignment the name and the value of each note are padded to: 4 bytes for most notes, or
+    /// 8 bytes for notes like `.note.gnu.property` on 64-bit objects.
+    pub align: u64,
     pub notes: Vec<ElfNote>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ElfNote {
+    /// `NT_GNU_BUILD_ID`, uniquely identifying the build that produced the object.
+    GnuBuildId(Vec<u8>),
     Unknown(ElfUnknownNote),
 }
 
-#[derive(Debug)]
+impl ElfNote {
+    pub fn name(&self) -> &str {
+        match self {
+            ElfNote::GnuBuildId(_) => "GNU",
+            ElfNote::Unknown(note) => &note.name,
+        }
+    }
+
+    pub fn type_(&self) -> u32 {
+        match self {
+            ElfNote::GnuBuildId(_) => NT_GNU_BUILD_ID,
+            ElfNote::Unknown(note) => note.type_,
+        }
+    }
+
+    pub fn value(&self) -> &[u8] {
+        match self {
+            ElfNote::GnuBuildId(id) => id,
+            ElfNote::Unknown(note) => &note.value,
+        }
+    }
+
+    /// Size of the note including its header, with the name and the value padded to `align`.
+    pub fn size(&self, bits: ElfClass, align: u64) -> usize {
+        let align = align as usize;
+        // The name is stored as a zero-terminated string.
+        let header_and_name = RawNoteHeader::size(bits) + self.name().len() + 1;
+        header_and_name.next_multiple_of(align) + self.value().len().next_multiple_of(align)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ElfUnknownNote {
     pub name: String,
     pub value: RawBytes,
@@ -236,6 +542,8 @@ pub enum ElfSymbolType {
     Function,
     Section,
     File,
+    GnuIfunc,
+    Tls,
     Unknown(u8),
 }
 
@@ -272,7 +580,7 @@ pub struct ElfRelocation<I: ElfIds> {
     pub addend: Option<i64>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum ElfRelocationType {
     // x86
@@ -288,6 +596,7 @@ pub enum ElfRelocationType {
     X86_GOTOff,
     X86_GOTPC,
     X86_GOT32X,
+    X86_IRelative,
     // x86_64
     X86_64_None,
     X86_64_64,
@@ -334,10 +643,147 @@ pub enum ElfRelocationType {
     X86_64_Code_6_GOTPCRelX,
     X86_64_Code_6_GOTPCOff,
     X86_64_Code_6_GOTPC32_TLSDesc,
+    // AArch64
+    Aarch64_None,
+    Aarch64_Abs64,
+    Aarch64_Abs32,
+    Aarch64_Abs16,
+    Aarch64_Prel64,
+    Aarch64_Prel32,
+    Aarch64_Prel16,
+    Aarch64_AdrPrelPgHi21,
+    Aarch64_AddAbsLo12Nc,
+    Aarch64_Jump26,
+    Aarch64_Call26,
+    Aarch64_LdSt64AbsLo12Nc,
+    Aarch64_AdrGotPage,
+    Aarch64_Ld64GotLo12Nc,
+    Aarch64_Copy,
+    Aarch64_GlobDat,
+    Aarch64_JumpSlot,
+    Aarch64_Relative,
+    Aarch64_IRelative,
     // Other:
     Unknown(u32),
 }
 
+/// Generates the conversions between [`ElfRelocationType`] and the raw numbers stored in the
+/// `r_info` field, from a single table per machine.
+macro_rules! relocation_types {
+    ($($machine:ident { $($raw:literal => $variant:ident,)* })*) => {
+        impl ElfRelocationType {
+            pub fn from_raw(machine: ElfMachine, raw: u32) -> Self {
+                match machine {
+                    $(ElfMachine::$machine => match raw {
+                        $($raw => ElfRelocationType::$variant,)*
+                        other => ElfRelocationType::Unknown(other),
+                    },)*
+                }
+            }
+
+            pub fn to_raw(self) -> u32 {
+                match self {
+                    $($(ElfRelocationType::$variant => $raw,)*)*
+                    ElfRelocationType::Unknown(raw) => raw,
+                }
+            }
+
+            #[cfg(test)]
+            fn known(machine: ElfMachine) -> &'static [ElfRelocationType] {
+                match machine {
+                    $(ElfMachine::$machine => &[$(ElfRelocationType::$variant,)*],)*
+                }
+            }
+        }
+    };
+}
+
+relocation_types! {
+    X86 {
+        0 => X86_None,
+        1 => X86_32,
+        2 => X86_PC32,
+        3 => X86_GOT32,
+        4 => X86_PLT32,
+        5 => X86_COPY,
+        6 => X86_GLOB_DAT,
+        7 => X86_JMP_Slot,
+        8 => X86_Relative,
+        9 => X86_GOTOff,
+        10 => X86_GOTPC,
+        43 => X86_GOT32X,
+        42 => X86_IRelative,
+    }
+    X86_64 {
+        0 => X86_64_None,
+        1 => X86_64_64,
+        2 => X86_64_PC32,
+        3 => X86_64_GOT32,
+        4 => X86_64_PLT32,
+        5 => X86_64_Copy,
+        6 => X86_64_GlobDat,
+        7 => X86_64_JumpSlot,
+        8 => X86_64_Relative,
+        9 => X86_64_GOTPCRel,
+        10 => X86_64_32,
+        11 => X86_64_32S,
+        12 => X86_64_16,
+        13 => X86_64_PC16,
+        14 => X86_64_8,
+        15 => X86_64_PC8,
+        16 => X86_64_DTPMod64,
+        17 => X86_64_DTPOff64,
+        18 => X86_64_TPOff64,
+        19 => X86_64_TLSGD,
+        20 => X86_64_TLSLD,
+        21 => X86_64_DTPOff32,
+        22 => X86_64_GOTTPOff,
+        23 => X86_64_TPOff32,
+        24 => X86_64_PC64,
+        25 => X86_64_GOTOff64,
+        26 => X86_64_GOTPC32,
+        32 => X86_64_Size32,
+        33 => X86_64_Size64,
+        34 => X86_64_GOTPC32_TLSDesc,
+        35 => X86_64_TLSDescCall,
+        36 => X86_64_TLSDesc,
+        37 => X86_64_IRelative,
+        38 => X86_64_IRelative64,
+        41 => X86_64_GOTPCRelX,
+        42 => X86_64_Rex_GOTPCRelX,
+        43 => X86_64_Code_4_GOTPCRelX,
+        44 => X86_64_Code_4_GOTPCOff,
+        45 => X86_64_Code_4_GOTPC32_TLSDesc,
+        46 => X86_64_Code_5_GOTPCRelX,
+        47 => X86_64_Code_5_GOTPCOff,
+        48 => X86_64_Code_5_GOTPC32_TLSDesc,
+        49 => X86_64_Code_6_GOTPCRelX,
+        50 => X86_64_Code_6_GOTPCOff,
+        51 => X86_64_Code_6_GOTPC32_TLSDesc,
+    }
+    Aarch64 {
+        0 => Aarch64_None,
+        257 => Aarch64_Abs64,
+        258 => Aarch64_Abs32,
+        259 => Aarch64_Abs16,
+        260 => Aarch64_Prel64,
+        261 => Aarch64_Prel32,
+        262 => Aarch64_Prel16,
+        275 => Aarch64_AdrPrelPgHi21,
+        277 => Aarch64_AddAbsLo12Nc,
+        282 => Aarch64_Jump26,
+        283 => Aarch64_Call26,
+        286 => Aarch64_LdSt64AbsLo12Nc,
+        311 => Aarch64_AdrGotPage,
+        312 => Aarch64_Ld64GotLo12Nc,
+        1024 => Aarch64_Copy,
+        1025 => Aarch64_GlobDat,
+        1026 => Aarch64_JumpSlot,
+        1027 => Aarch64_Relative,
+        1032 => Aarch64_IRelative,
+    }
+}
+
 #[derive(Debug)]
 pub struct ElfGroup<I: ElfIds> {
     pub symbol_table: I::SectionId,
@@ -353,6 +799,88 @@ pub struct ElfHash<I: ElfIds> {
     pub chain: Vec<u32>,
 }
 
+/// GNU-style hash table (`SHT_GNU_HASH`). Only the symbols starting from `symbol_offset` in the
+/// symbol table are hashed, and `chain` contains one entry for each of them.
+#[derive(Debug)]
+pub struct ElfGnuHash<I: ElfIds> {
+    pub symbol_table: I::SectionId,
+    pub symbol_offset: u32,
+    pub bloom_shift: u32,
+    pub bloom: Vec<u64>,
+    pub buckets: Vec<u32>,
+    pub chain: Vec<u32>,
+}
+
+/// Version of each symbol of the dynamic symbol table (`SHT_GNU_versym`), in the same order as
+/// the symbols in the table.
+#[derive(Debug)]
+pub struct ElfVersionSymbols<I: ElfIds> {
+    pub symbol_table: I::SectionId,
+    pub versions: Vec<ElfSymbolVersion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElfSymbolVersion {
+    /// Index of a version defined or needed by the object. Index 0 is for local symbols, and
+    /// index 1 is for global symbols without a version.
+    pub index: u16,
+    /// Hidden versions are not used to resolve references that don't specify a version.
+    pub hidden: bool,
+}
+
+/// Versions needed from the shared objects this object depends on (`SHT_GNU_verneed`).
+#[derive(Debug)]
+pub struct ElfVersionNeeded<I: ElfIds> {
+    pub string_table: I::SectionId,
+    pub files: Vec<ElfVersionNeededFile<I>>,
+}
+
+#[derive(Debug)]
+pub struct ElfVersionNeededFile<I: ElfIds> {
+    pub file: I::StringId,
+    pub versions: Vec<ElfNeededVersion<I>>,
+}
+
+#[derive(Debug)]
+pub struct ElfNeededVersion<I: ElfIds> {
+    pub name: I::StringId,
+    pub hash: u32,
+    pub flags: ElfVersionFlags,
+    /// Index used by the version symbols table to refer to this version.
+    pub index: u16,
+}
+
+/// Versions defined by this object (`SHT_GNU_verdef`).
+#[derive(Debug)]
+pub struct ElfVersionDefined<I: ElfIds> {
+    pub string_table: I::SectionId,
+    pub versions: Vec<ElfDefinedVersion<I>>,
+}
+
+#[derive(Debug)]
+pub struct ElfDefinedVersion<I: ElfIds> {
+    pub flags: ElfVersionFlags,
+    /// Index used by the version symbols table to refer to this version.
+    pub index: u16,
+    pub hash: u32,
+    /// Name of the version, followed by the names of the versions it inherits from.
+    pub names: Vec<I::StringId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Bitfield)]
+#[bitfield_repr(u16)]
+#[bitfield_display_comma_separated]
+pub struct ElfVersionFlags {
+    /// The version definition refers to the object itself.
+    #[bit(0)]
+    pub base: bool,
+    /// References to the version don't fail if it's missing.
+    #[bit(1)]
+    pub weak: bool,
+    #[bit(2)]
+    pub info: bool,
+}
+
 #[derive(Debug)]
 pub struct ElfDynamic<I: ElfIds> {
     pub string_table: I::SectionId,
@@ -387,10 +915,36 @@ pub enum ElfDynamicDirective {
     RelocationsWillModifyText,
     JumpRel { address: u64 },
     BindNow,
+    InitArray { address: u64 },
+    FiniArray { address: u64 },
+    InitArraySize { bytes: u64 },
+    FiniArraySize { bytes: u64 },
+    PreInitArray { address: u64 },
+    PreInitArraySize { bytes: u64 },
+    RelrSize { bytes: u64 },
+    Relr { address: u64 },
+    RelrEntrySize { bytes: u64 },
+    Flags(ElfDynamicFlags),
     Flags1(ElfDynamicFlags1),
     Unknown { tag: u64, value: u64 },
 }
 
+#[derive(Debug, Bitfield)]
+#[bitfield_repr(u64)]
+#[bitfield_display_comma_separated]
+pub struct ElfDynamicFlags {
+    #[bit(0)]
+    pub origin: bool,
+    #[bit(1)]
+    pub symbolic: bool,
+    #[bit(2)]
+    pub text_relocations: bool,
+    #[bit(3)]
+    pub bind_now: bool,
+    #[bit(4)]
+    pub static_tls: bool,
+}
+
 #[derive(Debug, Bitfield)]
 #[bitfield_repr(u64)]
 #[bitfield_display_comma_separated]
@@ -421,6 +975,7 @@ pub enum ElfSegmentType {
     Load,
     Dynamic,
     Note,
+    Tls,
     GnuStack,
     GnuRelRO,
     Null,
@@ -432,9 +987,18 @@ pub enum ElfSegmentContent<I: ElfIds> {
     Empty,
     ElfHeader,
     Sections(Vec<I::SectionId>),
+    /// Notes read directly from the segment, when no section covers it (like in executables
+    /// stripped of their section headers).
+    Notes(ElfNotesSegmentContent),
     Unknown(ElfUnknownSegmentContent),
 }
 
+#[derive(Debug, Clone)]
+pub struct ElfNotesSegmentContent {
+    pub location: ElfUnknownSegmentContent,
+    pub notes: ElfNotesTable,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ElfUnknownSegmentContent {
     pub file_offset: u64,
@@ -493,3 +1057,634 @@ impl Deref for RawBytes {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::serial::SerialIds;
+    use crate::ElfObjectBuilder;
+
+    #[test]
+    fn test_dynamic_flags_display_only_set_bits() {
+        let flags = |origin, text_relocations, static_tls| ElfDynamicFlags {
+            origin,
+            symbolic: false,
+            text_relocations,
+            bind_now: false,
+            static_tls,
+        };
+        assert_eq!("", flags(false, false, false).to_string());
+        assert_eq!("text_relocations", flags(false, true, false).to_string());
+        assert_eq!("origin, static_tls", flags(true, false, true).to_string());
+    }
+
+    #[test]
+    fn test_into_parts_roundtrip() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            [0x90, 0xc3],
+        );
+        let object = builder.build();
+
+        let mut before = Vec::new();
+        object.write(&mut before).unwrap();
+
+        let (env, type_, entry, sections, segments) = object.into_parts();
+        assert_eq!(3, sections.len());
+        let rebuilt = ElfObject::<SerialIds> { env, type_, entry, sections, segments };
+        let mut after = Vec::new();
+        rebuilt.write(&mut after).unwrap();
+
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_compute_layout_matches_write() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let text = builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            [0x90, 0x90, 0xc3],
+        );
+        let bss = builder.add_uninitialized_section(
+            ".bss",
+            ElfPermissions { read: true, write: true, execute: false },
+            16,
+        );
+        let mut object = builder.build();
+        object.sections.get_mut(&text).unwrap().memory_address = 0x401000;
+        object.segments.push(ElfSegment {
+            type_: ElfSegmentType::Load,
+            perms: ElfPermissions { read: true, write: false, execute: true },
+            content: ElfSegmentContent::Sections(vec![text]),
+            align: 0x1000,
+        });
+
+        let layout = object.compute_layout(ElfWriteOptions::default()).unwrap();
+        let mut written = Vec::new();
+        object.write(&mut written).unwrap();
+
+        assert_eq!(written.len() as u64, layout.len);
+        let text_layout = layout.sections[&text];
+        assert_eq!(0x401000, text_layout.memory_address);
+        assert_eq!(3, text_layout.file_size);
+        let offset = text_layout.file_offset.unwrap() as usize;
+        assert_eq!(0, offset % 0x1000);
+        assert_eq!(&[0x90, 0x90, 0xc3], &written[offset..offset + 3]);
+        assert_eq!(None, layout.sections[&bss].file_offset);
+
+        let stripped = ElfWriteOptions { omit_section_headers: true, ..ElfWriteOptions::default() };
+        assert!(object.compute_layout(stripped).unwrap().section_headers_offset.is_none());
+        assert!(layout.section_headers_offset.is_some());
+    }
+
+    #[test]
+    fn test_padding_uses_fill_byte() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let text = builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            [0x90, 0x90, 0xc3],
+        );
+        let mut object = builder.build();
+        object.sections.get_mut(&text).unwrap().memory_address = 0x401000;
+        object.segments.push(ElfSegment {
+            type_: ElfSegmentType::Load,
+            perms: ElfPermissions { read: true, write: false, execute: true },
+            content: ElfSegmentContent::Sections(vec![text]),
+            align: 0x1000,
+        });
+
+        // The headers are way smaller than a page, so the bytes right before the page-aligned
+        // .text are padding.
+        for fill_byte in [0x00, 0xff] {
+            let options = ElfWriteOptions { fill_byte, ..ElfWriteOptions::default() };
+            let offset = object.compute_layout(options).unwrap().sections[&text]
+                .file_offset
+                .unwrap() as usize;
+            let mut written = Vec::new();
+            object.write_with_options(&mut written, options).unwrap();
+            assert!(written[offset - 0x10..offset].iter().all(|&byte| byte == fill_byte));
+            assert_eq!(&[0x90, 0x90, 0xc3], &written[offset..offset + 3]);
+        }
+    }
+
+    fn segments_object(ranges: &[(u64, u64)]) -> ElfObject<SerialIds> {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let perms = ElfPermissions { read: true, write: false, execute: true };
+        let ids = ranges
+            .iter()
+            .map(|&(_, len)| builder.add_program_section(".text", perms, vec![0; len as usize]))
+            .collect::<Vec<_>>();
+        let mut object = builder.build();
+        for (id, &(address, _)) in ids.iter().zip(ranges) {
+            object.sections.get_mut(id).unwrap().memory_address = address;
+            object.segments.push(ElfSegment {
+                type_: ElfSegmentType::Load,
+                perms,
+                content: ElfSegmentContent::Sections(vec![*id]),
+                align: 0x1000,
+            });
+        }
+        object
+    }
+
+    #[test]
+    fn test_program_headers_without_overlaps() {
+        let object = segments_object(&[(0x1000, 0x10), (0x2000, 0x10), (0x1010, 0x10)]);
+        object.program_headers_overlap_check().unwrap();
+    }
+
+    #[test]
+    fn test_program_headers_overlapping_in_memory() {
+        let object = segments_object(&[(0x1000, 0x10), (0x2000, 0x10), (0x1008, 0x10)]);
+        match object.program_headers_overlap_check() {
+            Err(SegmentsCheckError::OverlappingMemory { first: 0, second: 2 }) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_program_headers_overlapping_sections() {
+        // Merge the two segments into one, containing both overlapping sections.
+        let mut object = segments_object(&[(0x1000, 0x10), (0x1008, 0x10)]);
+        let second = object.segments.pop().unwrap();
+        if let (ElfSegmentContent::Sections(first), ElfSegmentContent::Sections(second)) =
+            (&mut object.segments[0].content, second.content)
+        {
+            first.extend(second);
+        }
+        match object.program_headers_overlap_check() {
+            Err(SegmentsCheckError::OverlappingSections { segment: 0, .. }) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_program_headers_overlapping_in_file() {
+        let mut object = segments_object(&[]);
+        for file_offset in [0x1000, 0x1800] {
+            object.segments.push(ElfSegment {
+                type_: ElfSegmentType::Load,
+                perms: ElfPermissions { read: true, write: false, execute: false },
+                content: ElfSegmentContent::Unknown(ElfUnknownSegmentContent {
+                    file_offset,
+                    virtual_address: file_offset * 0x10,
+                    file_size: 0x1000,
+                    memory_size: 0x1000,
+                }),
+                align: 0x1000,
+            });
+        }
+        match object.program_headers_overlap_check() {
+            Err(SegmentsCheckError::OverlappingFile { first: 0, second: 1 }) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_type_predicates() {
+        let predicates = |type_| {
+            let object = ElfObjectBuilder::x86_64(type_).build();
+            [
+                object.is_relocatable(),
+                object.is_executable(),
+                object.is_shared_object(),
+                object.is_core(),
+            ]
+        };
+
+        assert_eq!([true, false, false, false], predicates(ElfType::Relocatable));
+        assert_eq!([false, true, false, false], predicates(ElfType::Executable));
+        assert_eq!([false, false, true, false], predicates(ElfType::SharedObject));
+        assert_eq!([false, false, false, true], predicates(ElfType::Core));
+    }
+
+    #[test]
+    fn test_note_section_roundtrip() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let note = builder.add_program_section(
+            ".note.test",
+            ElfPermissions { read: true, write: false, execute: false },
+            [],
+        );
+        let mut object = builder.build();
+        object.sections.get_mut(&note).unwrap().content = ElfSectionContent::Note(ElfNotesTable {
+            align: 8,
+            notes: vec![ElfNote::Unknown(ElfUnknownNote {
+                name: "plinky".into(),
+                value: RawBytes(vec![1, 2, 3]),
+                type_: 42,
+            })],
+        });
+
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        let loaded =
+            ElfObject::load(&mut std::io::Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+
+        let notes = loaded
+            .sections
+            .values()
+            .filter_map(|section| match &section.content {
+                ElfSectionContent::Note(table) => Some(&table.notes),
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        let [ElfNote::Unknown(note)] = notes.as_slice() else { panic!("expected a single note") };
+        assert_eq!("plinky", note.name);
+        assert_eq!(vec![1, 2, 3], note.value.0);
+        assert_eq!(42, note.type_);
+    }
+
+    #[test]
+    fn test_gnu_build_id_note_roundtrip() {
+        let build_id: Vec<u8> = (0..20).collect();
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let note = builder.add_program_section(
+            ".note.gnu.build-id",
+            ElfPermissions { read: true, write: false, execute: false },
+            [],
+        );
+        let mut object = builder.build();
+        object.sections.get_mut(&note).unwrap().content = ElfSectionContent::Note(ElfNotesTable {
+            align: 4,
+            notes: vec![ElfNote::GnuBuildId(build_id.clone())],
+        });
+
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        let loaded =
+            ElfObject::load(&mut std::io::Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+        let mut rewritten = Vec::new();
+        loaded.write(&mut rewritten).unwrap();
+        assert_eq!(buffer, rewritten);
+
+        let table = loaded
+            .sections
+            .values()
+            .find_map(|section| match &section.content {
+                ElfSectionContent::Note(table) => Some(table),
+                _ => None,
+            })
+            .unwrap();
+        let [ElfNote::GnuBuildId(loaded_id)] = table.notes.as_slice() else {
+            panic!("expected a single build-id note")
+        };
+        assert_eq!(&build_id, loaded_id);
+        assert_eq!(Some(&build_id[..]), loaded.gnu_build_id());
+    }
+
+    #[test]
+    fn test_gnu_property_note_roundtrip() {
+        // NT_GNU_PROPERTY_TYPE_0 with GNU_PROPERTY_X86_FEATURE_1_AND set to IBT and SHSTK. The
+        // property array is padded to 8 bytes on 64-bit objects, and to 4 bytes on 32-bit ones.
+        let property = [0x02, 0x00, 0x00, 0xc0, 0x04, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00];
+        let builders = [
+            (ElfObjectBuilder::x86_64(ElfType::Relocatable), 8, 4),
+            (ElfObjectBuilder::x86(ElfType::Relocatable), 4, 0),
+        ];
+        for (mut builder, align, padding) in builders {
+            let note = builder.add_program_section(
+                ".note.gnu.property",
+                ElfPermissions { read: true, write: false, execute: false },
+                [],
+            );
+            let mut value = property.to_vec();
+            value.extend(std::iter::repeat(0).take(padding));
+            let mut object = builder.build();
+            object.sections.get_mut(&note).unwrap().content =
+                ElfSectionContent::Note(ElfNotesTable {
+                    align,
+                    notes: vec![ElfNote::Unknown(ElfUnknownNote {
+                        name: "GNU".into(),
+                        value: RawBytes(value.clone()),
+                        type_: 5,
+                    })],
+                });
+
+            let mut buffer = Vec::new();
+            object.write(&mut buffer).unwrap();
+            let loaded =
+                ElfObject::load(&mut std::io::Cursor::new(&buffer), &mut SerialIds::new()).unwrap();
+            let mut rewritten = Vec::new();
+            loaded.write(&mut rewritten).unwrap();
+            assert_eq!(buffer, rewritten);
+
+            let table = loaded
+                .sections
+                .values()
+                .find_map(|section| match &section.content {
+                    ElfSectionContent::Note(table) => Some(table),
+                    _ => None,
+                })
+                .unwrap();
+            assert_eq!(align, table.align);
+            let [ElfNote::Unknown(note)] = table.notes.as_slice() else {
+                panic!("expected a single note")
+            };
+            assert_eq!("GNU", note.name);
+            assert_eq!(value, note.value.0);
+            assert_eq!(5, note.type_);
+        }
+    }
+
+    #[test]
+    fn test_ifunc_symbol_roundtrip() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let text = builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            [0xc3],
+        );
+        builder.add_symbol(
+            "resolver",
+            ElfSymbolBinding::Global,
+            ElfSymbolType::GnuIfunc,
+            ElfSymbolDefinition::Section(text),
+            0,
+            1,
+        );
+
+        let mut buffer = Vec::new();
+        builder.build().write(&mut buffer).unwrap();
+        let loaded =
+            ElfObject::load(&mut std::io::Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+
+        let ifuncs = loaded
+            .sections
+            .values()
+            .filter_map(|section| match &section.content {
+                ElfSectionContent::SymbolTable(table) => Some(table.symbols.values()),
+                _ => None,
+            })
+            .flatten()
+            .filter(|symbol| matches!(symbol.type_, ElfSymbolType::GnuIfunc))
+            .count();
+        assert_eq!(1, ifuncs);
+    }
+
+    fn serialize_text_object(text: &[u8]) -> Vec<u8> {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        builder.add_program_section(
+            ".text",
+            ElfPermissions { read: true, write: false, execute: true },
+            text.to_vec(),
+        );
+        let mut buffer = Vec::new();
+        builder.build().write(&mut buffer).unwrap();
+        buffer
+    }
+
+    fn load_content_hash(bytes: Vec<u8>) -> [u8; 32] {
+        ElfObject::load(&mut std::io::Cursor::new(bytes), &mut SerialIds::new())
+            .unwrap()
+            .content_hash()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_content_hash_of_identical_objects() {
+        let first = serialize_text_object(&[0x90, 0xc3]);
+        let second = serialize_text_object(&[0x90, 0xc3]);
+        assert_eq!(first, second);
+        assert_eq!(load_content_hash(first), load_content_hash(second));
+    }
+
+    #[test]
+    fn test_content_hash_of_different_objects() {
+        let first = serialize_text_object(&[0x90, 0xc3]);
+        let second = serialize_text_object(&[0xc3, 0x90]);
+        assert_ne!(load_content_hash(first), load_content_hash(second));
+    }
+
+    #[test]
+    fn test_content_hash_ignores_non_allocated_sections() {
+        let with_comment = |comment: &[u8]| {
+            let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+            builder.add_program_section(
+                ".text",
+                ElfPermissions { read: true, write: false, execute: true },
+                [0x90, 0xc3],
+            );
+            builder.add_program_section(
+                ".comment",
+                ElfPermissions { read: false, write: false, execute: false },
+                comment,
+            );
+            builder.build().content_hash().unwrap()
+        };
+        assert_eq!(with_comment(b"GCC 13.2.0\0"), with_comment(b"clang 17.0.6\0"));
+    }
+
+    #[test]
+    fn test_content_hash_includes_dynamic_relocations() {
+        let with_addend = |addend: i64| {
+            let mut builder = ElfObjectBuilder::x86_64(ElfType::SharedObject);
+            let perms = ElfPermissions { read: true, write: false, execute: false };
+            let rela = builder.add_program_section(".rela.dyn", perms, []);
+            // Adding a symbol makes the builder emit the symbol table with the null symbol.
+            builder.add_symbol(
+                "foo",
+                ElfSymbolBinding::Global,
+                ElfSymbolType::NoType,
+                ElfSymbolDefinition::Absolute,
+                0,
+                0,
+            );
+            let mut object = builder.build();
+
+            let null = *object.sections.keys().next().unwrap();
+            let null_symbol = *object
+                .sections
+                .values()
+                .find_map(|section| match &section.content {
+                    ElfSectionContent::SymbolTable(table) => table.symbols.keys().next(),
+                    _ => None,
+                })
+                .unwrap();
+            object.sections.get_mut(&rela).unwrap().content =
+                ElfSectionContent::RelocationsTable(ElfRelocationsTable {
+                    symbol_table: null,
+                    applies_to_section: null,
+                    relocations: vec![ElfRelocation {
+                        offset: 0x3000,
+                        symbol: null_symbol,
+                        relocation_type: ElfRelocationType::X86_64_Relative,
+                        addend: Some(addend),
+                    }],
+                });
+            // Relocations tables are only allocated when they're loaded by a segment.
+            object.segments.push(ElfSegment {
+                type_: ElfSegmentType::Load,
+                perms,
+                content: ElfSegmentContent::Sections(vec![rela]),
+                align: 0x1000,
+            });
+            object.content_hash().unwrap()
+        };
+        assert_eq!(with_addend(0x3001), with_addend(0x3001));
+        assert_ne!(with_addend(0x3001), with_addend(0x3002));
+    }
+
+    #[test]
+    fn test_relr_directives_roundtrip() {
+        let directives = roundtrip_dynamic(vec![
+            ElfDynamicDirective::Relr { address: 0x1000 },
+            ElfDynamicDirective::RelrSize { bytes: 16 },
+            ElfDynamicDirective::RelrEntrySize { bytes: 8 },
+            ElfDynamicDirective::Null,
+        ]);
+        assert!(matches!(
+            directives.as_slice(),
+            [
+                ElfDynamicDirective::Relr { address: 0x1000 },
+                ElfDynamicDirective::RelrSize { bytes: 16 },
+                ElfDynamicDirective::RelrEntrySize { bytes: 8 },
+                ElfDynamicDirective::Null,
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_init_fini_arrays_directives_roundtrip() {
+        let directives = roundtrip_dynamic(vec![
+            ElfDynamicDirective::PreInitArray { address: 0x1000 },
+            ElfDynamicDirective::PreInitArraySize { bytes: 8 },
+            ElfDynamicDirective::InitArray { address: 0x2000 },
+            ElfDynamicDirective::InitArraySize { bytes: 16 },
+            ElfDynamicDirective::FiniArray { address: 0x3000 },
+            ElfDynamicDirective::FiniArraySize { bytes: 24 },
+            ElfDynamicDirective::Null,
+        ]);
+        assert!(matches!(
+            directives.as_slice(),
+            [
+                ElfDynamicDirective::PreInitArray { address: 0x1000 },
+                ElfDynamicDirective::PreInitArraySize { bytes: 8 },
+                ElfDynamicDirective::InitArray { address: 0x2000 },
+                ElfDynamicDirective::InitArraySize { bytes: 16 },
+                ElfDynamicDirective::FiniArray { address: 0x3000 },
+                ElfDynamicDirective::FiniArraySize { bytes: 24 },
+                ElfDynamicDirective::Null,
+            ]
+        ));
+    }
+
+    /// Write a shared object with a dynamic section containing the directives, and read it back.
+    fn roundtrip_dynamic(directives: Vec<ElfDynamicDirective>) -> Vec<ElfDynamicDirective> {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::SharedObject);
+        let perms = ElfPermissions { read: true, write: false, execute: false };
+        let dynstr = builder.add_program_section(".dynstr", perms, []);
+        let dynamic = builder.add_program_section(".dynamic", perms, []);
+        let mut object = builder.build();
+        object.sections.get_mut(&dynstr).unwrap().content =
+            ElfSectionContent::StringTable(ElfStringTable::new(BTreeMap::from([(0, "".into())])));
+        object.sections.get_mut(&dynamic).unwrap().content =
+            ElfSectionContent::Dynamic(ElfDynamic { string_table: dynstr, directives });
+
+        let mut buffer = Vec::new();
+        object.write(&mut buffer).unwrap();
+        let loaded =
+            ElfObject::load(&mut std::io::Cursor::new(buffer), &mut SerialIds::new()).unwrap();
+
+        loaded
+            .sections
+            .into_values()
+            .find_map(|section| match section.content {
+                ElfSectionContent::Dynamic(dynamic) => Some(dynamic.directives),
+                _ => None,
+            })
+            .expect("missing dynamic section")
+    }
+
+    #[test]
+    fn test_note_content_size() {
+        let content = ElfSectionContent::<SerialIds>::Note(ElfNotesTable {
+            align: 8,
+            notes: vec![
+                // 12 bytes of header + "GNU\0" is already aligned, the value is padded to 8.
+                ElfNote::Unknown(ElfUnknownNote {
+                    name: "GNU".into(),
+                    value: RawBytes(vec![1, 2, 3, 4, 5]),
+                    type_: 3,
+                }),
+                // 12 bytes of header + "plinky\0" is padded to 24, the value is empty.
+                ElfNote::Unknown(ElfUnknownNote {
+                    name: "plinky".into(),
+                    value: RawBytes(Vec::new()),
+                    type_: 1,
+                }),
+            ],
+        });
+        assert_eq!(16 + 8 + 24, content.content_size(ElfClass::Elf64));
+        assert_eq!(16 + 8 + 24, content.content_size(ElfClass::Elf32));
+    }
+
+    #[test]
+    fn test_note_content_size_aligned_to_4() {
+        let content = ElfSectionContent::<SerialIds>::Note(ElfNotesTable {
+            align: 4,
+            notes: vec![
+                // 12 bytes of header + "GNU\0" is already aligned, the 5 bytes of value are padded
+                // to the next multiple of 4 (8 bytes).
+                ElfNote::Unknown(ElfUnknownNote {
+                    name: "GNU".into(),
+                    value: RawBytes(vec![1, 2, 3, 4, 5]),
+                    type_: 3,
+                }),
+                // 12 bytes of header + "plinky\0" is padded to the next multiple of 4 (20 bytes),
+                // the value is empty.
+                ElfNote::Unknown(ElfUnknownNote {
+                    name: "plinky".into(),
+                    value: RawBytes(Vec::new()),
+                    type_: 1,
+                }),
+            ],
+        });
+        assert_eq!(16 + 8 + 20, content.content_size(ElfClass::Elf64));
+    }
+
+    #[test]
+    fn test_unknown_content_size() {
+        let content = ElfSectionContent::<SerialIds>::Unknown(ElfUnknownSection {
+            id: 0x6fff4c03,
+            raw: RawBytes(vec![0; 13]),
+        });
+        assert_eq!(13, content.content_size(ElfClass::Elf64));
+    }
+
+    #[test]
+    fn test_relocation_type_raw_roundtrip() {
+        for machine in [ElfMachine::X86, ElfMachine::X86_64, ElfMachine::Aarch64] {
+            for &relocation_type in ElfRelocationType::known(machine) {
+                let raw = relocation_type.to_raw();
+                assert_eq!(relocation_type, ElfRelocationType::from_raw(machine, raw));
+            }
+        }
+    }
+
+    #[test]
+    fn test_relocation_type_raw_depends_on_machine() {
+        assert_eq!(
+            ElfRelocationType::X86_IRelative,
+            ElfRelocationType::from_raw(ElfMachine::X86, 42)
+        );
+        assert_eq!(
+            ElfRelocationType::X86_64_Rex_GOTPCRelX,
+            ElfRelocationType::from_raw(ElfMachine::X86_64, 42)
+        );
+        assert_eq!(
+            ElfRelocationType::Aarch64_Call26,
+            ElfRelocationType::from_raw(ElfMachine::Aarch64, 283)
+        );
+        assert_eq!(
+            ElfRelocationType::Unknown(12),
+            ElfRelocationType::from_raw(ElfMachine::X86, 12)
+        );
+        assert_eq!(12, ElfRelocationType::Unknown(12).to_raw());
+    }
+}
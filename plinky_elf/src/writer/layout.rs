@@ -1,19 +1,19 @@
 use crate::ids::ElfIds;
 use crate::raw::{
-    RawGroupFlags, RawHashHeader, RawHeader, RawIdentification, RawProgramHeader, RawRel, RawRela,
-    RawSectionHeader, RawSymbol,
+    RawGnuHashHeader, RawGroupFlags, RawHashHeader, RawHeader, RawIdentification, RawProgramHeader,
+    RawRel, RawRela, RawSectionHeader, RawSymbol,
 };
+use crate::writer::ElfWriteOptions;
 use crate::{
-    ElfClass, ElfObject, ElfSection, ElfSectionContent, ElfSegmentContent, ElfSegmentType,
+    ElfClass, ElfLayout, ElfObject, ElfSection, ElfSectionContent, ElfSectionLayout,
+    ElfSegmentContent, ElfSegmentType,
 };
 use plinky_macros::{Display, Error};
 use plinky_utils::raw_types::{RawType, RawTypeAsPointerSize};
 use std::collections::BTreeMap;
 
-const ALIGN: u64 = 0x1000;
-
 #[derive(Debug)]
-pub(super) struct WriteLayout<I: ElfIds> {
+pub(crate) struct WriteLayout<I: ElfIds> {
     parts: Vec<Part<I::SectionId>>,
     metadata: BTreeMap<Part<I::SectionId>, PartMetadata>,
     current_offset: u64,
@@ -23,7 +23,10 @@ pub(super) struct WriteLayout<I: ElfIds> {
 }
 
 impl<I: ElfIds> WriteLayout<I> {
-    pub(super) fn new(object: &ElfObject<I>) -> Result<Self, WriteLayoutError> {
+    pub(crate) fn new(
+        object: &ElfObject<I>,
+        options: ElfWriteOptions,
+    ) -> Result<Self, WriteLayoutError> {
         let mut layout = WriteLayout {
             parts: Vec::new(),
             metadata: BTreeMap::new(),
@@ -48,6 +51,7 @@ impl<I: ElfIds> WriteLayout<I> {
                 }
                 ElfSegmentContent::ElfHeader => Box::new(std::iter::empty()),
                 ElfSegmentContent::Sections(s) => Box::new(s.iter().map(move |s| (s, idx))),
+                ElfSegmentContent::Notes(_) => Box::new(std::iter::empty()),
                 ElfSegmentContent::Unknown(_) => Box::new(std::iter::empty()),
             })
             .collect::<BTreeMap<_, _>>();
@@ -59,7 +63,8 @@ impl<I: ElfIds> WriteLayout<I> {
         // "preamble"), and sections that belong to a segment.
         //
         // We then proceed to write all the preamble sections, and after that write all the
-        // segments while being careful of page-aligning each of them.
+        // segments while being careful of aligning each of them: the loader requires the file
+        // offset of a segment to be congruent to its address modulo its alignment.
         let mut put_in_preamble = Vec::new();
         let mut put_in_segments = BTreeMap::new();
         for (id, section) in &object.sections {
@@ -72,23 +77,25 @@ impl<I: ElfIds> WriteLayout<I> {
         for (id, section) in put_in_preamble {
             layout.add_section(id, section)?;
         }
-        for segment_sections in put_in_segments.values() {
-            layout.align_to_page();
+        for (segment_idx, segment_sections) in &put_in_segments {
+            let align = object.segments[*segment_idx].align;
+            layout.align_segment(align, segment_sections[0].1.memory_address);
             for (id, section) in segment_sections {
                 layout.add_section(id, section)?;
             }
         }
 
-        layout.add_part(
-            Part::SectionHeaders,
-            RawSectionHeader::size(layout.class) * object.sections.len(),
-        );
+        if !options.omit_section_headers {
+            layout.add_part(
+                Part::SectionHeaders,
+                RawSectionHeader::size(layout.class) * object.sections.len(),
+            );
+        }
         layout.add_part(
             Part::ProgramHeaders,
             RawProgramHeader::size(layout.class) * object.segments.len(),
         );
 
-
         Ok(layout)
     }
 
@@ -146,12 +153,38 @@ impl<I: ElfIds> WriteLayout<I> {
                         + hash.chain.len() * size,
                 )
             }
+            ElfSectionContent::GnuHash(hash) => {
+                let size = u32::size(self.class);
+                self.add_part(
+                    Part::GnuHash(id.clone()),
+                    RawGnuHashHeader::size(self.class)
+                        + hash.bloom.len() * <u64 as RawTypeAsPointerSize>::size(self.class)
+                        + hash.buckets.len() * size
+                        + hash.chain.len() * size,
+                )
+            }
             ElfSectionContent::Dynamic(dynamic) => {
                 let size = <u64 as RawTypeAsPointerSize>::size(self.class) * 2;
                 self.add_part(Part::Dynamic(id.clone()), dynamic.directives.len() * size);
             }
-            ElfSectionContent::Note(_) => {
-                return Err(WriteLayoutError::WritingNotesUnsupported);
+            ElfSectionContent::Note(table) => {
+                // Same alignments supported by the reader, which also prevents padding to zero.
+                if !matches!(table.align, 4 | 8) {
+                    return Err(WriteLayoutError::UnsupportedNoteAlignment(table.align));
+                }
+                self.add_part(Part::Note(id.clone()), section.content.content_size(self.class));
+            }
+            ElfSectionContent::VersionSymbols(_) => {
+                let len = section.content.content_size(self.class);
+                self.add_part(Part::VersionSymbols(id.clone()), len);
+            }
+            ElfSectionContent::VersionNeeded(_) => {
+                let len = section.content.content_size(self.class);
+                self.add_part(Part::VersionNeeded(id.clone()), len);
+            }
+            ElfSectionContent::VersionDefined(_) => {
+                let len = section.content.content_size(self.class);
+                self.add_part(Part::VersionDefined(id.clone()), len);
             }
             ElfSectionContent::Unknown(_) => {
                 return Err(WriteLayoutError::UnknownSection);
@@ -167,12 +200,12 @@ impl<I: ElfIds> WriteLayout<I> {
         self.current_offset += len;
     }
 
-    fn align_to_page(&mut self) {
-        let len = self.len();
-        if len % ALIGN == 0 {
+    fn align_segment(&mut self, align: u64, address: u64) {
+        let align = align.max(1);
+        let bytes_to_pad = (address % align + align - self.len() % align) % align;
+        if bytes_to_pad == 0 {
             return;
         }
-        let bytes_to_pad = ALIGN - len % ALIGN;
         self.add_part(Part::Padding(PaddingId(self.next_padding_id)), bytes_to_pad as _);
         self.next_padding_id += 1;
     }
@@ -192,24 +225,40 @@ impl<I: ElfIds> WriteLayout<I> {
     pub(super) fn metadata_of_section(&self, id: &I::SectionId) -> &PartMetadata {
         self.metadata
             .iter()
-            .filter(|(key, _)| match key {
-                Part::Identification => false,
-                Part::Header => false,
-                Part::SectionHeaders => false,
-                Part::ProgramHeaders => false,
-                Part::ProgramSection(this) => this == id,
-                Part::StringTable(this) => this == id,
-                Part::SymbolTable(this) => this == id,
-                Part::Padding(_) => false,
-                Part::Group(this) => this == id,
-                Part::Hash(this) => this == id,
-                Part::Dynamic(this) => this == id,
-                Part::RelocationsTable { id: this, .. } => this == id,
-            })
+            .filter(|(key, _)| key.section_id() == Some(id))
             .map(|(_, value)| value)
             .next()
             .unwrap()
     }
+
+    pub(crate) fn into_elf_layout(self, object: &ElfObject<I>) -> ElfLayout<I> {
+        let mut sections = object
+            .sections
+            .iter()
+            .map(|(id, section)| {
+                let layout = ElfSectionLayout {
+                    file_offset: None,
+                    file_size: 0,
+                    memory_address: section.memory_address,
+                };
+                (id.clone(), layout)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut section_headers_offset = None;
+        let mut program_headers_offset = 0;
+        for (part, metadata) in &self.metadata {
+            match part {
+                Part::SectionHeaders => section_headers_offset = Some(metadata.offset),
+                Part::ProgramHeaders => program_headers_offset = metadata.offset,
+                _ => {}
+            }
+            if let Some(section) = part.section_id().and_then(|id| sections.get_mut(id)) {
+                section.file_offset = Some(metadata.offset);
+                section.file_size = metadata.len;
+            }
+        }
+        ElfLayout { sections, section_headers_offset, program_headers_offset, len: self.len() }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
@@ -222,12 +271,41 @@ pub(super) enum Part<SectionId> {
     StringTable(SectionId),
     SymbolTable(SectionId),
     Hash(SectionId),
+    GnuHash(SectionId),
     RelocationsTable { id: SectionId, rela: bool },
     Group(SectionId),
     Dynamic(SectionId),
+    Note(SectionId),
+    VersionSymbols(SectionId),
+    VersionNeeded(SectionId),
+    VersionDefined(SectionId),
     Padding(PaddingId),
 }
 
+impl<SectionId> Part<SectionId> {
+    fn section_id(&self) -> Option<&SectionId> {
+        match self {
+            Part::Identification => None,
+            Part::Header => None,
+            Part::SectionHeaders => None,
+            Part::ProgramHeaders => None,
+            Part::ProgramSection(id) => Some(id),
+            Part::StringTable(id) => Some(id),
+            Part::SymbolTable(id) => Some(id),
+            Part::Padding(_) => None,
+            Part::Group(id) => Some(id),
+            Part::Hash(id) => Some(id),
+            Part::GnuHash(id) => Some(id),
+            Part::Dynamic(id) => Some(id),
+            Part::Note(id) => Some(id),
+            Part::VersionSymbols(id) => Some(id),
+            Part::VersionNeeded(id) => Some(id),
+            Part::VersionDefined(id) => Some(id),
+            Part::RelocationsTable { id, .. } => Some(id),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub(super) struct PaddingId(usize);
 
@@ -241,8 +319,87 @@ pub(super) struct PartMetadata {
 pub enum WriteLayoutError {
     #[display("relocation section mixing rel and rela")]
     MixedRelRela,
-    #[display("writing notes is not supported yet")]
-    WritingNotesUnsupported,
     #[display("unkown section encountered while calculating the layout")]
     UnknownSection,
+    #[display("notes aligned to {f0} bytes are not supported, only 4 and 8 are")]
+    UnsupportedNoteAlignment(u64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::serial::SerialIds;
+    use crate::{
+        ElfNote, ElfNotesTable, ElfObjectBuilder, ElfPermissions, ElfSegment, ElfType,
+        ElfUnknownNote, RawBytes,
+    };
+
+    #[test]
+    fn test_layout_with_note_section() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let note = builder.add_program_section(
+            ".note.test",
+            ElfPermissions { read: true, write: false, execute: false },
+            [],
+        );
+        let mut object = builder.build();
+        object.sections.get_mut(&note).unwrap().content = ElfSectionContent::Note(ElfNotesTable {
+            align: 8,
+            notes: vec![ElfNote::Unknown(ElfUnknownNote {
+                name: "GNU".into(),
+                value: RawBytes(vec![1, 2, 3, 4, 5]),
+                type_: 3,
+            })],
+        });
+
+        let layout = WriteLayout::<SerialIds>::new(&object, ElfWriteOptions::default()).unwrap();
+        assert!(layout.parts().contains(&Part::Note(note)));
+        assert_eq!(24, layout.metadata_of_section(&note).len);
+    }
+
+    #[test]
+    fn test_layout_with_unsupported_note_alignment() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Relocatable);
+        let note = builder.add_program_section(
+            ".note.test",
+            ElfPermissions { read: true, write: false, execute: false },
+            [],
+        );
+        let mut object = builder.build();
+        for align in [0, 2, 16] {
+            object.sections.get_mut(&note).unwrap().content =
+                ElfSectionContent::Note(ElfNotesTable { align, notes: Vec::new() });
+
+            let err = WriteLayout::<SerialIds>::new(&object, ElfWriteOptions::default()).err();
+            assert!(
+                matches!(err, Some(WriteLayoutError::UnsupportedNoteAlignment(a)) if a == align)
+            );
+        }
+    }
+
+    #[test]
+    fn test_segments_offsets_congruent_to_addresses() {
+        let mut builder = ElfObjectBuilder::x86_64(ElfType::Executable);
+        let perms = ElfPermissions { read: true, write: false, execute: true };
+        let segments = [(0x401000, 0x1000), (0x402010, 0x1000), (0x403007, 0x10), (0x403123, 1)];
+        let ids = segments
+            .iter()
+            .map(|_| builder.add_program_section(".text", perms, [0x90; 3]))
+            .collect::<Vec<_>>();
+        let mut object = builder.build();
+        for (id, &(address, align)) in ids.iter().zip(&segments) {
+            object.sections.get_mut(id).unwrap().memory_address = address;
+            object.segments.push(ElfSegment {
+                type_: ElfSegmentType::Load,
+                perms,
+                content: ElfSegmentContent::Sections(vec![*id]),
+                align,
+            });
+        }
+
+        let layout = WriteLayout::<SerialIds>::new(&object, ElfWriteOptions::default()).unwrap();
+        for (id, &(address, align)) in ids.iter().zip(&segments) {
+            assert_eq!(address % align, layout.metadata_of_section(id).offset % align);
+        }
+    }
 }
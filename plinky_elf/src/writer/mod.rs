@@ -1,25 +1,40 @@
 mod layout;
 
-pub(crate) use self::layout::WriteLayoutError;
+pub(crate) use self::layout::WriteLayout;
+pub use self::layout::WriteLayoutError;
 
 use crate::errors::WriteError;
 use crate::ids::{ElfIds, StringIdGetters};
 use crate::raw::{
-    RawGroupFlags, RawHashHeader, RawHeader, RawHeaderFlags, RawIdentification, RawProgramHeader,
-    RawProgramHeaderFlags, RawRel, RawRela, RawSectionHeader, RawSectionHeaderFlags, RawSymbol,
+    RawGnuHashHeader, RawGroupFlags, RawHashHeader, RawHeader, RawHeaderFlags, RawIdentification,
+    RawNoteHeader, RawProgramHeader, RawProgramHeaderFlags, RawRel, RawRela, RawSectionHeader,
+    RawSectionHeaderFlags, RawSymbol, RawVersionDefinition, RawVersionDefinitionAux,
+    RawVersionNeeded, RawVersionNeededAux, PN_XNUM,
 };
-use crate::writer::layout::{Part, WriteLayout};
+use crate::writer::layout::Part;
 use crate::{
-    ElfABI, ElfClass, ElfDeduplication, ElfDynamicDirective, ElfEndian, ElfMachine, ElfObject,
-    ElfPLTRelocationsMode, ElfPermissions, ElfProgramSection, ElfRelocationType, ElfSectionContent,
-    ElfSegmentContent, ElfSegmentType, ElfSymbolBinding, ElfSymbolDefinition, ElfSymbolTable,
-    ElfSymbolType, ElfSymbolVisibility, ElfType,
+    ElfABI, ElfClass, ElfDeduplication, ElfDynamicDirective, ElfEndian, ElfFunctionsArray,
+    ElfMachine, ElfNotesSegmentContent, ElfObject, ElfPLTRelocationsMode, ElfPermissions,
+    ElfProgramSection, ElfSectionContent, ElfSegmentContent, ElfSegmentType, ElfSymbolBinding,
+    ElfSymbolDefinition, ElfSymbolTable, ElfSymbolType, ElfSymbolVisibility, ElfType,
 };
 use plinky_utils::bitfields::Bitfield;
 use plinky_utils::raw_types::{RawPadding, RawType};
 use std::collections::BTreeMap;
 use std::io::Write;
 
+/// Section indices from this one onwards are reserved for special meanings.
+const SHN_LORESERVE: usize = 0xff00;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ElfWriteOptions {
+    /// Don't emit the section header table, leaving only the program headers (like `strip
+    /// --strip-section-headers`). The sections contents are still written.
+    pub omit_section_headers: bool,
+    /// Byte written in the padding between the parts of the file (zero by default).
+    pub fill_byte: u8,
+}
+
 pub(crate) struct Writer<'a, I>
 where
     I: ElfIds,
@@ -28,6 +43,7 @@ where
     writer: &'a mut dyn Write,
     layout: WriteLayout<I>,
     object: &'a ElfObject<I>,
+    options: ElfWriteOptions,
 }
 
 impl<'a, I> Writer<'a, I>
@@ -38,8 +54,29 @@ where
     pub(crate) fn new(
         writer: &'a mut dyn Write,
         object: &'a ElfObject<I>,
+        options: ElfWriteOptions,
     ) -> Result<Self, WriteError<I>> {
-        Ok(Self { writer, layout: WriteLayout::new(object)?, object })
+        // Extended section numbering is not supported: symbols would also need a SHT_SYMTAB_SHNDX
+        // section to refer to sections past the reserved range.
+        if object.sections.len() >= SHN_LORESERVE {
+            return Err(WriteError::TooManySections { count: object.sections.len() });
+        }
+        // Program header counts that don't fit in the ELF header are stored in the null section,
+        // which must be the first one for readers to find it.
+        let first_is_null = object
+            .sections
+            .values()
+            .next()
+            .is_some_and(|section| matches!(section.content, ElfSectionContent::Null));
+        if object.segments.len() >= PN_XNUM as usize
+            && (options.omit_section_headers
+                || !first_is_null
+                || u32::try_from(object.segments.len()).is_err())
+        {
+            return Err(WriteError::TooManySegments { count: object.segments.len() });
+        }
+        validate_relocations_tables(object)?;
+        Ok(Self { writer, layout: WriteLayout::new(object, options)?, object, options })
     }
 
     pub(crate) fn write(mut self) -> Result<(), WriteError<I>> {
@@ -56,7 +93,12 @@ where
                 Part::RelocationsTable { id, rela } => self.write_relocations_table(id, *rela)?,
                 Part::Group(id) => self.write_group(id)?,
                 Part::Hash(id) => self.write_hash(id)?,
+                Part::GnuHash(id) => self.write_gnu_hash(id)?,
                 Part::Dynamic(id) => self.write_dynamic(id)?,
+                Part::Note(id) => self.write_notes(id)?,
+                Part::VersionSymbols(id) => self.write_version_symbols(id)?,
+                Part::VersionNeeded(id) => self.write_version_needed(id)?,
+                Part::VersionDefined(id) => self.write_version_defined(id)?,
                 Part::Padding(_) => self.write_padding(part)?,
             }
         }
@@ -72,19 +114,32 @@ where
             },
             endian: match self.object.env.endian {
                 ElfEndian::Little => 1,
+                ElfEndian::Big => 2,
             },
             version: 1,
             abi: match self.object.env.abi {
                 ElfABI::SystemV => 0,
+                ElfABI::Gnu => 3,
             },
             abi_version: match self.object.env.abi {
                 ElfABI::SystemV => 0,
+                ElfABI::Gnu => 0,
             },
             padding: RawPadding,
         })
     }
 
     fn write_header(&mut self) -> Result<(), WriteError<I>> {
+        let (section_headers_offset, section_header_count, section_names_table_index) =
+            if self.options.omit_section_headers {
+                (0, 0, 0)
+            } else {
+                (
+                    self.layout.metadata(&Part::SectionHeaders).offset,
+                    self.object.sections.len() as _,
+                    self.find_section_names_string_table()?,
+                )
+            };
         self.write_raw(RawHeader {
             type_: match self.object.type_ {
                 ElfType::Relocatable => 1,
@@ -95,19 +150,21 @@ where
             machine: match self.object.env.machine {
                 ElfMachine::X86 => 3,
                 ElfMachine::X86_64 => 62,
+                ElfMachine::Aarch64 => 183,
             },
             version: 1,
             entry: self.object.entry.map(|n| n.get()).unwrap_or(0),
             program_headers_offset: self.layout.metadata(&Part::ProgramHeaders).offset,
-            section_headers_offset: self.layout.metadata(&Part::SectionHeaders).offset,
+            section_headers_offset,
             flags: RawHeaderFlags::zero(),
             elf_header_size: self.raw_type_size::<RawIdentification>()
                 + self.raw_type_size::<RawHeader>(),
             program_header_size: self.raw_type_size::<RawProgramHeader>(),
-            program_header_count: self.object.segments.len() as _,
+            program_header_count: u16::try_from(self.object.segments.len())
+                .map_or(PN_XNUM, |count| count.min(PN_XNUM)),
             section_header_size: self.raw_type_size::<RawSectionHeader>(),
-            section_header_count: self.object.sections.len() as _,
-            section_names_table_index: self.find_section_names_string_table()?,
+            section_header_count,
+            section_names_table_index,
         })
     }
 
@@ -115,7 +172,12 @@ where
         for (id, section) in &self.object.sections {
             let type_ = match &section.content {
                 ElfSectionContent::Null => {
-                    self.write_raw(RawSectionHeader::zero())?;
+                    // The checks in the constructor ensure the count fits in sh_info.
+                    let info = match self.object.segments.len() {
+                        count if count >= PN_XNUM as usize => count as u32,
+                        _ => 0,
+                    };
+                    self.write_raw(RawSectionHeader { info, ..RawSectionHeader::zero() })?;
                     continue;
                 }
 
@@ -123,25 +185,37 @@ where
                     self.write_raw(RawSectionHeader {
                         name_offset: section.name.offset(),
                         type_: 8,
-                        flags: self.perms_to_section_flags(&uninit.perms),
+                        flags: RawSectionHeaderFlags {
+                            tls: uninit.tls,
+                            ..self.perms_to_section_flags(&uninit.perms)
+                        },
                         memory_address: section.memory_address,
                         offset: 0,
                         size: uninit.len,
                         link: 0,
                         info: 0,
-                        addr_align: 0x1,
+                        addr_align: uninit.align,
                         entries_size: 0,
                     })?;
                     continue;
                 }
 
-                ElfSectionContent::Program(_) => 1,
+                ElfSectionContent::Program(p) => match p.functions_array {
+                    None => 1,
+                    Some(ElfFunctionsArray::Init) => 14,
+                    Some(ElfFunctionsArray::Fini) => 15,
+                    Some(ElfFunctionsArray::PreInit) => 16,
+                },
                 ElfSectionContent::SymbolTable(ElfSymbolTable { dynsym: false, .. }) => 2,
                 ElfSectionContent::SymbolTable(ElfSymbolTable { dynsym: true, .. }) => 11,
                 ElfSectionContent::StringTable(_) => 3,
                 ElfSectionContent::Hash(_) => 5,
+                ElfSectionContent::GnuHash(_) => 0x6ffffff6,
                 ElfSectionContent::Dynamic(_) => 6,
-                ElfSectionContent::Note(_) => todo!(),
+                ElfSectionContent::Note(_) => 7,
+                ElfSectionContent::VersionSymbols(_) => 0x6fffffff,
+                ElfSectionContent::VersionNeeded(_) => 0x6ffffffe,
+                ElfSectionContent::VersionDefined(_) => 0x6ffffffd,
                 ElfSectionContent::Unknown(_) => panic!("unknown section"),
                 ElfSectionContent::RelocationsTable(_) => self
                     .layout
@@ -164,6 +238,8 @@ where
             let mut flags = match &section.content {
                 ElfSectionContent::Program(p) => {
                     let mut flags = self.perms_to_section_flags(&p.perms);
+                    flags.tls = p.tls;
+                    flags.link_order = p.link_order.is_some();
                     match p.deduplication {
                         ElfDeduplication::Disabled => {}
                         ElfDeduplication::ZeroTerminatedStrings => {
@@ -209,10 +285,24 @@ where
                         self.section_idx(&table.symbol_table) as _
                     }
                     ElfSectionContent::Hash(hash) => self.section_idx(&hash.symbol_table) as _,
+                    ElfSectionContent::GnuHash(hash) => self.section_idx(&hash.symbol_table) as _,
                     ElfSectionContent::Group(group) => self.section_idx(&group.symbol_table) as _,
                     ElfSectionContent::Dynamic(dynamic) => {
                         self.section_idx(&dynamic.string_table) as _
                     }
+                    ElfSectionContent::VersionSymbols(versions) => {
+                        self.section_idx(&versions.symbol_table) as _
+                    }
+                    ElfSectionContent::VersionNeeded(needed) => {
+                        self.section_idx(&needed.string_table) as _
+                    }
+                    ElfSectionContent::VersionDefined(defined) => {
+                        self.section_idx(&defined.string_table) as _
+                    }
+                    ElfSectionContent::Program(ElfProgramSection {
+                        link_order: Some(linked),
+                        ..
+                    }) => self.section_idx(linked) as _,
                     _ => 0,
                 },
                 info: match &section.content {
@@ -244,9 +334,16 @@ where
                                 signature: group.signature.clone(),
                             })? as _
                     }
+                    // Number of entries in the table.
+                    ElfSectionContent::VersionNeeded(needed) => needed.files.len() as _,
+                    ElfSectionContent::VersionDefined(defined) => defined.versions.len() as _,
                     _ => 0,
                 },
-                addr_align: 0x1,
+                addr_align: match &section.content {
+                    ElfSectionContent::Program(program) => program.align,
+                    ElfSectionContent::Note(table) => table.align,
+                    _ => 0x1,
+                },
                 entries_size: match &section.content {
                     ElfSectionContent::Program(ElfProgramSection {
                         deduplication: ElfDeduplication::FixedSizeChunks { size },
@@ -266,6 +363,7 @@ where
                             RawRel::size(self.object.env.class) as _
                         }
                     }
+                    ElfSectionContent::VersionSymbols(_) => u16::size(self.object.env.class) as _,
                     _ => 0,
                 },
             })?;
@@ -298,6 +396,12 @@ where
                     for section_id in section_ids {
                         let section = self.object.sections.get(section_id).unwrap();
                         match &section.content {
+                            ElfSectionContent::Uninitialized(uninit) if uninit.tls => {
+                                // The uninitialized part of the TLS template follows the
+                                // initialized part, and is aligned on its own.
+                                memory_size = section.memory_address + uninit.len
+                                    - first_section.memory_address;
+                            }
                             ElfSectionContent::Uninitialized(uninit) => {
                                 if expected_next_file_offset != 0 {
                                     panic!("mixed uninitialized section with program sections in segment");
@@ -320,7 +424,8 @@ where
                 ElfSegmentContent::ElfHeader => {
                     (0, self.layout.header_size, 0, self.layout.header_size)
                 }
-                ElfSegmentContent::Unknown(unknown) => (
+                ElfSegmentContent::Notes(ElfNotesSegmentContent { location: unknown, .. })
+                | ElfSegmentContent::Unknown(unknown) => (
                     unknown.file_offset,
                     unknown.file_size,
                     unknown.virtual_address,
@@ -337,6 +442,7 @@ where
                     ElfSegmentType::Interpreter => 3,
                     ElfSegmentType::Note => 4,
                     ElfSegmentType::ProgramHeaderTable => 6,
+                    ElfSegmentType::Tls => 7,
                     ElfSegmentType::GnuStack => 0x6474e551,
                     ElfSegmentType::GnuRelRO => 0x6474e552,
                     ElfSegmentType::Unknown(_) => panic!("unknown segment"),
@@ -399,6 +505,8 @@ where
                 ElfSymbolType::Function => 2,
                 ElfSymbolType::Section => 3,
                 ElfSymbolType::File => 4,
+                ElfSymbolType::Tls => 6,
+                ElfSymbolType::GnuIfunc => 10,
                 ElfSymbolType::Unknown(other) => other & 0xF,
             };
             self.write_raw(RawSymbol {
@@ -437,7 +545,7 @@ where
             panic!("section {id:?} is not a relocation table")
         };
 
-        let ElfSectionContent::SymbolTable(symbol_table) = &self
+        let symbol_table = match &self
             .object
             .sections
             .get(&table.symbol_table)
@@ -446,79 +554,27 @@ where
                 relocations_table: id.clone(),
             })?
             .content
-        else {
-            panic!("section {id:?} is not a symbol table")
+        {
+            ElfSectionContent::SymbolTable(symbol_table) => Some(symbol_table),
+            ElfSectionContent::Null => None,
+            _ => panic!("section {id:?} is not a symbol table"),
         };
 
         for (idx, relocation) in table.relocations.iter().enumerate() {
-            let relocation_type = match relocation.relocation_type {
-                ElfRelocationType::X86_None => 0,
-                ElfRelocationType::X86_32 => 1,
-                ElfRelocationType::X86_PC32 => 2,
-                ElfRelocationType::X86_GOT32 => 3,
-                ElfRelocationType::X86_PLT32 => 4,
-                ElfRelocationType::X86_COPY => 5,
-                ElfRelocationType::X86_GLOB_DAT => 6,
-                ElfRelocationType::X86_JMP_Slot => 7,
-                ElfRelocationType::X86_Relative => 8,
-                ElfRelocationType::X86_GOTOff => 9,
-                ElfRelocationType::X86_GOTPC => 10,
-                ElfRelocationType::X86_GOT32X => 11,
-                ElfRelocationType::X86_64_None => 0,
-                ElfRelocationType::X86_64_64 => 1,
-                ElfRelocationType::X86_64_PC32 => 2,
-                ElfRelocationType::X86_64_GOT32 => 3,
-                ElfRelocationType::X86_64_PLT32 => 4,
-                ElfRelocationType::X86_64_Copy => 5,
-                ElfRelocationType::X86_64_GlobDat => 6,
-                ElfRelocationType::X86_64_JumpSlot => 7,
-                ElfRelocationType::X86_64_Relative => 8,
-                ElfRelocationType::X86_64_GOTPCRel => 9,
-                ElfRelocationType::X86_64_32 => 10,
-                ElfRelocationType::X86_64_32S => 11,
-                ElfRelocationType::X86_64_16 => 12,
-                ElfRelocationType::X86_64_PC16 => 13,
-                ElfRelocationType::X86_64_8 => 14,
-                ElfRelocationType::X86_64_PC8 => 15,
-                ElfRelocationType::X86_64_DTPMod64 => 16,
-                ElfRelocationType::X86_64_DTPOff64 => 17,
-                ElfRelocationType::X86_64_TPOff64 => 18,
-                ElfRelocationType::X86_64_TLSGD => 19,
-                ElfRelocationType::X86_64_TLSLD => 20,
-                ElfRelocationType::X86_64_DTPOff32 => 21,
-                ElfRelocationType::X86_64_GOTTPOff => 22,
-                ElfRelocationType::X86_64_TPOff32 => 23,
-                ElfRelocationType::X86_64_PC64 => 24,
-                ElfRelocationType::X86_64_GOTOff64 => 25,
-                ElfRelocationType::X86_64_GOTPC32 => 26,
-                ElfRelocationType::X86_64_Size32 => 32,
-                ElfRelocationType::X86_64_Size64 => 33,
-                ElfRelocationType::X86_64_GOTPC32_TLSDesc => 34,
-                ElfRelocationType::X86_64_TLSDescCall => 35,
-                ElfRelocationType::X86_64_TLSDesc => 36,
-                ElfRelocationType::X86_64_IRelative => 37,
-                ElfRelocationType::X86_64_IRelative64 => 38,
-                ElfRelocationType::X86_64_GOTPCRelX => 41,
-                ElfRelocationType::X86_64_Rex_GOTPCRelX => 42,
-                ElfRelocationType::X86_64_Code_4_GOTPCRelX => 43,
-                ElfRelocationType::X86_64_Code_4_GOTPCOff => 44,
-                ElfRelocationType::X86_64_Code_4_GOTPC32_TLSDesc => 45,
-                ElfRelocationType::X86_64_Code_5_GOTPCRelX => 46,
-                ElfRelocationType::X86_64_Code_5_GOTPCOff => 47,
-                ElfRelocationType::X86_64_Code_5_GOTPC32_TLSDesc => 48,
-                ElfRelocationType::X86_64_Code_6_GOTPCRelX => 49,
-                ElfRelocationType::X86_64_Code_6_GOTPCOff => 50,
-                ElfRelocationType::X86_64_Code_6_GOTPC32_TLSDesc => 51,
-                ElfRelocationType::Unknown(other) => other as u64,
+            let relocation_type = relocation.relocation_type.to_raw() as u64;
+            // Without a symbol table, relocations can only refer to the null symbol.
+            let symbol = match symbol_table {
+                Some(symbol_table) => {
+                    symbol_table.symbols.keys().position(|id| *id == relocation.symbol).ok_or_else(
+                        || WriteError::MissingSymbolInRelocation {
+                            symbol_id: relocation.symbol.clone(),
+                            relocations_table: id.clone(),
+                            relocation_idx: idx,
+                        },
+                    )? as u64
+                }
+                None => 0,
             };
-            let symbol =
-                symbol_table.symbols.keys().position(|id| *id == relocation.symbol).ok_or_else(
-                    || WriteError::MissingSymbolInRelocation {
-                        symbol_id: relocation.symbol.clone(),
-                        relocations_table: id.clone(),
-                        relocation_idx: idx,
-                    },
-                )? as u64;
             let info = match self.object.env.class {
                 ElfClass::Elf32 => relocation_type | (symbol << 8),
                 ElfClass::Elf64 => relocation_type | (symbol << 32),
@@ -566,6 +622,34 @@ where
         Ok(())
     }
 
+    fn write_gnu_hash(&mut self, id: &I::SectionId) -> Result<(), WriteError<I>> {
+        let ElfSectionContent::GnuHash(hash) = &self.object.sections.get(id).unwrap().content
+        else {
+            panic!("section {id:?} is not a GNU hash");
+        };
+        self.write_raw(RawGnuHashHeader {
+            bucket_count: hash.buckets.len().try_into().expect("too many buckets"),
+            symbol_offset: hash.symbol_offset,
+            bloom_count: hash.bloom.len().try_into().expect("too many bloom filter words"),
+            bloom_shift: hash.bloom_shift,
+        })?;
+        for word in &hash.bloom {
+            match self.object.env.class {
+                ElfClass::Elf32 => self.write_raw::<u32>(
+                    (*word).try_into().expect("bloom filter word does not fit in 32 bits"),
+                )?,
+                ElfClass::Elf64 => self.write_raw(*word)?,
+            }
+        }
+        for entry in &hash.buckets {
+            self.write_raw(*entry)?;
+        }
+        for entry in &hash.chain {
+            self.write_raw(*entry)?;
+        }
+        Ok(())
+    }
+
     fn write_dynamic(&mut self, id: &I::SectionId) -> Result<(), WriteError<I>> {
         let ElfSectionContent::Dynamic(dynamic) = &self.object.sections.get(id).unwrap().content
         else {
@@ -611,6 +695,16 @@ where
                 ElfDynamicDirective::RelocationsWillModifyText => (22, 0),
                 ElfDynamicDirective::JumpRel { address } => (23, *address),
                 ElfDynamicDirective::BindNow => (24, 0),
+                ElfDynamicDirective::InitArray { address } => (25, *address),
+                ElfDynamicDirective::FiniArray { address } => (26, *address),
+                ElfDynamicDirective::InitArraySize { bytes } => (27, *bytes),
+                ElfDynamicDirective::FiniArraySize { bytes } => (28, *bytes),
+                ElfDynamicDirective::Flags(flags) => (30, Bitfield::write(flags)),
+                ElfDynamicDirective::PreInitArray { address } => (32, *address),
+                ElfDynamicDirective::PreInitArraySize { bytes } => (33, *bytes),
+                ElfDynamicDirective::RelrSize { bytes } => (35, *bytes),
+                ElfDynamicDirective::Relr { address } => (36, *address),
+                ElfDynamicDirective::RelrEntrySize { bytes } => (37, *bytes),
                 ElfDynamicDirective::Flags1(flags) => (0x6ffffffb, Bitfield::write(flags)),
                 ElfDynamicDirective::Unknown { tag, value } => (*tag, *value),
             };
@@ -636,9 +730,121 @@ where
         Ok(())
     }
 
+    fn write_notes(&mut self, id: &I::SectionId) -> Result<(), WriteError<I>> {
+        let ElfSectionContent::Note(table) = &self.object.sections.get(id).unwrap().content else {
+            panic!("section {id:?} is not a note section");
+        };
+
+        // The name and the value are padded to the alignment of the table.
+        let align = table.align as usize;
+        let pad = |len: usize| vec![0; len.next_multiple_of(align) - len];
+        for note in &table.notes {
+            let name_size = note.name().len() + 1;
+            self.write_raw(RawNoteHeader {
+                name_size: name_size as _,
+                value_size: note.value().len() as _,
+                type_: note.type_(),
+            })?;
+            self.writer.write_all(note.name().as_bytes())?;
+            self.writer.write_all(b"\0")?;
+            let header_and_name = self.raw_type_size::<RawNoteHeader>() as usize + name_size;
+            self.writer.write_all(&pad(header_and_name))?;
+            self.writer.write_all(note.value())?;
+            self.writer.write_all(&pad(note.value().len()))?;
+        }
+        Ok(())
+    }
+
+    fn write_version_symbols(&mut self, id: &I::SectionId) -> Result<(), WriteError<I>> {
+        let ElfSectionContent::VersionSymbols(versions) =
+            &self.object.sections.get(id).unwrap().content
+        else {
+            panic!("section {id:?} is not a version symbols table");
+        };
+        for version in &versions.versions {
+            self.write_raw(version.index | if version.hidden { 0x8000 } else { 0 })?;
+        }
+        Ok(())
+    }
+
+    fn write_version_needed(&mut self, id: &I::SectionId) -> Result<(), WriteError<I>> {
+        let ElfSectionContent::VersionNeeded(needed) =
+            &self.object.sections.get(id).unwrap().content
+        else {
+            panic!("section {id:?} is not a version needed table");
+        };
+
+        // Each entry is immediately followed by its auxiliary entries, like GNU ld does.
+        let entry_size = self.raw_type_size::<RawVersionNeeded>() as u32;
+        let aux_size = self.raw_type_size::<RawVersionNeededAux>() as u32;
+        for (idx, file) in needed.files.iter().enumerate() {
+            let count: u16 = file.versions.len().try_into().map_err(|_| {
+                WriteError::TooManyVersions { section: id.clone(), count: file.versions.len() }
+            })?;
+            self.write_raw(RawVersionNeeded {
+                version: 1,
+                count,
+                file_offset: file.file.offset(),
+                aux_offset: if count == 0 { 0 } else { entry_size },
+                next_offset: if idx + 1 == needed.files.len() {
+                    0
+                } else {
+                    entry_size + aux_size * count as u32
+                },
+            })?;
+            for (idx, version) in file.versions.iter().enumerate() {
+                self.write_raw(RawVersionNeededAux {
+                    hash: version.hash,
+                    flags: version.flags,
+                    index: version.index,
+                    name_offset: version.name.offset(),
+                    next_offset: if idx + 1 == file.versions.len() { 0 } else { aux_size },
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_version_defined(&mut self, id: &I::SectionId) -> Result<(), WriteError<I>> {
+        let ElfSectionContent::VersionDefined(defined) =
+            &self.object.sections.get(id).unwrap().content
+        else {
+            panic!("section {id:?} is not a version definitions table");
+        };
+
+        // Each entry is immediately followed by its auxiliary entries, like GNU ld does.
+        let entry_size = self.raw_type_size::<RawVersionDefinition>() as u32;
+        let aux_size = self.raw_type_size::<RawVersionDefinitionAux>() as u32;
+        for (idx, version) in defined.versions.iter().enumerate() {
+            let count: u16 = version.names.len().try_into().map_err(|_| {
+                WriteError::TooManyVersions { section: id.clone(), count: version.names.len() }
+            })?;
+            self.write_raw(RawVersionDefinition {
+                version: 1,
+                flags: version.flags,
+                index: version.index,
+                count,
+                hash: version.hash,
+                aux_offset: if count == 0 { 0 } else { entry_size },
+                next_offset: if idx + 1 == defined.versions.len() {
+                    0
+                } else {
+                    entry_size + aux_size * count as u32
+                },
+            })?;
+            for (idx, name) in version.names.iter().enumerate() {
+                self.write_raw(RawVersionDefinitionAux {
+                    name_offset: name.offset(),
+                    next_offset: if idx + 1 == version.names.len() { 0 } else { aux_size },
+                })?;
+            }
+        }
+        Ok(())
+    }
+
     fn write_padding(&mut self, part: &Part<I::SectionId>) -> Result<(), WriteError<I>> {
         let metadata = self.layout.metadata(part);
-        let padding = vec![0; metadata.len as usize];
+        let padding = vec![self.options.fill_byte; metadata.len as usize];
         self.writer.write_all(&padding)?;
         Ok(())
     }
@@ -689,3 +895,71 @@ where
         Ok(())
     }
 }
+
+/// Ensure the `sh_link` and `sh_info` of relocation tables will point to real sections of the
+/// expected type, as otherwise the section headers would refer to the wrong (or no) section.
+/// Whether the symbol is the null one, the first of every symbol table.
+fn is_null_symbol<I: ElfIds>(object: &ElfObject<I>, symbol: &I::SymbolId) -> bool {
+    object.sections.values().any(|section| match &section.content {
+        ElfSectionContent::SymbolTable(table) => table.symbols.keys().next() == Some(symbol),
+        _ => false,
+    })
+}
+
+fn validate_relocations_tables<I>(object: &ElfObject<I>) -> Result<(), WriteError<I>>
+where
+    I: ElfIds,
+{
+    for (id, section) in &object.sections {
+        let ElfSectionContent::RelocationsTable(table) = &section.content else { continue };
+
+        // Relocations that don't need a symbol (like IRELATIVE ones in a static executable's
+        // .rela.plt) can be linked to the null section, as GNU ld does.
+        match object.sections.get(&table.symbol_table).map(|s| &s.content) {
+            Some(ElfSectionContent::SymbolTable(_)) => {}
+            // Without a symbol table, relocations can only refer to the null symbol, which is
+            // written as index 0 regardless of the table it comes from.
+            Some(ElfSectionContent::Null) => {
+                for (idx, relocation) in table.relocations.iter().enumerate() {
+                    if !is_null_symbol(object, &relocation.symbol) {
+                        return Err(WriteError::MissingSymbolInRelocation {
+                            symbol_id: relocation.symbol.clone(),
+                            relocations_table: id.clone(),
+                            relocation_idx: idx,
+                        });
+                    }
+                }
+            }
+            Some(_) => {
+                return Err(WriteError::WrongSectionTypeForRelocationsSymbolTable {
+                    relocations_table: id.clone(),
+                    symbol_table: table.symbol_table.clone(),
+                })
+            }
+            None => {
+                return Err(WriteError::MissingSymbolTableForRelocations {
+                    symbol_table: table.symbol_table.clone(),
+                    relocations_table: id.clone(),
+                })
+            }
+        }
+
+        // Dynamic relocations don't apply to a specific section, and point to the null section.
+        match object.sections.get(&table.applies_to_section).map(|s| &s.content) {
+            Some(ElfSectionContent::Null | ElfSectionContent::Program(_)) => {}
+            Some(_) => {
+                return Err(WriteError::WrongSectionTypeForRelocationsTarget {
+                    relocations_table: id.clone(),
+                    section: table.applies_to_section.clone(),
+                })
+            }
+            None => {
+                return Err(WriteError::MissingSectionForRelocations {
+                    section: table.applies_to_section.clone(),
+                    relocations_table: id.clone(),
+                })
+            }
+        }
+    }
+    Ok(())
+}
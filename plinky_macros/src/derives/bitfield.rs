@@ -1,6 +1,6 @@
 use crate::error::Error;
 use crate::parser::{Attributes, Ident, Item, Parser, Struct, StructFields};
-use crate::utils::{generate_impl_for, ident, literal};
+use crate::utils::{generate_impl_for, ident};
 use plinky_macros_quote::quote;
 use plinky_utils::quote::Quote;
 use proc_macro::TokenStream;
@@ -83,7 +83,7 @@ fn bitfield_fn_write(fields: &Fields) -> TokenStream {
         Fields::None => {}
         Fields::TupleLike(fields) => {
             for (idx, bit) in fields.iter().enumerate() {
-                setters.push(quote! { writer.set_bit(#bit, self.#{ literal(idx) }); });
+                setters.push(quote! { writer.set_bit(#bit, self.#idx); });
             }
         }
         Fields::StructLike(fields) => {
@@ -103,40 +103,35 @@ fn bitfield_fn_write(fields: &Fields) -> TokenStream {
 }
 
 fn display_fn_fmt(fields: &Fields) -> TokenStream {
-    let comma = quote! { f.write_str(", ")?; };
-
-    let mut writes = Vec::new();
+    let mut entries = Vec::new();
     match fields {
         Fields::None => {},
         Fields::TupleLike(fields) => {
             for (idx, bit) in fields.iter().enumerate() {
-                if idx != 0 {
-                    writes.push(comma.clone());
-                }
-                writes.push(quote! {
-                    if self.#{ literal(idx) } {
-                        f.write_str(stringify!(#bit))?;
-                    }
-                });
+                entries.push(quote! { (self.#idx, stringify!(#bit)), });
             }
         },
         Fields::StructLike(fields) => {
-            for (idx, (name, _)) in fields.iter().enumerate() {
-                if idx != 0 {
-                    writes.push(comma.clone());
-                }
-                writes.push(quote! {
-                    if self.#name {
-                        f.write_str(stringify!(#name))?;
-                    }
-                })
+            for (name, _) in fields.iter() {
+                entries.push(quote! { (self.#name, stringify!(#name)), });
             }
         },
     }
 
+    // Only the set bits are displayed, so the separator is written before all of them but the
+    // first one.
     quote! {
         fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-            #writes
+            let mut first = true;
+            for (set, name) in [#entries] {
+                if set {
+                    if !first {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str(name)?;
+                    first = false;
+                }
+            }
             Ok(())
         }
     }
@@ -214,6 +209,6 @@ struct BitIndex(usize);
 
 impl Quote for BitIndex {
     fn to_token_stream(&self) -> TokenStream {
-        literal(self.0).into()
+        self.0.to_token_stream()
     }
 }
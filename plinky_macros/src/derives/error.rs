@@ -1,5 +1,7 @@
 use crate::error::Error;
-use crate::parser::{Attributes, EnumVariantData, Item, Parser, StructFields, Type};
+use crate::parser::{
+    Attributes, EnumVariantData, Item, Parser, StructField, StructFields, TupleField, Type,
+};
 use crate::utils::{generate_for_each_variant, generate_impl_for, ident};
 use plinky_macros_quote::quote;
 use proc_macro::{TokenStream, TokenTree};
@@ -65,7 +67,12 @@ fn generate_error_source(item: &Item) -> Result<TokenStream, Error> {
             }
 
             if let Some(source) = source {
-                Ok(quote! { Some(#{ &source.access_ref }) })
+                if source.ty.is_boxed_trait_object() {
+                    // Box<dyn Error> doesn't implement Error, so point to the boxed value instead.
+                    Ok(quote! { Some(&**#{ &source.access_ref }) })
+                } else {
+                    Ok(quote! { Some(#{ &source.access_ref }) })
+                }
             } else {
                 Ok(quote! { None })
             }
@@ -83,6 +90,7 @@ fn generate_error_provide(item: &Item) -> Result<TokenStream, Error> {
     let mut any_provide = false;
     let body = generate_for_each_variant(item, |_span, attrs, fields| {
         let mut diagnostic = None;
+        let mut backtrace = None;
         for field in fields {
             if let Some(attr) = field.attrs.get("diagnostic")? {
                 attr.must_be_empty()?;
@@ -93,6 +101,15 @@ fn generate_error_provide(item: &Item) -> Result<TokenStream, Error> {
                     }
                 }
             }
+            if let Some(attr) = field.attrs.get("backtrace")? {
+                attr.must_be_empty()?;
+                match backtrace {
+                    None => backtrace = Some(field),
+                    Some(_) => {
+                        return Err(Error::new("multiple #[backtrace] attributes").span(attr.span));
+                    }
+                }
+            }
         }
 
         if let Some(attr) = attrs.get("transparent")? {
@@ -106,15 +123,20 @@ fn generate_error_provide(item: &Item) -> Result<TokenStream, Error> {
             };
             any_provide = true;
             Ok(quote!(#{ &field.access_ref }.provide(request)))
-        } else if let Some(diagnostic) = diagnostic {
-            any_provide = true;
-            Ok(quote! {
-                {
-                    request.provide_ref::<plinky_diagnostics::Diagnostic>(#{ &diagnostic.access_ref });
-                }
-            })
         } else {
-            Ok(quote!({}))
+            let mut provides = Vec::new();
+            if let Some(diagnostic) = diagnostic {
+                provides.push(quote! {
+                    request.provide_ref::<plinky_diagnostics::Diagnostic>(#{ &diagnostic.access_ref });
+                });
+            }
+            if let Some(backtrace) = backtrace {
+                provides.push(quote! {
+                    request.provide_ref::<std::backtrace::Backtrace>(#{ &backtrace.access_ref });
+                });
+            }
+            any_provide |= !provides.is_empty();
+            Ok(quote!({ #provides }))
         }
     })?;
     if any_provide {
@@ -129,10 +151,12 @@ fn generate_error_provide(item: &Item) -> Result<TokenStream, Error> {
 }
 
 fn generate_from_impls(item: &Item) -> Result<Vec<TokenStream>, Error> {
+    /// Returns the index of the field to convert from, if a From impl has to be generated.
+    /// Fields marked with #[backtrace] are not converted from, but captured in the impl.
     fn should_generate<'a>(
         container_attrs: &'a Attributes,
         fields_attrs: impl Iterator<Item = &'a Attributes>,
-    ) -> Result<bool, Error> {
+    ) -> Result<Option<usize>, Error> {
         let mut generate = None;
 
         if let Some(attr) = container_attrs.get("transparent")? {
@@ -140,9 +164,12 @@ fn generate_from_impls(item: &Item) -> Result<Vec<TokenStream>, Error> {
             generate = Some(attr.span);
         }
 
-        let mut fields_count = 0;
-        for field_attrs in fields_attrs {
-            fields_count += 1;
+        let mut source_fields = Vec::new();
+        for (idx, field_attrs) in fields_attrs.enumerate() {
+            if field_attrs.get("backtrace")?.is_some() {
+                continue;
+            }
+            source_fields.push(idx);
             if let Some(attr) = field_attrs.get("from")? {
                 attr.must_be_empty()?;
                 match generate {
@@ -157,16 +184,43 @@ fn generate_from_impls(item: &Item) -> Result<Vec<TokenStream>, Error> {
         }
 
         if let Some(span) = generate {
-            if fields_count == 1 {
-                Ok(true)
-            } else {
-                Err(Error::new("From impl can be generated only with one field").span(span))
+            match source_fields.as_slice() {
+                [idx] => Ok(Some(*idx)),
+                _ => Err(Error::new("From impl can be generated only with one field").span(span)),
             }
         } else {
-            Ok(false)
+            Ok(None)
         }
     }
 
+    fn initializer(attrs: &Attributes, source: bool, var: &TokenTree) -> TokenStream {
+        if source {
+            quote!(#var)
+        } else if attrs.get("backtrace").ok().flatten().is_some() {
+            quote!(std::backtrace::Backtrace::capture())
+        } else {
+            unreachable!("only the source and backtrace fields can be present");
+        }
+    }
+
+    fn tuple_initializers(fields: &[TupleField], source: usize, var: &TokenTree) -> TokenStream {
+        let mut initializers = Vec::new();
+        for (idx, field) in fields.iter().enumerate() {
+            initializers.push(quote!(#{ initializer(&field.attrs, idx == source, var) },));
+        }
+        quote!(#initializers)
+    }
+
+    fn struct_initializers(fields: &[StructField], source: usize, var: &TokenTree) -> TokenStream {
+        let mut initializers = Vec::new();
+        for (idx, field) in fields.iter().enumerate() {
+            initializers.push(quote! {
+                #{ &field.name }: #{ initializer(&field.attrs, idx == source, var) },
+            });
+        }
+        quote!(#initializers)
+    }
+
     fn render<F>(item: &Item, ty: &Type, setter: F) -> TokenStream
     where
         F: FnOnce(TokenTree) -> TokenStream,
@@ -187,22 +241,24 @@ fn generate_from_impls(item: &Item) -> Result<Vec<TokenStream>, Error> {
     match item {
         Item::Struct(struct_) => match &struct_.fields {
             StructFields::None => {
-                assert!(!should_generate(&struct_.attrs, std::iter::empty())?);
+                assert!(should_generate(&struct_.attrs, std::iter::empty())?.is_none());
             }
             StructFields::TupleLike(fields) => {
-                if should_generate(&struct_.attrs, fields.iter().map(|f| &f.attrs))? {
-                    let field = &fields[0];
-                    generated.push(render(item, &field.ty, |var| quote! { Self(#var) }));
+                if let Some(source) =
+                    should_generate(&struct_.attrs, fields.iter().map(|f| &f.attrs))?
+                {
+                    generated.push(render(item, &fields[source].ty, |var| {
+                        quote! { Self(#{ tuple_initializers(fields, source, &var) }) }
+                    }));
                 }
             }
             StructFields::StructLike(fields) => {
-                if should_generate(&struct_.attrs, fields.iter().map(|f| &f.attrs))? {
-                    let field = &fields[0];
-                    generated.push(render(
-                        item,
-                        &field.ty,
-                        |var| quote! { Self { #{ &field.name }: #var } },
-                    ));
+                if let Some(source) =
+                    should_generate(&struct_.attrs, fields.iter().map(|f| &f.attrs))?
+                {
+                    generated.push(render(item, &fields[source].ty, |var| {
+                        quote! { Self { #{ struct_initializers(fields, source, &var) } } }
+                    }));
                 }
             }
         },
@@ -210,26 +266,32 @@ fn generate_from_impls(item: &Item) -> Result<Vec<TokenStream>, Error> {
             for variant in &enum_.variants {
                 match &variant.data {
                     EnumVariantData::None => {
-                        assert!(!should_generate(&variant.attrs, std::iter::empty())?);
+                        assert!(should_generate(&variant.attrs, std::iter::empty())?.is_none());
                     }
                     EnumVariantData::TupleLike(fields) => {
-                        if should_generate(&variant.attrs, fields.iter().map(|f| &f.attrs))? {
-                            let field = &fields[0];
-                            generated.push(render(
-                                item,
-                                &field.ty,
-                                |var| quote! { Self::#{ &variant.name }(#var) },
-                            ));
+                        if let Some(source) =
+                            should_generate(&variant.attrs, fields.iter().map(|f| &f.attrs))?
+                        {
+                            generated.push(render(item, &fields[source].ty, |var| {
+                                quote! {
+                                    Self::#{ &variant.name }(
+                                        #{ tuple_initializers(fields, source, &var) }
+                                    )
+                                }
+                            }));
                         }
                     }
                     EnumVariantData::StructLike(fields) => {
-                        if should_generate(&variant.attrs, fields.iter().map(|f| &f.attrs))? {
-                            let field = &fields[0];
-                            generated.push(render(
-                                item,
-                                &field.ty,
-                                |var| quote! { Self::#{ &variant.name } { #{ &field.name }: #var } },
-                            ));
+                        if let Some(source) =
+                            should_generate(&variant.attrs, fields.iter().map(|f| &f.attrs))?
+                        {
+                            generated.push(render(item, &fields[source].ty, |var| {
+                                quote! {
+                                    Self::#{ &variant.name } {
+                                        #{ struct_initializers(fields, source, &var) }
+                                    }
+                                }
+                            }));
                         }
                     }
                 }
@@ -82,6 +82,24 @@ pub(crate) struct GenericParamConst {
 #[derive(Debug, Clone)]
 pub(crate) struct Type(pub(crate) TokenStream);
 
+impl Type {
+    /// Whether the type is a `Box<dyn ...>`, optionally with a path before `Box`.
+    pub(crate) fn is_boxed_trait_object(&self) -> bool {
+        let mut last_ident = None;
+        let mut tokens = self.0.clone().into_iter();
+        for token in tokens.by_ref() {
+            match token {
+                TokenTree::Ident(ident) => last_ident = Some(ident.to_string()),
+                TokenTree::Punct(punct) if punct.as_char() == ':' => {}
+                TokenTree::Punct(punct) if punct.as_char() == '<' => break,
+                _ => return false,
+            }
+        }
+        last_ident.as_deref() == Some("Box")
+            && matches!(tokens.next(), Some(TokenTree::Ident(ident)) if ident.to_string() == "dyn")
+    }
+}
+
 impl Quote for Type {
     fn to_token_stream(&self) -> TokenStream {
         self.0.clone()
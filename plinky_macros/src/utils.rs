@@ -58,7 +58,7 @@ where
                         let field_name = ident(&format!("f{idx}"));
                         declarations.push(quote! {
                             #[allow(unused)]
-                            let #field_name = &self.#{ literal(idx) };
+                            let #field_name = &self.#idx;
                         });
                         unified.push(UnifiedField {
                             attrs: &field.attrs,
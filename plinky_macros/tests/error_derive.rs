@@ -0,0 +1,70 @@
+#![feature(error_generic_member_access)]
+
+use plinky_macros::{Display, Error};
+use std::backtrace::Backtrace;
+use std::error::{request_ref, Error as _};
+
+#[derive(Debug, Display, Error)]
+#[display("inner failure")]
+struct InnerError;
+
+#[derive(Debug, Display, Error)]
+enum OuterError {
+    #[display("boxed source")]
+    Boxed(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[display("boxed source with a path")]
+    BoxedWithPath {
+        #[source]
+        inner: std::boxed::Box<dyn std::error::Error>,
+    },
+    #[display("boxed concrete source")]
+    BoxedConcrete(#[source] Box<InnerError>),
+}
+
+#[test]
+fn test_source_boxed_dyn_error() {
+    let error = OuterError::Boxed(Box::new(InnerError));
+    assert_eq!("inner failure", error.source().unwrap().to_string());
+}
+
+#[test]
+fn test_source_boxed_dyn_error_with_path() {
+    let error = OuterError::BoxedWithPath { inner: Box::new(InnerError) };
+    assert_eq!("inner failure", error.source().unwrap().to_string());
+}
+
+#[test]
+fn test_source_boxed_concrete_error() {
+    let error = OuterError::BoxedConcrete(Box::new(InnerError));
+    assert_eq!("inner failure", error.source().unwrap().to_string());
+}
+
+#[derive(Debug, Display, Error)]
+#[display("failed with a backtrace")]
+struct WithBacktrace {
+    #[from]
+    inner: InnerError,
+    #[backtrace]
+    backtrace: Backtrace,
+}
+
+#[derive(Debug, Display, Error)]
+enum EnumWithBacktrace {
+    #[display("tuple variant")]
+    Tuple(#[from] InnerError, #[backtrace] Backtrace),
+}
+
+#[test]
+fn test_provide_backtrace() {
+    let error = WithBacktrace::from(InnerError);
+    let provided = request_ref::<Backtrace>(&error).unwrap();
+    assert!(std::ptr::eq(&error.backtrace, provided));
+    assert_eq!("inner failure", error.source().unwrap().to_string());
+}
+
+#[test]
+fn test_provide_backtrace_in_enum_variant() {
+    let error = EnumWithBacktrace::from(InnerError);
+    let EnumWithBacktrace::Tuple(_, backtrace) = &error;
+    assert!(std::ptr::eq(backtrace, request_ref::<Backtrace>(&error).unwrap()));
+}
@@ -1,12 +1,9 @@
-use proc_macro::{Delimiter, Ident, Literal, Punct, Spacing, TokenStream, TokenTree};
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, TokenStream, TokenTree};
 
 #[proc_macro]
 pub fn quote(tokens: TokenStream) -> TokenStream {
     let nodes = parse(tokens);
-
-    let mut output = String::new();
-    render_node_list(&mut output, nodes);
-    output.parse().unwrap()
+    render_node_list(nodes)
 }
 
 fn parse(stream: TokenStream) -> Vec<Node> {
@@ -23,9 +20,12 @@ fn parse(stream: TokenStream) -> Vec<Node> {
                         Node::Interpolation(var)
                     }
                     Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
-                        let var = group.to_string();
+                        // Keep the inner tokens rather than the group itself: they are wrapped in
+                        // a fresh block when rendering, so nested braces and string literals are
+                        // emitted exactly as the compiler tokenized them.
+                        let expr = group.stream();
                         let _ = iter.next(); // Consume the peeked token.
-                        Node::Interpolation(var)
+                        Node::BlockInterpolation(expr)
                     }
                     _ => Node::Punct(pound),
                 }
@@ -41,12 +41,19 @@ fn parse(stream: TokenStream) -> Vec<Node> {
     result
 }
 
-fn render_node_list(output: &mut String, nodes: Vec<Node>) {
-    output.push('{');
+fn render_node_list(nodes: Vec<Node>) -> TokenStream {
+    let mut output = String::new();
     output.push_str("let mut __quote_buffer__ = proc_macro::TokenStream::new();");
+    let mut body = TokenStream::new();
     for node in nodes {
         match node {
             Node::Group(delimiter, subnodes) => {
+                // The nested block is emitted as tokens, so it's bound to a variable rather than
+                // being spliced in the middle of the generated code.
+                output.push_str("let __quote_group__ = ");
+                flush(&mut body, &mut output);
+                body.extend(render_node_list(subnodes));
+                output.push(';');
                 output.push_str(
                     "__quote_buffer__.extend([proc_macro::TokenTree::Group(proc_macro::Group::new(",
                 );
@@ -56,9 +63,7 @@ fn render_node_list(output: &mut String, nodes: Vec<Node>) {
                     Delimiter::Bracket => output.push_str("proc_macro::Delimiter::Bracket"),
                     Delimiter::None => output.push_str("proc_macro::Delimiter::None"),
                 }
-                output.push(',');
-                render_node_list(output, subnodes);
-                output.push_str("))]);");
+                output.push_str(", __quote_group__))]);");
             }
             Node::Ident(ident) => {
                 output.push_str("__quote_buffer__.extend([proc_macro::TokenTree::Ident(proc_macro::Ident::new(\"");
@@ -92,10 +97,28 @@ fn render_node_list(output: &mut String, nodes: Vec<Node>) {
                 output.push_str(&var);
                 output.push_str("));");
             }
+            Node::BlockInterpolation(expr) => {
+                // The expression is emitted as the original tokens rather than their string
+                // representation, which would lose the spans (and with them macro hygiene).
+                output.push_str("let __quote_value__ = ");
+                flush(&mut body, &mut output);
+                body.extend([TokenTree::Group(Group::new(Delimiter::Brace, expr))]);
+                output.push(';');
+                output.push_str(
+                    "__quote_buffer__.extend(plinky_utils::quote::Quote::to_token_stream(&__quote_value__));",
+                );
+            }
         }
     }
     output.push_str("__quote_buffer__");
-    output.push('}');
+    flush(&mut body, &mut output);
+    TokenTree::Group(Group::new(Delimiter::Brace, body)).into()
+}
+
+/// Parse the generated code accumulated so far and append it to the body.
+fn flush(body: &mut TokenStream, output: &mut String) {
+    body.extend(output.parse::<TokenStream>().unwrap());
+    output.clear();
 }
 
 #[derive(Debug)]
@@ -105,4 +128,5 @@ enum Node {
     Literal(Literal),
     Punct(Punct),
     Interpolation(String),
+    BlockInterpolation(TokenStream),
 }
@@ -0,0 +1,69 @@
+//! The proc_macro API can only be called while a macro is being expanded, so the output of the
+//! quote macro can't be inspected from a plain test. This crate expands the cases instead, and the
+//! tests look at the resulting string literals.
+
+extern crate proc_macro;
+
+use plinky_macros_quote::quote;
+use proc_macro::{Literal, TokenStream, TokenTree};
+
+/// Expands to a string literal containing the tokens generated by the named case.
+#[proc_macro]
+pub fn quoted(input: TokenStream) -> TokenStream {
+    let output = match input.to_string().as_str() {
+        "block_with_string_containing_braces" => block_with_string_containing_braces(),
+        "block_with_nested_blocks" => block_with_nested_blocks(),
+        "block_with_hygienic_variable" => block_with_hygienic_variable(),
+        "vec_of_u32" => vec_of_u32(),
+        "integers_and_bool" => integers_and_bool(),
+        other => panic!("unknown case: {other}"),
+    };
+    TokenTree::Literal(Literal::string(&output.to_string())).into()
+}
+
+fn block_with_string_containing_braces() -> TokenStream {
+    quote! {
+        const VALUE: &str = #{ TokenTree::Literal(Literal::string("{ \"nested\" } }")) };
+    }
+}
+
+fn block_with_nested_blocks() -> TokenStream {
+    let name = TokenTree::Literal(Literal::string("plinky"));
+    quote! {
+        const VALUE: &str = #{ { let inner = { &name }; inner.clone() } };
+        const OTHER: &str = #name;
+    }
+}
+
+// The block refers to a variable of the caller through a macro_rules! metavariable, which only
+// resolves if the block keeps the original spans.
+macro_rules! quote_clone_of {
+    ($value:ident) => {
+        quote! {
+            const VALUE: &str = #{ $value.clone() };
+        }
+    };
+}
+
+fn block_with_hygienic_variable() -> TokenStream {
+    let name = TokenTree::Literal(Literal::string("plinky"));
+    quote_clone_of!(name)
+}
+
+// The elements of a Vec are interpolated one after the other, without separators.
+fn vec_of_u32() -> TokenStream {
+    let values: Vec<u32> = vec![1, 2, 3];
+    quote! {
+        const VALUES: &str = stringify!(#values);
+    }
+}
+
+// Integers are unsuffixed, so they can also be used as tuple indexes.
+fn integers_and_bool() -> TokenStream {
+    let idx: usize = 1;
+    let byte: u8 = 255;
+    let flag = true;
+    quote! {
+        let value = (self.#idx, #byte, #flag);
+    }
+}
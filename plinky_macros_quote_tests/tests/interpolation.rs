@@ -0,0 +1,32 @@
+use plinky_macros_quote_tests::quoted;
+
+#[test]
+fn test_block_interpolation_with_string_containing_braces() {
+    assert_eq!(
+        r#"const VALUE : & str = "{ \"nested\" } }";"#,
+        quoted!(block_with_string_containing_braces)
+    );
+}
+
+#[test]
+fn test_block_interpolation_with_nested_blocks() {
+    assert_eq!(
+        r#"const VALUE : & str = "plinky"; const OTHER : & str = "plinky";"#,
+        quoted!(block_with_nested_blocks)
+    );
+}
+
+#[test]
+fn test_block_interpolation_with_hygienic_variable() {
+    assert_eq!(r#"const VALUE : & str = "plinky";"#, quoted!(block_with_hygienic_variable));
+}
+
+#[test]
+fn test_vec_of_u32() {
+    assert_eq!("const VALUES : & str = stringify! (1 2 3);", quoted!(vec_of_u32));
+}
+
+#[test]
+fn test_integers_and_bool() {
+    assert_eq!("let value = (self.1, 255, true);", quoted!(integers_and_bool));
+}
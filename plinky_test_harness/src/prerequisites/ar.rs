@@ -3,7 +3,6 @@ use crate::utils::run;
 use anyhow::Error;
 use std::path::Path;
 use std::process::Command;
-use tempfile::TempDir;
 
 #[derive(serde::Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
@@ -11,6 +10,8 @@ pub(super) struct ArArchive {
     name: String,
     #[serde(default)]
     symbol_table: Option<bool>,
+    #[serde(default)]
+    thin: bool,
     #[serde(flatten)]
     content: Prerequisites,
 }
@@ -22,29 +23,25 @@ impl ArArchive {
         source_dir: &Path,
         dest_dir: &Path,
     ) -> Result<(), Error> {
-        let inputs_dir = TempDir::with_prefix_in("prereq-", dest_dir)?.into_path();
-        self.content.build(arch, source_dir, &inputs_dir)?;
-
-        let mut to_archive = Vec::new();
-        for entry in std::fs::read_dir(&inputs_dir)? {
-            let path = entry?.path();
-            if path.is_file() {
-                to_archive.push(path.file_name().unwrap().to_os_string());
-            }
-        }
-        to_archive.sort();
+        let (inputs_dir, to_archive) =
+            self.content.build_in_temp_dir(arch, source_dir, dest_dir)?;
 
         let mut flags = "rc".to_string();
         match self.symbol_table {
             None | Some(true) => flags.push('s'),
             Some(false) => flags.push('S'),
         }
+        if self.thin {
+            flags.push('T');
+        }
 
         println!("archiving {to_archive:?} into {}...", self.name);
         run(Command::new("ar")
             .current_dir(&inputs_dir)
             .arg(flags)
-            .arg(dest_dir.join(&self.name))
+            // ar only makes the paths stored in thin archives relative to the archive when the
+            // archive path is relative too.
+            .arg(Path::new("..").join(&self.name))
             .args(&to_archive))?;
         Ok(())
     }
@@ -10,6 +10,12 @@ pub(super) struct AsmFile {
     source: String,
     format: Option<AsmFormat>,
     output: Option<String>,
+    /// Keep the compiler-generated local symbols starting with `.L` in the symbol table.
+    #[serde(default, rename = "keep-locals")]
+    keep_locals: bool,
+    /// Compress the debug sections with zlib, setting `SHF_COMPRESSED` on them.
+    #[serde(default, rename = "compress-debug-sections")]
+    compress_debug_sections: bool,
 }
 
 impl AsmFile {
@@ -28,12 +34,35 @@ impl AsmFile {
         };
 
         eprintln!("compiling {} into {dest_name}...", self.source);
-        run(Command::new("as")
+        let mut command = match (&self.format, arch) {
+            (Some(AsmFormat::Elf32), _) | (None, Arch::X86) => {
+                let mut command = Command::new("as");
+                command.arg("--32");
+                command
+            }
+            (Some(AsmFormat::Elf64), _) | (None, Arch::X86_64) => {
+                let mut command = Command::new("as");
+                command.arg("--64");
+                command
+            }
+            // The system assembler only targets the host, so use LLVM's to cross-assemble.
+            (Some(AsmFormat::Aarch64), _) => {
+                let mut command = Command::new("llvm-mc");
+                command.arg("--triple=aarch64-linux-gnu").arg("--filetype=obj");
+                command
+            }
+        };
+        if self.keep_locals {
+            command.arg(match self.format {
+                Some(AsmFormat::Aarch64) => "--save-temp-labels",
+                _ => "-L",
+            });
+        }
+        if self.compress_debug_sections {
+            command.arg("--compress-debug-sections=zlib");
+        }
+        run(command
             .current_dir(source_dir)
-            .arg(match (&self.format, arch) {
-                (Some(AsmFormat::Elf32), _) | (None, Arch::X86) => "--32",
-                (Some(AsmFormat::Elf64), _) | (None, Arch::X86_64) => "--64",
-            })
             .arg("-o")
             .arg(dest_dir.join(dest_name))
             .arg(&self.source))?;
@@ -47,4 +76,5 @@ impl AsmFile {
 enum AsmFormat {
     Elf32,
     Elf64,
+    Aarch64,
 }
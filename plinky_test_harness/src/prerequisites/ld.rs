@@ -3,7 +3,6 @@ use crate::utils::run;
 use anyhow::Error;
 use std::path::Path;
 use std::process::Command;
-use tempfile::TempDir;
 
 #[derive(serde::Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
@@ -11,10 +10,22 @@ pub(super) struct LdInvocation {
     dest: String,
     #[serde(default)]
     shared_library: bool,
+    #[serde(default)]
+    hash_style: HashStyle,
     #[serde(flatten)]
     content: Prerequisites,
 }
 
+// FIXME: defaults to sysv until plinky implements linking against GNU hash tables.
+#[derive(serde::Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+enum HashStyle {
+    #[default]
+    Sysv,
+    Gnu,
+    Both,
+}
+
 impl LdInvocation {
     pub(super) fn build(
         &self,
@@ -22,17 +33,7 @@ impl LdInvocation {
         source_dir: &Path,
         dest_dir: &Path,
     ) -> Result<(), Error> {
-        let inputs_dir = TempDir::with_prefix_in("prereq-", dest_dir)?.into_path();
-        self.content.build(arch, source_dir, &inputs_dir)?;
-
-        let mut to_link = Vec::new();
-        for entry in std::fs::read_dir(&inputs_dir)? {
-            let path = entry?.path();
-            if path.is_file() {
-                to_link.push(path.file_name().unwrap().to_os_string());
-            }
-        }
-        to_link.sort();
+        let (inputs_dir, to_link) = self.content.build_in_temp_dir(arch, source_dir, dest_dir)?;
 
         println!("linking {to_link:?} into {}...", self.dest);
         run(Command::new("ld")
@@ -41,7 +42,11 @@ impl LdInvocation {
             .arg(dest_dir.join(&self.dest))
             .args(to_link)
             .args(if self.shared_library { &["-shared"] as &[_] } else { &[] })
-            .arg("--hash-style=sysv") // FIXME: temporary until we implement GNU hash
+            .arg(match self.hash_style {
+                HashStyle::Sysv => "--hash-style=sysv",
+                HashStyle::Gnu => "--hash-style=gnu",
+                HashStyle::Both => "--hash-style=both",
+            })
             .args(match arch {
                 Arch::X86 => ["-m", "elf_i386"],
                 Arch::X86_64 => ["-m", "elf_x86_64"],
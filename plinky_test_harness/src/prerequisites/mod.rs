@@ -2,16 +2,25 @@ mod ar;
 mod asm;
 mod c;
 mod ld;
+mod plinky;
+mod raw;
 mod rust;
 
 use crate::prerequisites::ar::ArArchive;
 use crate::prerequisites::asm::AsmFile;
 use crate::prerequisites::c::CFile;
 use crate::prerequisites::ld::LdInvocation;
+use crate::prerequisites::plinky::PlinkyInvocation;
+use crate::prerequisites::raw::RawFile;
 use crate::prerequisites::rust::RustFile;
 use anyhow::Error;
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+pub use self::plinky::set_plinky_path;
+pub use self::raw::RawPatch;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize)]
 pub enum Arch {
@@ -35,6 +44,10 @@ pub struct Prerequisites {
     #[serde(default)]
     rust: Vec<RustFile>,
     #[serde(default)]
+    plinky: Vec<PlinkyInvocation>,
+    #[serde(default)]
+    raw: Vec<RawFile>,
+    #[serde(default)]
     arch: BTreeMap<Arch, Prerequisites>,
 }
 
@@ -55,9 +68,38 @@ impl Prerequisites {
         for ld in &self.ld {
             ld.build(arch, source_dir, dest_dir)?;
         }
+        for plinky in &self.plinky {
+            plinky.build(arch, source_dir, dest_dir)?;
+        }
+        for raw in &self.raw {
+            raw.build(arch, source_dir, dest_dir)?;
+        }
         if let Some(arch_specific) = self.arch.get(&arch) {
             arch_specific.build(arch, source_dir, dest_dir)?;
         }
         Ok(())
     }
+
+    /// Build the prerequisites in a new temporary directory inside `dest_dir`, returning its path
+    /// and the sorted names of the files built in it.
+    fn build_in_temp_dir(
+        &self,
+        arch: Arch,
+        source_dir: &Path,
+        dest_dir: &Path,
+    ) -> Result<(PathBuf, Vec<OsString>), Error> {
+        let inputs_dir = TempDir::with_prefix_in("prereq-", dest_dir)?.into_path();
+        self.build(arch, source_dir, &inputs_dir)?;
+
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&inputs_dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                files.push(path.file_name().unwrap().to_os_string());
+            }
+        }
+        files.sort();
+
+        Ok((inputs_dir, files))
+    }
 }
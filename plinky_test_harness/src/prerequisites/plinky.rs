@@ -0,0 +1,48 @@
+use crate::prerequisites::{Arch, Prerequisites};
+use crate::utils::run;
+use anyhow::{anyhow, Error};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+static PLINKY_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set the path of the plinky binary used to build `plinky` prerequisites. The harness can't
+/// locate it on its own, as the path is only known to the integration tests of the plinky crate.
+pub fn set_plinky_path(path: &Path) {
+    let _ = PLINKY_PATH.set(path.into());
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(super) struct PlinkyInvocation {
+    dest: String,
+    #[serde(default)]
+    flags: Vec<String>,
+    #[serde(flatten)]
+    content: Prerequisites,
+}
+
+impl PlinkyInvocation {
+    pub(super) fn build(
+        &self,
+        arch: Arch,
+        source_dir: &Path,
+        dest_dir: &Path,
+    ) -> Result<(), Error> {
+        let plinky = PLINKY_PATH
+            .get()
+            .ok_or_else(|| anyhow!("the path of plinky was not set by the test harness"))?;
+
+        let (inputs_dir, to_link) = self.content.build_in_temp_dir(arch, source_dir, dest_dir)?;
+
+        println!("linking {to_link:?} into {} with plinky...", self.dest);
+        run(Command::new(plinky)
+            .current_dir(&inputs_dir)
+            .arg("-o")
+            .arg(dest_dir.join(&self.dest))
+            .args(to_link)
+            .args(&self.flags))?;
+        Ok(())
+    }
+}
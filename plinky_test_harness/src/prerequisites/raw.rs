@@ -0,0 +1,78 @@
+use crate::prerequisites::{Arch, Prerequisites};
+use anyhow::{bail, Error};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// File written verbatim, optionally with some bytes patched, to test how malformed inputs are
+/// handled. The initial content is either provided inline (as bytes or as text), or is the only
+/// file built by the nested prerequisites.
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(super) struct RawFile {
+    dest: String,
+    #[serde(default)]
+    bytes: Option<Vec<u8>>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    patch: Vec<RawPatch>,
+    #[serde(flatten)]
+    content: Prerequisites,
+}
+
+impl RawFile {
+    pub(super) fn build(
+        &self,
+        arch: Arch,
+        source_dir: &Path,
+        dest_dir: &Path,
+    ) -> Result<(), Error> {
+        let mut bytes = match (&self.bytes, &self.text) {
+            (Some(_), Some(_)) => bail!("raw file {} can't have both bytes and text", self.dest),
+            (Some(bytes), None) => bytes.clone(),
+            (None, Some(text)) => text.as_bytes().to_vec(),
+            (None, None) => {
+                let inputs_dir = TempDir::with_prefix_in("prereq-", dest_dir)?.into_path();
+                self.content.build(arch, source_dir, &inputs_dir)?;
+
+                let mut files = Vec::new();
+                for entry in std::fs::read_dir(&inputs_dir)? {
+                    let path = entry?.path();
+                    if path.is_file() {
+                        files.push(path);
+                    }
+                }
+                match files.as_slice() {
+                    [file] => std::fs::read(file)?,
+                    _ => bail!("raw file {} must be built from exactly one file", self.dest),
+                }
+            }
+        };
+
+        eprintln!("writing raw file {} with {} patches...", self.dest, self.patch.len());
+        for patch in &self.patch {
+            patch.apply(&mut bytes)?;
+        }
+        std::fs::write(dest_dir.join(&self.dest), bytes)?;
+
+        Ok(())
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct RawPatch {
+    pub offset: usize,
+    pub byte: u8,
+}
+
+impl RawPatch {
+    /// Replace the byte at the patch offset, failing if the offset is out of bounds.
+    pub fn apply(&self, bytes: &mut [u8]) -> Result<(), Error> {
+        match bytes.get_mut(self.offset) {
+            Some(byte) => *byte = self.byte,
+            None => bail!("cannot patch offset {:#x} of a {} bytes file", self.offset, bytes.len()),
+        }
+        Ok(())
+    }
+}
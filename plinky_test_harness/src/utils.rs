@@ -1,5 +1,5 @@
 use anyhow::{bail, Error};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 pub fn run(command: &mut Command) -> Result<(), Error> {
@@ -19,6 +19,12 @@ pub fn run(command: &mut Command) -> Result<(), Error> {
     Ok(())
 }
 
+/// Find an executable in the directories listed in `PATH`, returning `None` if it's missing.
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).map(|dir| dir.join(name)).find(|candidate| candidate.is_file())
+}
+
 pub fn record_snapshot(
     snapshot_name: &str,
     snapshot_path: &Path,
@@ -43,15 +49,25 @@ pub fn record_snapshot(
         Err(err) => (format!("{action} failed to execute with error: {err}"), false),
     };
 
+    assert_snapshot(snapshot_name, snapshot_path, output_repr)?;
+    Ok(success)
+}
+
+/// Compare arbitrary content with the snapshot stored in the provided directory.
+pub fn assert_snapshot(
+    snapshot_name: &str,
+    snapshot_path: &Path,
+    content: String,
+) -> Result<(), Error> {
     let mut insta_settings = insta::Settings::clone_current();
     insta_settings.set_prepend_module_to_snapshot(false);
     insta_settings.set_omit_expression(true);
     insta_settings.set_snapshot_path(snapshot_path.canonicalize()?);
 
     insta_settings.bind(|| {
-        insta::assert_snapshot!(snapshot_name, output_repr);
+        insta::assert_snapshot!(snapshot_name, content);
     });
-    Ok(success)
+    Ok(())
 }
 
 #[track_caller]
@@ -0,0 +1,47 @@
+//! CRC-32 (IEEE 802.3, the same variant used by zlib), used for `.gnu_debuglink` checksums.
+
+const POLYNOMIAL: u32 = 0xedb88320;
+
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 { (value >> 1) ^ POLYNOMIAL } else { value >> 1 };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+};
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc = TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(0, crc32(b""));
+    }
+
+    #[test]
+    fn test_check_value() {
+        assert_eq!(0xcbf43926, crc32(b"123456789"));
+    }
+
+    #[test]
+    fn test_sentence() {
+        assert_eq!(0x414fa339, crc32(b"The quick brown fox jumps over the lazy dog"));
+    }
+}
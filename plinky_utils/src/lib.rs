@@ -1,9 +1,14 @@
 extern crate proc_macro;
 
 pub mod bitfields;
+pub mod crc32;
 pub mod filters_parser;
+pub mod md5;
 pub mod quote;
 pub mod raw_types;
+pub mod sha1;
+pub mod sha256;
+pub mod zlib;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Bits {
@@ -0,0 +1,159 @@
+//! Minimal MD5 implementation (RFC 1321), used for the `md5` style of build-ids. It's not
+//! collision resistant, but build-ids only need to tell apart different builds.
+
+const SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
+    14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15,
+    21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+const ROUND_CONSTANTS: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+const INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+pub struct Md5 {
+    state: [u32; 4],
+    buffer: [u8; 64],
+    buffer_len: usize,
+    total_len: u64,
+}
+
+impl Md5 {
+    pub fn new() -> Self {
+        Self { state: INITIAL_STATE, buffer: [0; 64], buffer_len: 0, total_len: 0 }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        while !data.is_empty() {
+            let len = (64 - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + len].copy_from_slice(&data[..len]);
+            self.buffer_len += len;
+            data = &data[len..];
+
+            if self.buffer_len == 64 {
+                let block = self.buffer;
+                self.compress(&block);
+                self.buffer_len = 0;
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> [u8; 16] {
+        let bit_len = self.total_len.wrapping_mul(8);
+
+        let mut padding = [0; 72];
+        padding[0] = 0x80;
+        let padding_len =
+            if self.buffer_len < 56 { 56 - self.buffer_len } else { 120 - self.buffer_len };
+        // Unlike the SHA family, MD5 stores the length and the result as little endian.
+        padding[padding_len..padding_len + 8].copy_from_slice(&bit_len.to_le_bytes());
+        // Padding must not count towards the message length, which is already computed above.
+        let total_len = self.total_len;
+        self.update(&padding[..padding_len + 8]);
+        self.total_len = total_len;
+
+        let mut result = [0; 16];
+        for (chunk, word) in result.chunks_exact_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut words = [0u32; 16];
+        for (word, chunk) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+
+        let [mut a, mut b, mut c, mut d] = self.state;
+        for i in 0..64 {
+            let (f, g) = match i {
+                0..=15 => ((b & c) | (!b & d), i),
+                16..=31 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                32..=47 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let temp = a
+                .wrapping_add(f)
+                .wrapping_add(ROUND_CONSTANTS[i])
+                .wrapping_add(words[g])
+                .rotate_left(SHIFTS[i]);
+
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(temp);
+        }
+
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(data: &[u8]) -> String {
+        use std::fmt::Write;
+
+        let mut result = String::new();
+        for byte in data {
+            write!(result, "{byte:02x}").unwrap();
+        }
+        result
+    }
+
+    fn hash(data: &[u8]) -> String {
+        let mut hasher = Md5::new();
+        hasher.update(data);
+        hex(&hasher.finish())
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!("d41d8cd98f00b204e9800998ecf8427e", hash(b""));
+    }
+
+    #[test]
+    fn test_abc() {
+        assert_eq!("900150983cd24fb0d6963f7d28e17f72", hash(b"abc"));
+    }
+
+    #[test]
+    fn test_two_blocks() {
+        assert_eq!(
+            "57edf4a22be3c955ac49da2e2107b67a",
+            hash(
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"
+            )
+        );
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let data = [0x5a; 1000];
+        let mut hasher = Md5::new();
+        for chunk in data.chunks(7) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hash(&data), hex(&hasher.finish()));
+    }
+}
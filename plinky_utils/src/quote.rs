@@ -1,4 +1,4 @@
-use proc_macro::{TokenStream, TokenTree};
+use proc_macro::{Ident, Literal, Span, TokenStream, TokenTree};
 
 pub trait Quote {
     fn to_token_stream(&self) -> TokenStream;
@@ -16,6 +16,33 @@ impl Quote for TokenTree {
     }
 }
 
+// Integers are quoted as unsuffixed literals, so that they can also be used as tuple indexes.
+macro_rules! quote_integer {
+    ($($ty:ty => $method:ident),* $(,)?) => {
+        $(
+            impl Quote for $ty {
+                fn to_token_stream(&self) -> TokenStream {
+                    TokenTree::Literal(Literal::$method(*self)).into()
+                }
+            }
+        )*
+    };
+}
+
+quote_integer! {
+    u8 => u8_unsuffixed,
+    u16 => u16_unsuffixed,
+    u32 => u32_unsuffixed,
+    u64 => u64_unsuffixed,
+    usize => usize_unsuffixed,
+}
+
+impl Quote for bool {
+    fn to_token_stream(&self) -> TokenStream {
+        TokenTree::Ident(Ident::new(if *self { "true" } else { "false" }, Span::call_site())).into()
+    }
+}
+
 impl<T: Quote> Quote for &T {
     fn to_token_stream(&self) -> TokenStream {
         (*self).to_token_stream()
@@ -297,6 +297,13 @@ impl RawWriteError {
         }
     }
 
+    pub fn custom<T>(err: String) -> Self {
+        Self {
+            source: ErrorSource::Type(std::any::type_name::<T>()),
+            inner: RawWriteErrorInner::Custom(CustomError(err)),
+        }
+    }
+
     pub fn wrap_type<T, R>(result: Result<R, RawWriteError>) -> Result<R, RawWriteError> {
         match result {
             Ok(ok) => Ok(ok),
@@ -326,6 +333,7 @@ impl std::error::Error for RawWriteError {
         match &self.inner {
             RawWriteErrorInner::Itself(itself) => Some(itself),
             RawWriteErrorInner::IO(io) => Some(io),
+            RawWriteErrorInner::Custom(custom) => Some(custom),
         }
     }
 }
@@ -339,6 +347,7 @@ impl std::fmt::Display for RawWriteError {
 #[derive(Debug)]
 enum RawWriteErrorInner {
     Itself(Box<RawWriteError>),
+    Custom(CustomError),
     IO(std::io::Error),
 }
 
@@ -0,0 +1,145 @@
+//! Minimal SHA-1 implementation (FIPS 180-4), used for the `sha1` style of build-ids. It's not
+//! collision resistant anymore, but it's what GNU ld uses by default.
+
+const INITIAL_STATE: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: [u8; 64],
+    buffer_len: usize,
+    total_len: u64,
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Self { state: INITIAL_STATE, buffer: [0; 64], buffer_len: 0, total_len: 0 }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        while !data.is_empty() {
+            let len = (64 - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + len].copy_from_slice(&data[..len]);
+            self.buffer_len += len;
+            data = &data[len..];
+
+            if self.buffer_len == 64 {
+                let block = self.buffer;
+                self.compress(&block);
+                self.buffer_len = 0;
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> [u8; 20] {
+        let bit_len = self.total_len.wrapping_mul(8);
+
+        let mut padding = [0; 72];
+        padding[0] = 0x80;
+        let padding_len =
+            if self.buffer_len < 56 { 56 - self.buffer_len } else { 120 - self.buffer_len };
+        padding[padding_len..padding_len + 8].copy_from_slice(&bit_len.to_be_bytes());
+        // Padding must not count towards the message length, which is already computed above.
+        let total_len = self.total_len;
+        self.update(&padding[..padding_len + 8]);
+        self.total_len = total_len;
+
+        let mut result = [0; 20];
+        for (chunk, word) in result.chunks_exact_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut schedule = [0u32; 80];
+        for (word, chunk) in schedule.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        for i in 16..80 {
+            schedule[i] = (schedule[i - 3] ^ schedule[i - 8] ^ schedule[i - 14] ^ schedule[i - 16])
+                .rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = self.state;
+        for (i, word) in schedule.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(data: &[u8]) -> String {
+        use std::fmt::Write;
+
+        let mut result = String::new();
+        for byte in data {
+            write!(result, "{byte:02x}").unwrap();
+        }
+        result
+    }
+
+    fn hash(data: &[u8]) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update(data);
+        hex(&hasher.finish())
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!("da39a3ee5e6b4b0d3255bfef95601890afd80709", hash(b""));
+    }
+
+    #[test]
+    fn test_abc() {
+        assert_eq!("a9993e364706816aba3e25717850c26c9cd0d89d", hash(b"abc"));
+    }
+
+    #[test]
+    fn test_two_blocks() {
+        assert_eq!(
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            hash(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")
+        );
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let data = [0x5a; 1000];
+        let mut hasher = Sha1::new();
+        for chunk in data.chunks(7) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hash(&data), hex(&hasher.finish()));
+    }
+}
@@ -0,0 +1,416 @@
+//! Decompression of zlib (RFC 1950) streams, used by ELF sections compressed with
+//! `SHF_COMPRESSED`. The whole DEFLATE (RFC 1951) format is supported.
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] =
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const CODE_LENGTHS_ORDER: [usize; 19] =
+    [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Decompress a zlib stream, failing as soon as the output grows past `max_len` bytes rather than
+/// trusting the stream to be as small as the caller expects.
+pub fn decompress(data: &[u8], max_len: usize) -> Result<Vec<u8>, ZlibError> {
+    let [cmf, flg, ..] = *data else { return Err(ZlibError::UnexpectedEnd) };
+    if cmf & 0xf != 8 || cmf >> 4 > 7 {
+        return Err(ZlibError::UnsupportedMethod(cmf));
+    }
+    if (((cmf as u16) << 8) | flg as u16) % 31 != 0 {
+        return Err(ZlibError::BadHeaderChecksum);
+    }
+    if flg & 0x20 != 0 {
+        return Err(ZlibError::PresetDictionary);
+    }
+
+    let mut reader = BitReader { data, position: 2, bit: 0 };
+    let mut output = Output { bytes: Vec::new(), max_len };
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => inflate_stored(&mut reader, &mut output)?,
+            1 => {
+                let (literals, distances) = fixed_tables()?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_tables(&mut reader)?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(ZlibError::BadBlockType),
+        }
+        if last {
+            break;
+        }
+    }
+
+    reader.align_to_byte();
+    let checksum = reader.bytes(4)?;
+    if u32::from_be_bytes(checksum.try_into().unwrap()) != adler32(&output.bytes) {
+        return Err(ZlibError::ChecksumMismatch);
+    }
+    Ok(output.bytes)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULO: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // Reducing the sums only every few KBs is fine, as they can't overflow in the meantime.
+    for chunk in data.chunks(4096) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MODULO;
+        b %= MODULO;
+    }
+    (b << 16) | a
+}
+
+fn inflate_stored(reader: &mut BitReader<'_>, output: &mut Output) -> Result<(), ZlibError> {
+    reader.align_to_byte();
+    let header = reader.bytes(4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if len != !u16::from_le_bytes([header[2], header[3]]) {
+        return Err(ZlibError::BadStoredLength);
+    }
+    output.ensure_room(len as usize)?;
+    output.bytes.extend_from_slice(reader.bytes(len as usize)?);
+    Ok(())
+}
+
+fn inflate_block(
+    reader: &mut BitReader<'_>,
+    output: &mut Output,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), ZlibError> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => {
+                output.ensure_room(1)?;
+                output.bytes.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let idx = symbol - 257;
+                let len = LENGTH_BASE[idx] as usize + reader.bits(LENGTH_EXTRA[idx])? as usize;
+
+                let idx = distances.decode(reader)? as usize;
+                if idx >= DISTANCE_BASE.len() {
+                    return Err(ZlibError::InvalidCode);
+                }
+                let distance =
+                    DISTANCE_BASE[idx] as usize + reader.bits(DISTANCE_EXTRA[idx])? as usize;
+                if distance > output.bytes.len() {
+                    return Err(ZlibError::BadDistance);
+                }
+
+                // The copied range can overlap with the bytes being written, so it has to be
+                // copied one byte at a time.
+                output.ensure_room(len)?;
+                let start = output.bytes.len() - distance;
+                for idx in 0..len {
+                    output.bytes.push(output.bytes[start + idx]);
+                }
+            }
+            _ => return Err(ZlibError::InvalidCode),
+        }
+    }
+}
+
+fn fixed_tables() -> Result<(Huffman, Huffman), ZlibError> {
+    let mut lengths = [0; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn read_dynamic_tables(reader: &mut BitReader<'_>) -> Result<(Huffman, Huffman), ZlibError> {
+    let literals_count = reader.bits(5)? as usize + 257;
+    let distances_count = reader.bits(5)? as usize + 1;
+    let code_lengths_count = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0; 19];
+    for &idx in &CODE_LENGTHS_ORDER[..code_lengths_count] {
+        code_lengths[idx] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    // The lengths of both tables are encoded together, and repetitions can cross between them.
+    let mut lengths = Vec::with_capacity(literals_count + distances_count);
+    while lengths.len() < literals_count + distances_count {
+        let (value, repeat) = match code_lengths.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or(ZlibError::BadCodeLengths)?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            18 => (0, 11 + reader.bits(7)?),
+            _ => return Err(ZlibError::InvalidCode),
+        };
+        if lengths.len() + repeat as usize > literals_count + distances_count {
+            return Err(ZlibError::BadCodeLengths);
+        }
+        lengths.extend(std::iter::repeat(value).take(repeat as usize));
+    }
+    if lengths[256] == 0 {
+        // The end of block symbol must be encodable.
+        return Err(ZlibError::BadCodeLengths);
+    }
+
+    Ok((Huffman::new(&lengths[..literals_count])?, Huffman::new(&lengths[literals_count..])?))
+}
+
+struct Output {
+    bytes: Vec<u8>,
+    max_len: usize,
+}
+
+impl Output {
+    /// Check there is room for `len` more bytes before writing them.
+    fn ensure_room(&self, len: usize) -> Result<(), ZlibError> {
+        if self.bytes.len() + len > self.max_len {
+            return Err(ZlibError::TooLarge(self.max_len));
+        }
+        Ok(())
+    }
+}
+
+/// Canonical Huffman code, decoded one bit at a time.
+struct Huffman {
+    /// Number of codes for each length in bits.
+    counts: [u16; 16],
+    /// Symbols sorted by the code representing them.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, ZlibError> {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Codes of a length can't be more than the ones left unused by the shorter lengths.
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(ZlibError::BadCodeLengths);
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader<'_>) -> Result<u16, ZlibError> {
+        // First code of the current length, and index of its symbol.
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= reader.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(ZlibError::InvalidCode)
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit: u8,
+}
+
+impl<'a> BitReader<'a> {
+    /// Read a number stored with the least significant bit first.
+    fn bits(&mut self, count: u8) -> Result<u32, ZlibError> {
+        let mut value = 0;
+        for idx in 0..count {
+            let byte = self.data.get(self.position).ok_or(ZlibError::UnexpectedEnd)?;
+            value |= (((byte >> self.bit) & 1) as u32) << idx;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], ZlibError> {
+        let bytes =
+            self.data.get(self.position..self.position + len).ok_or(ZlibError::UnexpectedEnd)?;
+        self.position += len;
+        Ok(bytes)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ZlibError {
+    UnsupportedMethod(u8),
+    BadHeaderChecksum,
+    PresetDictionary,
+    UnexpectedEnd,
+    BadBlockType,
+    BadStoredLength,
+    BadCodeLengths,
+    InvalidCode,
+    BadDistance,
+    ChecksumMismatch,
+    TooLarge(usize),
+}
+
+impl std::error::Error for ZlibError {}
+
+impl std::fmt::Display for ZlibError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZlibError::UnsupportedMethod(cmf) => {
+                write!(f, "unsupported compression method or window size {cmf:#x}")
+            }
+            ZlibError::BadHeaderChecksum => f.write_str("bad checksum of the zlib header"),
+            ZlibError::PresetDictionary => f.write_str("preset dictionaries are not supported"),
+            ZlibError::UnexpectedEnd => f.write_str("unexpected end of the compressed data"),
+            ZlibError::BadBlockType => f.write_str("bad deflate block type"),
+            ZlibError::BadStoredLength => f.write_str("mismatched length of stored block"),
+            ZlibError::BadCodeLengths => f.write_str("invalid Huffman code lengths"),
+            ZlibError::InvalidCode => f.write_str("invalid Huffman code"),
+            ZlibError::BadDistance => f.write_str("distance too far back"),
+            ZlibError::ChecksumMismatch => f.write_str("mismatched checksum of the data"),
+            ZlibError::TooLarge(max_len) => {
+                write!(f, "decompressed data is larger than the expected {max_len} bytes")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(1, adler32(b""));
+        assert_eq!(0x11e60398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn test_decompress_fixed_huffman() {
+        let compressed = [
+            0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01, 0x68, 0x03,
+            0x08, 0xb1,
+        ];
+        assert_eq!(b"hello hello hello hello", &decompress(&compressed, 1024).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_decompress_dynamic_huffman() {
+        let compressed = [
+            0x78, 0xda, 0x9d, 0xd2, 0xb7, 0x11, 0x80, 0x30, 0x00, 0x43, 0xd1, 0x9e, 0x29, 0x34,
+            0x02, 0x39, 0x6d, 0x43, 0x30, 0x60, 0x30, 0x36, 0xc9, 0xa4, 0xe9, 0x39, 0xd8, 0x00,
+            0xd5, 0xba, 0x57, 0xe9, 0x2b, 0xa9, 0x05, 0xdc, 0x1c, 0x5b, 0x27, 0x30, 0x5b, 0x59,
+            0x0d, 0x28, 0x17, 0x73, 0x68, 0x34, 0xe6, 0x44, 0x6f, 0xc7, 0x69, 0x85, 0xd9, 0xc5,
+            0xf2, 0xcd, 0xaa, 0xb8, 0x2f, 0xd4, 0xa6, 0x75, 0xd4, 0x6b, 0x3c, 0xc2, 0xf8, 0x84,
+            0x09, 0x08, 0x13, 0x12, 0x26, 0x22, 0x4c, 0x4c, 0x98, 0x84, 0x30, 0x29, 0x61, 0x32,
+            0xe6, 0x53, 0x2a, 0x84, 0x9f, 0x25, 0x3c, 0x54, 0xea, 0xdc, 0x8d,
+        ];
+        let mut expected = String::new();
+        for i in 0..12 {
+            expected.push_str(&format!("line {i}: the quick brown fox jumps over the lazy dog\n"));
+        }
+        assert_eq!(expected.as_bytes(), &decompress(&compressed, 1024).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_decompress_stored() {
+        let large = (0..200_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        for data in [&b""[..], b"hello world", &large] {
+            let compressed = compress(data);
+            assert_eq!(data, decompress(&compressed, data.len()).unwrap());
+        }
+        assert_eq!([0x78, 0x01, 0x01, 0x00, 0x00, 0xff, 0xff, 0, 0, 0, 1], compress(b"")[..]);
+    }
+
+    #[test]
+    fn test_decompress_errors() {
+        let mut compressed = compress(b"hello world");
+        let len = compressed.len();
+        compressed[len - 1] ^= 1;
+        assert_eq!(Err(ZlibError::ChecksumMismatch), decompress(&compressed, 11));
+        assert_eq!(Err(ZlibError::UnexpectedEnd), decompress(&compressed[..len - 6], 11));
+        assert_eq!(Err(ZlibError::BadHeaderChecksum), decompress(&[0x78, 0x02], 11));
+        assert_eq!(Err(ZlibError::UnsupportedMethod(0x79)), decompress(&[0x79, 0x01], 11));
+    }
+
+    #[test]
+    fn test_decompress_too_large() {
+        let stored = compress(b"hello world");
+        assert_eq!(Err(ZlibError::TooLarge(10)), decompress(&stored, 10));
+
+        // A few bytes of fixed Huffman codes expand to many more bytes of output.
+        let fixed = [
+            0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01, 0x68, 0x03,
+            0x08, 0xb1,
+        ];
+        assert_eq!(Err(ZlibError::TooLarge(22)), decompress(&fixed, 22));
+        assert_eq!(23, decompress(&fixed, 23).unwrap().len());
+    }
+
+    /// Compress the data with stored (uncompressed) blocks only, which is enough to build
+    /// streams for the tests.
+    fn compress(data: &[u8]) -> Vec<u8> {
+        // Empty inputs still need a block, marked as the last one.
+        let chunks = match data.is_empty() {
+            true => vec![data],
+            false => data.chunks(0xffff).collect::<Vec<_>>(),
+        };
+        let mut output = Vec::with_capacity(2 + chunks.len() * 5 + data.len() + 4);
+
+        // Deflate with a 32KB window and the fastest compression level.
+        output.extend_from_slice(&[0x78, 0x01]);
+        for (idx, chunk) in chunks.iter().enumerate() {
+            output.push((idx == chunks.len() - 1) as u8);
+            output.extend_from_slice(&(chunk.len() as u16).to_le_bytes());
+            output.extend_from_slice(&(!(chunk.len() as u16)).to_le_bytes());
+            output.extend_from_slice(chunk);
+        }
+        output.extend_from_slice(&adler32(data).to_be_bytes());
+        output
+    }
+}